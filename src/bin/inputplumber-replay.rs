@@ -0,0 +1,50 @@
+use std::{env, error::Error, process, time::Duration};
+
+use inputplumber::{constants::BUS_NAME, input::event_log};
+use zbus::Connection;
+
+/// DBus interface used to inject recorded events
+const COMPOSITE_DEVICE_INTERFACE: &str = "org.shadowblip.Input.CompositeDevice";
+
+/// Replays an event log recorded by a composite device by re-injecting each
+/// event with the same timing it was recorded with.
+///
+/// Usage: inputplumber-replay <event log> <composite device path>
+/// e.g. inputplumber-replay events.log /org/shadowblip/InputPlumber/CompositeDevice0
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().collect();
+    let (Some(log_path), Some(device_path)) = (args.get(1), args.get(2)) else {
+        eprintln!("Usage: inputplumber-replay <event log> <composite device path>");
+        process::exit(1);
+    };
+
+    let bytes = std::fs::read(log_path)?;
+    let records = event_log::decode_records(bytes.as_slice())
+        .map_err(|e| format!("Failed to read event log {log_path}: {e}"))?;
+    println!("Replaying {} events from {log_path}", records.len());
+
+    let connection = Connection::system().await?;
+    let mut last_timestamp = None;
+    for record in records {
+        // Wait the same amount of time between events as when they were recorded
+        if let Some(last_timestamp) = last_timestamp {
+            let delay = record.timestamp_us.saturating_sub(last_timestamp);
+            tokio::time::sleep(Duration::from_micros(delay)).await;
+        }
+        last_timestamp = Some(record.timestamp_us);
+
+        connection
+            .call_method(
+                Some(BUS_NAME),
+                device_path.as_str(),
+                Some(COMPOSITE_DEVICE_INTERFACE),
+                "InjectEventRecord",
+                &(record.encode(),),
+            )
+            .await?;
+    }
+
+    println!("Finished replaying events");
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::{env, error::Error, process};
+
+use inputplumber::config::CapabilityMap;
+
+/// Runs the tests defined in a capability map and exits with a non-zero
+/// status if any of them fail.
+///
+/// Usage: inputplumber-test <capability map>
+/// e.g. inputplumber-test rootfs/usr/share/inputplumber/capability_maps/ally_type1.yaml
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().collect();
+    let Some(path) = args.get(1) else {
+        eprintln!("Usage: inputplumber-test <capability map>");
+        process::exit(1);
+    };
+
+    let map = CapabilityMap::from_yaml_file(path.clone())
+        .map_err(|e| format!("Failed to load capability map {path}: {e}"))?;
+    let results = map.run_tests();
+    if results.is_empty() {
+        println!("No tests defined in {path}");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for result in results.iter() {
+        if result.passed {
+            println!("PASS {}", result.name);
+            continue;
+        }
+        failed += 1;
+        println!(
+            "FAIL {}: {}",
+            result.name,
+            result.message.as_deref().unwrap_or_default()
+        );
+    }
+
+    println!("{} passed, {failed} failed", results.len() - failed);
+    if failed > 0 {
+        process::exit(1);
+    }
+
+    Ok(())
+}
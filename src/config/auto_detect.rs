@@ -0,0 +1,88 @@
+//! Database of known controllers used to automatically select the device
+//! config and profile of common controllers without any manual configuration.
+
+/// Built-in database of known controllers. Maps the vendor and product id of
+/// a controller to the name of the [super::CompositeDeviceConfig] that should
+/// be used for it and the path to its device profile, relative to the
+/// profiles directory.
+pub const KNOWN_DEVICES: &[((u16, u16), (&str, &str))] = &[
+    // Sony DualShock 4
+    (
+        (0x054c, 0x09cc),
+        (
+            "Sony Interactive Entertainment Wireless Controller",
+            "default.yaml",
+        ),
+    ),
+    // Sony DualSense
+    (
+        (0x054c, 0x0ce6),
+        (
+            "Sony Interactive Entertainment DualSense Wireless Controller",
+            "default.yaml",
+        ),
+    ),
+    // Sony DualSense Edge
+    (
+        (0x054c, 0x0df2),
+        (
+            "Sony Interactive Entertainment DualSense Edge Wireless Controller",
+            "default.yaml",
+        ),
+    ),
+    // Nintendo Switch Pro Controller
+    (
+        (0x057e, 0x2009),
+        ("Nintendo Co., Ltd. Pro Controller", "default.yaml"),
+    ),
+    // Microsoft Xbox 360 Controller
+    (
+        (0x045e, 0x028e),
+        ("Microsoft X-Box 360 pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x028f),
+        ("Microsoft X-Box 360 pad", "default.yaml"),
+    ),
+    // Microsoft Xbox One Controller
+    (
+        (0x045e, 0x02d1),
+        ("Microsoft X-Box One pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x02dd),
+        ("Microsoft X-Box One pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x02ea),
+        ("Microsoft X-Box One pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x0b12),
+        ("Microsoft X-Box One pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x0b13),
+        ("Microsoft X-Box One pad", "default.yaml"),
+    ),
+    // Microsoft Xbox One Elite Controller
+    (
+        (0x045e, 0x02e3),
+        ("Microsoft X-Box One Elite pad", "default.yaml"),
+    ),
+    (
+        (0x045e, 0x0b00),
+        ("Microsoft X-Box One Elite pad", "default.yaml"),
+    ),
+];
+
+/// Returns the name of the [super::CompositeDeviceConfig] and the path to the
+/// device profile (relative to the profiles directory) to use for the known
+/// controller with the given vendor and product id.
+/// E.g. ("Microsoft X-Box 360 pad", "default.yaml")
+pub fn get_known_device(vendor_id: u16, product_id: u16) -> Option<(&'static str, &'static str)> {
+    KNOWN_DEVICES
+        .iter()
+        .find(|(id, _)| *id == (vendor_id, product_id))
+        .map(|(_, known)| *known)
+}
@@ -0,0 +1,48 @@
+use std::{error::Error, fs};
+
+use crate::config::{
+    auto_detect::{get_known_device, KNOWN_DEVICES},
+    CompositeDeviceConfig,
+};
+
+const DEVICES_PATH: &str = "./rootfs/usr/share/inputplumber/devices";
+const PROFILES_PATH: &str = "./rootfs/usr/share/inputplumber/profiles";
+
+#[tokio::test]
+async fn test_known_devices_exist() -> Result<(), Box<dyn Error>> {
+    let mut config_names = Vec::new();
+    for entry in fs::read_dir(DEVICES_PATH)? {
+        let path = entry?.path();
+        let config = CompositeDeviceConfig::from_yaml_file(path.display().to_string())?;
+        config_names.push(config.name);
+    }
+
+    for ((vendor_id, product_id), (config_name, profile_path)) in KNOWN_DEVICES {
+        assert!(
+            config_names.iter().any(|name| name == config_name),
+            "No config found for {vendor_id:04x}:{product_id:04x}: {config_name}"
+        );
+        let profile = std::path::Path::new(PROFILES_PATH).join(profile_path);
+        assert!(
+            profile.exists(),
+            "No profile found for {vendor_id:04x}:{product_id:04x}: {profile_path}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_known_device() -> Result<(), Box<dyn Error>> {
+    let known = get_known_device(0x054c, 0x0ce6);
+    assert_eq!(
+        known,
+        Some((
+            "Sony Interactive Entertainment DualSense Wireless Controller",
+            "default.yaml"
+        ))
+    );
+    assert_eq!(get_known_device(0xffff, 0x0000), None);
+
+    Ok(())
+}
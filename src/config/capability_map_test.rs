@@ -0,0 +1,327 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use crate::{
+    config::{AxisRange, CapabilityMap, CapabilityMapWarningKind},
+    input::capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+};
+
+const CAPABILITY_MAP: &str = r#"
+version: 1
+kind: CapabilityMap
+name: Test
+id: test
+mapping:
+  - name: Unordered
+    source_events:
+      - gamepad:
+          button: South
+      - gamepad:
+          button: East
+    target_event:
+      gamepad:
+        button: Guide
+  - name: Ordered
+    ordered: true
+    source_events:
+      - gamepad:
+          button: South
+      - gamepad:
+          button: East
+    target_event:
+      gamepad:
+        button: QuickAccess
+"#;
+
+#[tokio::test]
+async fn test_source_events_active_ordering() -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::_from_yaml(CAPABILITY_MAP.to_string())?;
+    let unordered = &map.mapping[0];
+    let ordered = &map.mapping[1];
+
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let east = Capability::Gamepad(Gamepad::Button(GamepadButton::East));
+    let north = Capability::Gamepad(Gamepad::Button(GamepadButton::North));
+
+    // Both mappings match when pressed in the defined order
+    let active = vec![south.clone(), east.clone()];
+    assert!(unordered.source_events_active(&active));
+    assert!(ordered.source_events_active(&active));
+
+    // Only the unordered mapping matches when pressed in reverse order
+    let active = vec![east.clone(), south.clone()];
+    assert!(unordered.source_events_active(&active));
+    assert!(!ordered.source_events_active(&active));
+
+    // Unrelated inputs do not affect the press order
+    let active = vec![north.clone(), south.clone(), east.clone()];
+    assert!(ordered.source_events_active(&active));
+
+    // Neither mapping matches if a source event is missing
+    let active = vec![south, north];
+    assert!(!unordered.source_events_active(&active));
+    assert!(!ordered.source_events_active(&active));
+
+    Ok(())
+}
+
+const COMPOSITE_CAPABILITY_MAP: &str = r#"
+version: 1
+kind: CapabilityMap
+name: Composite
+id: composite
+mapping:
+  - name: Knob
+    source_events: []
+    composite_source:
+      - gamepad:
+          button: South
+      - gamepad:
+          button: East
+    target_event:
+      gamepad:
+        button: Guide
+"#;
+
+/// Returns the active devices map for the given capabilities and the source
+/// devices they are pressed on
+fn active_devices(inputs: &[(&Capability, &[&str])]) -> HashMap<Capability, HashSet<String>> {
+    inputs
+        .iter()
+        .map(|(cap, devices)| {
+            let devices = devices.iter().map(|id| id.to_string()).collect();
+            ((*cap).clone(), devices)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_composite_source() -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::_from_yaml(COMPOSITE_CAPABILITY_MAP.to_string())?;
+    let knob = &map.mapping[0];
+    assert!(map.validate().is_empty());
+
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let east = Capability::Gamepad(Gamepad::Button(GamepadButton::East));
+    let north = Capability::Gamepad(Gamepad::Button(GamepadButton::North));
+
+    // Composite source events must be active on different source devices
+    let devices = active_devices(&[
+        (&south, &["evdev://event0"]),
+        (&east, &["hidraw://hidraw0"]),
+    ]);
+    assert!(knob.composite_source_active(&devices));
+    let devices = active_devices(&[(&south, &["evdev://event0"]), (&east, &["evdev://event0"])]);
+    assert!(!knob.composite_source_active(&devices));
+    let devices = active_devices(&[
+        (&south, &["evdev://event0", "hidraw://hidraw0"]),
+        (&east, &["evdev://event0"]),
+    ]);
+    assert!(knob.composite_source_active(&devices));
+    let devices = active_devices(&[(&south, &["evdev://event0"])]);
+    assert!(!knob.composite_source_active(&devices));
+
+    // Releasing an unrelated input does not release the mapping
+    let active = vec![south.clone(), east.clone()];
+    assert!(!knob.is_released(&active, true));
+
+    // Releasing any composite source event releases the mapping
+    let active = vec![south.clone(), north.clone()];
+    assert!(knob.is_released(&active, false));
+
+    // Mappings without composite source events are released once none of
+    // their source events are pressed
+    let map = CapabilityMap::_from_yaml(CAPABILITY_MAP.to_string())?;
+    let unordered = &map.mapping[0];
+    assert!(unordered.composite_source_active(&HashMap::new()));
+    assert!(!unordered.is_released(&[south.clone()], true));
+    assert!(unordered.is_released(&[north], true));
+
+    Ok(())
+}
+
+/// Gamepad with 4 buttons, 4 bits of padding, and an X/Y stick
+const HID_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, 0x09, 0x05, 0xA1, 0x01, 0x05, 0x09, 0x19, 0x01, 0x29, 0x04, 0x15, 0x00, 0x25, 0x01,
+    0x75, 0x01, 0x95, 0x04, 0x81, 0x02, 0x75, 0x04, 0x95, 0x01, 0x81, 0x03, 0x05, 0x01, 0x09, 0x30,
+    0x09, 0x31, 0x15, 0x00, 0x26, 0xFF, 0x00, 0x75, 0x08, 0x95, 0x02, 0x81, 0x02, 0xC0,
+];
+
+#[tokio::test]
+async fn test_from_hid_descriptor() -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::from_hid_descriptor(HID_DESCRIPTOR)?;
+    let capabilities: Vec<Capability> = map
+        .mapping
+        .iter()
+        .map(|m| Capability::from(m.target_event.clone()))
+        .collect();
+
+    assert_eq!(
+        capabilities,
+        vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        ]
+    );
+
+    // Truncated descriptors should fail to parse
+    assert!(CapabilityMap::from_hid_descriptor(&HID_DESCRIPTOR[..13]).is_err());
+
+    Ok(())
+}
+
+const INVALID_CAPABILITY_MAP: &str = r#"
+version: 1
+kind: CapabilityMap
+name: Invalid
+id: invalid
+mapping:
+  - name: Typo
+    source_events:
+      - gamepad:
+          button: Suoth
+    target_event:
+      gamepad:
+        button: South
+  - name: Typo
+    source_events: []
+    target_event:
+      gamepad:
+        button: Guide
+  - name: Forward
+    source_events:
+      - gamepad:
+          button: North
+    target_event:
+      gamepad:
+        button: West
+  - name: Backward
+    source_events:
+      - gamepad:
+          button: West
+    target_event:
+      gamepad:
+        button: North
+"#;
+
+#[tokio::test]
+async fn test_validate() -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::_from_yaml(CAPABILITY_MAP.to_string())?;
+    assert!(map.validate().is_empty());
+
+    let map = CapabilityMap::_from_yaml(INVALID_CAPABILITY_MAP.to_string())?;
+    let warnings: Vec<(String, String, CapabilityMapWarningKind)> = map
+        .validate()
+        .into_iter()
+        .map(|w| (w.mapping, w.field, w.kind))
+        .collect();
+    let expected = [
+        (
+            "Typo",
+            "source_events[0]",
+            CapabilityMapWarningKind::UnknownSourceEvent,
+        ),
+        ("Typo", "name", CapabilityMapWarningKind::DuplicateName),
+        (
+            "Typo",
+            "source_events",
+            CapabilityMapWarningKind::EmptySourceEvents,
+        ),
+        (
+            "Forward",
+            "target_event",
+            CapabilityMapWarningKind::CircularMapping,
+        ),
+        (
+            "Backward",
+            "target_event",
+            CapabilityMapWarningKind::CircularMapping,
+        ),
+    ];
+    assert_eq!(warnings.len(), expected.len());
+    for (mapping, field, kind) in expected {
+        let warning = (mapping.to_string(), field.to_string(), kind);
+        assert!(warnings.contains(&warning), "Missing warning: {warning:?}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_axis_range_normalize() -> Result<(), Box<dyn Error>> {
+    assert_eq!(AxisRange::UnsignedByte.normalize(0.0), -1.0);
+    assert_eq!(AxisRange::UnsignedByte.normalize(255.0), 1.0);
+    assert!(AxisRange::UnsignedByte.normalize(128.0).abs() < 0.01);
+    assert_eq!(AxisRange::UnsignedShort.normalize(0.0), -1.0);
+    assert_eq!(AxisRange::UnsignedShort.normalize(65535.0), 1.0);
+    assert!(AxisRange::UnsignedShort.normalize(32768.0).abs() < 0.001);
+    assert_eq!(AxisRange::Signed.normalize(-0.5), -0.5);
+
+    Ok(())
+}
+
+const TESTED_CAPABILITY_MAP: &str = r#"
+version: 1
+kind: CapabilityMap
+name: Tested
+id: tested
+mapping:
+  - name: Guide
+    source_events:
+      - gamepad:
+          button: South
+      - gamepad:
+          button: East
+    target_event:
+      gamepad:
+        button: Guide
+tests:
+  - name: Chord
+    input_sequence:
+      - capability: Gamepad:Button:South
+        value: 1.0
+        ms_offset: 0
+      - capability: Gamepad:Button:East
+        value: 1.0
+        ms_offset: 10
+      - capability: Gamepad:Button:North
+        value: 1.0
+        ms_offset: 20
+      - capability: Gamepad:Button:South
+        value: 0.0
+        ms_offset: 40
+      - capability: Gamepad:Button:East
+        value: 0.0
+        ms_offset: 30
+    expected_output:
+      - capability: Gamepad:Button:Guide
+        value: 1.0
+      - capability: Gamepad:Button:North
+        value: 1.0
+      - capability: Gamepad:Button:Guide
+        value: 0.0
+  - input_sequence:
+      - capability: Gamepad:Button:South
+        value: 1.0
+    expected_output:
+      - capability: Gamepad:Button:South
+        value: 1.0
+"#;
+
+#[tokio::test]
+async fn test_run_tests() -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::_from_yaml(TESTED_CAPABILITY_MAP.to_string())?;
+    let results = map.run_tests();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].name, "Chord");
+    assert!(results[0].passed, "{:?}", results[0].message);
+    assert_eq!(results[1].name, "Test 2");
+    assert!(!results[1].passed);
+
+    Ok(())
+}
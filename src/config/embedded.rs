@@ -0,0 +1,34 @@
+//! Capability maps and device profiles compiled into the binary, so
+//! InputPlumber can run without the files in /usr/share/inputplumber.
+use phf::phf_map;
+
+/// Built-in capability maps by file name without the extension
+pub static CAPABILITY_MAPS: phf::Map<&'static str, &'static str> = phf_map! {
+    "ally_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ally_type1.yaml"),
+    "anbernic_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/anbernic_type1.yaml"),
+    "ayaneo_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type1.yaml"),
+    "ayaneo_type2" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type2.yaml"),
+    "ayaneo_type3" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type3.yaml"),
+    "ayaneo_type4" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type4.yaml"),
+    "ayaneo_type5" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type5.yaml"),
+    "ayaneo_type6" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayaneo_type6.yaml"),
+    "ayn_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/ayn_type1.yaml"),
+    "gpd_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/gpd_type1.yaml"),
+    "gpd_type2" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/gpd_type2.yaml"),
+    "gpd_type3" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/gpd_type3.yaml"),
+    "msiclaw_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/msiclaw_type1.yaml"),
+    "nintendo_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/nintendo_type1.yaml"),
+    "onexplayer_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/onexplayer_type1.yaml"),
+    "onexplayer_type2" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/onexplayer_type2.yaml"),
+    "onexplayer_type3" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/onexplayer_type3.yaml"),
+    "onexplayer_type4" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/onexplayer_type4.yaml"),
+    "orangepi_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/orangepi_type1.yaml"),
+    "steam_deck_type1" => include_str!("../../rootfs/usr/share/inputplumber/capability_maps/steam_deck_type1.yaml"),
+};
+
+/// Built-in device profiles by file name without the extension
+pub static DEVICE_PROFILES: phf::Map<&'static str, &'static str> = phf_map! {
+    "default" => include_str!("../../rootfs/usr/share/inputplumber/profiles/default.yaml"),
+    "mouse_keyboard_wasd" => include_str!("../../rootfs/usr/share/inputplumber/profiles/mouse_keyboard_wasd.yaml"),
+    "test" => include_str!("../../rootfs/usr/share/inputplumber/profiles/test.yaml"),
+};
@@ -1,16 +1,36 @@
+pub mod auto_detect;
+#[cfg(test)]
+mod auto_detect_test;
+#[cfg(test)]
+mod capability_map_test;
+#[cfg(feature = "embedded-profiles")]
+pub mod embedded;
 pub mod path;
-
-use std::io;
+pub mod sdl;
+#[cfg(test)]
+mod sdl_test;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::Path,
+    str::FromStr,
+};
 
 use ::procfs::CpuInfo;
 use glob_match::glob_match;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     dmi::data::DMIData,
-    input::event::{native::NativeEvent, value::InputValue},
+    drivers::hid_descriptor::{self, Usage, USAGE_PAGE_BUTTON, USAGE_PAGE_GENERIC_DESKTOP},
+    input::{
+        capability::Capability,
+        event::{native::NativeEvent, value::InputValue},
+        output_event::OutputEventKind,
+    },
     udev::device::UdevDevice,
 };
 
@@ -21,6 +41,43 @@ pub enum LoadError {
     IoError(#[from] io::Error),
     #[error("Unable to deserialize: {0}")]
     DeserializeError(#[from] serde_yaml::Error),
+    #[cfg(feature = "json5-profiles")]
+    #[error("Unable to deserialize JSON5: {0}")]
+    Json5Error(#[from] json5::Error),
+    #[cfg(not(feature = "json5-profiles"))]
+    #[error("Support for JSON5 profiles is not enabled: {0}")]
+    Json5NotEnabled(String),
+    #[error("Unable to parse HID descriptor: {0}")]
+    HidDescriptorError(String),
+    #[error("No embedded resource found with name: {0}")]
+    NotEmbedded(String),
+    #[error("Invalid SDL name: {0}")]
+    SdlAliasError(String),
+}
+
+/// Device profiles the user assigned to composite devices, which persist
+/// across restarts. Maps [CompositeDeviceConfig] names to profile paths.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ProfileAssignments(pub HashMap<String, String>);
+
+impl ProfileAssignments {
+    /// Load the [ProfileAssignments] from the given YAML file
+    pub fn from_yaml_file(path: &Path) -> Result<ProfileAssignments, LoadError> {
+        let file = std::fs::File::open(path)?;
+        let assignments: ProfileAssignments = serde_yaml::from_reader(file)?;
+        Ok(assignments)
+    }
+
+    /// Write the [ProfileAssignments] to the given YAML file, creating its
+    /// parent directories if they do not exist.
+    pub fn save_yaml_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_yaml::to_string(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,22 +88,122 @@ pub struct DeviceProfile {
     pub name: String, //useful?
     pub target_devices: Option<Vec<String>>,
     pub description: Option<String>,
+    /// Optional path to a profile this profile extends. Relative paths are
+    /// relative to the directory of this profile. Mappings in this profile
+    /// replace mappings in the base profile with the same name.
+    pub base_profile: Option<String>,
+    /// Optional path to a profile used to translate source events that have
+    /// no mapping in this profile. Relative paths are relative to the
+    /// directory of this profile.
+    pub fallback_profile: Option<String>,
+    /// Optional list of source events that act as a shift modifier. While
+    /// any of them is held, mappings with 'shift' enabled are used instead
+    /// of the regular mappings.
+    pub modifier_caps: Option<Vec<CapabilityConfig>>,
+    /// Optional list of mutually exclusive pairs of source buttons (e.g.
+    /// DPad left and right). Pressing one button of a pair while the other
+    /// is held releases the other button, for games that misbehave when
+    /// both are pressed.
+    pub anti_ghosting: Option<Vec<(CapabilityConfig, CapabilityConfig)>>,
     pub mapping: Vec<ProfileMapping>,
 }
 
 impl DeviceProfile {
     /// Load a [CapabilityProfile] from the given YAML string
     pub fn from_yaml(content: String) -> Result<DeviceProfile, LoadError> {
-        let device: DeviceProfile = serde_yaml::from_str(content.as_str())?;
+        let mut device: DeviceProfile = serde_yaml::from_str(content.as_str())?;
+        device.resolve_aliases()?;
         Ok(device)
     }
 
     /// Load a [CapabilityProfile] from the given YAML file
     pub fn from_yaml_file(path: String) -> Result<DeviceProfile, LoadError> {
         let file = std::fs::File::open(path)?;
-        let device: DeviceProfile = serde_yaml::from_reader(file)?;
+        let mut device: DeviceProfile = serde_yaml::from_reader(file)?;
+        device.resolve_aliases()?;
+        Ok(device)
+    }
+
+    /// Load a [DeviceProfile] from the given JSON5 string
+    #[cfg(feature = "json5-profiles")]
+    pub fn from_json5(content: &str) -> Result<DeviceProfile, LoadError> {
+        let mut device: DeviceProfile = json5::from_str(content)?;
+        device.resolve_aliases()?;
+        Ok(device)
+    }
+
+    /// Load a [DeviceProfile] from the given file. The format is detected by
+    /// the file extension: ".json5" and ".json" files are parsed as JSON5 and
+    /// all other files as YAML.
+    pub fn from_file(path: &str) -> Result<DeviceProfile, LoadError> {
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            #[cfg(feature = "json5-profiles")]
+            "json5" | "json" => {
+                let content = std::fs::read_to_string(path)?;
+                DeviceProfile::from_json5(content.as_str())
+            }
+            #[cfg(not(feature = "json5-profiles"))]
+            "json5" | "json" => Err(LoadError::Json5NotEnabled(path.to_string())),
+            _ => DeviceProfile::from_yaml_file(path.to_string()),
+        }
+    }
+
+    /// Load the [DeviceProfile] with the given name that was compiled into the
+    /// binary, e.g. "default".
+    #[cfg(feature = "embedded-profiles")]
+    pub fn from_embedded(name: &str) -> Result<DeviceProfile, LoadError> {
+        let Some(content) = embedded::DEVICE_PROFILES.get(name) else {
+            return Err(LoadError::NotEmbedded(name.to_string()));
+        };
+        let mut device: DeviceProfile = serde_yaml::from_str(content)?;
+        device.resolve_aliases()?;
         Ok(device)
     }
+
+    /// Apply the mappings of this profile on top of the given base profile.
+    /// Mappings in this profile replace base profile mappings with the same
+    /// name, and target devices are inherited if this profile has none.
+    pub fn extend(&mut self, base: DeviceProfile) {
+        let mut mapping: Vec<ProfileMapping> = base
+            .mapping
+            .into_iter()
+            .filter(|base_mapping| !self.mapping.iter().any(|m| m.name == base_mapping.name))
+            .collect();
+        mapping.append(&mut self.mapping);
+        self.mapping = mapping;
+        if self.target_devices.is_none() {
+            self.target_devices = base.target_devices;
+        }
+        if self.modifier_caps.is_none() {
+            self.modifier_caps = base.modifier_caps;
+        }
+        if self.anti_ghosting.is_none() {
+            self.anti_ghosting = base.anti_ghosting;
+        }
+    }
+
+    /// Rewrite any SDL GameController style names (e.g. "a", "leftshoulder")
+    /// used in the profile mappings into their InputPlumber equivalent.
+    fn resolve_aliases(&mut self) -> Result<(), LoadError> {
+        for mapping in self.mapping.iter_mut() {
+            sdl::resolve_aliases(&mut mapping.source_event)?;
+            for target_event in mapping.target_events.iter_mut() {
+                sdl::resolve_aliases(target_event)?;
+            }
+        }
+        for modifier in self.modifier_caps.iter_mut().flatten() {
+            sdl::resolve_aliases(modifier)?;
+        }
+        for (a, b) in self.anti_ghosting.iter_mut().flatten() {
+            sdl::resolve_aliases(a)?;
+            sdl::resolve_aliases(b)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -54,10 +211,93 @@ impl DeviceProfile {
 pub struct ProfileMapping {
     pub name: String,
     pub source_event: CapabilityConfig,
+    /// Optional list of source device id prefixes (e.g. "hidraw://") that
+    /// must be in use for this mapping to be active.
+    pub required_sources: Option<Vec<String>>,
     pub target_events: Vec<CapabilityConfig>,
+    /// If set, this mapping is only activated when the source button is held
+    /// for at least this many milliseconds. Releasing the button earlier
+    /// activates the mapping for the same source event without 'hold_ms'.
+    pub hold_ms: Option<u64>,
+    /// If set, this mapping is only activated when the source button is
+    /// tapped the given number of times. Mappings for the same source event
+    /// can use different tap counts to bind several actions to one button.
+    pub tap_dance: Option<TapDance>,
+    /// If true, repeated presses of the source event that arrive within
+    /// 'repeat_threshold_ms' of the previous press are discarded. This can be
+    /// used to suppress key repeat on buttons mapped to keyboard keys.
+    pub suppress_key_repeat: Option<bool>,
+    /// Time in milliseconds within which repeated presses are discarded when
+    /// 'suppress_key_repeat' is enabled. Defaults to 50ms.
+    pub repeat_threshold_ms: Option<u64>,
+    /// Optional list of conditions on the current state of other inputs that
+    /// must all be met for this mapping to be active.
+    pub conditions: Option<Vec<MappingCondition>>,
+    /// If true, this mapping is only active while one of the profile
+    /// 'modifier_caps' is held, and takes priority over regular mappings.
+    pub shift: Option<bool>,
+    /// Optional sequence of target events that are pressed and released one
+    /// after another when the source button is pressed, instead of the
+    /// 'target_events'. Releasing the source button cancels the combo.
+    pub combo: Option<Vec<ComboStep>>,
 }
 
 impl ProfileMapping {
+    /// Returns true if every required source prefix in this mapping matches
+    /// at least one of the given source device ids (e.g. "evdev://event0").
+    /// Mappings without any required sources are always satisfied.
+    pub fn required_sources_satisfied(&self, source_device_ids: &[String]) -> bool {
+        let Some(required_sources) = self.required_sources.as_ref() else {
+            return true;
+        };
+        required_sources.iter().all(|prefix| {
+            source_device_ids
+                .iter()
+                .any(|id| id.starts_with(prefix.as_str()))
+        })
+    }
+
+    /// Returns true if any target event of this mapping snaps the angle of
+    /// the stick, which requires both axes of the stick to be known.
+    pub fn has_angle_snap(&self) -> bool {
+        self.target_events.iter().any(|target| {
+            target
+                .gamepad
+                .as_ref()
+                .and_then(|gamepad| gamepad.axis.as_ref())
+                .and_then(|axis| axis.angle_snap)
+                .is_some()
+        })
+    }
+
+    /// Returns the scroll speed of the first wheel button target event of
+    /// this mapping that has a scroll speed in clicks per second.
+    pub fn scroll_speed(&self) -> Option<u32> {
+        self.target_events.iter().find_map(|target| {
+            let mouse = target.mouse.as_ref()?;
+            let is_wheel = mouse
+                .button
+                .as_ref()
+                .is_some_and(|button| button.starts_with("Wheel"));
+            if !is_wheel {
+                return None;
+            }
+            mouse.scroll_speed.filter(|speed| *speed > 0)
+        })
+    }
+
+    /// Returns true if all conditions of this mapping are met by the given
+    /// last known input values. Mappings without any conditions are always
+    /// satisfied.
+    pub fn conditions_satisfied(&self, values: &HashMap<Capability, InputValue>) -> bool {
+        let Some(conditions) = self.conditions.as_ref() else {
+            return true;
+        };
+        conditions
+            .iter()
+            .all(|condition| condition.is_satisfied(values))
+    }
+
     /// Returns true if the given event matches this profile mapping's source
     /// event. This method assumes that the event capability already matches, so
     /// this should only be called when trying to match specific properties of
@@ -164,6 +404,9 @@ pub struct CapabilityMap {
     pub id: String,
     pub mapping: Vec<CapabilityMapping>,
     //pub filtered_events: Option<Vec<Capability>>,
+    /// Optional list of test cases that verify the translations of this
+    /// capability map
+    pub tests: Option<Vec<CapabilityMapTest>>,
 }
 
 impl CapabilityMap {
@@ -179,6 +422,569 @@ impl CapabilityMap {
         let device: CapabilityMap = serde_yaml::from_reader(file)?;
         Ok(device)
     }
+
+    /// Load the [CapabilityMap] with the given name that was compiled into the
+    /// binary, e.g. "ally_type1".
+    #[cfg(feature = "embedded-profiles")]
+    pub fn from_embedded(name: &str) -> Result<CapabilityMap, LoadError> {
+        let Some(content) = embedded::CAPABILITY_MAPS.get(name) else {
+            return Err(LoadError::NotEmbedded(name.to_string()));
+        };
+        let device: CapabilityMap = serde_yaml::from_str(content)?;
+        Ok(device)
+    }
+
+    /// Generate a [CapabilityMap] from the given HID report descriptor. A
+    /// mapping is generated for every button and axis found in the descriptor
+    /// using the standard HID usage page assignments for gamepads.
+    pub fn from_hid_descriptor(descriptor: &[u8]) -> Result<CapabilityMap, LoadError> {
+        let usages = hid_descriptor::parse_input_usages(descriptor)
+            .map_err(|e| LoadError::HidDescriptorError(e.to_string()))?;
+
+        let mut capabilities = HashSet::new();
+        let mut mapping: Vec<CapabilityMapping> = Vec::new();
+        for usage in usages {
+            let Some(config) = capability_config_from_hid_usage(usage) else {
+                log::trace!("No capability for HID usage: {usage:?}");
+                continue;
+            };
+            // Multiple usages can map to the same capability (e.g. X and Y axes)
+            let capability = Capability::from(config.clone());
+            if capabilities.contains(&capability) {
+                continue;
+            }
+            mapping.push(CapabilityMapping {
+                name: capability.to_capability_string(),
+                source_events: vec![config.clone()],
+                composite_source: None,
+                ordered: None,
+                target_event: config,
+            });
+            capabilities.insert(capability);
+        }
+
+        Ok(CapabilityMap {
+            version: 1,
+            kind: "CapabilityMap".to_string(),
+            name: "HID Descriptor".to_string(),
+            id: "hid_descriptor".to_string(),
+            mapping,
+            tests: None,
+        })
+    }
+
+    /// Check the capability map for common mistakes, such as typos in event
+    /// names, and return a list of warnings describing each problem found.
+    pub fn validate(&self) -> Vec<CapabilityMapWarning> {
+        let mut warnings = Vec::new();
+        let warn = |mapping: &CapabilityMapping, field: &str, kind| CapabilityMapWarning {
+            mapping: mapping.name.clone(),
+            field: field.to_string(),
+            kind,
+        };
+
+        // Graph of source capabilities to the target capabilities they are
+        // translated to, used to detect circular mappings.
+        let mut translations: HashMap<Capability, HashSet<Capability>> = HashMap::new();
+        let mut names = HashSet::new();
+        for mapping in self.mapping.iter() {
+            if !names.insert(mapping.name.as_str()) {
+                warnings.push(warn(
+                    mapping,
+                    "name",
+                    CapabilityMapWarningKind::DuplicateName,
+                ));
+            }
+            // Mappings can consist of only composite source events
+            let has_composite_source = mapping
+                .composite_source
+                .as_ref()
+                .is_some_and(|events| !events.is_empty());
+            if mapping.source_events.is_empty() && !has_composite_source {
+                warnings.push(warn(
+                    mapping,
+                    "source_events",
+                    CapabilityMapWarningKind::EmptySourceEvents,
+                ));
+            }
+
+            let target_cap = Capability::from(mapping.target_event.clone());
+            if target_cap == Capability::NotImplemented {
+                warnings.push(warn(
+                    mapping,
+                    "target_event",
+                    CapabilityMapWarningKind::UnknownTargetEvent,
+                ));
+            }
+
+            let source_fields = mapping
+                .source_events
+                .iter()
+                .enumerate()
+                .map(|(i, event)| (format!("source_events[{i}]"), event))
+                .chain(
+                    mapping
+                        .composite_source
+                        .iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(i, event)| (format!("composite_source[{i}]"), event)),
+                );
+            for (field, event) in source_fields {
+                let source_cap = Capability::from(event.clone());
+                if source_cap == Capability::NotImplemented {
+                    warnings.push(warn(
+                        mapping,
+                        field.as_str(),
+                        CapabilityMapWarningKind::UnknownSourceEvent,
+                    ));
+                    continue;
+                }
+                // Mapping a capability to itself is a passthrough, not a loop
+                if target_cap != Capability::NotImplemented && source_cap != target_cap {
+                    translations
+                        .entry(source_cap)
+                        .or_default()
+                        .insert(target_cap.clone());
+                }
+            }
+        }
+
+        // A mapping is circular if its target can be translated back into
+        // one of its own source events.
+        for mapping in self.mapping.iter() {
+            let target_cap = Capability::from(mapping.target_event.clone());
+            let mut visited = HashSet::new();
+            let mut pending = vec![target_cap];
+            let mut is_circular = false;
+            while let Some(cap) = pending.pop() {
+                if !visited.insert(cap.clone()) {
+                    continue;
+                }
+                let Some(targets) = translations.get(&cap) else {
+                    continue;
+                };
+                for target in targets {
+                    let is_source = mapping
+                        .source_events
+                        .iter()
+                        .any(|event| Capability::from(event.clone()) == *target);
+                    if is_source {
+                        is_circular = true;
+                    }
+                    pending.push(target.clone());
+                }
+            }
+            if is_circular {
+                warnings.push(warn(
+                    mapping,
+                    "target_event",
+                    CapabilityMapWarningKind::CircularMapping,
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// Run all test cases defined in the capability map and return the result
+    /// of each test.
+    pub fn run_tests(&self) -> Vec<TestResult> {
+        self.tests
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, test)| test.run(self, i))
+            .collect()
+    }
+}
+
+/// A test case of a [CapabilityMap] that checks the events emitted for a
+/// sequence of input events
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CapabilityMapTest {
+    pub name: Option<String>,
+    pub input_sequence: Vec<CapabilityMapTestInput>,
+    pub expected_output: Vec<CapabilityMapTestEvent>,
+}
+
+impl CapabilityMapTest {
+    /// Run the test against the given capability map. The index is used to
+    /// name tests without a name.
+    fn run(&self, map: &CapabilityMap, index: usize) -> TestResult {
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("Test {}", index + 1));
+        let output = match self.simulate(map) {
+            Ok(output) => output,
+            Err(message) => {
+                return TestResult {
+                    name,
+                    passed: false,
+                    message: Some(message),
+                };
+            }
+        };
+
+        let passed = output.len() == self.expected_output.len()
+            && output
+                .iter()
+                .zip(self.expected_output.iter())
+                .all(|(actual, expected)| actual.matches(expected));
+        let message = if passed {
+            None
+        } else {
+            Some(format!(
+                "Expected {}, got {}",
+                format_test_events(&self.expected_output),
+                format_test_events(&output)
+            ))
+        };
+
+        TestResult {
+            name,
+            passed,
+            message,
+        }
+    }
+
+    /// Translate the input sequence the same way a composite device would
+    /// and return the emitted events. Inputs are processed in the order of
+    /// their time offsets. Chord windows and source device groups are not
+    /// simulated, so all inputs are treated as coming from a single source
+    /// device.
+    fn simulate(&self, map: &CapabilityMap) -> Result<Vec<CapabilityMapTestEvent>, String> {
+        for expected in self.expected_output.iter() {
+            if Capability::from_str(expected.capability.as_str()).is_err() {
+                return Err(format!(
+                    "Invalid expected capability: {}",
+                    expected.capability
+                ));
+            }
+        }
+
+        // Find the capabilities that are translated by the capability map,
+        // like CompositeDevice::load_capability_map().
+        let mut translatable = HashSet::new();
+        let mut axis_ranges = HashMap::new();
+        for mapping in map.mapping.iter() {
+            let target_cap = Capability::from(mapping.target_event.clone());
+            for source_event in mapping.source_events.iter() {
+                let cap = Capability::from(source_event.clone());
+                let axis_range = source_event
+                    .gamepad
+                    .as_ref()
+                    .and_then(|gamepad| gamepad.axis.as_ref())
+                    .and_then(|axis| axis.axis_range);
+                if let Some(range) = axis_range {
+                    axis_ranges.insert(cap.clone(), range);
+                    if cap == target_cap {
+                        continue;
+                    }
+                }
+                translatable.insert(cap);
+            }
+            for source_event in mapping.composite_source.iter().flatten() {
+                translatable.insert(Capability::from(source_event.clone()));
+            }
+        }
+
+        let mut inputs: Vec<&CapabilityMapTestInput> = self.input_sequence.iter().collect();
+        inputs.sort_by_key(|input| input.ms_offset.unwrap_or_default());
+
+        let mut output = Vec::new();
+        let mut active_inputs: Vec<Capability> = Vec::new();
+        let mut emitted_mappings: HashSet<&str> = HashSet::new();
+        for input in inputs {
+            let Ok(cap) = Capability::from_str(input.capability.as_str()) else {
+                return Err(format!("Invalid input capability: {}", input.capability));
+            };
+            let value = axis_ranges
+                .get(&cap)
+                .map(|range| range.normalize(input.value))
+                .unwrap_or(input.value);
+
+            // Events that are not translated pass through unchanged
+            if !translatable.contains(&cap) {
+                output.push(CapabilityMapTestEvent {
+                    capability: input.capability.clone(),
+                    value,
+                });
+                continue;
+            }
+
+            let pressed = value != 0.0;
+            let idx = active_inputs.iter().position(|c| c == &cap);
+            match (pressed, idx) {
+                (true, None) => active_inputs.push(cap),
+                (false, Some(idx)) => {
+                    active_inputs.remove(idx);
+                }
+                _ => continue,
+            }
+
+            // Test inputs do not specify a source device, so every input is
+            // treated as coming from its own source device.
+            let active_devices: HashMap<Capability, HashSet<String>> = active_inputs
+                .iter()
+                .map(|cap| (cap.clone(), HashSet::from([cap.to_capability_string()])))
+                .collect();
+
+            for mapping in map.mapping.iter() {
+                let target_cap = Capability::from(mapping.target_event.clone());
+                if target_cap == Capability::NotImplemented {
+                    continue;
+                }
+                let composite_source_active = mapping.composite_source_active(&active_devices);
+
+                if !pressed
+                    && emitted_mappings.contains(mapping.name.as_str())
+                    && mapping.is_released(&active_inputs, composite_source_active)
+                {
+                    emitted_mappings.remove(mapping.name.as_str());
+                    output.push(CapabilityMapTestEvent {
+                        capability: target_cap.to_capability_string(),
+                        value: 0.0,
+                    });
+                }
+
+                if pressed
+                    && mapping.source_events_active(&active_inputs)
+                    && composite_source_active
+                {
+                    emitted_mappings.insert(mapping.name.as_str());
+                    output.push(CapabilityMapTestEvent {
+                        capability: target_cap.to_capability_string(),
+                        value: 1.0,
+                    });
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// An input event of a [CapabilityMapTest]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CapabilityMapTestInput {
+    /// Capability string of the event, e.g. "Gamepad:Button:South"
+    pub capability: String,
+    /// Value of the event. Buttons are pressed with any non-zero value.
+    pub value: f64,
+    /// Time in milliseconds since the start of the test at which the event
+    /// is sent
+    pub ms_offset: Option<u64>,
+}
+
+/// An event emitted by a [CapabilityMap] in a [CapabilityMapTest]. Pressed
+/// and released buttons have the values 1.0 and 0.0.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CapabilityMapTestEvent {
+    /// Capability string of the event, e.g. "Gamepad:Button:Guide"
+    pub capability: String,
+    pub value: f64,
+}
+
+impl CapabilityMapTestEvent {
+    /// Returns true if this event has the same capability and value as the
+    /// given event
+    fn matches(&self, other: &CapabilityMapTestEvent) -> bool {
+        let capability = Capability::from_str(self.capability.as_str());
+        let other_capability = Capability::from_str(other.capability.as_str());
+        capability.is_ok()
+            && capability == other_capability
+            && (self.value - other.value).abs() < 1e-6
+    }
+}
+
+/// Format the given test events as a list of "capability=value" pairs
+fn format_test_events(events: &[CapabilityMapTestEvent]) -> String {
+    let events: Vec<String> = events
+        .iter()
+        .map(|event| format!("{}={}", event.capability, event.value))
+        .collect();
+    format!("[{}]", events.join(", "))
+}
+
+/// Result of running a [CapabilityMapTest]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// Name of the test
+    pub name: String,
+    pub passed: bool,
+    /// Description of why the test failed, including the emitted events
+    pub message: Option<String>,
+}
+
+/// A problem found in a [CapabilityMap] by [CapabilityMap::validate]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityMapWarning {
+    /// Name of the mapping with the problem
+    pub mapping: String,
+    /// Field of the mapping with the problem, e.g. "source_events[0]"
+    pub field: String,
+    pub kind: CapabilityMapWarningKind,
+}
+
+impl fmt::Display for CapabilityMapWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Mapping '{}' field '{}': {}",
+            self.mapping, self.field, self.kind
+        )
+    }
+}
+
+/// Kinds of problems that can be found in a [CapabilityMap]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapabilityMapWarningKind {
+    UnknownSourceEvent,
+    UnknownTargetEvent,
+    DuplicateName,
+    EmptySourceEvents,
+    CircularMapping,
+}
+
+impl fmt::Display for CapabilityMapWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::UnknownSourceEvent => "unknown or unimplemented source event",
+            Self::UnknownTargetEvent => "unknown or unimplemented target event",
+            Self::DuplicateName => "another mapping has the same name",
+            Self::EmptySourceEvents => "no source events are defined",
+            Self::CircularMapping => "target event is translated back into a source event",
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// Returns the gamepad [CapabilityConfig] for the given standard HID usage
+pub fn capability_config_from_hid_usage(usage: Usage) -> Option<CapabilityConfig> {
+    let mut gamepad = GamepadCapability {
+        axis: None,
+        button: None,
+        trigger: None,
+        gyro: None,
+    };
+    match usage.page {
+        USAGE_PAGE_BUTTON => {
+            let button = match usage.id {
+                1 => "South",
+                2 => "East",
+                3 => "North",
+                4 => "West",
+                5 => "LeftBumper",
+                6 => "RightBumper",
+                7 => "LeftTrigger",
+                8 => "RightTrigger",
+                9 => "Select",
+                10 => "Start",
+                11 => "LeftStick",
+                12 => "RightStick",
+                13 => "Guide",
+                _ => return None,
+            };
+            gamepad.button = Some(button.to_string());
+        }
+        USAGE_PAGE_GENERIC_DESKTOP => match usage.id {
+            // X, Y
+            0x30 | 0x31 => {
+                gamepad.axis = Some(AxisCapability {
+                    name: "LeftStick".to_string(),
+                    direction: None,
+                    deadzone: None,
+                    curve: None,
+                    angle_snap: None,
+                    input_min: None,
+                    input_max: None,
+                    output_min: None,
+                    output_max: None,
+                    source_range: None,
+                    target_range: None,
+                    axis_range: None,
+                })
+            }
+            // Rx, Ry
+            0x33 | 0x34 => {
+                gamepad.axis = Some(AxisCapability {
+                    name: "RightStick".to_string(),
+                    direction: None,
+                    deadzone: None,
+                    curve: None,
+                    angle_snap: None,
+                    input_min: None,
+                    input_max: None,
+                    output_min: None,
+                    output_max: None,
+                    source_range: None,
+                    target_range: None,
+                    axis_range: None,
+                })
+            }
+            // Z, Rz
+            0x32 | 0x35 => {
+                let name = if usage.id == 0x32 {
+                    "LeftTrigger"
+                } else {
+                    "RightTrigger"
+                };
+                gamepad.trigger = Some(TriggerCapability {
+                    name: name.to_string(),
+                    deadzone: None,
+                    input_min: None,
+                    input_max: None,
+                    output_min: None,
+                    output_max: None,
+                })
+            }
+            // Hat switch
+            0x39 => {
+                gamepad.axis = Some(AxisCapability {
+                    name: "Hat0".to_string(),
+                    direction: None,
+                    deadzone: None,
+                    curve: None,
+                    angle_snap: None,
+                    input_min: None,
+                    input_max: None,
+                    output_min: None,
+                    output_max: None,
+                    source_range: None,
+                    target_range: None,
+                    axis_range: None,
+                })
+            }
+            // D-pad Up, Down, Right, Left
+            0x90..=0x93 => {
+                let button = match usage.id {
+                    0x90 => "DPadUp",
+                    0x91 => "DPadDown",
+                    0x92 => "DPadRight",
+                    _ => "DPadLeft",
+                };
+                gamepad.button = Some(button.to_string());
+            }
+            _ => return None,
+        },
+        _ => return None,
+    }
+
+    Some(CapabilityConfig {
+        gamepad: Some(gamepad),
+        keyboard: None,
+        keysym: None,
+        mouse: None,
+        dbus: None,
+        touchpad: None,
+        touchscreen: None,
+        gesture: None,
+    })
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -186,18 +992,226 @@ impl CapabilityMap {
 pub struct CapabilityMapping {
     pub name: String,
     pub source_events: Vec<CapabilityConfig>,
+    /// Optional list of source events that must ALL be active at the same time
+    /// to trigger the mapping. Unlike `source_events`, these events may be
+    /// emitted by different physical source devices (e.g. a knob that sends
+    /// both an evdev and a hidraw event for the same action).
+    pub composite_source: Option<Vec<CapabilityConfig>>,
+    /// If true, the source events must be pressed in the order they are
+    /// defined in order to trigger the mapping.
+    pub ordered: Option<bool>,
+    /// Optional time in milliseconds after the first source event is pressed
+    /// in which all other source events must be pressed. If the window passes
+    /// before the chord is complete, the pressed source events are emitted
+    /// as individual events instead.
+    pub chord_window_ms: Option<u64>,
+    /// Optional list of source device id prefixes (e.g. "evdev://",
+    /// "hidraw://hidraw1") that are treated as a single logical source. If
+    /// defined, only source events pressed on a matching source device are
+    /// used to trigger the mapping.
+    pub source_device_group: Option<Vec<String>>,
     pub target_event: CapabilityConfig,
 }
 
+impl CapabilityMapping {
+    /// Returns the capabilities of all implemented source events of this
+    /// mapping.
+    pub fn source_capabilities(&self) -> Vec<Capability> {
+        self.source_events
+            .iter()
+            .map(|event| event.clone().into())
+            .filter(|cap| *cap != Capability::NotImplemented)
+            .collect()
+    }
+
+    /// Returns true if all source events of this mapping are in the given list
+    /// of active inputs. If the mapping is ordered, the source events must also
+    /// have been pressed in the same order as they are defined in the mapping.
+    pub fn source_events_active(&self, active_inputs: &[Capability]) -> bool {
+        let source_caps = self.source_capabilities();
+
+        if !self.ordered.unwrap_or(false) {
+            return source_caps.iter().all(|cap| active_inputs.contains(cap));
+        }
+
+        // Compare the press order of the source events element-wise
+        let pressed: Vec<&Capability> = active_inputs
+            .iter()
+            .filter(|cap| source_caps.contains(cap))
+            .collect();
+        if pressed.len() != source_caps.len() {
+            return false;
+        }
+        pressed
+            .into_iter()
+            .zip(source_caps.iter())
+            .all(|(pressed, source)| pressed == source)
+    }
+
+    /// Returns true if every composite source event of this mapping is active
+    /// on a different source device, given the ids of the source devices each
+    /// active capability is pressed on. Mappings without composite source
+    /// events are always active.
+    pub fn composite_source_active(
+        &self,
+        active_devices: &HashMap<Capability, HashSet<String>>,
+    ) -> bool {
+        let Some(composite_source) = self.composite_source.as_ref() else {
+            return true;
+        };
+        let caps: Vec<Capability> = composite_source
+            .iter()
+            .map(|event| event.clone().into())
+            .filter(|cap| *cap != Capability::NotImplemented)
+            .collect();
+        assign_source_devices(&caps, active_devices, &mut Vec::new())
+    }
+
+    /// Returns true if the target event of this mapping should be released
+    /// after a source event was released. A mapping is released once none of
+    /// its source events are active anymore, or as soon as its composite
+    /// source events are no longer active.
+    pub fn is_released(&self, active_inputs: &[Capability], composite_source_active: bool) -> bool {
+        let source_caps = self.source_capabilities();
+        let has_source_event_pressed = source_caps.iter().any(|cap| active_inputs.contains(cap));
+        if self.composite_source.is_none() {
+            return !has_source_event_pressed;
+        }
+        // Mappings can consist of only composite source events
+        let source_events_released = !source_caps.is_empty() && !has_source_event_pressed;
+        source_events_released || !composite_source_active
+    }
+}
+
+/// Assign each of the given capabilities to a different source device it is
+/// active on, skipping the given devices that are already assigned. Returns
+/// true if every capability could be assigned.
+fn assign_source_devices<'a>(
+    caps: &[Capability],
+    active_devices: &'a HashMap<Capability, HashSet<String>>,
+    assigned: &mut Vec<&'a str>,
+) -> bool {
+    let Some((cap, remaining)) = caps.split_first() else {
+        return true;
+    };
+    let Some(devices) = active_devices.get(cap) else {
+        return false;
+    };
+    for device in devices.iter() {
+        if assigned.contains(&device.as_str()) {
+            continue;
+        }
+        assigned.push(device.as_str());
+        if assign_source_devices(remaining, active_devices, assigned) {
+            return true;
+        }
+        assigned.pop();
+    }
+    false
+}
+
+/// Defines how many times the source button of a [ProfileMapping] must be
+/// tapped to activate the mapping.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct TapDance {
+    /// Number of taps that activate the mapping
+    pub taps: u32,
+    /// Time in milliseconds after the last tap to wait for another tap before
+    /// the tap count is resolved
+    pub window_ms: u64,
+}
+
+/// A single step of a combo mapping
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ComboStep {
+    /// Target event to press
+    pub capability: CapabilityConfig,
+    /// Time in milliseconds to hold the target event
+    pub press_ms: u64,
+    /// Time in milliseconds to wait after releasing the target event before
+    /// the next step
+    pub release_ms: u64,
+}
+
+/// Defines a condition on the current state of an input that must be met for a
+/// [ProfileMapping] to be active.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingCondition {
+    /// The value of the given axis must be above the threshold
+    AxisAbove {
+        capability: CapabilityConfig,
+        threshold: f64,
+    },
+    /// The value of the given axis must be below the threshold
+    AxisBelow {
+        capability: CapabilityConfig,
+        threshold: f64,
+    },
+    /// The given button must be pressed
+    ButtonPressed { capability: CapabilityConfig },
+}
+
+impl MappingCondition {
+    /// Returns true if this condition is met by the given last known input
+    /// values. Inputs without a known value are treated as released or
+    /// centered.
+    pub fn is_satisfied(&self, values: &HashMap<Capability, InputValue>) -> bool {
+        match self {
+            MappingCondition::AxisAbove {
+                capability,
+                threshold,
+            } => Self::axis_value(capability, values) > *threshold,
+            MappingCondition::AxisBelow {
+                capability,
+                threshold,
+            } => Self::axis_value(capability, values) < *threshold,
+            MappingCondition::ButtonPressed { capability } => {
+                let cap: Capability = capability.clone().into();
+                values.get(&cap).is_some_and(|value| value.pressed())
+            }
+        }
+    }
+
+    /// Returns the current value of the given axis. Single axes use their
+    /// signed value, while sticks and other multi-axis inputs use the
+    /// magnitude of their deflection.
+    fn axis_value(capability: &CapabilityConfig, values: &HashMap<Capability, InputValue>) -> f64 {
+        let cap: Capability = capability.clone().into();
+        match values.get(&cap) {
+            Some(InputValue::Bool(value)) => f64::from(u8::from(*value)),
+            Some(InputValue::Float(value)) => *value,
+            Some(InputValue::Vector2 { x, y }) => {
+                x.unwrap_or_default().hypot(y.unwrap_or_default())
+            }
+            Some(InputValue::Vector3 { x, y, z }) => {
+                let (x, y, z) = (
+                    x.unwrap_or_default(),
+                    y.unwrap_or_default(),
+                    z.unwrap_or_default(),
+                );
+                (x * x + y * y + z * z).sqrt()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct CapabilityConfig {
     pub gamepad: Option<GamepadCapability>,
     pub keyboard: Option<String>,
+    /// XKB keysym name that is translated to a key code using the keyboard
+    /// layout of the system (e.g. "udiaeresis")
+    pub keysym: Option<String>,
     pub mouse: Option<MouseCapability>,
     pub dbus: Option<String>,
     pub touchpad: Option<TouchpadCapability>,
     pub touchscreen: Option<TouchCapability>,
+    pub gesture: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -215,6 +1229,74 @@ pub struct AxisCapability {
     pub name: String,
     pub direction: Option<String>,
     pub deadzone: Option<f64>,
+    /// Optional response curve to apply when translating to this axis
+    pub curve: Option<AxisCurve>,
+    /// Optional number of evenly spaced directions to snap the angle of the
+    /// stick to when translating to this axis (e.g. 8 to emulate an octagonal
+    /// gate)
+    pub angle_snap: Option<u32>,
+    /// Optional minimum and maximum source values when translating to this
+    /// axis. Source values are clamped to this range and rescaled to the
+    /// full range.
+    pub input_min: Option<f64>,
+    pub input_max: Option<f64>,
+    /// Optional range the translated axis value is clamped to after all
+    /// other transformations.
+    pub output_min: Option<f64>,
+    pub output_max: Option<f64>,
+    /// Optional source range that is linearly remapped into the target range
+    /// when translating to this axis (e.g. [0.3, 1.0] to only use the upper
+    /// part of a trigger). Source values outside of this range are clamped.
+    pub source_range: Option<[f64; 2]>,
+    /// Optional range the source range is linearly remapped into when
+    /// translating to this axis (e.g. [0.0, 1.0] to only use the positive
+    /// half of the axis).
+    pub target_range: Option<[f64; 2]>,
+    /// Optional range of the raw values the source device reports for this
+    /// axis when used as a capability map source event. Unsigned values are
+    /// remapped to the signed range with the midpoint as zero.
+    pub axis_range: Option<AxisRange>,
+}
+
+/// Range of the raw values a source device reports for an axis
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisRange {
+    /// Values between 0 and 255
+    UnsignedByte,
+    /// Values between 0 and 65535
+    UnsignedShort,
+    /// Values that are already centered around zero
+    Signed,
+}
+
+impl AxisRange {
+    /// Remap the given raw axis value in this range to a value between -1.0
+    /// and 1.0 with the midpoint of the range as zero. Signed values are
+    /// returned unchanged.
+    pub fn normalize(&self, value: f64) -> f64 {
+        let max = match self {
+            AxisRange::UnsignedByte => u8::MAX as f64,
+            AxisRange::UnsignedShort => u16::MAX as f64,
+            AxisRange::Signed => return value,
+        };
+        (value / max * 2.0 - 1.0).clamp(-1.0, 1.0)
+    }
+}
+
+/// Response curve applied to axis values. Curves are applied to the magnitude
+/// of each axis value, so the sign of the value is preserved.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisCurve {
+    /// Output is the same as the input
+    Linear,
+    /// Output is the square of the input for more precision near the center
+    Quadratic,
+    /// CSS style cubic bezier curve with the control points (x1, y1, x2, y2)
+    CubicBezier(f64, f64, f64, f64),
+    /// Smooth S-curve with more precision near the center and the edges
+    SCurve,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -222,6 +1304,15 @@ pub struct AxisCapability {
 pub struct TriggerCapability {
     pub name: String,
     pub deadzone: Option<f64>,
+    /// Optional minimum and maximum source values when translating to this
+    /// trigger. Source values are clamped to this range and rescaled to the
+    /// full range.
+    pub input_min: Option<f64>,
+    pub input_max: Option<f64>,
+    /// Optional range the translated trigger value is clamped to after all
+    /// other transformations.
+    pub output_min: Option<f64>,
+    pub output_max: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -237,7 +1328,16 @@ pub struct GyroCapability {
 #[serde(rename_all = "snake_case")]
 pub struct MouseCapability {
     pub button: Option<String>,
+    /// Two clicks of the given mouse button in quick succession. Only used
+    /// as a source event.
+    pub double_click: Option<String>,
     pub motion: Option<MouseMotionCapability>,
+    /// Absolute mouse position on the screen, e.g. to position the mouse
+    /// where the touchscreen was touched
+    pub absolute_motion: Option<bool>,
+    /// Number of scroll wheel clicks per second to emit while the source
+    /// event is held. Only used for wheel buttons (e.g. "WheelUp").
+    pub scroll_speed: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -259,6 +1359,8 @@ pub struct TouchpadCapability {
 pub struct TouchCapability {
     pub button: Option<String>,
     pub motion: Option<TouchMotionCapability>,
+    /// Velocity of the finger on the touch surface
+    pub velocity: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -269,14 +1371,14 @@ pub struct TouchMotionCapability {
 }
 
 /// Defines a platform match for loading a [CompositeDevice]
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Match {
     pub dmi_data: Option<DMIMatch>,
 }
 
 /// Match DMI data for loading a [CompositeDevice]
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct DMIMatch {
     pub bios_release: Option<String>,
@@ -290,19 +1392,94 @@ pub struct DMIMatch {
     pub cpu_vendor: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct SourceDevice {
     pub group: String,
     pub evdev: Option<Evdev>,
     pub hidraw: Option<Hidraw>,
     pub iio: Option<IIO>,
+    /// Network stream that receives input events from a remote host
+    pub network: Option<Network>,
+    /// Treat the matching evdev device as a multi-touch touchscreen
+    pub touchscreen: Option<Touchscreen>,
     pub unique: Option<bool>,
     pub blocked: Option<bool>,
     pub ignore: Option<bool>,
+    /// Events from higher priority source devices will block the same events
+    /// from lower priority source devices for a short time. If a priority is
+    /// configured for any source device, capabilities provided by multiple
+    /// source devices are only accepted from the highest priority one.
+    /// Defaults to 0.
+    pub priority: Option<u8>,
+    /// Filters applied in order to events from the source device before they
+    /// are translated.
+    pub filters: Option<Vec<FilterConfig>>,
+    /// USB bus number the device must be connected to. E.g. "1"
+    pub usb_bus: Option<String>,
+    /// USB port path the device must be connected to on its bus. E.g. "2.3"
+    /// for port 3 of a hub connected to port 2.
+    pub usb_port: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+impl SourceDevice {
+    /// Returns true if the source device config only matches devices
+    /// connected to a specific USB bus or port.
+    pub fn has_usb_topology(&self) -> bool {
+        self.usb_bus.is_some() || self.usb_port.is_some()
+    }
+
+    /// Returns true if the given device is connected to the USB bus and port
+    /// defined in the source device config. Always returns true if neither
+    /// is defined.
+    pub fn matches_usb_topology(&self, device: &UdevDevice) -> bool {
+        if !self.has_usb_topology() {
+            return true;
+        }
+        let Some((bus, port)) = device.usb_topology() else {
+            return false;
+        };
+        if let Some(usb_bus) = self.usb_bus.as_ref() {
+            log::trace!("Checking USB bus: {usb_bus} against {bus}");
+            if usb_bus != &bus {
+                return false;
+            }
+        }
+        if let Some(usb_port) = self.usb_port.as_ref() {
+            log::trace!("Checking USB port: {usb_port} against {port}");
+            if usb_port != &port {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Defines a filter applied to events from a source device. Filters without a
+/// capability apply to all events from the source device.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterConfig {
+    /// Set analog values within the deadzone to zero
+    Deadzone {
+        capability: Option<String>,
+        deadzone: f64,
+    },
+    /// Drop analog events that changed less than the threshold
+    Threshold {
+        capability: Option<String>,
+        threshold: f64,
+    },
+    /// Invert the given axes of analog events. All axes are inverted by default.
+    Invert {
+        capability: Option<String>,
+        x: Option<bool>,
+        y: Option<bool>,
+        z: Option<bool>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Evdev {
     pub name: Option<String>,
@@ -310,9 +1487,18 @@ pub struct Evdev {
     pub handler: Option<String>,
     pub vendor_id: Option<String>,
     pub product_id: Option<String>,
+    pub mouse_sensitivity: Option<MouseSensitivity>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Multipliers applied to relative mouse motion from an evdev source device
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MouseSensitivity {
+    pub x_sensitivity: f64,
+    pub y_sensitivity: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Hidraw {
     pub vendor_id: Option<u16>,
@@ -322,16 +1508,50 @@ pub struct Hidraw {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct IIO {
     pub id: Option<String>,
     pub name: Option<String>,
     pub mount_matrix: Option<MountMatrix>,
+    /// Maximum rate in Hz at which events are emitted for each capability
+    pub max_hz: Option<u32>,
+    /// Optional time window in microseconds in which events with the same
+    /// capability are coalesced into a single event with the latest value
+    pub coalesce_window_us: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Configuration for an evdev multi-touch touchscreen source device
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Touchscreen {
+    /// Regions of the touchscreen that emit a gamepad button when touched
+    pub button_zones: Option<Vec<TouchButtonZone>>,
+}
+
+/// Rectangular region of a touchscreen that emits a gamepad button while it
+/// is touched. Coordinates are normalized between 0.0 and 1.0, where (0, 0)
+/// is the top-left corner of the touchscreen.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchButtonZone {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Name of the gamepad button to emit (e.g. "South")
+    pub button: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Network {
+    /// Address to listen on for input events (e.g. "0.0.0.0:27020")
+    pub address: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct MountMatrix {
@@ -341,7 +1561,7 @@ pub struct MountMatrix {
 }
 
 /// Defines a combined device
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct CompositeDeviceConfig {
     pub version: u32,
@@ -352,6 +1572,50 @@ pub struct CompositeDeviceConfig {
     pub capability_map_id: Option<String>,
     pub source_devices: Vec<SourceDevice>,
     pub target_devices: Option<Vec<String>>,
+    pub reconnect: Option<ReconnectPolicy>,
+    /// Whether or not gamepad buttons should also emit the keyboard keys
+    /// Steam emits for controllers in legacy mode (e.g. "South" as "Enter"),
+    /// for games that only support Steam's legacy controller mode.
+    pub legacy_steam_mode: Option<bool>,
+    /// Optional routing of output events to source devices. Maps the kind of
+    /// output event to a list of source device id prefixes (e.g. "evdev://",
+    /// "hidraw://hidraw1") that should receive it. Output events of kinds
+    /// without a route are sent to all source devices.
+    pub output_routing: Option<HashMap<OutputEventKind, Vec<String>>>,
+    /// Optional thresholds at which source devices are reported as degraded
+    pub source_health: Option<SourceHealthConfig>,
+    /// Path to the file the config was loaded from, if any. This is used to
+    /// reload the config at runtime.
+    #[serde(skip)]
+    pub path: Option<String>,
+}
+
+/// Defines how a [CompositeDevice] should try to reconnect evdev source
+/// devices that stop unexpectedly (e.g. a Bluetooth controller briefly
+/// disconnecting).
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ReconnectPolicy {
+    /// Number of times to wait for the device to reappear before giving up
+    pub reconnect_attempts: u32,
+    /// Amount of time in milliseconds to wait for the device on each attempt
+    pub reconnect_delay_ms: u64,
+}
+
+/// Defines the thresholds at which a source device of a [CompositeDevice] is
+/// reported as degraded. Thresholds that are not set use their default value.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SourceHealthConfig {
+    /// Time in milliseconds without events from a continuously reporting
+    /// source device before an event gap is reported
+    pub event_gap_ms: Option<u64>,
+    /// Average event latency in milliseconds above which a source device is
+    /// degraded
+    pub max_latency_ms: Option<f32>,
+    /// Ratio of failed source commands between 0.0 and 1.0 above which a
+    /// source device is degraded
+    pub max_error_rate: Option<f32>,
 }
 
 impl CompositeDeviceConfig {
@@ -363,8 +1627,9 @@ impl CompositeDeviceConfig {
 
     /// Load a [CompositeDevice] from the given YAML file
     pub fn from_yaml_file(path: String) -> Result<CompositeDeviceConfig, LoadError> {
-        let file = std::fs::File::open(path)?;
-        let device: CompositeDeviceConfig = serde_yaml::from_reader(file)?;
+        let file = std::fs::File::open(&path)?;
+        let mut device: CompositeDeviceConfig = serde_yaml::from_reader(file)?;
+        device.path = Some(path);
         Ok(device)
     }
 
@@ -386,38 +1651,50 @@ impl CompositeDeviceConfig {
 
     /// Returns a [SourceDevice] if it matches the given [UdevDevice].
     pub fn get_matching_device(&self, udevice: &UdevDevice) -> Option<SourceDevice> {
+        let index = self.get_matching_device_index(udevice)?;
+        self.source_devices.get(index).cloned()
+    }
+
+    /// Returns the index of the source device config that matches the given
+    /// udev device. If priorities are configured, source devices listed
+    /// earlier in the config take precedence over source devices with the same
+    /// priority when both provide the same capability.
+    /// If multiple source device configs match, configs that match the USB
+    /// topology of the device are preferred so identical devices (e.g. arcade
+    /// sticks with the same vendor and product id) can use different configs.
+    pub fn get_matching_device_index(&self, udevice: &UdevDevice) -> Option<usize> {
         let subsystem = udevice.subsystem();
-        match subsystem.as_str() {
-            "input" => {
-                for config in self.source_devices.iter() {
-                    if let Some(evdev_config) = config.evdev.as_ref() {
-                        if self.has_matching_evdev(udevice, evdev_config) {
-                            return Some(config.clone());
-                        }
-                    }
-                }
-            }
-            "hidraw" => {
-                for config in self.source_devices.iter() {
-                    if let Some(hidraw_config) = config.hidraw.as_ref() {
-                        if self.has_matching_hidraw(udevice, hidraw_config) {
-                            return Some(config.clone());
-                        }
-                    }
-                }
+        let is_match = |config: &SourceDevice| match subsystem.as_str() {
+            "input" => config
+                .evdev
+                .as_ref()
+                .is_some_and(|evdev_config| self.has_matching_evdev(udevice, evdev_config)),
+            "hidraw" => config
+                .hidraw
+                .as_ref()
+                .is_some_and(|hidraw_config| self.has_matching_hidraw(udevice, hidraw_config)),
+            "iio" => config
+                .iio
+                .as_ref()
+                .is_some_and(|iio_config| self.has_matching_iio(udevice, iio_config)),
+            "network" => config
+                .network
+                .as_ref()
+                .is_some_and(|network_config| network_config.address == udevice.sysname()),
+            _ => false,
+        };
+
+        let mut first_match = None;
+        for (index, config) in self.source_devices.iter().enumerate() {
+            if !config.matches_usb_topology(udevice) || !is_match(config) {
+                continue;
             }
-            "iio" => {
-                for config in self.source_devices.iter() {
-                    if let Some(iio_config) = config.iio.as_ref() {
-                        if self.has_matching_iio(udevice, iio_config) {
-                            return Some(config.clone());
-                        }
-                    }
-                }
+            if config.has_usb_topology() {
+                return Some(index);
             }
-            _ => (),
-        };
-        None
+            first_match.get_or_insert(index);
+        }
+        first_match
     }
 
     /// Returns true if a given hidraw device is within a list of hidraw configs.
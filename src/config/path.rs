@@ -1,6 +1,6 @@
 //! Module for searching for InputPlumber config files
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Base system fallback path to use if one cannot be found with XDG
 const FALLBACK_BASE_PATH: &str = "/usr/share/inputplumber";
@@ -42,6 +42,18 @@ pub fn get_devices_paths() -> Vec<PathBuf> {
     paths
 }
 
+/// Returns true if the given path is a file inside one of the device
+/// configuration directories returned by [get_devices_paths].
+pub fn is_in_devices_paths(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    get_devices_paths()
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| path.starts_with(dir))
+}
+
 /// Returns a list of directories in preference order to find capability map configs.
 /// E.g. ["/etc/inputplumber/capability_maps.d", "/usr/share/inputplumber/capability_maps"]
 pub fn get_capability_maps_paths() -> Vec<PathBuf> {
@@ -53,3 +65,15 @@ pub fn get_capability_maps_paths() -> Vec<PathBuf> {
 
     paths
 }
+
+/// Returns the path to the user config file that maps composite device names
+/// to the device profile the user assigned to them.
+/// E.g. "/root/.config/inputplumber/device_profiles.yaml"
+pub fn get_profile_assignments_path() -> PathBuf {
+    let Ok(base_dirs) = xdg::BaseDirectories::with_prefix("inputplumber") else {
+        log::warn!("Unable to determine user config path. Using fallback path.");
+        return PathBuf::from("/etc/inputplumber/device_profiles.yaml");
+    };
+
+    base_dirs.get_config_home().join("device_profiles.yaml")
+}
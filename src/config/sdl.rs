@@ -0,0 +1,206 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::input::capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger};
+
+use super::{AxisCapability, CapabilityConfig, LoadError, TriggerCapability};
+
+/// Map of SDL GameController database style names to their [Capability]
+/// equivalent. This allows profiles to be written with names like "a" or
+/// "leftshoulder" instead of the InputPlumber capability names.
+pub static SDL_ALIASES: LazyLock<HashMap<&'static str, Capability>> = LazyLock::new(|| {
+    HashMap::from([
+        (
+            "a",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        ),
+        (
+            "b",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+        ),
+        (
+            "x",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+        ),
+        (
+            "y",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+        ),
+        (
+            "back",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+        ),
+        (
+            "guide",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+        ),
+        (
+            "start",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+        ),
+        (
+            "leftstick",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+        ),
+        (
+            "rightstick",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+        ),
+        (
+            "leftshoulder",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+        ),
+        (
+            "rightshoulder",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+        ),
+        (
+            "dpup",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+        ),
+        (
+            "dpdown",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+        ),
+        (
+            "dpleft",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+        ),
+        (
+            "dpright",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+        ),
+        (
+            "misc1",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+        ),
+        (
+            "paddle1",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+        ),
+        (
+            "paddle2",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+        ),
+        (
+            "paddle3",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+        ),
+        (
+            "paddle4",
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+        ),
+        (
+            "lefttrigger",
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+        ),
+        (
+            "righttrigger",
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        ),
+    ])
+});
+
+/// Map of SDL GameController database style half axis names to the stick and
+/// direction they refer to. SDL only uses these names with a "+" or "-" prefix
+/// to select one direction of the axis, e.g. "-leftx" for left stick left.
+pub static SDL_HALF_AXES: LazyLock<HashMap<&'static str, (GamepadAxis, &'static str)>> =
+    LazyLock::new(|| {
+        HashMap::from([
+            ("-leftx", (GamepadAxis::LeftStick, "left")),
+            ("+leftx", (GamepadAxis::LeftStick, "right")),
+            ("-lefty", (GamepadAxis::LeftStick, "up")),
+            ("+lefty", (GamepadAxis::LeftStick, "down")),
+            ("-rightx", (GamepadAxis::RightStick, "left")),
+            ("+rightx", (GamepadAxis::RightStick, "right")),
+            ("-righty", (GamepadAxis::RightStick, "up")),
+            ("+righty", (GamepadAxis::RightStick, "down")),
+        ])
+    });
+
+/// SDL axis names that refer to a single axis of a stick. InputPlumber sticks
+/// are always two dimensional, so these can only be used as half axes.
+const SDL_FULL_AXES: [&str; 4] = ["leftx", "lefty", "rightx", "righty"];
+
+/// Rewrite any SDL style gamepad names in the given [CapabilityConfig] into
+/// their InputPlumber equivalent. SDL names can be used in place of a button,
+/// axis, or trigger name, regardless of which kind of gamepad input the SDL
+/// name actually refers to. Returns an error if a full SDL axis name like
+/// "leftx" is used, since it cannot be mapped to an InputPlumber capability.
+pub fn resolve_aliases(config: &mut CapabilityConfig) -> Result<(), LoadError> {
+    let Some(gamepad) = config.gamepad.as_mut() else {
+        return Ok(());
+    };
+
+    // Find the SDL name that was used, if any
+    let name = if let Some(button) = gamepad.button.as_ref() {
+        button.clone()
+    } else if let Some(axis) = gamepad.axis.as_ref() {
+        axis.name.clone()
+    } else if let Some(trigger) = gamepad.trigger.as_ref() {
+        trigger.name.clone()
+    } else {
+        return Ok(());
+    };
+    if SDL_FULL_AXES.contains(&name.as_str()) {
+        return Err(LoadError::SdlAliasError(format!(
+            "'{name}' refers to a single stick axis, use '+{name}' or '-{name}' instead"
+        )));
+    }
+    if let Some((axis, direction)) = SDL_HALF_AXES.get(name.as_str()) {
+        let previous = gamepad.axis.take();
+        gamepad.axis = Some(axis_capability(axis, Some(direction.to_string()), previous));
+        gamepad.button = None;
+        gamepad.trigger = None;
+        return Ok(());
+    }
+    let Some(capability) = SDL_ALIASES.get(name.as_str()) else {
+        return Ok(());
+    };
+
+    match capability {
+        Capability::Gamepad(Gamepad::Button(button)) => {
+            gamepad.button = Some(button.to_string());
+            gamepad.axis = None;
+            gamepad.trigger = None;
+        }
+        Capability::Gamepad(Gamepad::Trigger(trigger)) => {
+            let previous = gamepad.trigger.take();
+            gamepad.trigger = Some(TriggerCapability {
+                name: trigger.to_string(),
+                deadzone: previous.as_ref().and_then(|t| t.deadzone),
+                input_min: previous.as_ref().and_then(|t| t.input_min),
+                input_max: previous.as_ref().and_then(|t| t.input_max),
+                output_min: previous.as_ref().and_then(|t| t.output_min),
+                output_max: previous.as_ref().and_then(|t| t.output_max),
+            });
+            gamepad.button = None;
+            gamepad.axis = None;
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Returns an [AxisCapability] for the given stick and direction that keeps
+/// the remaining options of the previously configured axis, if any.
+fn axis_capability(
+    axis: &GamepadAxis,
+    direction: Option<String>,
+    previous: Option<AxisCapability>,
+) -> AxisCapability {
+    AxisCapability {
+        name: axis.to_string(),
+        direction,
+        deadzone: previous.as_ref().and_then(|a| a.deadzone),
+        curve: previous.as_ref().and_then(|a| a.curve.clone()),
+        angle_snap: previous.as_ref().and_then(|a| a.angle_snap),
+        input_min: previous.as_ref().and_then(|a| a.input_min),
+        input_max: previous.as_ref().and_then(|a| a.input_max),
+        output_min: previous.as_ref().and_then(|a| a.output_min),
+        output_max: previous.as_ref().and_then(|a| a.output_max),
+        source_range: previous.as_ref().and_then(|a| a.source_range),
+        target_range: previous.as_ref().and_then(|a| a.target_range),
+        axis_range: previous.as_ref().and_then(|a| a.axis_range),
+    }
+}
@@ -0,0 +1,79 @@
+use std::error::Error;
+
+use crate::config::{DeviceProfile, LoadError};
+
+const SDL_PROFILE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: SDL
+mapping:
+  - name: A
+    source_event:
+      gamepad:
+        button: a
+    target_events:
+      - gamepad:
+          button: leftshoulder
+  - name: Trigger
+    source_event:
+      gamepad:
+        button: back
+    target_events:
+      - gamepad:
+          button: righttrigger
+  - name: Half axis
+    source_event:
+      gamepad:
+        button: dpleft
+    target_events:
+      - gamepad:
+          axis:
+            name: -leftx
+  - name: Half axis options
+    source_event:
+      gamepad:
+        axis:
+          name: +righty
+          deadzone: 0.3
+    target_events:
+      - gamepad:
+          button: South
+"#;
+
+#[tokio::test]
+async fn test_sdl_aliases() -> Result<(), Box<dyn Error>> {
+    let profile = DeviceProfile::from_yaml(SDL_PROFILE.to_string())?;
+    let mapping = &profile.mapping;
+
+    let source = mapping[0].source_event.gamepad.as_ref().unwrap();
+    assert_eq!(source.button.as_deref(), Some("South"));
+    let target = mapping[0].target_events[0].gamepad.as_ref().unwrap();
+    assert_eq!(target.button.as_deref(), Some("LeftBumper"));
+
+    // SDL names can be used in place of another kind of gamepad input
+    let target = mapping[1].target_events[0].gamepad.as_ref().unwrap();
+    assert!(target.button.is_none());
+    let trigger = target.trigger.as_ref().unwrap();
+    assert_eq!(trigger.name, "RightTrigger");
+
+    let target = mapping[2].target_events[0].gamepad.as_ref().unwrap();
+    let axis = target.axis.as_ref().unwrap();
+    assert_eq!(axis.name, "LeftStick");
+    assert_eq!(axis.direction.as_deref(), Some("left"));
+
+    // Axis options are kept when a half axis name is resolved
+    let source = mapping[3].source_event.gamepad.as_ref().unwrap();
+    let axis = source.axis.as_ref().unwrap();
+    assert_eq!(axis.name, "RightStick");
+    assert_eq!(axis.direction.as_deref(), Some("down"));
+    assert_eq!(axis.deadzone, Some(0.3));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sdl_full_axis_rejected() {
+    let profile = SDL_PROFILE.replace("name: -leftx", "name: leftx");
+    let result = DeviceProfile::from_yaml(profile);
+    assert!(matches!(result, Err(LoadError::SdlAliasError(_))));
+}
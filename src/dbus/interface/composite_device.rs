@@ -1,17 +1,55 @@
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    os::{fd::OwnedFd, unix::net::UnixStream},
+    str::FromStr,
+};
 
+use tokio::{io::AsyncReadExt, sync::mpsc};
 use zbus::{
     fdo,
+    message::Header,
+    object_server::SignalContext,
     zvariant::{self, Value},
+    Connection,
 };
 use zbus_macros::interface;
 
-use crate::input::{
-    capability::{Capability, Gamepad, Mouse},
-    composite_device::{client::CompositeDeviceClient, InterceptMode},
-    event::{native::NativeEvent, value::InputValue},
+use crate::{
+    dbus::privilege::ensure_privileged,
+    input::{
+        capability::Capability,
+        composite_device::{
+            client::CompositeDeviceClient, HookPoint, InterceptChord, InterceptMode,
+        },
+        event::{native::NativeEvent, value::InputValue},
+        event_log::{event_log_path, EventLogRecord},
+        event_stream::EventStream,
+        gyro_mouse::GyroMouseConfig,
+        motion_profile::MotionProfileConfig,
+        target::TargetDevice,
+        touchpad_mouse::TouchpadMouseEmulation,
+    },
+    udev::device::UdevDevice,
 };
 
+/// Number of events that can be waiting to be streamed from an event hook
+const EVENT_HOOK_BUFFER_SIZE: usize = 1024;
+
+/// Number of bytes after which an undecodable simulated event is considered
+/// invalid instead of incomplete
+const MAX_SIMULATED_RECORD_SIZE: usize = 1024;
+
+/// Parse the given hook point name ("pre" or "post") into a [HookPoint]
+fn parse_hook_point(point: &str) -> fdo::Result<HookPoint> {
+    match point {
+        "pre" => Ok(HookPoint::Pre),
+        "post" => Ok(HookPoint::Post),
+        _ => Err(fdo::Error::InvalidArgs(format!(
+            "Invalid hook point '{point}'. Expected 'pre' or 'post'."
+        ))),
+    }
+}
+
 /// The [CompositeDeviceInterface] provides a DBus interface that can be exposed for managing
 /// a [CompositeDevice]. It works by sending command messages to a channel that the
 /// [CompositeDevice] is listening on.
@@ -53,6 +91,76 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Returns a JSON array describing every capability map and device profile
+    /// translation that is currently applied to input events. Each entry has
+    /// the form: {"source_capability", "target_capability", "profile_name"}
+    async fn get_effective_capability_map(&self) -> fdo::Result<String> {
+        self.composite_device
+            .get_effective_mapping_json()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Reload the composite device config from the file it was loaded from
+    /// without restarting the device. New source devices matching the
+    /// updated config are added, and running source devices are blocked or
+    /// unblocked according to the updated config.
+    async fn reload_config(&self) -> fdo::Result<()> {
+        self.composite_device
+            .reload_config()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the number of times each profile mapping was activated, keyed
+    /// by the name of the mapping. Mappings that were never activated are
+    /// not included.
+    async fn get_mapping_statistics(&self) -> fdo::Result<HashMap<String, u64>> {
+        self.composite_device
+            .get_mapping_statistics()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Reset the activation counts of all profile mappings
+    async fn reset_mapping_statistics(&self) -> fdo::Result<()> {
+        self.composite_device
+            .reset_mapping_statistics()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Run the tests defined in the capability map of this device. Returns
+    /// the name of each test, whether it passed, and why it failed.
+    async fn run_capability_map_tests(&self) -> fdo::Result<Vec<(String, bool, String)>> {
+        let results = self
+            .composite_device
+            .run_capability_map_tests()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                (
+                    result.name,
+                    result.passed,
+                    result.message.unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the composite device config that was used to create this
+    /// device as YAML. Useful as a starting point for writing a custom config.
+    async fn get_config(&self) -> fdo::Result<String> {
+        let config = self
+            .composite_device
+            .get_config()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        serde_yaml::to_string(&config).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Load the device profile from the given path
     async fn load_profile_path(&self, path: String) -> fdo::Result<()> {
         self.composite_device
@@ -61,6 +169,132 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Load the given WebAssembly module as the script used to transform
+    /// input events before they are translated. An empty module unloads the
+    /// current script.
+    async fn load_wasm_script(&self, module: Vec<u8>) -> fdo::Result<()> {
+        self.composite_device
+            .load_wasm_script(module)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Save the path of the currently loaded profile to the user config, so
+    /// the profile is loaded again for this device after restarts.
+    async fn save_profile_assignment(&self) -> fdo::Result<()> {
+        self.composite_device
+            .save_profile_assignment()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Treat events with the 'from' capability from the given source device as
+    /// the 'to' capability, e.g. ("hidraw://hidraw0", "Gamepad:Button:South",
+    /// "Gamepad:Button:East"). This can be used to fix buttons on devices that
+    /// report nonstandard HID usages.
+    async fn set_capability_override(
+        &self,
+        source_id: String,
+        from: String,
+        to: String,
+    ) -> fdo::Result<()> {
+        let parse = |cap: &str| {
+            Capability::from_str(cap).map_err(|_| {
+                fdo::Error::Failed(format!(
+                    "Failed to parse event string {cap} into capability."
+                ))
+            })
+        };
+        let from = parse(from.as_str())?;
+        let to = parse(to.as_str())?;
+
+        self.composite_device
+            .override_capability(source_id, from, to)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the capabilities of the source device with the given id, e.g.
+    /// "evdev://event3". This can be used to find out which source device
+    /// provides which inputs.
+    async fn get_source_device_capabilities(&self, source_id: String) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .composite_device
+            .get_source_device_capabilities(source_id)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capabilities
+            .iter()
+            .map(|cap| cap.to_capability_string())
+            .collect())
+    }
+
+    /// Set the maximum rate in Hz at which events with the given capability
+    /// are processed from each source device, e.g. ("Gamepad:Gyro", 250). A
+    /// rate of zero removes the limit.
+    async fn set_capability_rate_limit(&self, capability: String, max_hz: u32) -> fdo::Result<()> {
+        let capability = Capability::from_str(capability.as_str()).map_err(|_| {
+            fdo::Error::Failed(format!(
+                "Failed to parse event string {capability} into capability."
+            ))
+        })?;
+
+        self.composite_device
+            .set_capability_rate_limit(capability, max_hz)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Set the list of profile paths that can be cycled through with
+    /// NextProfile and PreviousProfile
+    async fn set_profile_cycle_list(&self, paths: Vec<String>) -> fdo::Result<()> {
+        self.composite_device
+            .set_profile_cycle_list(paths)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Set the button combo that loads the next profile in the profile cycle
+    /// list, e.g. ["Gamepad:Button:Guide", "Gamepad:Button:RightBumper"]
+    async fn set_profile_cycle_combo(&self, combo: Vec<String>) -> fdo::Result<()> {
+        let mut caps = Vec::with_capacity(combo.len());
+        for event_str in combo {
+            if !event_str.contains("Button") && !event_str.starts_with("Keyboard") {
+                return Err(fdo::Error::Failed(format!(
+                    "The event '{event_str}' is not a Button capability."
+                )));
+            }
+            let cap = Capability::from_str(event_str.as_str()).map_err(|_| {
+                fdo::Error::Failed(format!(
+                    "Failed to parse event string {event_str} into capability."
+                ))
+            })?;
+            caps.push(cap);
+        }
+
+        self.composite_device
+            .set_profile_cycle_combo(caps)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Load the next profile in the profile cycle list
+    async fn next_profile(&self) -> fdo::Result<()> {
+        self.composite_device
+            .next_profile()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Load the previous profile in the profile cycle list
+    async fn previous_profile(&self) -> fdo::Result<()> {
+        self.composite_device
+            .previous_profile()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Load the device profile from the given YAML/JSON string
     async fn load_profile_from_yaml(&self, profile: String) -> fdo::Result<()> {
         self.composite_device
@@ -69,17 +303,77 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Add the source device at the given path (e.g. "/dev/input/event3" or
+    /// "/dev/hidraw2") to the composite device, even if it does not match
+    /// the composite device config. Only root may call this method, since it
+    /// can grab any input device on the system.
+    async fn add_source_device(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        device_path: String,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        let device = UdevDevice::from_path(device_path.as_str())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.composite_device
+            .add_source_device(device)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Set the target input device types the composite device should emulate,
     /// such as ["gamepad", "mouse", "keyboard"]. This method will stop all
     /// current virtual devices for the composite device and create and attach
-    /// new target devices.
-    async fn set_target_devices(&self, target_device_types: Vec<String>) -> fdo::Result<()> {
+    /// new target devices. Only root may set network target devices or web
+    /// gamepads with a bind address, since they send input events over the
+    /// network.
+    async fn set_target_devices(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        target_device_types: Vec<String>,
+    ) -> fdo::Result<()> {
+        if target_device_types
+            .iter()
+            .any(|kind| TargetDevice::is_privileged_kind(kind))
+        {
+            ensure_privileged(connection, &header).await?;
+        }
         self.composite_device
             .set_target_devices(target_device_types)
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Create a target device of the given type, such as "mouse", and attach
+    /// it to the composite device without stopping any other target devices.
+    /// Only root may add network target devices or web gamepads with a bind
+    /// address.
+    async fn add_target_device(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        target_device_type: String,
+    ) -> fdo::Result<()> {
+        if TargetDevice::is_privileged_kind(target_device_type.as_str()) {
+            ensure_privileged(connection, &header).await?;
+        }
+        self.composite_device
+            .add_target_device(target_device_type)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Stop the target device with the given DBus path without stopping any
+    /// other target devices.
+    async fn remove_target_device(&self, path: String) -> fdo::Result<()> {
+        self.composite_device
+            .remove_target_device(path)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Directly write to the composite device's target devices with the given event
     fn send_event(&self, event: String, value: zvariant::Value) -> fdo::Result<()> {
         let cap = Capability::from_str(event.as_str()).map_err(|_| {
@@ -139,6 +433,233 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// Record all events processed by the composite device to the event log
+    /// with the given file name in the event log directory
+    /// (/var/log/inputplumber). Only root may call this method, since the
+    /// log includes keyboard input.
+    async fn enable_event_log(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        name: String,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        let path =
+            event_log_path(name.as_str()).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.composite_device
+            .enable_event_log(path.to_string_lossy().to_string())
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Start streaming all processed events to a Unix domain socket at up to
+    /// the given rate in Hz, replacing any running event stream. A rate of zero
+    /// does not limit the stream. Returns the file descriptor of the socket,
+    /// which events can be read from in the binary event log format. Only
+    /// root may call this method, since the stream includes keyboard input.
+    async fn start_event_stream(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        max_hz: u32,
+    ) -> fdo::Result<zvariant::OwnedFd> {
+        ensure_privileged(connection, &header).await?;
+        let fd = self
+            .composite_device
+            .start_event_stream(max_hz)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(fd.into())
+    }
+
+    /// Register an event hook at the given point in the event pipeline.
+    /// Hooks at the "pre" point receive every event before it is translated,
+    /// and hooks at the "post" point receive every translated event. Returns
+    /// the id of the hook and the file descriptor of a socket the events can
+    /// be read from in the binary event log format. The hook is unregistered
+    /// when the socket is closed. Only root may call this method, since hooks
+    /// receive keyboard input.
+    async fn register_event_hook(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        point: String,
+    ) -> fdo::Result<(u32, zvariant::OwnedFd)> {
+        ensure_privileged(connection, &header).await?;
+        let point = parse_hook_point(point.as_str())?;
+        let (mut stream, fd) =
+            EventStream::new(0).map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let (tx, mut rx) = mpsc::channel(EVENT_HOOK_BUFFER_SIZE);
+        let id = self
+            .composite_device
+            .register_hook(point, tx)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        // Forward hooked events to the socket until the reader closes it
+        let composite_device = self.composite_device.clone();
+        tokio::task::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if !stream.write(&event) {
+                    break;
+                }
+            }
+            if let Err(e) = composite_device.unregister_hook(point, id).await {
+                log::debug!("Failed to unregister event hook {id}: {e:?}");
+            }
+        });
+
+        Ok((id as u32, fd.into()))
+    }
+
+    /// Add a simulated source device with the given id and capabilities
+    /// (e.g. ["Gamepad:Button:South"]) that does not exist on the system.
+    /// Returns the file descriptor of a socket that events can be written to
+    /// in the binary event log format, which are processed as if they came
+    /// from the source device. The simulated source device is removed when
+    /// the socket is closed.
+    async fn simulate_source_device(
+        &self,
+        id: String,
+        capabilities: Vec<String>,
+    ) -> fdo::Result<zvariant::OwnedFd> {
+        let mut caps = HashSet::new();
+        for capability in capabilities {
+            let cap = Capability::from_str(capability.as_str()).map_err(|_| {
+                fdo::Error::Failed(format!(
+                    "Failed to parse event string {capability} into capability."
+                ))
+            })?;
+            caps.insert(cap);
+        }
+        let (local, remote) = UnixStream::pair().map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        local
+            .set_nonblocking(true)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let mut socket = tokio::net::UnixStream::from_std(local)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let events = self
+            .composite_device
+            .simulate_source_device(id.clone(), caps)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        // Forward events written to the socket until the writer closes it.
+        // Incomplete records are kept until the rest of them is read.
+        tokio::task::spawn(async move {
+            let mut buffer = Vec::new();
+            let mut chunk = [0; 4096];
+            loop {
+                let len = match socket.read(&mut chunk).await {
+                    Ok(0) => break,
+                    Ok(len) => len,
+                    Err(e) => {
+                        log::debug!("Stopping simulated source device {id}: {e:?}");
+                        break;
+                    }
+                };
+                buffer.extend_from_slice(&chunk[..len]);
+                loop {
+                    match EventLogRecord::decode(&buffer) {
+                        Ok((record, len)) => {
+                            buffer.drain(..len);
+                            if events.send(record.event).await.is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) if buffer.len() >= MAX_SIMULATED_RECORD_SIZE => {
+                            log::warn!("Invalid event from simulated source device {id}: {e}");
+                            return;
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+        });
+
+        let fd: OwnedFd = remote.into();
+        Ok(fd.into())
+    }
+
+    /// Unregister the event hook with the given id at the given point in the
+    /// event pipeline ("pre" or "post"). Only root may call this method.
+    async fn unregister_event_hook(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        point: String,
+        id: u32,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        let point = parse_hook_point(point.as_str())?;
+        self.composite_device
+            .unregister_hook(point, id as usize)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Stop streaming events. Only root may call this method.
+    async fn stop_event_stream(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        self.composite_device
+            .stop_event_stream()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Stop recording events to the event log. Only root may call this
+    /// method.
+    async fn disable_event_log(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        self.composite_device
+            .disable_event_log()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable dry run mode. In dry run mode, events that would be
+    /// emitted are logged instead of being written to target devices, and
+    /// output events like rumble are not written to source devices. This
+    /// can be used to test profiles without affecting running applications.
+    async fn set_dry_run(&self, enabled: bool) -> fdo::Result<()> {
+        self.composite_device
+            .set_dry_run(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Inject a single binary event log record into the composite device.
+    /// This is used by 'inputplumber-replay' to replay recorded event logs.
+    async fn inject_event_record(&self, record: Vec<u8>) -> fdo::Result<()> {
+        let (record, _) = EventLogRecord::decode(record.as_slice())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        self.composite_device
+            .inject_event(record.event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Replay the events recorded in the binary event log with the given file
+    /// name in the event log directory (/var/log/inputplumber) with their
+    /// original timing. Returns once all events have been replayed.
+    async fn start_replay(&self, name: String) -> fdo::Result<()> {
+        let path =
+            event_log_path(name.as_str()).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.composite_device
+            .start_replay(path.to_string_lossy().to_string())
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Directly write to the composite device's target devices with the given button event list
     async fn send_button_chord(&self, mut events: Vec<String>) -> fdo::Result<()> {
         // Store built native events to send in a command to the CompositeDevice
@@ -189,36 +710,32 @@ impl CompositeDeviceInterface {
         activation_events: Vec<String>,
         target_event: String,
     ) -> fdo::Result<()> {
-        let mut activation_caps: Vec<Capability> = Vec::new();
+        let chord =
+            intercept_chord_from_strings("default".to_string(), activation_events, target_event)?;
 
-        // Iterate in the given order for press events
-        for event_str in activation_events {
-            // Validate the event is valid and create a NativeEvent
-            if event_str.contains("Button") || event_str.starts_with("Keyboard") {
-                let cap = Capability::from_str(event_str.as_str()).map_err(|_| {
-                    fdo::Error::Failed(format!(
-                        "Failed to parse event string {event_str} into capability."
-                    ))
-                })?;
-                activation_caps.push(cap);
-            } else {
-                return Err(fdo::Error::Failed(format!(
-                    "The event '{event_str}' is not a Button capability."
-                )));
-            };
-        }
-        let mut target_cap: Capability = Capability::None;
-        if target_event.contains("Button") || target_event.starts_with("Keyboard") {
-            let cap = Capability::from_str(target_event.as_str()).map_err(|_| {
-                fdo::Error::Failed(format!(
-                    "Failed to parse event string {target_event} into capability."
-                ))
-            })?;
-            target_cap = cap
+        self.composite_device
+            .set_intercept_activation(vec![chord])
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Set multiple named chords that can activate input interception. Each
+    /// chord is a tuple of the mode name, the activation events, and the
+    /// target event to send when the chord is activated.
+    async fn set_intercept_chords(
+        &self,
+        chords: Vec<(String, Vec<String>, String)>,
+    ) -> fdo::Result<()> {
+        let mut intercept_chords = Vec::with_capacity(chords.len());
+        for (mode_name, activation_events, target_event) in chords {
+            let chord = intercept_chord_from_strings(mode_name, activation_events, target_event)?;
+            intercept_chords.push(chord);
         }
 
         self.composite_device
-            .set_intercept_activation(activation_caps, target_cap)
+            .set_intercept_activation(intercept_chords)
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
@@ -234,27 +751,10 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
-
-        Ok(capability_strings)
+        Ok(capabilities
+            .iter()
+            .map(|cap| cap.to_capability_string())
+            .collect())
     }
 
     /// List of capabilities that all target devices implement
@@ -266,27 +766,25 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
+        Ok(capabilities
+            .iter()
+            .map(|cap| cap.to_capability_string())
+            .collect())
+    }
 
-        Ok(capability_strings)
+    /// List of capabilities of all inputs that are currently pressed
+    #[zbus(property)]
+    async fn active_inputs(&self) -> fdo::Result<Vec<String>> {
+        let inputs = self
+            .composite_device
+            .get_active_inputs()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(inputs
+            .iter()
+            .map(|cap| cap.to_capability_string())
+            .collect())
     }
 
     /// List of source devices that this composite device is processing inputs for
@@ -317,6 +815,19 @@ impl CompositeDeviceInterface {
         }
     }
 
+    /// Name of the mode activated by the last intercept chord. Empty if
+    /// intercept mode was not activated by a chord.
+    #[zbus(property)]
+    async fn intercept_mode_name(&self) -> fdo::Result<String> {
+        let name = self
+            .composite_device
+            .get_intercept_mode_name()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(name.unwrap_or_default())
+    }
+
     #[zbus(property)]
     async fn set_intercept_mode(&self, mode: u32) -> zbus::Result<()> {
         let mode = match mode {
@@ -332,6 +843,233 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// Whether or not source events are written to target devices without
+    /// any capability map or device profile translation.
+    #[zbus(property)]
+    async fn passthrough_mode(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_passthrough_mode()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_passthrough_mode(&self, enabled: bool) -> zbus::Result<()> {
+        self.composite_device
+            .set_passthrough_mode(enabled)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Emitted when the intercept mode changes with the name of the new mode
+    /// ("None", "Pass", or "Always"). Overlays can listen for this signal
+    /// instead of polling the InterceptMode property.
+    #[zbus(signal, name = "InterceptModeChanged")]
+    pub async fn intercept_mode_changed_signal(
+        ctxt: &SignalContext<'_>,
+        mode: String,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when the health status of a source device changes with the
+    /// source device id, the new status ("Healthy" or "Degraded"), its
+    /// average event latency in milliseconds, the number of event gaps
+    /// detected and the ratio of failed commands sent to it.
+    #[zbus(signal, name = "SourceDeviceHealthChanged")]
+    pub async fn source_device_health_changed_signal(
+        ctxt: &SignalContext<'_>,
+        device_id: String,
+        status: String,
+        avg_latency_ms: f64,
+        event_gap_alerts: u32,
+        error_rate: f64,
+    ) -> zbus::Result<()>;
+
+    /// Whether and how touchpad motion is translated into mouse motion.
+    /// 0 = disabled, 1 = flat (linear), 2 = adaptive (velocity-based acceleration)
+    #[zbus(property)]
+    async fn touchpad_mouse_emulation(&self) -> fdo::Result<u32> {
+        let mode = self
+            .composite_device
+            .get_touchpad_mouse_emulation()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        match mode {
+            TouchpadMouseEmulation::Disabled => Ok(0),
+            TouchpadMouseEmulation::Flat => Ok(1),
+            TouchpadMouseEmulation::Adaptive => Ok(2),
+        }
+    }
+
+    #[zbus(property)]
+    async fn set_touchpad_mouse_emulation(&self, mode: u32) -> zbus::Result<()> {
+        let mode = match mode {
+            0 => TouchpadMouseEmulation::Disabled,
+            1 => TouchpadMouseEmulation::Flat,
+            2 => TouchpadMouseEmulation::Adaptive,
+            _ => TouchpadMouseEmulation::Disabled,
+        };
+        self.composite_device
+            .set_touchpad_mouse_emulation(mode)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Amount of time in milliseconds in which both clicks of a double click
+    /// must happen for profile mappings with a double click source event
+    #[zbus(property)]
+    async fn double_click_window(&self) -> fdo::Result<u64> {
+        self.composite_device
+            .get_double_click_window()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_double_click_window(&self, window_ms: u64) -> zbus::Result<()> {
+        self.composite_device
+            .set_double_click_window(window_ms)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Mutually exclusive pairs of source buttons, e.g.
+    /// [("Gamepad:Button:DPadLeft", "Gamepad:Button:DPadRight")]. Pressing
+    /// one button of a pair while the other is held releases the other
+    /// button, for games that misbehave when both are pressed.
+    #[zbus(property)]
+    async fn anti_ghosting(&self) -> fdo::Result<Vec<(String, String)>> {
+        let axes = self
+            .composite_device
+            .get_anti_ghosting()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(axes
+            .iter()
+            .map(|(a, b)| (a.to_capability_string(), b.to_capability_string()))
+            .collect())
+    }
+
+    #[zbus(property)]
+    async fn set_anti_ghosting(&self, axes: Vec<(String, String)>) -> zbus::Result<()> {
+        let parse = |event_str: String| {
+            Capability::from_str(event_str.as_str()).map_err(|_| {
+                zbus::Error::Failure(format!(
+                    "Failed to parse event string {event_str} into capability."
+                ))
+            })
+        };
+        let mut pairs = Vec::with_capacity(axes.len());
+        for (a, b) in axes {
+            pairs.push((parse(a)?, parse(b)?));
+        }
+        self.composite_device
+            .set_anti_ghosting(pairs)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Whether or not gyro motion is translated into mouse motion
+    #[zbus(property)]
+    async fn gyro_mouse_enabled(&self) -> fdo::Result<bool> {
+        let config = self
+            .composite_device
+            .get_gyro_mouse_mode()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(config.is_some())
+    }
+
+    /// Enable or disable translating gyro motion into mouse motion. The
+    /// sensitivity is the number of pixels the mouse moves per degree of
+    /// rotation, and the smoothing window is the time constant in
+    /// milliseconds used to smooth out gyro noise. If an activation button
+    /// is given (e.g. "Gamepad:Button:RightBumper"), gyro motion only moves
+    /// the mouse while the button is held. An empty string means the mouse
+    /// is always moved.
+    async fn set_gyro_mouse_mode(
+        &self,
+        enabled: bool,
+        sensitivity_x: f64,
+        sensitivity_y: f64,
+        smoothing_window_ms: u32,
+        activation_button: String,
+    ) -> fdo::Result<()> {
+        let config = if enabled {
+            let activation_button = if activation_button.is_empty() {
+                None
+            } else {
+                let capability =
+                    Capability::from_str(activation_button.as_str()).map_err(|_| {
+                        fdo::Error::Failed(format!(
+                            "Failed to parse event string {activation_button} into capability."
+                        ))
+                    })?;
+                Some(capability)
+            };
+            Some(GyroMouseConfig {
+                sensitivity_x,
+                sensitivity_y,
+                smoothing_window_ms,
+                activation_button,
+            })
+        } else {
+            None
+        };
+        self.composite_device
+            .set_gyro_mouse_mode(config)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Enable or disable loading a different profile depending on how the
+    /// device is held. The pitch of the device is measured in degrees by the
+    /// accelerometer, where a positive pitch tilts the top of the device
+    /// back. Each orientation must be held for the given time in milliseconds
+    /// before its profile is loaded. Profiles are given as absolute paths,
+    /// and an empty string means the profile is not changed for that
+    /// orientation.
+    #[allow(clippy::too_many_arguments)]
+    async fn set_motion_profile(
+        &self,
+        enabled: bool,
+        tilted_back_angle: f64,
+        tilted_forward_angle: f64,
+        hold_ms: u64,
+        face_up_profile: String,
+        tilted_back_profile: String,
+        tilted_forward_profile: String,
+    ) -> fdo::Result<()> {
+        let profile = |path: String| if path.is_empty() { None } else { Some(path) };
+        let config = if enabled {
+            if tilted_forward_angle >= tilted_back_angle {
+                return Err(fdo::Error::InvalidArgs(
+                    "Tilted forward angle must be less than tilted back angle".to_string(),
+                ));
+            }
+            Some(MotionProfileConfig {
+                tilted_back_angle,
+                tilted_forward_angle,
+                hold_ms,
+                face_up_profile: profile(face_up_profile),
+                tilted_back_profile: profile(tilted_back_profile),
+                tilted_forward_profile: profile(tilted_forward_profile),
+            })
+        } else {
+            None
+        };
+        self.composite_device
+            .set_motion_profile(config)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(())
+    }
+
     /// Target devices that this [CompositeDevice] is managing
     #[zbus(property)]
     async fn target_devices(&self) -> fdo::Result<Vec<String>> {
@@ -356,3 +1094,44 @@ impl CompositeDeviceInterface {
         Ok(paths)
     }
 }
+
+/// Build an [InterceptChord] from the given activation and target event strings
+fn intercept_chord_from_strings(
+    mode_name: String,
+    activation_events: Vec<String>,
+    target_event: String,
+) -> fdo::Result<InterceptChord> {
+    let mut activation_caps: Vec<Capability> = Vec::new();
+
+    // Iterate in the given order for press events
+    for event_str in activation_events {
+        // Validate the event is valid and create a NativeEvent
+        if event_str.contains("Button") || event_str.starts_with("Keyboard") {
+            let cap = Capability::from_str(event_str.as_str()).map_err(|_| {
+                fdo::Error::Failed(format!(
+                    "Failed to parse event string {event_str} into capability."
+                ))
+            })?;
+            activation_caps.push(cap);
+        } else {
+            return Err(fdo::Error::Failed(format!(
+                "The event '{event_str}' is not a Button capability."
+            )));
+        };
+    }
+    let mut target_cap: Capability = Capability::None;
+    if target_event.contains("Button") || target_event.starts_with("Keyboard") {
+        let cap = Capability::from_str(target_event.as_str()).map_err(|_| {
+            fdo::Error::Failed(format!(
+                "Failed to parse event string {target_event} into capability."
+            ))
+        })?;
+        target_cap = cap
+    }
+
+    Ok(InterceptChord {
+        activation_caps,
+        target_cap,
+        mode_name,
+    })
+}
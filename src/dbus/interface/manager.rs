@@ -1,11 +1,12 @@
-use std::time::Duration;
+use std::{path::Path, time::Duration};
 
 use tokio::sync::mpsc;
-use zbus::fdo;
+use zbus::{fdo, message::Header, Connection};
 use zbus_macros::interface;
 
 use crate::{
-    config::CompositeDeviceConfig,
+    config::{path::is_in_devices_paths, CompositeDeviceConfig},
+    dbus::privilege::ensure_privileged,
     input::{manager::ManagerCommand, target::TargetDeviceTypeId},
 };
 
@@ -128,4 +129,96 @@ impl ManagerInterface {
 
         Ok(())
     }
+
+    /// Merge the composite device at the given other path into the given
+    /// composite device. All source devices of the other composite device
+    /// are moved to the given composite device and the other composite
+    /// device is stopped, e.g. to combine both halves of a split controller.
+    async fn merge_composite_devices(
+        &self,
+        composite_path: String,
+        other_composite_path: String,
+    ) -> fdo::Result<()> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::MergeCompositeDevices {
+                    path: composite_path.clone(),
+                    other_path: other_composite_path.clone(),
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        if let Err(e) = response {
+            let err = format!("Failed to merge composite device {other_composite_path} into {composite_path}: {e:?}");
+            return Err(fdo::Error::Failed(err));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name and path of all device profiles found in the given
+    /// directory. E.g. [("Default", "/usr/share/inputplumber/profiles/default.yaml")]
+    async fn list_profiles(&self, directory: String) -> fdo::Result<Vec<(String, String)>> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::ListProfiles { directory, sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(profiles) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+
+        Ok(profiles)
+    }
+
+    /// Register a known controller with the given vendor and product id so the
+    /// given composite device config and device profile are automatically used
+    /// for it. Both paths should be absolute paths to the config and profile
+    /// files, and the config must be in one of the device config directories
+    /// (e.g. "/etc/inputplumber/devices.d"). Only root may call this method,
+    /// since the config decides which devices are grabbed and hidden.
+    async fn register_known_device(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        vendor_id: u16,
+        product_id: u16,
+        config_path: String,
+        profile_path: String,
+    ) -> fdo::Result<()> {
+        ensure_privileged(connection, &header).await?;
+        if !is_in_devices_paths(Path::new(config_path.as_str())) {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Config '{config_path}' is not in a device config directory"
+            )));
+        }
+        let config = CompositeDeviceConfig::from_yaml_file(config_path)
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        self.tx
+            .send_timeout(
+                ManagerCommand::RegisterKnownDevice {
+                    vendor_id,
+                    product_id,
+                    config,
+                    profile_path,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,36 @@
+use zbus::{fdo, SignalContext};
+use zbus_macros::interface;
+
+/// The [TargetDBusInterface] provides a DBus interface that can be exposed for
+/// the DBus target device. Input events written to the device are emitted as
+/// signals on this interface.
+pub struct TargetDBusInterface {}
+
+impl TargetDBusInterface {
+    pub fn new() -> TargetDBusInterface {
+        TargetDBusInterface {}
+    }
+}
+
+impl Default for TargetDBusInterface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.DBusDevice")]
+impl TargetDBusInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("DBusDevice".into())
+    }
+
+    /// Emitted when an input event occurs
+    #[zbus(signal)]
+    pub async fn input_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        value: f64,
+    ) -> zbus::Result<()>;
+}
@@ -0,0 +1,23 @@
+use zbus::fdo;
+use zbus_macros::interface;
+
+/// The [TargetGamepadInterface] provides a DBus interface that can be exposed
+/// for target gamepad devices.
+pub struct TargetGamepadInterface {
+    name: String,
+}
+
+impl TargetGamepadInterface {
+    pub fn new(name: String) -> TargetGamepadInterface {
+        TargetGamepadInterface { name }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Gamepad")]
+impl TargetGamepadInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok(self.name.clone())
+    }
+}
@@ -0,0 +1,52 @@
+use std::str::FromStr;
+
+use evdev::{EventType, InputEvent, KeyCode};
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::{
+    capability::Capability,
+    event::{evdev::EvdevEvent, native::NativeEvent, value::InputValue},
+    target::client::TargetDeviceClient,
+};
+
+/// The [TargetKeyboardInterface] provides a DBus interface that can be exposed
+/// for target keyboard devices. It works by sending events to a channel that
+/// the target keyboard device is listening on.
+pub struct TargetKeyboardInterface {
+    target_device: TargetDeviceClient,
+}
+
+impl TargetKeyboardInterface {
+    pub fn new(target_device: TargetDeviceClient) -> TargetKeyboardInterface {
+        TargetKeyboardInterface { target_device }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Keyboard")]
+impl TargetKeyboardInterface {
+    /// Name of the composite device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Keyboard".into())
+    }
+
+    /// Send the given key to the virtual keyboard. The key is the name of an
+    /// evdev key code (e.g. "KEY_A").
+    async fn send_key(&self, key: String, value: bool) -> fdo::Result<()> {
+        let Ok(code) = KeyCode::from_str(key.as_str()) else {
+            return Err(fdo::Error::InvalidArgs(format!("Invalid key: {key}")));
+        };
+        let event = EvdevEvent::from(InputEvent::new(EventType::KEY.0, code.0, 0));
+        let capability = event.as_capability();
+        if !matches!(capability, Capability::Keyboard(_)) {
+            return Err(fdo::Error::InvalidArgs(format!("Unsupported key: {key}")));
+        }
+
+        let event = NativeEvent::new(capability, InputValue::Bool(value));
+        self.target_device
+            .write_event(event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
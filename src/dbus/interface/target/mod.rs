@@ -0,0 +1,4 @@
+pub mod dbus;
+pub mod gamepad;
+pub mod keyboard;
+pub mod mouse;
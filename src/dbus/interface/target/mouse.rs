@@ -0,0 +1,27 @@
+use zbus::fdo;
+use zbus_macros::interface;
+
+/// The [TargetMouseInterface] provides a DBus interface that can be exposed
+/// for target mouse devices.
+pub struct TargetMouseInterface {}
+
+impl TargetMouseInterface {
+    pub fn new() -> TargetMouseInterface {
+        TargetMouseInterface {}
+    }
+}
+
+impl Default for TargetMouseInterface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Mouse")]
+impl TargetMouseInterface {
+    /// Name of the composite device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Mouse".into())
+    }
+}
@@ -0,0 +1,25 @@
+//! The DBus policy allows any local user to call InputPlumber methods, so
+//! methods that expose input from, or give control over, devices that may
+//! belong to other users must check the privileges of their caller.
+use zbus::{fdo, message::Header, names::BusName, Connection};
+
+/// Returns an [fdo::Error::AccessDenied] error unless the sender of the
+/// message with the given header is running as root.
+pub async fn ensure_privileged(connection: &Connection, header: &Header<'_>) -> fdo::Result<()> {
+    let Some(sender) = header.sender() else {
+        return Err(fdo::Error::AccessDenied(
+            "Unable to determine the sender of the message".to_string(),
+        ));
+    };
+    let dbus = fdo::DBusProxy::new(connection).await?;
+    let uid = dbus
+        .get_connection_unix_user(BusName::from(sender.to_owned()))
+        .await?;
+    if uid != 0 {
+        return Err(fdo::Error::AccessDenied(format!(
+            "User {uid} is not allowed to call this method"
+        )));
+    }
+
+    Ok(())
+}
@@ -51,6 +51,13 @@ pub const STICK_Y_MIN: f64 = u8::MIN as f64;
 pub const STICK_Y_MAX: f64 = u8::MAX as f64;
 pub const TRIGGER_MAX: f64 = u8::MAX as f64;
 
+// Adaptive trigger effect modes
+pub const TRIGGER_EFFECT_OFF: u8 = 0x05;
+pub const TRIGGER_EFFECT_CONTINUOUS_RESISTANCE: u8 = 0x01;
+pub const TRIGGER_EFFECT_FEEDBACK: u8 = 0x21;
+pub const TRIGGER_EFFECT_WEAPON: u8 = 0x25;
+pub const TRIGGER_EFFECT_VIBRATION: u8 = 0x26;
+
 // DualSense hardware limits
 pub const DS5_ACC_RES_PER_G: u32 = 8192;
 pub const DS5_TOUCHPAD_WIDTH: f64 = 1920.0;
@@ -118,6 +125,21 @@ impl Driver {
         Ok(())
     }
 
+    /// Writes the given raw output report to the gamepad. This can be used to
+    /// send output reports that are not supported by [Driver::write].
+    pub fn write_report(
+        &self,
+        report_id: u8,
+        data: &[u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::with_capacity(data.len() + 1);
+        buf.push(report_id);
+        buf.extend_from_slice(data);
+        let _bytes_written = self.device.write(&buf)?;
+
+        Ok(())
+    }
+
     /// Release the LEDs from Wireless firmware control
     /// When in wireless mode this must be signaled to control LEDs
     /// This cannot be applied during the BT pair animation.
@@ -145,6 +167,56 @@ impl Driver {
         self.write(state)
     }
 
+    /// Set the resistance of the adaptive triggers to the given force. A force
+    /// of zero turns off the trigger effect.
+    pub fn set_trigger_resistance(
+        &self,
+        left_force: u8,
+        right_force: u8,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        log::debug!("Setting trigger resistance to: {left_force}, {right_force}");
+        let effect = |force: u8| -> [u8; 11] {
+            let mut ffb = [0; 11];
+            if force == 0 {
+                ffb[0] = TRIGGER_EFFECT_OFF;
+            } else {
+                // Resistance starting from the top of the trigger pull
+                ffb[0] = TRIGGER_EFFECT_CONTINUOUS_RESISTANCE;
+                ffb[1] = 0;
+                ffb[2] = force;
+            }
+            ffb
+        };
+        let state = SetStatePackedOutputData {
+            allow_left_trigger_ffb: true,
+            allow_right_trigger_ffb: true,
+            left_trigger_ffb: effect(left_force),
+            right_trigger_ffb: effect(right_force),
+            ..Default::default()
+        };
+
+        self.write(state)
+    }
+
+    /// Set the raw adaptive trigger effect parameters of the left and/or right
+    /// trigger. Triggers without an effect are left unchanged.
+    pub fn set_trigger_effect(
+        &self,
+        left_effect: Option<[u8; 11]>,
+        right_effect: Option<[u8; 11]>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        log::debug!("Setting trigger effects to: {left_effect:?}, {right_effect:?}");
+        let state = SetStatePackedOutputData {
+            allow_left_trigger_ffb: left_effect.is_some(),
+            allow_right_trigger_ffb: right_effect.is_some(),
+            left_trigger_ffb: left_effect.unwrap_or_default(),
+            right_trigger_ffb: right_effect.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        self.write(state)
+    }
+
     /// Use rumble emulation to rumble the gamepad
     pub fn rumble(
         &self,
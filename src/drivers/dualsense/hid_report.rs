@@ -778,3 +778,38 @@ impl Default for UsbPackedOutputReportShort {
         }
     }
 }
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "78")]
+pub struct BluetoothPackedOutputReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8, // Report ID (always 0x31)
+
+    // byte 1
+    #[packed_field(bytes = "1")]
+    pub seq_tag: u8, // Sequence number in the upper 4 bits
+    // byte 2
+    #[packed_field(bytes = "2")]
+    pub tag: u8, // Always 0x10
+
+    // byte 3-49
+    #[packed_field(bytes = "3..=49")]
+    pub state: SetStatePackedOutputData,
+
+    // byte 74-77
+    #[packed_field(bytes = "74..=77", endian = "lsb")]
+    pub crc32: u32,
+}
+
+impl Default for BluetoothPackedOutputReport {
+    fn default() -> Self {
+        Self {
+            report_id: 0x31,
+            seq_tag: 0,
+            tag: 0x10,
+            state: Default::default(),
+            crc32: 0,
+        }
+    }
+}
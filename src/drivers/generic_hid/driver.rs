@@ -0,0 +1,107 @@
+use std::{error::Error, ffi::CString, io};
+
+use hidapi::HidDevice;
+
+use crate::{
+    drivers::hid_descriptor::{InputField, ReportDescriptor},
+    udev::device::UdevDevice,
+};
+
+use super::event::{Event, FieldInput};
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+/// Maximum size of an input report that will be read from the device
+const MAX_REPORT_SIZE: usize = 16384;
+
+/// Driver for HID gamepads without a vendor specific protocol. Input reports
+/// are decoded using the layout described by the HID report descriptor of the
+/// device.
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Layout of the input reports of the device
+    descriptor: ReportDescriptor,
+    /// Size of the largest input report, including the report id
+    report_size: usize,
+    /// Last value of every input field in the report descriptor
+    state: Vec<Option<i32>>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let descriptor = read_report_descriptor(&udevice)?;
+        let descriptor = ReportDescriptor::parse(&descriptor)?;
+        let path = udevice.devnode();
+        if !descriptor.is_gamepad() {
+            return Err(format!("Device '{path}' is not a HID gamepad").into());
+        }
+        let report_size = descriptor.max_input_report_size();
+        if report_size == 0 || report_size > MAX_REPORT_SIZE {
+            return Err(
+                format!("Device '{path}' has an invalid report size: {report_size}").into(),
+            );
+        }
+
+        let cs_path = CString::new(path)?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let state = vec![None; descriptor.fields.len()];
+
+        Ok(Self {
+            device,
+            descriptor,
+            report_size,
+            state,
+        })
+    }
+
+    /// Returns the input fields of the device
+    pub fn fields(&self) -> &[InputField] {
+        self.descriptor.fields.as_slice()
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = vec![0; self.report_size];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        let (report_id, data) = if self.descriptor.uses_report_ids() {
+            (buf[0], &buf[1..bytes_read])
+        } else {
+            (0, &buf[..bytes_read])
+        };
+
+        // Emit an event for every field whose value changed
+        let mut events = Vec::new();
+        for (field, state) in self.descriptor.fields.iter().zip(self.state.iter_mut()) {
+            if field.report_id != report_id {
+                continue;
+            }
+            let Some(value) = field.read(data) else {
+                continue;
+            };
+            if *state == Some(value) {
+                continue;
+            }
+            *state = Some(value);
+            events.push(Event::Field(FieldInput {
+                field: *field,
+                value,
+            }));
+        }
+
+        Ok(events)
+    }
+}
+
+/// Read the HID report descriptor of the given hidraw device from sysfs
+pub fn read_report_descriptor(device: &UdevDevice) -> Result<Vec<u8>, io::Error> {
+    let path = format!("{}/device/report_descriptor", device.syspath());
+    std::fs::read(path)
+}
@@ -0,0 +1,15 @@
+use crate::drivers::hid_descriptor::InputField;
+
+/// Events that can be emitted by a generic HID device
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// The value of an input report field changed
+    Field(FieldInput),
+}
+
+/// Raw value of an input report field
+#[derive(Clone, Debug)]
+pub struct FieldInput {
+    pub field: InputField,
+    pub value: i32,
+}
@@ -0,0 +1,366 @@
+//! Minimal HID report descriptor parser used to discover the buttons and axes
+//! that a generic HID device reports in its input reports.
+//! See: https://www.usb.org/sites/default/files/hid1_11.pdf
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+/// Generic Desktop usage page
+pub const USAGE_PAGE_GENERIC_DESKTOP: u16 = 0x01;
+/// Button usage page
+pub const USAGE_PAGE_BUTTON: u16 = 0x09;
+
+// Generic Desktop application usages
+const USAGE_JOYSTICK: u16 = 0x04;
+const USAGE_GAMEPAD: u16 = 0x05;
+
+// Item types
+const ITEM_TYPE_MAIN: u8 = 0;
+const ITEM_TYPE_GLOBAL: u8 = 1;
+const ITEM_TYPE_LOCAL: u8 = 2;
+
+// Main item tags
+const MAIN_INPUT: u8 = 0x8;
+const MAIN_OUTPUT: u8 = 0x9;
+const MAIN_COLLECTION: u8 = 0xA;
+const MAIN_FEATURE: u8 = 0xB;
+const MAIN_END_COLLECTION: u8 = 0xC;
+
+// Collection types
+const COLLECTION_APPLICATION: u32 = 0x01;
+
+// Global item tags
+const GLOBAL_USAGE_PAGE: u8 = 0x0;
+const GLOBAL_LOGICAL_MINIMUM: u8 = 0x1;
+const GLOBAL_LOGICAL_MAXIMUM: u8 = 0x2;
+const GLOBAL_REPORT_SIZE: u8 = 0x7;
+const GLOBAL_REPORT_ID: u8 = 0x8;
+const GLOBAL_REPORT_COUNT: u8 = 0x9;
+const GLOBAL_PUSH: u8 = 0xA;
+const GLOBAL_POP: u8 = 0xB;
+
+// Local item tags
+const LOCAL_USAGE: u8 = 0x0;
+const LOCAL_USAGE_MINIMUM: u8 = 0x1;
+const LOCAL_USAGE_MAXIMUM: u8 = 0x2;
+
+/// Prefix byte of a long item
+const LONG_ITEM_PREFIX: u8 = 0xFE;
+
+/// Maximum number of usages a single usage minimum/maximum pair can expand
+/// to. Larger ranges are truncated, since no gamepad reports that many.
+const MAX_USAGE_RANGE: u16 = 256;
+
+/// A usage reported by an input item in a HID report descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Usage {
+    pub page: u16,
+    pub id: u16,
+}
+
+/// A variable field of a HID input report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputField {
+    /// Id of the input report this field is part of, or 0 if the device does
+    /// not use report ids
+    pub report_id: u8,
+    /// Offset of the field in bits from the start of the report data, not
+    /// including the report id
+    pub bit_offset: usize,
+    /// Size of the field in bits
+    pub bit_size: usize,
+    pub usage: Usage,
+    pub logical_minimum: i32,
+    pub logical_maximum: i32,
+}
+
+impl InputField {
+    /// Read the value of this field from the given report data, not including
+    /// the report id. Returns None if the data is too short.
+    pub fn read(&self, data: &[u8]) -> Option<i32> {
+        if self.bit_size == 0 || self.bit_size > 32 {
+            return None;
+        }
+        let mut value: u32 = 0;
+        for bit in 0..self.bit_size {
+            let offset = self.bit_offset + bit;
+            let byte = data.get(offset / 8)?;
+            if byte & (1 << (offset % 8)) != 0 {
+                value |= 1 << bit;
+            }
+        }
+
+        // Fields with a negative logical minimum report signed values
+        if self.logical_minimum < 0 && self.bit_size < 32 {
+            let shift = 32 - self.bit_size;
+            return Some(((value << shift) as i32) >> shift);
+        }
+        Some(value as i32)
+    }
+}
+
+/// Global item state, which can be saved and restored with push and pop items
+#[derive(Debug, Clone, Copy, Default)]
+struct GlobalState {
+    usage_page: u16,
+    logical_minimum: i32,
+    logical_maximum: u32,
+    /// Size in bytes of the logical maximum item data
+    logical_maximum_size: usize,
+    report_id: u8,
+    report_size: usize,
+    report_count: usize,
+}
+
+/// The input reports described by a HID report descriptor
+#[derive(Debug, Clone, Default)]
+pub struct ReportDescriptor {
+    /// All usages reported in input reports, in the order they first appear
+    pub usages: Vec<Usage>,
+    /// Variable fields of all input reports. Fields of array input items are
+    /// not included.
+    pub fields: Vec<InputField>,
+    /// Usages of all application collections (e.g. Gamepad)
+    pub applications: Vec<Usage>,
+    /// Size of every input report in bytes, not including the report id
+    report_sizes: HashMap<u8, usize>,
+}
+
+impl ReportDescriptor {
+    /// Parse the given HID report descriptor. Constant (padding) input items
+    /// are ignored.
+    pub fn parse(descriptor: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut report = ReportDescriptor::default();
+        let mut seen = HashSet::new();
+        let mut report_bits: HashMap<u8, usize> = HashMap::new();
+
+        // Global state
+        let mut global = GlobalState::default();
+        let mut global_stack = Vec::new();
+
+        // Local state, reset after every main item
+        let mut local_usages: Vec<Usage> = Vec::new();
+        let mut usage_minimum: Option<Usage> = None;
+        let mut usage_maximum: Option<Usage> = None;
+
+        let mut i = 0;
+        while i < descriptor.len() {
+            let prefix = descriptor[i];
+
+            // Long items are not used by any defined tags, so just skip them
+            if prefix == LONG_ITEM_PREFIX {
+                let Some(size) = descriptor.get(i + 1) else {
+                    return Err("Unexpected end of descriptor in long item".into());
+                };
+                i += 3 + *size as usize;
+                continue;
+            }
+
+            let size = match prefix & 0x03 {
+                3 => 4,
+                size => size as usize,
+            };
+            let item_type = (prefix >> 2) & 0x03;
+            let tag = prefix >> 4;
+            let Some(data) = descriptor.get(i + 1..i + 1 + size) else {
+                return Err(format!("Unexpected end of descriptor at byte {i}").into());
+            };
+            let value = data.iter().enumerate().fold(0u32, |value, (idx, byte)| {
+                value | ((*byte as u32) << (8 * idx))
+            });
+            i += 1 + size;
+
+            // Local usages can either be a usage id on the current usage page,
+            // or an extended usage that includes the usage page in the upper
+            // bytes.
+            let current_page = global.usage_page;
+            let to_usage = move |value: u32| -> Usage {
+                if size == 4 {
+                    Usage {
+                        page: (value >> 16) as u16,
+                        id: value as u16,
+                    }
+                } else {
+                    Usage {
+                        page: current_page,
+                        id: value as u16,
+                    }
+                }
+            };
+
+            match item_type {
+                ITEM_TYPE_MAIN => {
+                    if tag == MAIN_INPUT {
+                        let usages = item_usages(&local_usages, usage_minimum, usage_maximum)?;
+                        let bits = report_bits.entry(global.report_id).or_default();
+                        let bit_offset = *bits;
+                        *bits = bits
+                            .saturating_add(global.report_size.saturating_mul(global.report_count));
+
+                        // Bit 0 of the input item data indicates constant
+                        // (padding) and bit 1 indicates a variable item.
+                        let is_constant = value & 0x01 != 0;
+                        let is_variable = value & 0x02 != 0;
+                        if !is_constant {
+                            for usage in usages.iter() {
+                                if seen.insert(*usage) {
+                                    report.usages.push(*usage);
+                                }
+                            }
+                        }
+                        if !is_constant && is_variable {
+                            // If there are fewer usages than fields, the last
+                            // usage applies to the remaining fields, so only
+                            // the first field of each usage is kept.
+                            let count = global.report_count.min(usages.len());
+                            for (idx, usage) in usages.into_iter().take(count).enumerate() {
+                                report.fields.push(InputField {
+                                    report_id: global.report_id,
+                                    bit_offset: bit_offset + idx * global.report_size,
+                                    bit_size: global.report_size,
+                                    usage,
+                                    logical_minimum: global.logical_minimum,
+                                    logical_maximum: logical_maximum(&global),
+                                });
+                            }
+                        }
+                    }
+                    if tag == MAIN_COLLECTION && value == COLLECTION_APPLICATION {
+                        if let Some(usage) = local_usages.first() {
+                            report.applications.push(*usage);
+                        }
+                    }
+                    if matches!(
+                        tag,
+                        MAIN_INPUT
+                            | MAIN_OUTPUT
+                            | MAIN_FEATURE
+                            | MAIN_COLLECTION
+                            | MAIN_END_COLLECTION
+                    ) {
+                        local_usages.clear();
+                        usage_minimum = None;
+                        usage_maximum = None;
+                    }
+                }
+                ITEM_TYPE_GLOBAL => match tag {
+                    GLOBAL_USAGE_PAGE => global.usage_page = value as u16,
+                    GLOBAL_LOGICAL_MINIMUM => global.logical_minimum = sign_extend(value, size),
+                    GLOBAL_LOGICAL_MAXIMUM => {
+                        global.logical_maximum = value;
+                        global.logical_maximum_size = size;
+                    }
+                    GLOBAL_REPORT_SIZE => global.report_size = value as usize,
+                    GLOBAL_REPORT_ID => global.report_id = value as u8,
+                    GLOBAL_REPORT_COUNT => global.report_count = value as usize,
+                    GLOBAL_PUSH => global_stack.push(global),
+                    GLOBAL_POP => {
+                        let Some(state) = global_stack.pop() else {
+                            return Err("Pop item without matching push item".into());
+                        };
+                        global = state;
+                    }
+                    _ => (),
+                },
+                ITEM_TYPE_LOCAL => match tag {
+                    LOCAL_USAGE => local_usages.push(to_usage(value)),
+                    LOCAL_USAGE_MINIMUM => usage_minimum = Some(to_usage(value)),
+                    LOCAL_USAGE_MAXIMUM => usage_maximum = Some(to_usage(value)),
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+
+        report.report_sizes = report_bits
+            .into_iter()
+            .map(|(id, bits)| (id, bits.div_ceil(8)))
+            .collect();
+
+        Ok(report)
+    }
+
+    /// Returns true if the descriptor describes a joystick or gamepad
+    pub fn is_gamepad(&self) -> bool {
+        self.applications.iter().any(|usage| {
+            usage.page == USAGE_PAGE_GENERIC_DESKTOP
+                && matches!(usage.id, USAGE_JOYSTICK | USAGE_GAMEPAD)
+        })
+    }
+
+    /// Returns true if the input reports of the device start with a report id
+    pub fn uses_report_ids(&self) -> bool {
+        self.report_sizes.keys().any(|id| *id != 0)
+    }
+
+    /// Returns the size in bytes of the largest input report, including the
+    /// report id if the device uses report ids
+    pub fn max_input_report_size(&self) -> usize {
+        let size = self
+            .report_sizes
+            .values()
+            .max()
+            .copied()
+            .unwrap_or_default();
+        if self.uses_report_ids() {
+            size + 1
+        } else {
+            size
+        }
+    }
+}
+
+/// Parse the given HID report descriptor and return all usages that are
+/// reported in input reports, in the order they first appear. Constant
+/// (padding) input items are ignored.
+pub fn parse_input_usages(descriptor: &[u8]) -> Result<Vec<Usage>, Box<dyn Error + Send + Sync>> {
+    Ok(ReportDescriptor::parse(descriptor)?.usages)
+}
+
+/// Returns the usages of an input item, which are all local usages followed by
+/// the usages in the usage minimum/maximum range, if any.
+fn item_usages(
+    local_usages: &[Usage],
+    usage_minimum: Option<Usage>,
+    usage_maximum: Option<Usage>,
+) -> Result<Vec<Usage>, Box<dyn Error + Send + Sync>> {
+    let mut usages = local_usages.to_vec();
+    let (Some(min), Some(max)) = (usage_minimum, usage_maximum) else {
+        return Ok(usages);
+    };
+    if max.id < min.id {
+        return Err("Usage maximum is less than usage minimum".into());
+    }
+    let last = max.id.min(min.id.saturating_add(MAX_USAGE_RANGE - 1));
+    if last < max.id {
+        log::warn!(
+            "Truncating usage range {:#x}-{:#x} to {MAX_USAGE_RANGE} usages",
+            min.id,
+            max.id
+        );
+    }
+    for id in min.id..=last {
+        usages.push(Usage { page: min.page, id });
+    }
+    Ok(usages)
+}
+
+/// Returns the logical maximum of the given global state. The logical maximum
+/// is only signed if the logical minimum is negative.
+fn logical_maximum(global: &GlobalState) -> i32 {
+    if global.logical_minimum >= 0 {
+        return global.logical_maximum.min(i32::MAX as u32) as i32;
+    }
+    sign_extend(global.logical_maximum, global.logical_maximum_size)
+}
+
+/// Sign extend the given item data of the given size in bytes
+fn sign_extend(value: u32, size: usize) -> i32 {
+    match size {
+        1 => value as u8 as i8 as i32,
+        2 => value as u16 as i16 as i32,
+        _ => value as i32,
+    }
+}
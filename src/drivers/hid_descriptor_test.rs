@@ -0,0 +1,109 @@
+use std::error::Error;
+
+use crate::drivers::hid_descriptor::{
+    parse_input_usages, InputField, ReportDescriptor, Usage, USAGE_PAGE_BUTTON,
+    USAGE_PAGE_GENERIC_DESKTOP,
+};
+
+/// Gamepad with 4 buttons, 4 bits of padding, and an X/Y stick
+const HID_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, 0x09, 0x05, 0xA1, 0x01, 0x05, 0x09, 0x19, 0x01, 0x29, 0x04, 0x15, 0x00, 0x25, 0x01,
+    0x75, 0x01, 0x95, 0x04, 0x81, 0x02, 0x75, 0x04, 0x95, 0x01, 0x81, 0x03, 0x05, 0x01, 0x09, 0x30,
+    0x09, 0x31, 0x15, 0x00, 0x26, 0xFF, 0x00, 0x75, 0x08, 0x95, 0x02, 0x81, 0x02, 0xC0,
+];
+
+#[tokio::test]
+async fn test_report_descriptor_fields() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let descriptor = ReportDescriptor::parse(HID_DESCRIPTOR)?;
+    assert!(descriptor.is_gamepad());
+    assert!(!descriptor.uses_report_ids());
+    assert_eq!(descriptor.max_input_report_size(), 3);
+
+    let button = |id, bit_offset| InputField {
+        report_id: 0,
+        bit_offset,
+        bit_size: 1,
+        usage: Usage {
+            page: USAGE_PAGE_BUTTON,
+            id,
+        },
+        logical_minimum: 0,
+        logical_maximum: 1,
+    };
+    let axis = |id, bit_offset| InputField {
+        report_id: 0,
+        bit_offset,
+        bit_size: 8,
+        usage: Usage {
+            page: USAGE_PAGE_GENERIC_DESKTOP,
+            id,
+        },
+        logical_minimum: 0,
+        logical_maximum: 255,
+    };
+    assert_eq!(
+        descriptor.fields,
+        vec![
+            button(1, 0),
+            button(2, 1),
+            button(3, 2),
+            button(4, 3),
+            axis(0x30, 8),
+            axis(0x31, 16),
+        ]
+    );
+
+    // Buttons 1 and 3 pressed, X centered, and Y at its maximum
+    let report = [0b0000_0101, 0x80, 0xFF];
+    let values: Vec<Option<i32>> = descriptor
+        .fields
+        .iter()
+        .map(|field| field.read(&report))
+        .collect();
+    assert_eq!(
+        values,
+        vec![Some(1), Some(0), Some(1), Some(0), Some(128), Some(255)]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_report_descriptor_signed_fields() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Report id 1 with a signed 16 bit X axis between -32768 and 32767
+    let descriptor = [
+        0x05, 0x01, 0x09, 0x04, 0xA1, 0x01, 0x85, 0x01, 0x09, 0x30, 0x16, 0x00, 0x80, 0x26, 0xFF,
+        0x7F, 0x75, 0x10, 0x95, 0x01, 0x81, 0x02, 0xC0,
+    ];
+    let descriptor = ReportDescriptor::parse(&descriptor)?;
+    assert!(descriptor.is_gamepad());
+    assert!(descriptor.uses_report_ids());
+    assert_eq!(descriptor.max_input_report_size(), 3);
+
+    let field = descriptor.fields[0];
+    assert_eq!(field.report_id, 1);
+    assert_eq!(field.logical_minimum, -32768);
+    assert_eq!(field.logical_maximum, 32767);
+    assert_eq!(field.read(&[0x00, 0x80]), Some(-32768));
+    assert_eq!(field.read(&[0xFF, 0xFF]), Some(-1));
+    assert_eq!(field.read(&[0xFF]), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_usage_range() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Button usage minimum 1 and maximum 0xFFFF, reported twice
+    let descriptor = [
+        0x05, 0x09, 0x19, 0x01, 0x2A, 0xFF, 0xFF, 0x81, 0x02, 0x19, 0x01, 0x2A, 0xFF, 0xFF, 0x81,
+        0x02,
+    ];
+    let usages = parse_input_usages(&descriptor)?;
+
+    // Large ranges are truncated and duplicate usages are removed
+    assert_eq!(usages.len(), 256);
+    assert_eq!(usages.first().map(|u| u.id), Some(1));
+    assert_eq!(usages.last().map(|u| u.id), Some(256));
+
+    Ok(())
+}
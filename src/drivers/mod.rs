@@ -1,8 +1,13 @@
 pub mod dualsense;
 pub mod fts3528;
+pub mod generic_hid;
+pub mod hid_descriptor;
+#[cfg(test)]
+mod hid_descriptor_test;
 pub mod iio_imu;
 pub mod lego;
 pub mod opineo;
 pub mod rog_ally;
 pub mod steam_deck;
+pub mod switch_pro;
 pub mod xpad_uhid;
@@ -12,7 +12,9 @@ use super::{
         AccelerometerEvent, AccelerometerInput, AxisEvent, AxisInput, BinaryInput, ButtonEvent,
         Event, TouchAxisInput, TriggerEvent, TriggerInput,
     },
-    hid_report::{PackedMappingsReport, PackedRumbleReport, ReportType},
+    hid_report::{
+        PackedHapticPulseReport, PackedMappingsReport, PackedRumbleReport, Pad, ReportType,
+    },
 };
 
 /// Vendor ID
@@ -90,6 +92,28 @@ impl Driver {
         Ok(())
     }
 
+    /// Send a haptic pulse to the given trackpad(s). The period is measured
+    /// in microseconds and count is the number of pulses to send.
+    pub fn haptic_pulse(
+        &mut self,
+        side: Pad,
+        amplitude: u16,
+        period: u16,
+        count: u16,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut report = PackedHapticPulseReport::new();
+        report.side = side;
+        report.amplitude = Integer::from_primitive(amplitude);
+        report.period = Integer::from_primitive(period);
+        report.count = Integer::from_primitive(count);
+
+        // Write the report to the device
+        let buf = report.pack()?;
+        let _bytes_written = self.device.write(&buf)?;
+
+        Ok(())
+    }
+
     /// Set lizard mode, which will automatically try to emulate mouse/keyboard
     /// if enabled.
     pub fn set_lizard_mode(&self, enabled: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
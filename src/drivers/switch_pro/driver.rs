@@ -0,0 +1,299 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::{types::SizedInteger, PackedStruct};
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{
+        AxisEvent, BinaryInput, ButtonEvent, Event, InertialEvent, InertialInput, JoyAxisInput,
+    },
+    hid_report::{
+        StandardInputReport, Subcommand, SubcommandOutputReport, UsbCommand, INPUT_REPORT_STANDARD,
+        INPUT_REPORT_SUBCOMMAND_REPLY, INPUT_REPORT_USB_REPLY, OUTPUT_REPORT_USB,
+    },
+};
+
+// Hardware ID's
+pub const VID: u16 = 0x057e;
+pub const PID: u16 = 0x2009;
+
+// Bus type of controllers connected over USB
+const BUS_USB: u16 = 0x03;
+
+// Input report sizes
+const INPUT_REPORT_SIZE: usize = 49;
+const INPUT_REPORT_USB_SIZE: usize = 64;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+// Number of reads to wait for a reply to a command during initialization
+const REPLY_ATTEMPTS: usize = 20;
+// Player light of the first player
+const PLAYER_LIGHT_1: u8 = 0x01;
+
+// Input report axis ranges. These are the approximate ranges of the factory
+// stick calibration.
+// TODO: Read the stick calibration from the SPI flash of the controller
+pub const JOY_AXIS_MAX: f64 = 3448.0;
+pub const JOY_AXIS_MIN: f64 = 648.0;
+
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Whether the controller is connected over USB
+    is_usb: bool,
+    /// Counter that is incremented for every subcommand sent
+    packet_counter: u8,
+    /// State for the device
+    state: Option<StandardInputReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID || info.product_id() != PID {
+            return Err(format!("Device '{path}' is not a Switch Pro Controller").into());
+        }
+        let mut driver = Self {
+            device,
+            is_usb: udevice.id_bustype() == BUS_USB,
+            packet_counter: 0,
+            state: None,
+        };
+        driver.initialize()?;
+
+        Ok(driver)
+    }
+
+    /// Run the init sequence of the controller, which switches it to the
+    /// standard full input report mode and enables the IMU and vibration.
+    fn initialize(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Controllers connected over USB only send input reports over USB
+        // after a handshake.
+        if self.is_usb {
+            log::debug!("Performing USB handshake");
+            self.send_usb_command(UsbCommand::Handshake)?;
+            self.send_usb_command(UsbCommand::Baudrate3M)?;
+            self.send_usb_command(UsbCommand::Handshake)?;
+            self.send_usb_command(UsbCommand::ForceUsb)?;
+        }
+
+        log::debug!("Initializing Switch Pro Controller");
+        self.send_subcommand(Subcommand::SetInputReportMode, &[INPUT_REPORT_STANDARD])?;
+        self.send_subcommand(Subcommand::EnableImu, &[0x01])?;
+        self.send_subcommand(Subcommand::EnableVibration, &[0x01])?;
+        self.send_subcommand(Subcommand::SetPlayerLights, &[PLAYER_LIGHT_1])?;
+
+        Ok(())
+    }
+
+    /// Send the given USB command and wait for the controller to reply
+    fn send_usb_command(
+        &mut self,
+        command: UsbCommand,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let command = command as u8;
+        self.device.write(&[OUTPUT_REPORT_USB, command])?;
+        if !self.wait_for_reply(|buf| buf[0] == INPUT_REPORT_USB_REPLY && buf[1] == command)? {
+            log::warn!("No reply to USB command: {command:#04x}");
+        }
+        Ok(())
+    }
+
+    /// Send the given subcommand with its arguments and wait for the
+    /// controller to acknowledge it.
+    pub fn send_subcommand(
+        &mut self,
+        subcommand: Subcommand,
+        args: &[u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let subcommand = subcommand as u8;
+        let mut report = SubcommandOutputReport {
+            packet_counter: self.packet_counter,
+            subcommand,
+            ..Default::default()
+        };
+        if args.len() > report.data.len() {
+            return Err(format!("Too many arguments for subcommand: {subcommand:#04x}").into());
+        }
+        report.data[..args.len()].copy_from_slice(args);
+        self.packet_counter = (self.packet_counter + 1) & 0x0F;
+
+        let buf = report.pack()?;
+        self.device.write(&buf)?;
+        if !self.wait_for_reply(|buf| {
+            buf[0] == INPUT_REPORT_SUBCOMMAND_REPLY && buf[14] == subcommand
+        })? {
+            log::warn!("No reply to subcommand: {subcommand:#04x}");
+        }
+        Ok(())
+    }
+
+    /// Read input reports until one matches the given reply. Returns false
+    /// if no matching reply was received.
+    fn wait_for_reply(
+        &self,
+        is_reply: impl Fn(&[u8; INPUT_REPORT_USB_SIZE]) -> bool,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        for _ in 0..REPLY_ATTEMPTS {
+            let mut buf = [0; INPUT_REPORT_USB_SIZE];
+            let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+            if bytes_read > 0 && is_reply(&buf) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; INPUT_REPORT_USB_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+
+        let report_id = buf[0];
+        let slice = &buf[..bytes_read];
+
+        let events = match report_id {
+            INPUT_REPORT_STANDARD => {
+                if bytes_read < INPUT_REPORT_SIZE {
+                    return Err("Invalid packet size for standard input report.".into());
+                }
+                // Handle the incoming input report
+                let sized_buf = slice[..INPUT_REPORT_SIZE].try_into()?;
+
+                self.handle_input_report(sized_buf)?
+            }
+            INPUT_REPORT_SUBCOMMAND_REPLY => {
+                log::trace!("Got subcommand reply: {:#04x}", buf[14]);
+                vec![]
+            }
+            _ => {
+                //log::debug!("Invalid Report ID.");
+                vec![]
+            }
+        };
+
+        Ok(events)
+    }
+
+    /// Unpacks the buffer into a [StandardInputReport] structure and updates
+    /// the internal state
+    fn handle_input_report(
+        &mut self,
+        buf: [u8; INPUT_REPORT_SIZE],
+    ) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        let input_report = StandardInputReport::unpack(&buf)?;
+
+        // Update the state
+        let old_state = self.update_state(input_report);
+
+        // Translate the state into a stream of input events
+        let events = self.translate_events(old_state);
+
+        Ok(events)
+    }
+
+    /// Update input state
+    fn update_state(&mut self, input_report: StandardInputReport) -> Option<StandardInputReport> {
+        let old_state = self.state;
+        self.state = Some(input_report);
+        old_state
+    }
+
+    /// Translate the state into individual events
+    fn translate_events(&self, old_state: Option<StandardInputReport>) -> Vec<Event> {
+        let mut events = Vec::new();
+        let Some(state) = self.state else {
+            return events;
+        };
+        let Some(old_state) = old_state else {
+            return events;
+        };
+
+        // Binary Events
+        let buttons = state.buttons;
+        let old_buttons = old_state.buttons;
+        let button_events: [(bool, bool, fn(BinaryInput) -> ButtonEvent); 18] = [
+            (buttons.a, old_buttons.a, ButtonEvent::A),
+            (buttons.b, old_buttons.b, ButtonEvent::B),
+            (buttons.x, old_buttons.x, ButtonEvent::X),
+            (buttons.y, old_buttons.y, ButtonEvent::Y),
+            (buttons.l, old_buttons.l, ButtonEvent::L),
+            (buttons.r, old_buttons.r, ButtonEvent::R),
+            (buttons.zl, old_buttons.zl, ButtonEvent::ZL),
+            (buttons.zr, old_buttons.zr, ButtonEvent::ZR),
+            (buttons.plus, old_buttons.plus, ButtonEvent::Plus),
+            (buttons.minus, old_buttons.minus, ButtonEvent::Minus),
+            (buttons.home, old_buttons.home, ButtonEvent::Home),
+            (buttons.capture, old_buttons.capture, ButtonEvent::Capture),
+            (buttons.l_stick, old_buttons.l_stick, ButtonEvent::LStick),
+            (buttons.r_stick, old_buttons.r_stick, ButtonEvent::RStick),
+            (buttons.dpad_up, old_buttons.dpad_up, ButtonEvent::DPadUp),
+            (
+                buttons.dpad_down,
+                old_buttons.dpad_down,
+                ButtonEvent::DPadDown,
+            ),
+            (
+                buttons.dpad_left,
+                old_buttons.dpad_left,
+                ButtonEvent::DPadLeft,
+            ),
+            (
+                buttons.dpad_right,
+                old_buttons.dpad_right,
+                ButtonEvent::DPadRight,
+            ),
+        ];
+        for (pressed, was_pressed, event) in button_events {
+            if pressed != was_pressed {
+                events.push(Event::Button(event(BinaryInput { pressed })));
+            }
+        }
+
+        // Axis events
+        if state.l_stick != old_state.l_stick {
+            let (x, y) = state.left_stick();
+            events.push(Event::Axis(AxisEvent::LStick(JoyAxisInput { x, y })));
+        }
+        if state.r_stick != old_state.r_stick {
+            let (x, y) = state.right_stick();
+            events.push(Event::Axis(AxisEvent::RStick(JoyAxisInput { x, y })));
+        }
+
+        // IMU events
+        let imu = state.imu();
+        let old_imu = old_state.imu();
+        if imu.accel_x != old_imu.accel_x
+            || imu.accel_y != old_imu.accel_y
+            || imu.accel_z != old_imu.accel_z
+        {
+            events.push(Event::Inertia(InertialEvent::Accelerometer(
+                InertialInput {
+                    x: imu.accel_x.to_primitive(),
+                    y: imu.accel_y.to_primitive(),
+                    z: imu.accel_z.to_primitive(),
+                },
+            )));
+        }
+        if imu.gyro_x != old_imu.gyro_x
+            || imu.gyro_y != old_imu.gyro_y
+            || imu.gyro_z != old_imu.gyro_z
+        {
+            events.push(Event::Inertia(InertialEvent::Gyro(InertialInput {
+                x: imu.gyro_x.to_primitive(),
+                y: imu.gyro_y.to_primitive(),
+                z: imu.gyro_z.to_primitive(),
+            })));
+        }
+
+        events
+    }
+}
@@ -0,0 +1,84 @@
+/// Events that can be emitted by the controller
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Axis(AxisEvent),
+    Inertia(InertialEvent),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Axis input contain (x, y) coordinates
+#[derive(Clone, Debug)]
+pub struct JoyAxisInput {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Button events represend binary inputs
+#[derive(Clone, Debug)]
+pub enum ButtonEvent {
+    /// A Button (right face button)
+    A(BinaryInput),
+    /// B Button (bottom face button)
+    B(BinaryInput),
+    /// X Button (top face button)
+    X(BinaryInput),
+    /// Y Button (left face button)
+    Y(BinaryInput),
+    /// Left shoulder button
+    L(BinaryInput),
+    /// Right shoulder button
+    R(BinaryInput),
+    /// Left digital trigger
+    ZL(BinaryInput),
+    /// Right digital trigger
+    ZR(BinaryInput),
+    /// Plus (+) button
+    Plus(BinaryInput),
+    /// Minus (-) button
+    Minus(BinaryInput),
+    /// Home button
+    Home(BinaryInput),
+    /// Capture button
+    Capture(BinaryInput),
+    /// Z-axis button on the left stick
+    LStick(BinaryInput),
+    /// Z-axis button on the right stick
+    RStick(BinaryInput),
+    /// DPad up
+    DPadUp(BinaryInput),
+    /// DPad right
+    DPadRight(BinaryInput),
+    /// DPad down
+    DPadDown(BinaryInput),
+    /// DPad left
+    DPadLeft(BinaryInput),
+}
+
+/// Axis events are events that have (x, y) values
+#[derive(Clone, Debug)]
+pub enum AxisEvent {
+    LStick(JoyAxisInput),
+    RStick(JoyAxisInput),
+}
+
+/// InertialInput represents the state of the accelerometer or gyro (x, y, z)
+/// values
+#[derive(Clone, Debug)]
+pub struct InertialInput {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// InertialEvent has data from the IMU
+#[derive(Clone, Debug)]
+pub enum InertialEvent {
+    Accelerometer(InertialInput),
+    Gyro(InertialInput),
+}
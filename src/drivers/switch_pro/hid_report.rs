@@ -0,0 +1,226 @@
+//! Reference: https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/blob/master/bluetooth_hid_notes.md
+use packed_struct::prelude::*;
+
+/// Input report ID of the standard full mode report with IMU data
+pub const INPUT_REPORT_STANDARD: u8 = 0x30;
+/// Input report ID of subcommand replies
+pub const INPUT_REPORT_SUBCOMMAND_REPLY: u8 = 0x21;
+/// Input report ID of replies to USB commands
+pub const INPUT_REPORT_USB_REPLY: u8 = 0x81;
+
+/// Output report ID for sending rumble data and a subcommand
+pub const OUTPUT_REPORT_SUBCOMMAND: u8 = 0x01;
+/// Output report ID for USB only commands
+pub const OUTPUT_REPORT_USB: u8 = 0x80;
+
+/// Rumble data that does not vibrate either actuator
+pub const RUMBLE_NEUTRAL: [u8; 8] = [0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40];
+
+/// Commands that can be sent with the [OUTPUT_REPORT_USB] report
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UsbCommand {
+    /// Handshake with the controller
+    Handshake = 0x02,
+    /// Switch the UART baudrate to 3Mbit
+    Baudrate3M = 0x03,
+    /// Only talk over USB HID without any timeouts
+    ForceUsb = 0x04,
+}
+
+/// Subcommands that can be sent with the [OUTPUT_REPORT_SUBCOMMAND] report
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subcommand {
+    /// Set the input report mode, e.g. to [INPUT_REPORT_STANDARD]
+    SetInputReportMode = 0x03,
+    /// Set the player lights. The low nibble turns the lights on, the high
+    /// nibble makes them flash.
+    SetPlayerLights = 0x30,
+    /// Enable or disable the IMU
+    EnableImu = 0x40,
+    /// Enable or disable vibration
+    EnableVibration = 0x48,
+}
+
+/// State of the buttons in the input report
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq, Default)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "3")]
+pub struct ButtonState {
+    // byte 0 (right)
+    #[packed_field(bits = "0")]
+    pub zr: bool,
+    #[packed_field(bits = "1")]
+    pub r: bool,
+    #[packed_field(bits = "2")]
+    pub sl_right: bool,
+    #[packed_field(bits = "3")]
+    pub sr_right: bool,
+    #[packed_field(bits = "4")]
+    pub a: bool,
+    #[packed_field(bits = "5")]
+    pub b: bool,
+    #[packed_field(bits = "6")]
+    pub x: bool,
+    #[packed_field(bits = "7")]
+    pub y: bool,
+
+    // byte 1 (shared)
+    #[packed_field(bits = "8")]
+    pub charging_grip: bool,
+    #[packed_field(bits = "10")]
+    pub capture: bool,
+    #[packed_field(bits = "11")]
+    pub home: bool,
+    #[packed_field(bits = "12")]
+    pub l_stick: bool,
+    #[packed_field(bits = "13")]
+    pub r_stick: bool,
+    #[packed_field(bits = "14")]
+    pub plus: bool,
+    #[packed_field(bits = "15")]
+    pub minus: bool,
+
+    // byte 2 (left)
+    #[packed_field(bits = "16")]
+    pub zl: bool,
+    #[packed_field(bits = "17")]
+    pub l: bool,
+    #[packed_field(bits = "18")]
+    pub sl_left: bool,
+    #[packed_field(bits = "19")]
+    pub sr_left: bool,
+    #[packed_field(bits = "20")]
+    pub dpad_left: bool,
+    #[packed_field(bits = "21")]
+    pub dpad_right: bool,
+    #[packed_field(bits = "22")]
+    pub dpad_up: bool,
+    #[packed_field(bits = "23")]
+    pub dpad_down: bool,
+}
+
+/// Single sample of accelerometer and gyro data
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq, Default)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "12")]
+pub struct ImuData {
+    #[packed_field(bytes = "0..=1", endian = "lsb")]
+    pub accel_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "2..=3", endian = "lsb")]
+    pub accel_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "4..=5", endian = "lsb")]
+    pub accel_z: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "6..=7", endian = "lsb")]
+    pub gyro_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "8..=9", endian = "lsb")]
+    pub gyro_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "10..=11", endian = "lsb")]
+    pub gyro_z: Integer<i16, packed_bits::Bits<16>>,
+}
+
+/// Standard full mode input report. The controller sends this report at
+/// 120Hz with three IMU samples taken 5ms apart.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "49")]
+pub struct StandardInputReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8,
+    // byte 1
+    #[packed_field(bytes = "1")]
+    pub timer: u8,
+    // byte 2
+    #[packed_field(bytes = "2")]
+    pub battery_connection: u8,
+    // bytes 3-5
+    #[packed_field(bytes = "3..=5")]
+    pub buttons: ButtonState,
+    // bytes 6-8
+    #[packed_field(bytes = "6..=8")]
+    pub l_stick: [u8; 3],
+    // bytes 9-11
+    #[packed_field(bytes = "9..=11")]
+    pub r_stick: [u8; 3],
+    // byte 12
+    #[packed_field(bytes = "12")]
+    pub vibrator_report: u8,
+    // bytes 13-48
+    #[packed_field(bytes = "13..=24")]
+    pub imu_0: ImuData,
+    #[packed_field(bytes = "25..=36")]
+    pub imu_1: ImuData,
+    #[packed_field(bytes = "37..=48")]
+    pub imu_2: ImuData,
+}
+
+impl StandardInputReport {
+    /// Returns the raw 12-bit (x, y) values of the left stick
+    pub fn left_stick(&self) -> (u16, u16) {
+        decode_stick(self.l_stick)
+    }
+
+    /// Returns the raw 12-bit (x, y) values of the right stick
+    pub fn right_stick(&self) -> (u16, u16) {
+        decode_stick(self.r_stick)
+    }
+
+    /// Returns the most recent IMU sample in the report
+    pub fn imu(&self) -> ImuData {
+        self.imu_2
+    }
+}
+
+impl Default for StandardInputReport {
+    fn default() -> Self {
+        Self {
+            report_id: INPUT_REPORT_STANDARD,
+            timer: Default::default(),
+            battery_connection: Default::default(),
+            buttons: Default::default(),
+            l_stick: Default::default(),
+            r_stick: Default::default(),
+            vibrator_report: Default::default(),
+            imu_0: Default::default(),
+            imu_1: Default::default(),
+            imu_2: Default::default(),
+        }
+    }
+}
+
+/// Decode the two 12-bit stick values packed into three bytes
+fn decode_stick(data: [u8; 3]) -> (u16, u16) {
+    let x = data[0] as u16 | ((data[1] as u16 & 0x0F) << 8);
+    let y = (data[1] as u16 >> 4) | ((data[2] as u16) << 4);
+    (x, y)
+}
+
+/// Output report used to send rumble data along with a subcommand
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "49")]
+pub struct SubcommandOutputReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8,
+    // byte 1
+    #[packed_field(bytes = "1")]
+    pub packet_counter: u8,
+    // bytes 2-9
+    #[packed_field(bytes = "2..=9")]
+    pub rumble: [u8; 8],
+    // byte 10
+    #[packed_field(bytes = "10")]
+    pub subcommand: u8,
+    // bytes 11-48
+    #[packed_field(bytes = "11..=48")]
+    pub data: [u8; 38],
+}
+
+impl Default for SubcommandOutputReport {
+    fn default() -> Self {
+        Self {
+            report_id: OUTPUT_REPORT_SUBCOMMAND,
+            packet_counter: Default::default(),
+            rumble: RUMBLE_NEUTRAL,
+            subcommand: Default::default(),
+            data: [0; 38],
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use packed_struct::{types::SizedInteger, PackedStruct};
+
+use crate::drivers::switch_pro::hid_report::StandardInputReport;
+
+#[tokio::test]
+async fn test_switch_pro_input_report() -> Result<(), Box<dyn Error>> {
+    let mut buf = [0; 49];
+    buf[0] = 0x30;
+    // A and ZR
+    buf[3] = 0x88;
+    // Home and Plus
+    buf[4] = 0x12;
+    // DPad Up and L
+    buf[5] = 0x42;
+    // Left stick x: 0x123, y: 0x456
+    buf[6..=8].copy_from_slice(&[0x23, 0x61, 0x45]);
+    // Right stick x: 0xfff, y: 0x000
+    buf[9..=11].copy_from_slice(&[0xff, 0x0f, 0x00]);
+    // Accelerometer x of the last IMU sample
+    buf[37..=38].copy_from_slice(&(-1000_i16).to_le_bytes());
+
+    let report = StandardInputReport::unpack(&buf)?;
+    assert!(report.buttons.a);
+    assert!(report.buttons.zr);
+    assert!(!report.buttons.b);
+    assert!(report.buttons.home);
+    assert!(report.buttons.plus);
+    assert!(!report.buttons.minus);
+    assert!(report.buttons.dpad_up);
+    assert!(report.buttons.l);
+    assert!(!report.buttons.zl);
+    assert_eq!(report.left_stick(), (0x123, 0x456));
+    assert_eq!(report.right_stick(), (0xfff, 0x000));
+    assert_eq!(report.imu().accel_x.to_primitive(), -1000);
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use super::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Prevents both buttons of a mutually exclusive pair (e.g. DPad left and
+/// right) from being pressed at the same time, which some older games do not
+/// handle. Pressing a button while the other button of its pair is held
+/// releases the other button first. When the newer button is released, the
+/// other button is pressed again if it is still held.
+#[derive(Debug, Default)]
+pub struct AntiGhosting {
+    /// Pairs of mutually exclusive button capabilities
+    axes: Vec<(Capability, Capability)>,
+    /// Buttons of any pair that are currently held on the source device
+    held: HashSet<Capability>,
+    /// Held buttons that were released because the other button of their
+    /// pair was pressed
+    suppressed: HashSet<Capability>,
+}
+
+impl AntiGhosting {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the pairs of mutually exclusive button capabilities. An empty list
+    /// disables anti-ghosting.
+    pub fn set_axes(&mut self, axes: Vec<(Capability, Capability)>) {
+        self.axes = axes;
+        self.held.clear();
+        self.suppressed.clear();
+    }
+
+    /// Returns the pairs of mutually exclusive button capabilities
+    pub fn axes(&self) -> &[(Capability, Capability)] {
+        &self.axes
+    }
+
+    /// Returns true if any mutually exclusive pairs are set
+    pub fn is_enabled(&self) -> bool {
+        !self.axes.is_empty()
+    }
+
+    /// Update the state of the button of the given event and return the
+    /// events that should be emitted instead. Events of capabilities that are
+    /// not part of any pair are returned unchanged.
+    pub fn update(&mut self, event: NativeEvent) -> Vec<NativeEvent> {
+        let cap = event.as_capability();
+        let InputValue::Bool(pressed) = event.get_value() else {
+            return vec![event];
+        };
+        let partners = self.partners(&cap);
+        if partners.is_empty() {
+            return vec![event];
+        }
+
+        let mut events = Vec::new();
+        if pressed {
+            self.held.insert(cap.clone());
+            self.suppressed.remove(&cap);
+            for partner in partners {
+                if !self.is_active(&partner) {
+                    continue;
+                }
+                log::trace!("Releasing {partner:?} while {cap:?} is pressed");
+                events.push(NativeEvent::new(partner.clone(), InputValue::Bool(false)));
+                self.suppressed.insert(partner);
+            }
+            events.push(event);
+            return events;
+        }
+
+        self.held.remove(&cap);
+        // The button was already released when its partner was pressed
+        if self.suppressed.remove(&cap) {
+            return events;
+        }
+        events.push(event);
+        for partner in partners {
+            if !self.suppressed.contains(&partner) {
+                continue;
+            }
+            // Only restore the button if none of its own partners are active
+            if self
+                .partners(&partner)
+                .iter()
+                .any(|other| self.is_active(other))
+            {
+                continue;
+            }
+            log::trace!("Restoring {partner:?} after {cap:?} was released");
+            self.suppressed.remove(&partner);
+            events.push(NativeEvent::new(partner, InputValue::Bool(true)));
+        }
+        events
+    }
+
+    /// Returns true if the given button is held and not suppressed
+    fn is_active(&self, cap: &Capability) -> bool {
+        self.held.contains(cap) && !self.suppressed.contains(cap)
+    }
+
+    /// Returns the capabilities that are mutually exclusive with the given
+    /// capability
+    fn partners(&self, cap: &Capability) -> Vec<Capability> {
+        self.axes
+            .iter()
+            .filter_map(|(a, b)| {
+                if a == cap {
+                    Some(b.clone())
+                } else if b == cap {
+                    Some(a.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,80 @@
+use crate::input::{
+    anti_ghosting::AntiGhosting,
+    capability::{Capability, Gamepad, GamepadButton},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+fn button(button: GamepadButton) -> Capability {
+    Capability::Gamepad(Gamepad::Button(button))
+}
+
+fn event(cap: &Capability, pressed: bool) -> NativeEvent {
+    NativeEvent::new(cap.clone(), InputValue::Bool(pressed))
+}
+
+/// Returns the capability and pressed state of each of the given events
+fn summarize(events: Vec<NativeEvent>) -> Vec<(Capability, bool)> {
+    events
+        .iter()
+        .map(|event| (event.as_capability(), event.pressed()))
+        .collect()
+}
+
+fn dpad_anti_ghosting() -> AntiGhosting {
+    let mut anti_ghosting = AntiGhosting::new();
+    anti_ghosting.set_axes(vec![(
+        button(GamepadButton::DPadLeft),
+        button(GamepadButton::DPadRight),
+    )]);
+    anti_ghosting
+}
+
+#[tokio::test]
+async fn test_anti_ghosting_disabled() {
+    let anti_ghosting = AntiGhosting::new();
+    assert!(!anti_ghosting.is_enabled());
+}
+
+#[tokio::test]
+async fn test_anti_ghosting_releases_conflicting_button() {
+    let mut anti_ghosting = dpad_anti_ghosting();
+    let left = button(GamepadButton::DPadLeft);
+    let right = button(GamepadButton::DPadRight);
+
+    let events = summarize(anti_ghosting.update(event(&left, true)));
+    assert_eq!(events, vec![(left.clone(), true)]);
+
+    // Pressing right while left is held releases left first
+    let events = summarize(anti_ghosting.update(event(&right, true)));
+    assert_eq!(events, vec![(left.clone(), false), (right.clone(), true)]);
+
+    // Releasing right presses left again since it is still held
+    let events = summarize(anti_ghosting.update(event(&right, false)));
+    assert_eq!(events, vec![(right.clone(), false), (left.clone(), true)]);
+
+    let events = summarize(anti_ghosting.update(event(&left, false)));
+    assert_eq!(events, vec![(left, false)]);
+}
+
+#[tokio::test]
+async fn test_anti_ghosting_suppressed_release() {
+    let mut anti_ghosting = dpad_anti_ghosting();
+    let left = button(GamepadButton::DPadLeft);
+    let right = button(GamepadButton::DPadRight);
+
+    anti_ghosting.update(event(&left, true));
+    anti_ghosting.update(event(&right, true));
+
+    // Left was already released, so its release is dropped
+    assert!(anti_ghosting.update(event(&left, false)).is_empty());
+    let events = summarize(anti_ghosting.update(event(&right, false)));
+    assert_eq!(events, vec![(right, false)]);
+}
+
+#[tokio::test]
+async fn test_anti_ghosting_other_buttons() {
+    let mut anti_ghosting = dpad_anti_ghosting();
+    let south = button(GamepadButton::South);
+    let events = summarize(anti_ghosting.update(event(&south, true)));
+    assert_eq!(events, vec![(south, true)]);
+}
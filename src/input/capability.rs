@@ -19,8 +19,14 @@ pub enum Capability {
     Gamepad(Gamepad),
     Mouse(Mouse),
     Keyboard(Keyboard),
+    /// Keyboard input identified by its XKB keysym name (e.g. "udiaeresis")
+    /// instead of a key code, so the emitted symbol does not depend on the
+    /// keyboard layout.
+    KeyboardKeysym(String),
     Touchpad(Touchpad),
     Touchscreen(Touch),
+    /// Multi-touch gestures recognized from touch input
+    Gesture(Gesture),
 }
 
 impl fmt::Display for Capability {
@@ -32,9 +38,11 @@ impl fmt::Display for Capability {
             Capability::Gamepad(_) => write!(f, "Gamepad"),
             Capability::Mouse(_) => write!(f, "Mouse"),
             Capability::Keyboard(_) => write!(f, "Keyboard"),
+            Capability::KeyboardKeysym(_) => write!(f, "KeyboardKeysym"),
             Capability::DBus(_) => write!(f, "DBus"),
             Capability::Touchpad(_) => write!(f, "Touchpad"),
             Capability::Touchscreen(_) => write!(f, "Touchscreen"),
+            Capability::Gesture(_) => write!(f, "Gesture"),
         }
     }
 }
@@ -57,6 +65,13 @@ impl FromStr for Capability {
             "Keyboard" => Ok(Capability::Keyboard(Keyboard::from_str(
                 parts.join(":").as_str(),
             )?)),
+            "KeyboardKeysym" => {
+                let keysym = parts.join(":");
+                if keysym.is_empty() {
+                    return Err(());
+                }
+                Ok(Capability::KeyboardKeysym(keysym))
+            }
             "Mouse" => Ok(Capability::Mouse(Mouse::from_str(
                 parts.join(":").as_str(),
             )?)),
@@ -69,11 +84,63 @@ impl FromStr for Capability {
             "Touchscreen" => Ok(Capability::Touchscreen(Touch::from_str(
                 parts.join(":").as_str(),
             )?)),
+            "Gesture" => Ok(Capability::Gesture(Gesture::from_str(
+                parts.join(":").as_str(),
+            )?)),
             _ => Err(()),
         }
     }
 }
 
+impl Capability {
+    /// Returns the fully qualified string representation of the capability
+    /// (e.g. "Gamepad:Button:South"), which can be parsed back into a
+    /// [Capability] using [Capability::from_str].
+    pub fn to_capability_string(&self) -> String {
+        match self {
+            Capability::Gamepad(gamepad) => match gamepad {
+                Gamepad::Button(button) => format!("Gamepad:Button:{button}"),
+                Gamepad::Axis(axis) => format!("Gamepad:Axis:{axis}"),
+                Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{trigger}"),
+                Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
+                Gamepad::Gyro => "Gamepad:Gyro".to_string(),
+            },
+            Capability::Mouse(mouse) => match mouse {
+                Mouse::Motion => "Mouse:Motion".to_string(),
+                Mouse::AbsoluteMotion => "Mouse:AbsoluteMotion".to_string(),
+                Mouse::Button(button) => format!("Mouse:Button:{button}"),
+                Mouse::DoubleClick(button) => format!("Mouse:DoubleClick:{button}"),
+            },
+            Capability::Keyboard(key) => format!("Keyboard:{key}"),
+            Capability::KeyboardKeysym(keysym) => format!("KeyboardKeysym:{keysym}"),
+            Capability::DBus(action) => format!("DBus:{}", action.as_str()),
+            Capability::Touchpad(touchpad) => {
+                let touch = match touchpad {
+                    Touchpad::LeftPad(touch) => touch,
+                    Touchpad::RightPad(touch) => touch,
+                    Touchpad::CenterPad(touch) => touch,
+                };
+                format!("Touchpad:{touchpad}:{}", touch.to_capability_string())
+            }
+            Capability::Touchscreen(touch) => {
+                format!("Touchscreen:{}", touch.to_capability_string())
+            }
+            Capability::Gesture(gesture) => match gesture {
+                Gesture::TwoFingerSwipe(direction) => {
+                    format!("Gesture:TwoFingerSwipe:{direction}")
+                }
+                Gesture::ThreeFingerSwipe(direction) => {
+                    format!("Gesture:ThreeFingerSwipe:{direction}")
+                }
+                Gesture::TwoFingerPinch(direction) => {
+                    format!("Gesture:TwoFingerPinch:{direction}")
+                }
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
 impl From<CapabilityConfig> for Capability {
     fn from(value: CapabilityConfig) -> Self {
         // Gamepad
@@ -134,6 +201,11 @@ impl From<CapabilityConfig> for Capability {
             return Capability::Keyboard(key);
         }
 
+        // Keysym
+        if let Some(keysym) = value.keysym.as_ref() {
+            return Capability::KeyboardKeysym(keysym.clone());
+        }
+
         // Mouse
         if let Some(mouse) = value.mouse.as_ref() {
             // Motion
@@ -141,6 +213,11 @@ impl From<CapabilityConfig> for Capability {
                 return Capability::Mouse(Mouse::Motion);
             }
 
+            // Absolute motion
+            if mouse.absolute_motion.unwrap_or_default() {
+                return Capability::Mouse(Mouse::AbsoluteMotion);
+            }
+
             // Button
             if let Some(button_string) = mouse.button.as_ref() {
                 let button = MouseButton::from_str(button_string);
@@ -151,6 +228,17 @@ impl From<CapabilityConfig> for Capability {
                 let button = button.unwrap();
                 return Capability::Mouse(Mouse::Button(button));
             }
+
+            // Double click
+            if let Some(button_string) = mouse.double_click.as_ref() {
+                let button = MouseButton::from_str(button_string);
+                if button.is_err() {
+                    log::error!("Invalid or unimplemented button: {button_string}");
+                    return Capability::NotImplemented;
+                }
+                let button = button.unwrap();
+                return Capability::Mouse(Mouse::DoubleClick(button));
+            }
         }
 
         // DBus
@@ -169,6 +257,8 @@ impl From<CapabilityConfig> for Capability {
             let touch = {
                 if touchpad.touch.motion.is_some() {
                     Touch::Motion
+                } else if touchpad.touch.velocity.unwrap_or_default() {
+                    Touch::Velocity
                 } else if touchpad.touch.button.is_some() {
                     let button_string = touchpad.touch.button.as_ref().unwrap();
                     let button = TouchButton::from_str(button_string.as_str());
@@ -212,6 +302,15 @@ impl From<CapabilityConfig> for Capability {
             }
         }
 
+        // Gesture
+        if let Some(gesture_string) = value.gesture.as_ref() {
+            let Ok(gesture) = Gesture::from_str(gesture_string) else {
+                log::error!("Invalid or unimplemented gesture: {gesture_string}");
+                return Capability::NotImplemented;
+            };
+            return Capability::Gesture(gesture);
+        }
+
         Capability::NotImplemented
     }
 }
@@ -275,15 +374,22 @@ impl FromStr for Gamepad {
 pub enum Mouse {
     /// Represents (x, y) relative mouse motion
     Motion,
+    /// Represents the (x, y) absolute position of the mouse on the screen,
+    /// normalized between 0.0 and 1.0 where (0, 0) is the top-left corner
+    AbsoluteMotion,
     /// Mouse Buttons are typically binary mouse input that represents button presses
     Button(MouseButton),
+    /// Two clicks of the given mouse button in quick succession
+    DoubleClick(MouseButton),
 }
 
 impl fmt::Display for Mouse {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Mouse::Motion => write!(f, "Motion"),
+            Mouse::AbsoluteMotion => write!(f, "AbsoluteMotion"),
             Mouse::Button(_) => write!(f, "Button"),
+            Mouse::DoubleClick(_) => write!(f, "DoubleClick"),
         }
     }
 }
@@ -298,9 +404,13 @@ impl FromStr for Mouse {
         };
         match *part {
             "Motion" => Ok(Mouse::Motion),
+            "AbsoluteMotion" => Ok(Mouse::AbsoluteMotion),
             "Button" => Ok(Mouse::Button(MouseButton::from_str(
                 parts.join(":").as_str(),
             )?)),
+            "DoubleClick" => Ok(Mouse::DoubleClick(MouseButton::from_str(
+                parts.join(":").as_str(),
+            )?)),
             _ => Err(()),
         }
     }
@@ -1149,6 +1259,9 @@ impl FromStr for Touchpad {
 pub enum Touch {
     Motion,
     Button(TouchButton),
+    /// Represents the (x, y) velocity of the finger on the touch surface in
+    /// normalized units per second
+    Velocity,
 }
 
 impl fmt::Display for Touch {
@@ -1156,6 +1269,18 @@ impl fmt::Display for Touch {
         match self {
             Touch::Motion => write!(f, "Motion"),
             Touch::Button(_) => write!(f, "Button"),
+            Touch::Velocity => write!(f, "Velocity"),
+        }
+    }
+}
+
+impl Touch {
+    /// Returns the fully qualified string representation of the touch input
+    fn to_capability_string(&self) -> String {
+        match self {
+            Touch::Motion => "Motion".to_string(),
+            Touch::Button(button) => format!("Button:{button}"),
+            Touch::Velocity => "Velocity".to_string(),
         }
     }
 }
@@ -1169,6 +1294,7 @@ impl FromStr for Touch {
         };
         match *part {
             "Motion" => Ok(Touch::Motion),
+            "Velocity" => Ok(Touch::Velocity),
             "Button" => Ok(Touch::Button(TouchButton::from_str(
                 parts.join(":").as_str(),
             )?)),
@@ -1202,3 +1328,127 @@ impl FromStr for TouchButton {
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Gesture {
+    /// Two fingers moving together in the same direction
+    TwoFingerSwipe(SwipeDirection),
+    /// Three fingers moving together in the same direction
+    ThreeFingerSwipe(SwipeDirection),
+    /// Two fingers moving towards or away from each other
+    TwoFingerPinch(PinchDirection),
+}
+
+impl Gesture {
+    /// Returns a list of all possible gestures
+    pub fn all() -> Vec<Gesture> {
+        let directions = [
+            SwipeDirection::Up,
+            SwipeDirection::Down,
+            SwipeDirection::Left,
+            SwipeDirection::Right,
+        ];
+        let mut gestures = Vec::new();
+        for direction in directions.iter() {
+            gestures.push(Gesture::TwoFingerSwipe(direction.clone()));
+        }
+        for direction in directions.iter() {
+            gestures.push(Gesture::ThreeFingerSwipe(direction.clone()));
+        }
+        gestures.push(Gesture::TwoFingerPinch(PinchDirection::In));
+        gestures.push(Gesture::TwoFingerPinch(PinchDirection::Out));
+        gestures
+    }
+}
+
+impl fmt::Display for Gesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Gesture::TwoFingerSwipe(_) => write!(f, "TwoFingerSwipe"),
+            Gesture::ThreeFingerSwipe(_) => write!(f, "ThreeFingerSwipe"),
+            Gesture::TwoFingerPinch(_) => write!(f, "TwoFingerPinch"),
+        }
+    }
+}
+
+impl FromStr for Gesture {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let Some((part, parts)) = parts.split_first() else {
+            return Err(());
+        };
+        match *part {
+            "TwoFingerSwipe" => Ok(Gesture::TwoFingerSwipe(SwipeDirection::from_str(
+                parts.join(":").as_str(),
+            )?)),
+            "ThreeFingerSwipe" => Ok(Gesture::ThreeFingerSwipe(SwipeDirection::from_str(
+                parts.join(":").as_str(),
+            )?)),
+            "TwoFingerPinch" => Ok(Gesture::TwoFingerPinch(PinchDirection::from_str(
+                parts.join(":").as_str(),
+            )?)),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl fmt::Display for SwipeDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SwipeDirection::Up => write!(f, "Up"),
+            SwipeDirection::Down => write!(f, "Down"),
+            SwipeDirection::Left => write!(f, "Left"),
+            SwipeDirection::Right => write!(f, "Right"),
+        }
+    }
+}
+
+impl FromStr for SwipeDirection {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Up" => Ok(SwipeDirection::Up),
+            "Down" => Ok(SwipeDirection::Down),
+            "Left" => Ok(SwipeDirection::Left),
+            "Right" => Ok(SwipeDirection::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PinchDirection {
+    /// Fingers moving towards each other
+    In,
+    /// Fingers moving away from each other
+    Out,
+}
+
+impl fmt::Display for PinchDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PinchDirection::In => write!(f, "In"),
+            PinchDirection::Out => write!(f, "Out"),
+        }
+    }
+}
+
+impl FromStr for PinchDirection {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "In" => Ok(PinchDirection::In),
+            "Out" => Ok(PinchDirection::Out),
+            _ => Err(()),
+        }
+    }
+}
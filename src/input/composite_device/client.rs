@@ -1,13 +1,26 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    os::fd::OwnedFd,
+    time::Duration,
+};
 use thiserror::Error;
-use tokio::sync::mpsc::{channel, error::SendError, Sender};
+use tokio::sync::mpsc::{
+    channel,
+    error::{SendError, TrySendError},
+    Receiver, Sender,
+};
 
+use crate::config::{CompositeDeviceConfig, TestResult};
 use crate::input::event::native::NativeEvent;
 use crate::input::target::client::TargetDeviceClient;
-use crate::input::{capability::Capability, event::Event, output_event::OutputEvent};
+use crate::input::{
+    capability::Capability, event::Event, gyro_mouse::GyroMouseConfig,
+    motion_profile::MotionProfileConfig, output_event::OutputEvent,
+    touchpad_mouse::TouchpadMouseEmulation,
+};
 use crate::udev::device::UdevDevice;
 
-use super::{CompositeCommand, InterceptMode};
+use super::{CompositeCommand, HookPoint, InterceptChord, InterceptMode};
 
 /// Possible errors for a composite device client
 #[derive(Error, Debug)]
@@ -18,6 +31,8 @@ pub enum ClientError {
     ServiceError(Box<dyn std::error::Error>),
     #[error("device no longer exists")]
     ChannelClosed,
+    #[error("device command channel is full")]
+    ChannelFull,
 }
 
 impl From<SendError<CompositeCommand>> for ClientError {
@@ -53,6 +68,28 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get the capabilities of all currently pressed inputs
+    pub async fn get_active_inputs(&self) -> Result<Vec<Capability>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetActiveInputs(tx)).await?;
+        if let Some(inputs) = rx.recv().await {
+            return Ok(inputs);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Request the number of queued commands as a fraction of the command
+    /// channel capacity without blocking. The load is sent to the returned
+    /// receiver once the composite device processes the request.
+    pub fn try_get_channel_load(&self) -> Result<Receiver<f32>, ClientError> {
+        let (tx, rx) = channel(1);
+        match self.tx.try_send(CompositeCommand::GetChannelLoad(tx)) {
+            Ok(_) => Ok(rx),
+            Err(TrySendError::Full(_)) => Err(ClientError::ChannelFull),
+            Err(TrySendError::Closed(_)) => Err(ClientError::ChannelClosed),
+        }
+    }
+
     /// Process the given event from the given device
     pub async fn process_event(&self, device_id: String, event: Event) -> Result<(), ClientError> {
         self.tx
@@ -97,6 +134,21 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get capabilities from the source device with the given id
+    pub async fn get_source_device_capabilities(
+        &self,
+        source_id: String,
+    ) -> Result<HashSet<Capability>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetSourceDeviceCapabilities(source_id, tx))
+            .await?;
+        if let Some(capabilities) = rx.recv().await {
+            return Ok(capabilities);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Get capabilities from all target devices
     pub async fn get_target_capabilities(&self) -> Result<HashSet<Capability>, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -127,6 +179,160 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Enable or disable passthrough mode, where source events are written
+    /// to target devices without any translation.
+    pub async fn set_passthrough_mode(&self, enabled: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetPassthroughMode(enabled))
+            .await?;
+        Ok(())
+    }
+
+    /// Load the given WebAssembly module as the script used to transform
+    /// events. An empty module unloads the current script.
+    pub async fn load_wasm_script(&self, module: Vec<u8>) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::LoadWasmScript(module, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns true if passthrough mode is enabled
+    pub async fn get_passthrough_mode(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetPassthroughMode(tx))
+            .await?;
+        if let Some(enabled) = rx.recv().await {
+            return Ok(enabled);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the gyro to mouse emulation configuration, if enabled
+    pub async fn get_gyro_mouse_mode(&self) -> Result<Option<GyroMouseConfig>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetGyroMouseMode(tx)).await?;
+        if let Some(config) = rx.recv().await {
+            return Ok(config);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the gyro to mouse emulation configuration. Emulation is disabled
+    /// if the configuration is [None].
+    pub async fn set_gyro_mouse_mode(
+        &self,
+        config: Option<GyroMouseConfig>,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetGyroMouseMode(config))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the configuration used to load a different profile depending on
+    /// the orientation of the device. Motion profile switching is disabled if
+    /// the configuration is [None].
+    pub async fn set_motion_profile(
+        &self,
+        config: Option<MotionProfileConfig>,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetMotionProfile(config))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the amount of time in milliseconds in which both clicks of a
+    /// double click must happen
+    pub async fn get_double_click_window(&self) -> Result<u64, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetDoubleClickWindow(tx))
+            .await?;
+        if let Some(window_ms) = rx.recv().await {
+            return Ok(window_ms);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the amount of time in milliseconds in which both clicks of a
+    /// double click must happen
+    pub async fn set_double_click_window(&self, window_ms: u64) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetDoubleClickWindow(window_ms))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the mutually exclusive pairs of source buttons
+    pub async fn get_anti_ghosting(&self) -> Result<Vec<(Capability, Capability)>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetAntiGhosting(tx)).await?;
+        if let Some(axes) = rx.recv().await {
+            return Ok(axes);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the mutually exclusive pairs of source buttons. An empty list
+    /// disables anti-ghosting.
+    pub async fn set_anti_ghosting(
+        &self,
+        axes: Vec<(Capability, Capability)>,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetAntiGhosting(axes))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the touchpad to mouse emulation mode
+    pub async fn get_touchpad_mouse_emulation(
+        &self,
+    ) -> Result<TouchpadMouseEmulation, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetTouchpadMouseEmulation(tx))
+            .await?;
+        if let Some(mode) = rx.recv().await {
+            return Ok(mode);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the touchpad to mouse emulation mode
+    pub async fn set_touchpad_mouse_emulation(
+        &self,
+        mode: TouchpadMouseEmulation,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetTouchpadMouseEmulation(mode))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the name of the mode activated by the last intercept chord, if
+    /// intercept mode was activated by a chord.
+    pub async fn get_intercept_mode_name(&self) -> Result<Option<String>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetInterceptModeName(tx))
+            .await?;
+        if let Some(name) = rx.recv().await {
+            return Ok(name);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Get the source device paths of the composite device
     pub async fn get_source_device_paths(&self) -> Result<Vec<String>, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -189,6 +395,24 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Create a target device of the given type (e.g. "mouse") and attach it
+    /// to the composite device. Other target devices are not affected.
+    pub async fn add_target_device(&self, kind: String) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::AddTargetDevice(kind))
+            .await?;
+        Ok(())
+    }
+
+    /// Stop the target device with the given DBus path and remove it from the
+    /// composite device. Other target devices are not affected.
+    pub async fn remove_target_device(&self, path: String) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::RemoveTargetDevice(path))
+            .await?;
+        Ok(())
+    }
+
     /// Attach the given target devices to the composite device
     pub async fn attach_target_devices(
         &self,
@@ -200,6 +424,85 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Pause the target device with the given DBus path. The target device
+    /// will drop input events until it is resumed.
+    pub async fn pause_target(&self, path: String) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::PauseTarget(path)).await?;
+        Ok(())
+    }
+
+    /// Resume the paused target device with the given DBus path
+    pub async fn resume_target(&self, path: String) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::ResumeTarget(path)).await?;
+        Ok(())
+    }
+
+    /// Replace the given capability of events from the source device with the
+    /// given id with another capability
+    pub async fn override_capability(
+        &self,
+        source_id: String,
+        from: Capability,
+        to: Capability,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::OverrideCapability(source_id, from, to))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the maximum rate in Hz at which events with the given capability
+    /// are processed from each source device. A rate of zero removes the limit.
+    pub async fn set_capability_rate_limit(
+        &self,
+        capability: Capability,
+        max_hz: u32,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetCapabilityRateLimit(capability, max_hz))
+            .await?;
+        Ok(())
+    }
+
+    /// Stop the composite device if no events are received from any source
+    /// device within the given duration. A duration of zero disables the
+    /// watchdog.
+    pub async fn set_event_watchdog(&self, timeout: Duration) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetEventWatchdog(timeout))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the list of profile paths to cycle through
+    pub async fn set_profile_cycle_list(&self, paths: Vec<String>) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetProfileCycleList(paths))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the button combo that loads the next profile in the profile cycle
+    /// list
+    pub async fn set_profile_cycle_combo(&self, combo: Vec<Capability>) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetProfileCycleCombo(combo))
+            .await?;
+        Ok(())
+    }
+
+    /// Load the next profile in the profile cycle list
+    pub async fn next_profile(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::NextProfile).await?;
+        Ok(())
+    }
+
+    /// Load the previous profile in the profile cycle list
+    pub async fn previous_profile(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::PreviousProfile).await?;
+        Ok(())
+    }
+
     /// Get the name of the currently loaded profile
     pub async fn get_profile_name(&self) -> Result<String, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -210,6 +513,79 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get the number of times each profile mapping was activated, keyed by
+    /// the name of the mapping
+    pub async fn get_mapping_statistics(&self) -> Result<HashMap<String, u64>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetMappingStatistics(tx))
+            .await?;
+        if let Some(statistics) = rx.recv().await {
+            return Ok(statistics);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Reset the activation counts of all profile mappings
+    pub async fn reset_mapping_statistics(&self) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::ResetMappingStatistics)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the parsed config that was used to create the composite device
+    pub async fn get_config(&self) -> Result<CompositeDeviceConfig, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetCompositeDeviceConfig(tx))
+            .await?;
+        if let Some(config) = rx.recv().await {
+            return Ok(config);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the effective input mapping of the composite device as a JSON array
+    /// of all capability map and device profile translations.
+    pub async fn get_effective_mapping_json(&self) -> Result<String, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetEffectiveMapping(tx))
+            .await?;
+        if let Some(json) = rx.recv().await {
+            return Ok(json);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Run the tests defined in the capability map of the composite device
+    /// and return their results. Returns no results if no capability map is
+    /// loaded.
+    pub async fn run_capability_map_tests(&self) -> Result<Vec<TestResult>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::RunCapabilityMapTests(tx))
+            .await?;
+        if let Some(results) = rx.recv().await {
+            return Ok(results);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Reload the composite device config from the file it was loaded from
+    pub async fn reload_config(&self) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::ReloadConfig(tx)).await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Load the device profile from the given path
     pub async fn load_profile_path(&self, path: String) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
@@ -225,6 +601,29 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Save the path of the currently loaded profile so it is loaded again
+    /// when the composite device is recreated, e.g. after a restart.
+    pub async fn save_profile_assignment(&self) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SaveProfileAssignment(tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Watch the device profile at the given path and automatically reload it
+    /// whenever the file changes.
+    pub async fn watch_profile(&self, path: String) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::WatchProfile(path)).await?;
+        Ok(())
+    }
+
     /// Load the device profile from the given path
     pub async fn load_profile_from_yaml(&self, profile: String) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
@@ -277,6 +676,131 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Record all events processed by the composite device to the event log
+    /// at the given path. Events are appended if the log already exists.
+    pub async fn enable_event_log(&self, path: String) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::EnableEventLog(path)).await?;
+        Ok(())
+    }
+
+    /// Start streaming all processed events to a Unix domain socket at up to
+    /// the given rate in Hz. A rate of zero does not limit the stream. Returns
+    /// the file descriptor of the socket to read events from.
+    pub async fn start_event_stream(&self, max_hz: u32) -> Result<OwnedFd, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::StartEventStream(max_hz, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(fd) => Ok(fd),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Register the given channel to receive a copy of every event at the
+    /// given point in the event pipeline. Events are dropped if the receiver
+    /// falls behind. Returns the id of the hook.
+    pub async fn register_hook(
+        &self,
+        point: HookPoint,
+        hook: Sender<NativeEvent>,
+    ) -> Result<usize, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::RegisterHook(point, hook, tx))
+            .await?;
+        if let Some(id) = rx.recv().await {
+            return Ok(id);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Unregister the hook with the given id at the given hook point
+    pub async fn unregister_hook(&self, point: HookPoint, id: usize) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::UnregisterHook(point, id))
+            .await?;
+        Ok(())
+    }
+
+    /// Stop streaming events
+    pub async fn stop_event_stream(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::StopEventStream).await?;
+        Ok(())
+    }
+
+    /// Stop recording events to the event log
+    pub async fn disable_event_log(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::DisableEventLog).await?;
+        Ok(())
+    }
+
+    /// Enable or disable dry run mode, where events are only logged instead
+    /// of being written to target and source devices.
+    pub async fn set_dry_run(&self, enabled: bool) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::SetDryRun(enabled)).await?;
+        Ok(())
+    }
+
+    /// Inject the given synthetic event, skipping source device blocking and
+    /// capability map translation. This is useful for scripts and test
+    /// harnesses that already have events in native form.
+    pub async fn inject_event(&self, event: NativeEvent) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::InjectEvent(event)).await?;
+        Ok(())
+    }
+
+    /// Replay the events recorded in the event log at the given path with
+    /// their original timing. Returns once all events have been replayed.
+    pub async fn start_replay(&self, path: String) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::StartReplay(path, tx))
+            .await?;
+        if rx.recv().await.is_some() {
+            return Ok(());
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Add a simulated source device with the given id and capabilities that
+    /// does not exist on the system. Returns a channel that events can be
+    /// sent to, which are processed as if they came from the source device.
+    /// The simulated source device is removed when the channel is dropped.
+    pub async fn simulate_source_device(
+        &self,
+        id: String,
+        capabilities: HashSet<Capability>,
+    ) -> Result<Sender<NativeEvent>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SimulateSourceDevice(id, capabilities, tx))
+            .await?;
+        if let Some(events) = rx.recv().await {
+            return Ok(events);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Query all running source devices for their capabilities and rebuild
+    /// the capabilities of the composite device.
+    pub async fn refresh_capabilities(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::RefreshCapabilities).await?;
+        Ok(())
+    }
+
+    /// Refresh the stored device information of the source device with the
+    /// given id (e.g. "evdev://event0").
+    pub async fn refresh_source_info(&self, device_id: String) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::RefreshSourceInfo(device_id))
+            .await?;
+        Ok(())
+    }
+
     /// Remove the given event type from list of recently translated events
     pub async fn remove_recent_event(&self, capability: Capability) -> Result<(), ClientError> {
         self.tx
@@ -285,18 +809,24 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
-    /// Set the events to look for to activate input interception while in
+    /// Set the chords to look for to activate input interception while in
     /// "PASS" mode.
     pub async fn set_intercept_activation(
         &self,
-        activation_caps: Vec<Capability>,
-        target_cap: Capability,
+        chords: Vec<InterceptChord>,
     ) -> Result<(), ClientError> {
         self.tx
-            .send(CompositeCommand::SetInterceptActivation(
-                activation_caps,
-                target_cap,
-            ))
+            .send(CompositeCommand::SetInterceptActivation(chords))
+            .await?;
+        Ok(())
+    }
+
+    /// Take over all source devices of the given composite device and stop
+    /// it. The source devices of the other composite device are added to
+    /// this composite device once they have been released.
+    pub async fn merge_device(&self, other: &CompositeDeviceClient) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::MergeDevice(other.tx.clone()))
             .await?;
         Ok(())
     }
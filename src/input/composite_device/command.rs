@@ -1,47 +1,223 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    os::fd::OwnedFd,
+    time::Duration,
+};
 
 use tokio::sync::mpsc;
 
 use crate::{
+    config::{CompositeDeviceConfig, TestResult},
     input::{
         capability::Capability,
         event::{native::NativeEvent, Event},
+        gyro_mouse::GyroMouseConfig,
+        motion_profile::MotionProfileConfig,
         output_event::OutputEvent,
         target::client::TargetDeviceClient,
+        touchpad_mouse::TouchpadMouseEmulation,
     },
     udev::device::UdevDevice,
 };
 
-use super::InterceptMode;
+use super::{HookPoint, InterceptChord, InterceptMode};
 
 /// CompositeDevice commands define all the different ways to interact with [CompositeDevice]
 /// over a channel. These commands are processed in an asyncronous thread and
 /// dispatched as they come in.
 #[derive(Debug, Clone)]
 pub enum CompositeCommand {
+    /// Create a target device of the given type and attach it to the
+    /// composite device without affecting any other target devices
+    AddTargetDevice(String),
     AttachTargetDevices(HashMap<String, TargetDeviceClient>),
+    GetActiveInputs(mpsc::Sender<Vec<Capability>>),
+    /// Get the number of queued commands as a fraction of the command channel
+    /// capacity. Used by source devices to slow down when the channel is
+    /// close to full.
+    GetChannelLoad(mpsc::Sender<f32>),
+    /// Stop recording processed events to the event log
+    DisableEventLog,
+    /// Record all processed events to the event log at the given path
+    EnableEventLog(String),
     GetCapabilities(mpsc::Sender<HashSet<Capability>>),
+    /// Get the parsed config that was used to create the composite device
+    GetCompositeDeviceConfig(mpsc::Sender<CompositeDeviceConfig>),
     GetDBusDevicePaths(mpsc::Sender<Vec<String>>),
+    /// Get the amount of time in milliseconds in which both clicks of a
+    /// double click must happen
+    GetDoubleClickWindow(mpsc::Sender<u64>),
+    /// Get the mutually exclusive pairs of source buttons
+    GetAntiGhosting(mpsc::Sender<Vec<(Capability, Capability)>>),
+    GetEffectiveMapping(mpsc::Sender<String>),
+    GetGyroMouseMode(mpsc::Sender<Option<GyroMouseConfig>>),
     GetInterceptMode(mpsc::Sender<InterceptMode>),
+    GetInterceptModeName(mpsc::Sender<Option<String>>),
     GetName(mpsc::Sender<String>),
+    GetPassthroughMode(mpsc::Sender<bool>),
+    /// Get the number of times each profile mapping was activated, keyed by
+    /// the name of the mapping
+    GetMappingStatistics(mpsc::Sender<HashMap<String, u64>>),
     GetProfileName(mpsc::Sender<String>),
+    /// Get the capabilities of the source device with the given id
+    GetSourceDeviceCapabilities(String, mpsc::Sender<HashSet<Capability>>),
     GetSourceDevicePaths(mpsc::Sender<Vec<String>>),
     GetTargetCapabilities(mpsc::Sender<HashSet<Capability>>),
     GetTargetDevicePaths(mpsc::Sender<Vec<String>>),
+    GetTouchpadMouseEmulation(mpsc::Sender<TouchpadMouseEmulation>),
+    /// Load the next profile in the profile cycle list
+    NextProfile,
+    /// Load the previous profile in the profile cycle list
+    PreviousProfile,
+    /// Translate the given event using the device profile and write it to
+    /// target devices. Used internally to re-enqueue events translated by the
+    /// capability map.
     HandleEvent(NativeEvent),
+    /// Translate and write an event generated by a profile mapping with a
+    /// hold time. Unlike [CompositeCommand::HandleEvent], the event is not
+    /// recorded to the event log.
+    HandleHoldEvent(NativeEvent),
+    /// Sent when a source button with a hold mapping has been held for the
+    /// hold time.
+    HoldTimerExpired(NativeEvent),
+    /// Sent when the double click window of a held back mouse button click
+    /// may have passed
+    DoubleClickWindowExpired,
+    /// Sent when events held back by the rate limit may be allowed
+    RateLimitTimerExpired,
+    /// Detect event gaps and signal source devices whose health changed
+    CheckSourceHealth,
+    /// Sent when no additional tap of a source button with tap dance
+    /// mappings arrived within the tap window. Contains the press event and
+    /// the tap count when the timer was started.
+    TapDanceTimerExpired(NativeEvent, u32),
+    /// Sent when the chord window of the capability mapping with the given
+    /// name has passed without all of its source events being pressed.
+    ChordWindowExpired(String),
+    /// Inject a synthetic event that is already in native form. The event
+    /// skips source device blocking and capability map translation, but is
+    /// still translated by the device profile and routed like any other
+    /// event. Unlike [CompositeCommand::WriteSendEvent], no delay is applied
+    /// to events emitted in quick succession.
+    InjectEvent(NativeEvent),
     LoadProfileFromYaml(String, mpsc::Sender<Result<(), String>>),
+    /// Load the given WebAssembly module as the script used to transform
+    /// events before they are translated. An empty module unloads the
+    /// current script.
+    LoadWasmScript(Vec<u8>, mpsc::Sender<Result<(), String>>),
     LoadProfilePath(String, mpsc::Sender<Result<(), String>>),
+    /// Replace the first capability with the second capability for all events
+    /// from the source device with the given id
+    OverrideCapability(String, Capability, Capability),
+    /// Pause the target device with the given DBus path
+    PauseTarget(String),
     ProcessEvent(String, Event),
     ProcessOutputEvent(OutputEvent),
+    /// Query all running source devices for their capabilities and rebuild
+    /// the capabilities of the composite device
+    RefreshCapabilities,
+    RefreshSourceInfo(String),
+    /// Reload the composite device config from the file it was loaded from,
+    /// adding any new source devices and blocking or unblocking running
+    /// source devices according to the updated config
+    ReloadConfig(mpsc::Sender<Result<(), String>>),
     RemoveRecentEvent(Capability),
-    SetInterceptActivation(Vec<Capability>, Capability),
+    /// Stop the target device with the given DBus path without affecting any
+    /// other target devices
+    RemoveTargetDevice(String),
+    /// Reset the activation counts of all profile mappings
+    ResetMappingStatistics,
+    /// Run the tests defined in the loaded capability map
+    RunCapabilityMapTests(mpsc::Sender<Vec<TestResult>>),
+    /// Register a channel that receives a copy of every event at the given
+    /// point in the event pipeline. The id of the hook is sent back.
+    RegisterHook(HookPoint, mpsc::Sender<NativeEvent>, mpsc::Sender<usize>),
+    /// Unregister the hook with the given id at the given hook point
+    UnregisterHook(HookPoint, usize),
+    /// Resume the paused target device with the given DBus path
+    ResumeTarget(String),
+    /// Start streaming all processed events to a new Unix domain socket at
+    /// up to the given rate in Hz, replacing any running event stream. The
+    /// file descriptor of the socket to read events from is sent back.
+    StartEventStream(u32, mpsc::Sender<Result<OwnedFd, String>>),
+    /// Stop the running event stream, if any
+    StopEventStream,
+    /// Replay the events recorded in the event log at the given path with the
+    /// same timing they were recorded with. Events are injected as if sent
+    /// with [CompositeCommand::InjectEvent]. The sender is notified once all
+    /// events have been replayed.
+    StartReplay(String, mpsc::Sender<()>),
+    /// Save the path of the currently loaded profile as the profile to load
+    /// for this composite device in the user config, so it is loaded again
+    /// after restarts.
+    SaveProfileAssignment(mpsc::Sender<Result<(), String>>),
+    /// Set the maximum rate in Hz at which events with the given capability
+    /// are processed from each source device. A rate of zero removes the limit.
+    SetCapabilityRateLimit(Capability, u32),
+    /// Enable or disable dry run mode. In dry run mode, events are logged
+    /// instead of being written to target devices, and output events are
+    /// not written to source devices.
+    SetDryRun(bool),
+    /// Set the amount of time in milliseconds in which both clicks of a
+    /// double click must happen
+    SetDoubleClickWindow(u64),
+    /// Set the mutually exclusive pairs of source buttons. Pressing one
+    /// button of a pair while the other is held releases the other button.
+    /// An empty list disables anti-ghosting.
+    SetAntiGhosting(Vec<(Capability, Capability)>),
+    /// Stop the composite device if no events are received from any source
+    /// device within the given duration. A duration of zero disables the
+    /// watchdog.
+    SetEventWatchdog(Duration),
+    SetInterceptActivation(Vec<InterceptChord>),
     SetInterceptMode(InterceptMode),
+    /// Enable or disable passthrough mode. In passthrough mode, source events
+    /// are written to target devices without capability map or device
+    /// profile translation.
+    SetPassthroughMode(bool),
+    /// Set the configuration used to translate gyro motion into mouse
+    /// motion, or disable gyro mouse emulation if [None]
+    SetGyroMouseMode(Option<GyroMouseConfig>),
+    /// Set the configuration used to load a different profile depending on
+    /// the orientation of the device, or disable it if [None]
+    SetMotionProfile(Option<MotionProfileConfig>),
+    /// Set the button combo that loads the next profile in the profile cycle
+    /// list when pressed
+    SetProfileCycleCombo(Vec<Capability>),
+    /// Set the list of profile paths to cycle through with
+    /// [CompositeCommand::NextProfile] and [CompositeCommand::PreviousProfile]
+    SetProfileCycleList(Vec<String>),
     SetTargetDevices(Vec<String>),
+    /// Set whether and how touchpad motion is translated into mouse motion
+    SetTouchpadMouseEmulation(TouchpadMouseEmulation),
+    /// Add a simulated source device with the given id and capabilities that
+    /// does not exist on the system. A channel is sent back that events can
+    /// be sent to, which are processed as if they came from the source
+    /// device. The simulated source device is removed when the channel is
+    /// closed.
+    SimulateSourceDevice(
+        String,
+        HashSet<Capability>,
+        mpsc::Sender<mpsc::Sender<NativeEvent>>,
+    ),
+    SimulatedSourceDeviceStopped(String),
     SourceDeviceAdded(UdevDevice),
     SourceDeviceRemoved(UdevDevice),
     SourceDeviceStopped(UdevDevice),
+    /// Take over all source devices of the composite device with the given
+    /// transmitter, which is stopped afterwards
+    MergeDevice(mpsc::Sender<CompositeCommand>),
+    /// Stop the composite device and hand its source devices over to the
+    /// composite device with the given transmitter and DBus path
+    MergeInto(mpsc::Sender<CompositeCommand>, String),
+    SourceDeviceReconnectFailed(UdevDevice),
+    WatchProfile(String),
     WriteChordEvent(Vec<NativeEvent>),
+    /// Write the given event directly to the target devices capable of
+    /// handling it, without any translation.
     WriteEvent(NativeEvent),
+    /// Write the given event from the DBus send_event interface, bypassing
+    /// intercept logic. Events emitted in quick succession will be delayed.
     WriteSendEvent(NativeEvent),
     Stop,
 }
@@ -3,44 +3,119 @@ pub mod command;
 
 use std::{
     borrow::Borrow,
-    collections::{BTreeSet, HashMap, HashSet},
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     error::Error,
+    future::poll_fn,
+    path::{Path, PathBuf},
+    task::Poll,
+    time::Instant,
 };
 
-use evdev::InputEvent;
-use tokio::{sync::mpsc, task::JoinSet, time::Duration};
+use evdev::{FFEffectCode, FFEffectData, FFEffectKind, FFReplay, FFTrigger, InputEvent};
+use serde::Serialize;
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    task::{AbortHandle, JoinSet},
+    time::Duration,
+};
 use zbus::Connection;
 
 use crate::{
     config::{
-        path::get_profiles_path, CapabilityMap, CapabilityMapping, CompositeDeviceConfig,
-        DeviceProfile, ProfileMapping,
+        path::{get_profile_assignments_path, get_profiles_path},
+        AxisRange, CapabilityMap, CapabilityMapping, ComboStep, CompositeDeviceConfig,
+        DeviceProfile, ProfileAssignments, ProfileMapping,
     },
     dbus::interface::{
         composite_device::CompositeDeviceInterface, source::iio_imu::SourceIioImuInterface,
     },
+    drivers::generic_hid::driver::read_report_descriptor,
     input::{
-        capability::{Capability, Gamepad, GamepadButton, Mouse},
+        anti_ghosting::AntiGhosting,
+        capability::{
+            Capability, Gamepad, GamepadButton, Keyboard, Mouse, MouseButton, Touch, Touchpad,
+        },
+        double_click::DoubleClickDetector,
         event::{
             native::NativeEvent,
             value::{InputValue, TranslationError},
             Event,
         },
-        output_event::UinputOutputEvent,
-        source::{evdev::EventDevice, hidraw::HidRawDevice, iio::IioDevice, SourceDevice},
+        event_log::{decode_records, EventLogWriter},
+        event_stream::EventStream,
+        filter::{self, FilterResult, RateLimitFilter, SourceDeviceFilter},
+        gyro_mouse::GyroMouseEmulator,
+        motion_profile::MotionProfileSwitcher,
+        output_event::{OutputEventKind, UinputOutputEvent},
+        source::{
+            evdev::EventDevice, hidraw::HidRawDevice, iio::IioDevice, network::NetworkDevice,
+            SourceDevice,
+        },
+        source_health::{HealthThresholds, SourceHealthMonitor},
+        touchpad_mouse::TouchpadMouseEmulator,
     },
     udev::{device::UdevDevice, hide_device, unhide_device},
+    watcher::{self, FileWatcher},
 };
 
+#[cfg(feature = "wasm-scripts")]
+use crate::input::wasm_transform::{capability_id, WasmTransform};
+
 use self::{client::CompositeDeviceClient, command::CompositeCommand};
 
 use super::{
-    manager::ManagerCommand, output_event::OutputEvent, source::client::SourceDeviceClient,
-    target::client::TargetDeviceClient,
+    manager::ManagerCommand,
+    output_capability::{OutputCapability, LED},
+    output_event::OutputEvent,
+    source::client::SourceDeviceClient,
+    target::{
+        client::{ClientError as TargetClientError, TargetDeviceClient},
+        command::TargetCommand,
+        TargetDevice,
+    },
 };
 
 /// Size of the command channel buffer for processing input events and commands.
 const BUFFER_SIZE: usize = 16384;
+/// Amount of time to hold the tap mapping of a profile mapping with a hold
+/// time when the source button is released before the hold time.
+const HOLD_TAP_DURATION: Duration = Duration::from_millis(50);
+/// Maximum number of times to check if a reconnected source device has been
+/// initialized by udev before trying again
+const RECONNECT_INIT_ATTEMPTS: u32 = 100;
+/// Amount of time to wait between checks if a reconnected source device has
+/// been initialized by udev
+const RECONNECT_INIT_INTERVAL: Duration = Duration::from_millis(10);
+/// Amount of time an event from a higher priority source device will block
+/// the same event from lower priority source devices.
+const PRIORITY_SHADOW_DURATION: Duration = Duration::from_millis(100);
+/// Maximum number of events to buffer for a target device whose channel is full
+const TARGET_OVERFLOW_SIZE: usize = 256;
+/// Default time within which repeated presses are discarded for profile
+/// mappings with key repeat suppression enabled
+const KEY_REPEAT_THRESHOLD: Duration = Duration::from_millis(50);
+/// Size of the channel buffer for input events sent back by target devices
+const TARGET_EVENT_CALLBACK_BUFFER_SIZE: usize = 256;
+/// Maximum number of base profiles a device profile can inherit from
+const MAX_PROFILE_INHERITANCE_DEPTH: usize = 8;
+/// Length in milliseconds of ephemeral rumble effects, after which the rumble
+/// stops if it was not stopped or played again.
+const EPHEMERAL_RUMBLE_LENGTH_MS: u16 = 2000;
+/// Interval at which the health of the source devices is checked
+const SOURCE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// Keyboard keys that are emitted alongside gamepad buttons in legacy Steam
+/// mode, matching the keys Steam emits for controllers in legacy mode.
+const LEGACY_STEAM_KEYS: &[(GamepadButton, Keyboard)] = &[
+    (GamepadButton::DPadUp, Keyboard::KeyUp),
+    (GamepadButton::DPadDown, Keyboard::KeyDown),
+    (GamepadButton::DPadLeft, Keyboard::KeyLeft),
+    (GamepadButton::DPadRight, Keyboard::KeyRight),
+    (GamepadButton::South, Keyboard::KeyEnter),
+    (GamepadButton::East, Keyboard::KeyEsc),
+    (GamepadButton::Select, Keyboard::KeyTab),
+    (GamepadButton::Start, Keyboard::KeyEsc),
+];
 
 /// The [InterceptMode] defines whether or not inputs should be routed over
 /// DBus instead of to the target devices. This can be used by overlays to
@@ -55,6 +130,37 @@ pub enum InterceptMode {
     Always,
 }
 
+/// An [InterceptChord] defines a set of capabilities that will activate
+/// intercept mode when pressed together while in [InterceptMode::Pass].
+#[derive(Debug, Clone)]
+pub struct InterceptChord {
+    /// Capabilities that must be pressed to activate the chord, starting with
+    /// the first capability in the list.
+    pub activation_caps: Vec<Capability>,
+    /// Capability to send when the chord activates intercept mode
+    pub target_cap: Capability,
+    /// Name of the mode activated by the chord (e.g. "menu")
+    pub mode_name: String,
+}
+
+/// A [HookPoint] defines where in the event pipeline a registered hook
+/// receives a copy of every event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookPoint {
+    /// Events from source devices before they are translated
+    Pre,
+    /// Translated events before they are written to target devices
+    Post,
+}
+
+/// A single translation of the effective mapping of a composite device
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct EffectiveMapping {
+    source_capability: String,
+    target_capability: String,
+    profile_name: String,
+}
+
 /// A [CompositeDevice] represents any number source input devices that
 /// can translate input to any target devices
 #[derive(Debug)]
@@ -74,14 +180,94 @@ pub struct CompositeDevice {
     /// Name of the currently loaded [DeviceProfile] for the CompositeDevice.
     /// The [DeviceProfile] is used to translate input events.
     device_profile: Option<String>,
+    /// Path of the currently loaded [DeviceProfile], if it was loaded from a
+    /// file.
+    device_profile_path: Option<String>,
     /// Map of profile source events to translate to one or more profile mapping
     /// configs that define how the source event should be translated.
     device_profile_config_map: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Name of the fallback profile of the currently loaded [DeviceProfile]
+    fallback_profile: Option<String>,
+    /// Map of source events to the mappings of the fallback profile, which
+    /// are used for source events that have no mapping in
+    /// [CompositeDevice::device_profile_config_map].
+    fallback_profile_config_map: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Source events of the loaded [DeviceProfile] that act as a shift
+    /// modifier for the profile shift mappings.
+    modifier_caps: Vec<Capability>,
+    /// Map of profile source events to the profile mappings that are used
+    /// instead of [CompositeDevice::device_profile_config_map] while a
+    /// modifier is held.
+    shift_mappings: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Source buttons that were pressed while a modifier was held and keep
+    /// using their shift mapping until they are released.
+    shift_active_sources: HashSet<Capability>,
+    /// Running timers for source buttons that are pressed with a profile
+    /// mapping that has a hold time.
+    hold_timers: HashMap<Capability, AbortHandle>,
+    /// Tasks that emit scroll wheel events while the source button of a
+    /// profile mapping with a scroll speed is held, by mapping name.
+    scroll_tasks: HashMap<String, AbortHandle>,
+    /// Tasks that execute the combo of a profile mapping, by source button.
+    /// A combo is cancelled if its source button is released early.
+    active_combo_tasks: HashMap<Capability, AbortHandle>,
+    /// Source buttons that were held long enough to activate their hold mapping
+    hold_active: HashSet<Capability>,
+    /// Running chord window timers for capability mappings that have been
+    /// partially pressed, keyed by mapping name.
+    chord_timers: HashMap<String, AbortHandle>,
+    /// Source capabilities of incomplete chords that were emitted as
+    /// individual events and need to be released the same way.
+    chord_forwarded_inputs: HashSet<Capability>,
+    /// Source buttons that were released before their hold time and are
+    /// emitting their tap mapping.
+    hold_taps: HashSet<Capability>,
+    /// Tap counts and running tap window timers for source buttons with tap
+    /// dance mappings whose taps have not been resolved yet.
+    tap_dance_timers: HashMap<Capability, (u32, AbortHandle)>,
+    /// Resolved tap counts of source buttons that are emitting the action of
+    /// their tap dance mapping.
+    tap_dance_active: HashMap<Capability, u32>,
+    /// List of profile paths that can be cycled through at runtime
+    profile_cycle_list: Vec<String>,
+    /// Index of the active profile in the profile cycle list
+    profile_cycle_index: usize,
+    /// Button combo that loads the next profile in the profile cycle list
+    profile_cycle_combo: Vec<Capability>,
+    /// Buttons in the profile cycle combo that are currently pressed
+    profile_cycle_pressed: HashSet<Capability>,
+    /// Whether or not the profile cycle combo was triggered and is waiting
+    /// for its buttons to be released
+    profile_cycle_triggered: bool,
+    /// Translates touchpad motion into mouse motion when enabled. This keeps
+    /// track of the current finger position on the touchpad.
+    touchpad_mouse: TouchpadMouseEmulator,
+    /// Translates gyro motion into mouse motion when enabled. This keeps
+    /// track of the smoothed gyro velocity.
+    gyro_mouse: GyroMouseEmulator,
+    /// Loads a different profile depending on the orientation of the device
+    /// when enabled. This keeps track of the last known orientation.
+    motion_profile: MotionProfileSwitcher,
+    /// Detects double clicks of mouse buttons that are used as a source event
+    /// in the loaded profile. This keeps track of held back clicks.
+    double_click: DoubleClickDetector,
+    /// Releases conflicting buttons of mutually exclusive pairs
+    anti_ghosting: AntiGhosting,
+    /// Watcher for the [DeviceProfile] file that will automatically reload the
+    /// profile when it changes. The watch is removed when this is dropped.
+    profile_watcher: Option<FileWatcher>,
+    /// Map of source axis capabilities to the range of the raw values the
+    /// source devices report for them, as defined in the capability map.
+    axis_ranges: HashMap<Capability, AxisRange>,
     /// List of input capabilities that can be translated by the capability map
     translatable_capabilities: Vec<Capability>,
     /// List of currently "pressed" actions used to translate multiple input
     /// sequences into a single input event.
     translatable_active_inputs: Vec<Capability>,
+    /// Map of currently "pressed" translatable capabilities to the set of
+    /// source device ids that are pressing them. This is used to match
+    /// composite source mappings whose events come from different devices.
+    translatable_active_inputs_by_device: HashMap<Capability, HashSet<String>>,
     /// List of translated events that were emitted less than 8ms ago. This
     /// is required to support "on release" style buttons on some devices where
     /// a button "up" event will fire immediately after a "down" event upon
@@ -98,6 +284,38 @@ pub struct CompositeDevice {
     tx: mpsc::Sender<CompositeCommand>,
     /// Receiver channel for listening for commands
     rx: mpsc::Receiver<CompositeCommand>,
+    /// Ids of source devices that were removed from this composite device. If
+    /// one of them is added again, its capabilities may have changed (e.g.
+    /// after a firmware update), so the capabilities are refreshed.
+    source_devices_removed: HashSet<String>,
+    /// Time of the last press of source capabilities whose profile mapping
+    /// suppresses key repeat. Entries are removed when the button is released.
+    key_repeat_last_press: HashMap<Capability, Instant>,
+    /// Last known value of each input capability, which is used to evaluate
+    /// the conditions of profile mappings. Partial stick events are merged
+    /// with the previous value.
+    last_input_values: HashMap<Capability, InputValue>,
+    /// Number of times each profile mapping was activated, keyed by the name
+    /// of the mapping. Button mappings are counted when the button is
+    /// pressed, other mappings for every translated event.
+    mapping_hit_counts: HashMap<String, u64>,
+    /// Maximum time without events from source devices before the composite
+    /// device is stopped. The watchdog is disabled by default because some
+    /// source devices, like IIO sensors, can legitimately be idle.
+    event_watchdog: Option<Duration>,
+    /// Time of the last event received from a source device
+    last_event_time: Instant,
+    /// Health of each source device, measured from the events they send and
+    /// the results of commands sent to them
+    source_health: SourceHealthMonitor,
+    /// Task that periodically checks the health of the source devices
+    source_health_task: Option<AbortHandle>,
+    /// Transmitter and DBus path of the composite device that this device is
+    /// being merged into, which takes over its source devices once stopped
+    merge_target: Option<(mpsc::Sender<CompositeCommand>, String)>,
+    /// Receiver channels for input events sent back by target devices, such
+    /// as touches on a virtual DualSense touchpad.
+    target_event_callbacks: Vec<mpsc::Receiver<NativeEvent>>,
     /// Map of source device id to their respective transmitter channel.
     /// E.g. {"evdev://event0": <Sender>}
     source_devices: HashMap<String, SourceDeviceClient>,
@@ -106,20 +324,97 @@ pub struct CompositeDevice {
     /// HashSet of source devices that are blocked from passing their input events to target
     /// events.
     source_devices_blocked: HashSet<String>,
+    /// Optional log that all processed events are recorded to
+    event_log: Option<EventLogWriter>,
+    /// Optional stream that all processed events are sent to
+    event_stream: Option<EventStream>,
+    /// Channels registered to receive a copy of every event at a point in
+    /// the event pipeline, by hook id
+    hooks: HashMap<usize, (HookPoint, mpsc::Sender<NativeEvent>)>,
+    /// Id of the next registered hook
+    next_hook_id: usize,
+    /// Whether or not events are only logged instead of being written to
+    /// target and source devices
+    dry_run: bool,
+    /// Whether or not source events are written to target devices without
+    /// any capability map or device profile translation
+    passthrough: bool,
+    /// Optional user provided WebAssembly script used to transform events
+    /// before they are translated
+    #[cfg(feature = "wasm-scripts")]
+    transform_script: Option<WasmTransform>,
+    /// Set of source device ids that stopped unexpectedly and are waiting to
+    /// reconnect.
+    source_devices_reconnecting: HashSet<String>,
+    /// Set of source device ids that are simulated and do not exist on the
+    /// system. Events for these are sent through a channel instead.
+    source_devices_simulated: HashSet<String>,
     /// Physical device path for source devices. E.g. ["/dev/input/event0"]
     source_device_paths: Vec<String>,
     /// All currently running source device threads
     source_device_tasks: JoinSet<()>,
     /// Unique identifiers for running source devices. E.g. ["evdev://event0"]
     source_devices_used: Vec<String>,
+    /// Map of source device id to the udev information of that device. This
+    /// can be refreshed from the running source device with
+    /// [CompositeCommand::RefreshSourceInfo].
+    /// E.g. {"evdev://event0": <UdevDevice>}
+    source_devices_info: HashMap<String, UdevDevice>,
+    /// Map of source device id to the output capabilities that source device
+    /// can handle. This is used to route output events like LED events only
+    /// to source devices that are capable of handling them.
+    /// E.g. {"hidraw://hidraw0": [OutputCapability::LED(LED::Color)]}
+    source_devices_output_capabilities: HashMap<String, HashSet<OutputCapability>>,
+    /// Map of output event kinds to the source device id prefixes that should
+    /// receive them. Output events of kinds that are not in this map are sent
+    /// to all source devices.
+    /// E.g. {OutputEventKind::ForceFeedback: ["hidraw://"]}
+    output_routing: HashMap<OutputEventKind, Vec<String>>,
+    /// Map of source device id to its configured event priority. Source
+    /// devices without a configured priority are not in this map and have a
+    /// priority of 0.
+    source_devices_priority: HashMap<String, u8>,
+    /// Map of capabilities to the source device that last emitted them and
+    /// when. This is used to block events from lower priority source devices.
+    last_event_source: HashMap<Capability, (String, Instant)>,
+    /// Map of source device id to the capabilities that source device provides
+    source_devices_capabilities: HashMap<String, Vec<Capability>>,
+    /// Map of source device id to the index of its matching source device
+    /// config. Source devices listed earlier in the config take precedence
+    /// over later ones that provide the same capability.
+    source_devices_config_index: HashMap<String, usize>,
+    /// Map of source device id to user defined capability overrides for events
+    /// from that source device. This can be used to fix events that are
+    /// detected as the wrong capability.
+    /// E.g. {"hidraw://hidraw0": {<Capability::Gamepad(..South)>: <Capability::Gamepad(..East)>}}
+    capability_overrides: HashMap<String, HashMap<Capability, Capability>>,
+    /// Map of source device id to the chain of filters that are run on events
+    /// from that source device before they are translated.
+    source_devices_filters: HashMap<String, Vec<Box<dyn SourceDeviceFilter + Send>>>,
+    /// Per-capability rate limits that are applied to events from all source
+    /// devices after their own filter chain.
+    rate_limit: RateLimitFilter,
+    /// Whether a timer is running that sends held back events of the rate
+    /// limit once the rate limit allows them.
+    rate_limit_timer_running: bool,
+    /// Map of capabilities to the highest priority source device that
+    /// provides them. Events for a capability from lower priority source
+    /// devices are dropped. This is empty unless a priority is configured for
+    /// a source device.
+    capability_source: HashMap<Capability, String>,
     /// Map of DBus paths to their respective transmitter channel.
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/gamepad0": <Sender>}
     target_devices: HashMap<String, TargetDeviceClient>,
-    /// Map of device capabilities to a list of target devices that implements
-    /// that capability. This list contains the DBus path for the target device
-    /// so its transmitter channel can be looked up in `target_devices`.
-    /// E.g. {Capability::Keyboard: ["/org/shadowblip/InputPlumber/devices/target/keyboard0"]}
-    target_devices_by_capability: HashMap<Capability, HashSet<String>>,
+    /// Map of target device DBus paths to the set of capabilities that target
+    /// device implements. This is cached when the target device is attached so
+    /// events are only written to target devices capable of handling them.
+    /// E.g. {"/org/shadowblip/InputPlumber/devices/target/keyboard0": [Capability::Keyboard]}
+    target_devices_capabilities: HashMap<String, HashSet<Capability>>,
+    /// Map of target device DBus paths to events that could not be written
+    /// because the target device channel was full. These are written at the
+    /// start of the next command loop iteration so one slow target device does
+    /// not stall the others.
+    target_devices_overflow: HashMap<String, VecDeque<NativeEvent>>,
     /// List of target devices waiting to be attached to this composite device.
     /// This is used to block/requeue multiple calls to set_target_devices().
     /// E.g. ["/org/shadowblip/InputPlumber/devices/target/gamepad0"]
@@ -134,10 +429,19 @@ pub struct CompositeDevice {
     /// This mapping maps the composite device effect ids to source device effect ids.
     /// E.g. {3: {"evdev://event0": 6, "evdev://event1": 2}}
     ff_effect_id_source_map: HashMap<i16, HashMap<String, i16>>,
-    /// List of intercept mode activation Capabilities
-    intercept_activation_caps: Vec<Capability>,
-    /// Capability to send when intercept mode is activated for the first time.
-    intercept_mode_target_cap: Capability,
+    /// Data of all uploaded force feedback effects by composite effect id.
+    /// After target devices are recreated, these effects are re-uploaded to
+    /// any source devices that are missing them.
+    ff_effects_to_restore: Vec<(i16, FFEffectData)>,
+    /// Source device effect ids of the rumble effect used to play force
+    /// feedback events for effects that were never uploaded.
+    /// E.g. {"evdev://event0": 4}
+    ff_ephemeral_effect_ids: HashMap<String, i16>,
+    /// List of chords that can activate intercept mode
+    intercept_chords: Vec<InterceptChord>,
+    /// Name of the mode activated by the last intercept chord, if intercept
+    /// mode was activated by a chord.
+    intercept_mode_name: Option<String>,
     /// List of currently active events that could trigger intercept mode.
     intercept_active_inputs: Vec<Capability>,
     /// List of currently active buttons and keys. Used to block "up" events for
@@ -156,6 +460,7 @@ impl CompositeDevice {
         log::info!("Creating CompositeDevice with config: {}", config.name);
         let (tx, rx) = mpsc::channel(BUFFER_SIZE);
         let name = config.name.clone();
+        let output_routing = config.output_routing.clone().unwrap_or_default();
         let mut device = Self {
             conn,
             manager,
@@ -164,37 +469,109 @@ impl CompositeDevice {
             capabilities: HashSet::new(),
             capability_map,
             device_profile: None,
+            device_profile_path: None,
+            fallback_profile: None,
+            fallback_profile_config_map: HashMap::new(),
             device_profile_config_map: HashMap::new(),
+            modifier_caps: Vec::new(),
+            shift_mappings: HashMap::new(),
+            shift_active_sources: HashSet::new(),
+            hold_timers: HashMap::new(),
+            scroll_tasks: HashMap::new(),
+            active_combo_tasks: HashMap::new(),
+            chord_timers: HashMap::new(),
+            chord_forwarded_inputs: HashSet::new(),
+            hold_active: HashSet::new(),
+            hold_taps: HashSet::new(),
+            tap_dance_timers: HashMap::new(),
+            tap_dance_active: HashMap::new(),
+            profile_cycle_list: Vec::new(),
+            profile_cycle_index: 0,
+            profile_cycle_combo: Vec::new(),
+            profile_cycle_pressed: HashSet::new(),
+            profile_cycle_triggered: false,
+            touchpad_mouse: TouchpadMouseEmulator::new(),
+            gyro_mouse: GyroMouseEmulator::new(),
+            motion_profile: MotionProfileSwitcher::new(),
+            double_click: DoubleClickDetector::new(),
+            anti_ghosting: AntiGhosting::new(),
+            profile_watcher: None,
+            axis_ranges: HashMap::new(),
             translatable_capabilities: Vec::new(),
             translatable_active_inputs: Vec::new(),
+            translatable_active_inputs_by_device: HashMap::new(),
             translated_recent_events: HashSet::new(),
             emitted_mappings: HashMap::new(),
             dbus_path: None,
             intercept_mode: InterceptMode::None,
             tx,
             rx,
+            last_input_values: HashMap::new(),
+            mapping_hit_counts: HashMap::new(),
+            event_watchdog: None,
+            last_event_time: Instant::now(),
+            source_health: SourceHealthMonitor::new(),
+            source_health_task: None,
+            merge_target: None,
+            target_event_callbacks: Vec::new(),
+            key_repeat_last_press: HashMap::new(),
+            source_devices_removed: HashSet::new(),
             source_devices: HashMap::new(),
             source_devices_discovered: Vec::new(),
             source_devices_blocked: HashSet::new(),
+            source_devices_reconnecting: HashSet::new(),
+            source_devices_simulated: HashSet::new(),
+            event_log: None,
+            event_stream: None,
+            hooks: HashMap::new(),
+            next_hook_id: 0,
+            dry_run: false,
+            passthrough: false,
+            #[cfg(feature = "wasm-scripts")]
+            transform_script: None,
             source_device_paths: Vec::new(),
             source_device_tasks: JoinSet::new(),
             source_devices_used: Vec::new(),
+            source_devices_info: HashMap::new(),
+            source_devices_output_capabilities: HashMap::new(),
+            output_routing,
+            source_devices_priority: HashMap::new(),
+            last_event_source: HashMap::new(),
+            source_devices_capabilities: HashMap::new(),
+            source_devices_config_index: HashMap::new(),
+            source_devices_filters: HashMap::new(),
+            rate_limit: RateLimitFilter::new(),
+            rate_limit_timer_running: false,
+            capability_overrides: HashMap::new(),
+            capability_source: HashMap::new(),
             target_devices: HashMap::new(),
-            target_devices_by_capability: HashMap::new(),
+            target_devices_capabilities: HashMap::new(),
+            target_devices_overflow: HashMap::new(),
             target_devices_queued: HashSet::new(),
             target_dbus_devices: HashMap::new(),
             ff_effect_ids: (0..64).collect(),
             ff_effect_id_source_map: HashMap::new(),
-            intercept_activation_caps: vec![Capability::Gamepad(Gamepad::Button(
-                GamepadButton::Guide,
-            ))],
-            intercept_mode_target_cap: Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            ff_effects_to_restore: Vec::new(),
+            ff_ephemeral_effect_ids: HashMap::new(),
+            intercept_chords: vec![InterceptChord {
+                activation_caps: vec![Capability::Gamepad(Gamepad::Button(GamepadButton::Guide))],
+                target_cap: Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+                mode_name: "default".to_string(),
+            }],
+            intercept_mode_name: None,
             intercept_active_inputs: Vec::new(),
             active_inputs: Vec::new(),
         };
 
+        if let Some(config) = device.config.source_health.as_ref() {
+            device.source_health.set_thresholds(config.into());
+        }
+
         // Load the capability map if one was defined
-        if device.capability_map.is_some() {
+        if let Some(map) = device.capability_map.as_ref() {
+            for warning in map.validate() {
+                log::warn!("Capability map '{}': {warning}", map.name);
+            }
             device.load_capability_map()?;
         }
 
@@ -202,8 +579,16 @@ impl CompositeDevice {
         let profile_dir = get_profiles_path();
         let profile_path = profile_dir.join("default.yaml");
         let profile_path = profile_path.to_string_lossy().to_string();
-        let profile = DeviceProfile::from_yaml_file(profile_path)?;
-        device.load_device_profile(profile)?;
+        #[cfg(feature = "embedded-profiles")]
+        if let Err(e) = device.load_device_profile_from_path(profile_path) {
+            log::debug!("Failed to load default profile, using embedded profile: {e:?}");
+            device.load_device_profile(DeviceProfile::from_embedded("default")?)?;
+        }
+        #[cfg(not(feature = "embedded-profiles"))]
+        device.load_device_profile_from_path(profile_path)?;
+
+        // Load the profile the user assigned to this device, if any
+        device.load_assigned_profile();
 
         // If a capability map is defined, add those target capabilities to
         // the hashset of implemented capabilities.
@@ -221,6 +606,21 @@ impl CompositeDevice {
             return Err(e.to_string().into());
         }
 
+        // Network source devices are not discovered through udev, so add any
+        // that are defined in the config.
+        let network_configs: Vec<_> = device
+            .config
+            .source_devices
+            .iter()
+            .filter_map(|config| config.network.clone())
+            .collect();
+        for network in network_configs {
+            let device_info = UdevDevice::from_network_address(&network.address);
+            if let Err(e) = device.add_source_device(device_info) {
+                return Err(e.to_string().into());
+            }
+        }
+
         Ok(device)
     }
 
@@ -244,6 +644,7 @@ impl CompositeDevice {
 
     /// Starts the [CompositeDevice] and listens for events from all source
     /// devices to translate the events and send them to the appropriate target.
+    #[tracing::instrument(skip_all, fields(device_name = %self.name))]
     pub async fn run(
         &mut self,
         targets: HashMap<String, TargetDeviceClient>,
@@ -260,6 +661,7 @@ impl CompositeDevice {
                     format!("Failed to set composite device for target device: {:?}", e).into(),
                 );
             }
+            self.register_target_event_callback(path, target).await;
 
             // Query the target device for its capabilities
             let caps = match target.get_capabilities().await {
@@ -269,36 +671,69 @@ impl CompositeDevice {
                 }
             };
 
-            // Track the target device by capabilities it has
-            for cap in caps {
-                self.target_devices_by_capability
-                    .entry(cap)
-                    .and_modify(|devices| {
-                        devices.insert(path.clone());
-                    })
-                    .or_insert_with(|| {
-                        let mut devices = HashSet::new();
-                        devices.insert(path.clone());
-                        devices
-                    });
-            }
+            // Cache the capabilities of the target device
+            self.target_devices_capabilities
+                .insert(path.clone(), caps.into_iter().collect());
         }
         self.target_devices = targets;
 
         // Loop and listen for command events
         log::debug!("CompositeDevice started");
+        self.start_source_health_task();
         let mut buffer = Vec::with_capacity(BUFFER_SIZE);
         'main: loop {
-            let num = self.rx.recv_many(&mut buffer, BUFFER_SIZE).await;
+            // Time left until the event watchdog fires, if it is armed
+            let watchdog = self
+                .event_watchdog
+                .map(|timeout| timeout.saturating_sub(self.last_event_time.elapsed()));
+
+            // Wait for commands or input events sent back by target devices
+            let rx = &mut self.rx;
+            let result = tokio::select! {
+                result = async {
+                    let recv = rx.recv_many(&mut buffer, BUFFER_SIZE);
+                    match watchdog {
+                        Some(remaining) => tokio::time::timeout(remaining, recv).await.ok(),
+                        None => Some(recv.await),
+                    }
+                } => result,
+                Some(event) = recv_target_event(&mut self.target_event_callbacks) => {
+                    log::trace!("Received event from target device: {event:?}");
+                    if let Err(e) = self.handle_event(event).await {
+                        log::error!("Failed to handle target device event: {e:?}");
+                    }
+                    continue;
+                }
+            };
+            let Some(num) = result else {
+                log::warn!(
+                    "No events received from source devices within {:?}. Stopping CompositeDevice: {:?}",
+                    self.event_watchdog.unwrap_or_default(),
+                    self.dbus_path
+                );
+                // Only fire once and stop through the regular command path
+                self.event_watchdog = None;
+                if let Err(e) = self.tx.try_send(CompositeCommand::Stop) {
+                    log::error!("Failed to send stop command: {e:?}");
+                    break;
+                }
+                continue;
+            };
             if num == 0 {
                 log::warn!("Unable to receive more commands. Channel closed.");
                 break;
             }
             //log::trace!("Received {num} command(s)");
+
+            // Write any events that overflowed target device channels before
+            // processing new commands.
+            self.drain_target_overflow().await;
+
             for cmd in buffer.drain(..) {
                 log::trace!("Received command: {:?}", cmd);
                 match cmd {
                     CompositeCommand::ProcessEvent(device_id, event) => {
+                        self.last_event_time = Instant::now();
                         if let Err(e) = self.process_event(device_id, event).await {
                             log::error!("Failed to process event: {:?}", e);
                             // TODO: Use proper errors to check for 'SendError' and
@@ -316,6 +751,24 @@ impl CompositeDevice {
                             log::error!("Failed to send capabilities: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetSourceDeviceCapabilities(id, sender) => {
+                        let Some(source) = self.source_devices.get(&id) else {
+                            log::error!("No running source device found with id: {id}");
+                            continue;
+                        };
+                        let caps = match source.get_capabilities().await {
+                            Ok(caps) => caps,
+                            Err(e) => {
+                                log::error!(
+                                    "Failed to get capabilities of source device {id}: {e:?}"
+                                );
+                                continue;
+                            }
+                        };
+                        if let Err(e) = sender.send(caps.into_iter().collect()).await {
+                            log::error!("Failed to send source device capabilities: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetTargetCapabilities(sender) => {
                         let target_caps = match self.get_target_capabilities().await {
                             Ok(caps) => caps,
@@ -328,12 +781,59 @@ impl CompositeDevice {
                             log::error!("Failed to send target capabilities: {:?}", e);
                         }
                     }
-                    CompositeCommand::SetInterceptMode(mode) => self.set_intercept_mode(mode),
+                    CompositeCommand::SetInterceptMode(mode) => self.set_intercept_mode(mode).await,
                     CompositeCommand::GetInterceptMode(sender) => {
                         if let Err(e) = sender.send(self.intercept_mode.clone()).await {
                             log::error!("Failed to send intercept mode: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetTouchpadMouseEmulation(sender) => {
+                        if let Err(e) = sender.send(self.touchpad_mouse.mode()).await {
+                            log::error!("Failed to send touchpad mouse emulation: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetTouchpadMouseEmulation(mode) => {
+                        log::debug!("Setting touchpad mouse emulation: {mode:?}");
+                        self.touchpad_mouse.set_mode(mode);
+                    }
+                    CompositeCommand::GetGyroMouseMode(sender) => {
+                        if let Err(e) = sender.send(self.gyro_mouse.config()).await {
+                            log::error!("Failed to send gyro mouse mode: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetGyroMouseMode(config) => {
+                        log::debug!("Setting gyro mouse mode: {config:?}");
+                        self.gyro_mouse.set_config(config);
+                    }
+                    CompositeCommand::SetMotionProfile(config) => {
+                        log::debug!("Setting motion profile: {config:?}");
+                        self.motion_profile.set_config(config);
+                    }
+                    CompositeCommand::GetDoubleClickWindow(sender) => {
+                        let window_ms = self.double_click.window().as_millis() as u64;
+                        if let Err(e) = sender.send(window_ms).await {
+                            log::error!("Failed to send double click window: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetDoubleClickWindow(window_ms) => {
+                        log::debug!("Setting double click window: {window_ms}ms");
+                        self.double_click.set_window(window_ms);
+                    }
+                    CompositeCommand::GetAntiGhosting(sender) => {
+                        let axes = self.anti_ghosting.axes().to_vec();
+                        if let Err(e) = sender.send(axes).await {
+                            log::error!("Failed to send anti-ghosting pairs: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetAntiGhosting(axes) => {
+                        log::debug!("Setting anti-ghosting pairs: {axes:?}");
+                        self.anti_ghosting.set_axes(axes);
+                    }
+                    CompositeCommand::GetInterceptModeName(sender) => {
+                        if let Err(e) = sender.send(self.intercept_mode_name.clone()).await {
+                            log::error!("Failed to send intercept mode name: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetSourceDevicePaths(sender) => {
                         if let Err(e) = sender.send(self.get_source_device_paths()).await {
                             log::error!("Failed to send source device paths: {:?}", e);
@@ -351,6 +851,21 @@ impl CompositeDevice {
                             log::error!("Failed to send dbus device paths: {:?}", e);
                         }
                     }
+                    CompositeCommand::SimulateSourceDevice(id, capabilities, sender) => {
+                        match self.add_simulated_source_device(id, capabilities).await {
+                            Ok(events) => {
+                                if let Err(e) = sender.send(events).await {
+                                    log::error!("Failed to send simulated source device: {:?}", e);
+                                }
+                            }
+                            Err(e) => {
+                                log::error!("Failed to add simulated source device: {e:?}");
+                            }
+                        }
+                    }
+                    CompositeCommand::SimulatedSourceDeviceStopped(id) => {
+                        self.remove_simulated_source_device(id).await;
+                    }
                     CompositeCommand::SourceDeviceAdded(device) => {
                         if let Err(e) = self.on_source_device_added(device).await {
                             log::error!("Failed to add source device: {:?}", e);
@@ -358,10 +873,16 @@ impl CompositeDevice {
                     }
                     CompositeCommand::SourceDeviceStopped(device) => {
                         log::debug!("Detected source device stopped: {}", device.devnode());
-                        if let Err(e) = self.on_source_device_removed(device).await {
+                        let should_reconnect = self.should_reconnect_source_device(&device);
+                        if let Err(e) = self.on_source_device_removed(device.clone()).await {
                             log::error!("Failed to remove source device: {:?}", e);
                         }
-                        if self.source_devices_used.is_empty() {
+                        if should_reconnect {
+                            self.reconnect_source_device(device);
+                        }
+                        if self.source_devices_used.is_empty()
+                            && self.source_devices_reconnecting.is_empty()
+                        {
                             log::debug!(
                                 "No source devices remain. Stopping CompositeDevice {:?}",
                                 self.dbus_path
@@ -371,10 +892,39 @@ impl CompositeDevice {
                     }
                     CompositeCommand::SourceDeviceRemoved(device) => {
                         log::debug!("Detected source device removed: {}", device.devnode());
-                        if let Err(e) = self.on_source_device_removed(device).await {
+                        let should_reconnect = self.should_reconnect_source_device(&device);
+                        if let Err(e) = self.on_source_device_removed(device.clone()).await {
                             log::error!("Failed to remove source device: {:?}", e);
                         }
-                        if self.source_devices_used.is_empty() {
+                        if should_reconnect {
+                            self.reconnect_source_device(device);
+                        }
+                        if self.source_devices_used.is_empty()
+                            && self.source_devices_reconnecting.is_empty()
+                        {
+                            log::debug!(
+                                "No source devices remain. Stopping CompositeDevice {:?}",
+                                self.dbus_path
+                            );
+                            break 'main;
+                        }
+                    }
+                    CompositeCommand::MergeDevice(other) => {
+                        if let Err(e) = self.merge_device(other) {
+                            log::error!("Failed to merge composite device: {e:?}");
+                        }
+                    }
+                    CompositeCommand::MergeInto(target, path) => {
+                        log::info!("Merging CompositeDevice {:?} into {path}", self.dbus_path);
+                        self.merge_target = Some((target, path));
+                        break 'main;
+                    }
+                    CompositeCommand::SourceDeviceReconnectFailed(device) => {
+                        log::warn!("Source device failed to reconnect: {}", device.devnode());
+                        self.source_devices_reconnecting.remove(&device.get_id());
+                        if self.source_devices_used.is_empty()
+                            && self.source_devices_reconnecting.is_empty()
+                        {
                             log::debug!(
                                 "No source devices remain. Stopping CompositeDevice {:?}",
                                 self.dbus_path
@@ -387,17 +937,112 @@ impl CompositeDevice {
                             log::error!("Failed to set target devices: {e:?}");
                         }
                     }
+                    CompositeCommand::AddTargetDevice(kind) => {
+                        if let Err(e) = self.add_target_device(kind).await {
+                            log::error!("Failed to add target device: {e:?}");
+                        }
+                    }
+                    CompositeCommand::RemoveTargetDevice(path) => {
+                        if let Err(e) = self.remove_target_device(path).await {
+                            log::error!("Failed to remove target device: {e:?}");
+                        }
+                    }
                     CompositeCommand::AttachTargetDevices(targets) => {
                         if let Err(e) = self.attach_target_devices(targets).await {
                             log::error!("Failed to attach target devices: {e:?}");
                         }
                     }
+                    CompositeCommand::OverrideCapability(source_id, from, to) => {
+                        log::debug!("Overriding capability {from:?} -> {to:?} for {source_id}");
+                        self.capability_overrides
+                            .entry(source_id)
+                            .or_default()
+                            .insert(from, to);
+                    }
+                    CompositeCommand::SetCapabilityRateLimit(capability, max_hz) => {
+                        log::debug!("Setting rate limit of {capability:?} to {max_hz}Hz");
+                        self.rate_limit.set_limit(capability, max_hz);
+                    }
+                    CompositeCommand::SetEventWatchdog(timeout) => {
+                        if timeout.is_zero() {
+                            log::debug!("Disabling event watchdog");
+                            self.event_watchdog = None;
+                        } else {
+                            log::debug!("Arming event watchdog with a timeout of {timeout:?}");
+                            self.event_watchdog = Some(timeout);
+                            self.last_event_time = Instant::now();
+                        }
+                    }
+                    CompositeCommand::PauseTarget(path) => {
+                        if let Err(e) = self.pause_target(path, true).await {
+                            log::error!("Failed to pause target device: {e:?}");
+                        }
+                    }
+                    CompositeCommand::ResumeTarget(path) => {
+                        if let Err(e) = self.pause_target(path, false).await {
+                            log::error!("Failed to resume target device: {e:?}");
+                        }
+                    }
                     CompositeCommand::GetName(sender) => {
                         let name = self.name.clone();
                         if let Err(e) = sender.send(name).await {
                             log::error!("Failed to send device name: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetActiveInputs(sender) => {
+                        if let Err(e) = sender.send(self.active_inputs.clone()).await {
+                            log::error!("Failed to send active inputs: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetChannelLoad(sender) => {
+                        let load = self.rx.len() as f32 / BUFFER_SIZE as f32;
+                        if let Err(e) = sender.send(load).await {
+                            log::error!("Failed to send channel load: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetCompositeDeviceConfig(sender) => {
+                        if let Err(e) = sender.send(self.config.clone()).await {
+                            log::error!("Failed to send composite device config: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetEffectiveMapping(sender) => {
+                        let json = self.effective_mapping_json();
+                        if let Err(e) = sender.send(json).await {
+                            log::error!("Failed to send effective mapping: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ReloadConfig(sender) => {
+                        let result = match self.reload_config().await {
+                            Ok(_) => Ok(()),
+                            Err(e) => {
+                                log::error!("Failed to reload config: {e:?}");
+                                Err(e.to_string())
+                            }
+                        };
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send reload config result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RunCapabilityMapTests(sender) => {
+                        let results = self
+                            .capability_map
+                            .as_ref()
+                            .map(|map| map.run_tests())
+                            .unwrap_or_default();
+                        if let Err(e) = sender.send(results).await {
+                            log::error!("Failed to send capability map test results: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetMappingStatistics(sender) => {
+                        let statistics = self.mapping_hit_counts.clone();
+                        if let Err(e) = sender.send(statistics).await {
+                            log::error!("Failed to send mapping statistics: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ResetMappingStatistics => {
+                        log::debug!("Resetting mapping statistics");
+                        self.mapping_hit_counts.clear();
+                    }
                     CompositeCommand::GetProfileName(sender) => {
                         let profile_name = self.device_profile.clone().unwrap_or_default();
                         if let Err(e) = sender.send(profile_name).await {
@@ -416,7 +1061,10 @@ impl CompositeDevice {
                             }
                         };
                         let result = match self.load_device_profile(profile) {
-                            Ok(_) => Ok(()),
+                            Ok(_) => {
+                                self.device_profile_path = None;
+                                Ok(())
+                            }
                             Err(e) => Err(e.to_string()),
                         };
                         if let Err(e) = sender.send(result).await {
@@ -424,17 +1072,7 @@ impl CompositeDevice {
                         }
                     }
                     CompositeCommand::LoadProfilePath(path, sender) => {
-                        log::debug!("Loading profile from path: {path}");
-                        let profile = match DeviceProfile::from_yaml_file(path) {
-                            Ok(p) => p,
-                            Err(e) => {
-                                if let Err(er) = sender.send(Err(e.to_string().into())).await {
-                                    log::error!("Failed to send failed to load profile: {er:?}");
-                                }
-                                continue;
-                            }
-                        };
-                        let result = match self.load_device_profile(profile) {
+                        let result = match self.load_device_profile_from_path(path) {
                             Ok(_) => Ok(()),
                             Err(e) => Err(e.to_string()),
                         };
@@ -442,6 +1080,38 @@ impl CompositeDevice {
                             log::error!("Failed to send load profile result: {:?}", e);
                         }
                     }
+                    CompositeCommand::SaveProfileAssignment(sender) => {
+                        let result = self.save_profile_assignment().map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send save profile assignment result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetProfileCycleList(paths) => {
+                        log::debug!("Setting profile cycle list: {paths:?}");
+                        self.profile_cycle_list = paths;
+                        self.profile_cycle_index = 0;
+                    }
+                    CompositeCommand::SetProfileCycleCombo(combo) => {
+                        log::debug!("Setting profile cycle combo: {combo:?}");
+                        self.profile_cycle_combo = combo;
+                        self.profile_cycle_pressed.clear();
+                        self.profile_cycle_triggered = false;
+                    }
+                    CompositeCommand::NextProfile => {
+                        if let Err(e) = self.cycle_profile(true) {
+                            log::error!("Failed to load next profile: {e:?}");
+                        }
+                    }
+                    CompositeCommand::PreviousProfile => {
+                        if let Err(e) = self.cycle_profile(false) {
+                            log::error!("Failed to load previous profile: {e:?}");
+                        }
+                    }
+                    CompositeCommand::WatchProfile(path) => {
+                        if let Err(e) = self.watch_profile(path) {
+                            log::error!("Failed to watch profile: {:?}", e);
+                        }
+                    }
                     CompositeCommand::WriteEvent(event) => {
                         if let Err(e) = self.write_event(event).await {
                             log::error!("Failed to write event: {:?}", e);
@@ -462,11 +1132,138 @@ impl CompositeDevice {
                             log::error!("Failed to write event: {:?}", e);
                         }
                     }
+                    CompositeCommand::HandleHoldEvent(event) => {
+                        if let Err(e) = self.translate_and_write_event(event).await {
+                            log::error!("Failed to write hold event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::HoldTimerExpired(event) => {
+                        if let Err(e) = self.on_hold_timer_expired(event).await {
+                            log::error!("Failed to write hold event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::DoubleClickWindowExpired => {
+                        if let Err(e) = self.on_double_click_window_expired().await {
+                            log::error!("Failed to write held back click events: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RateLimitTimerExpired => {
+                        if let Err(e) = self.on_rate_limit_timer_expired().await {
+                            log::error!("Failed to write rate limited events: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::CheckSourceHealth => {
+                        for device_id in self.source_health.check(Instant::now()) {
+                            self.signal_source_health_changed(&device_id);
+                        }
+                    }
+                    CompositeCommand::TapDanceTimerExpired(event, taps) => {
+                        if let Err(e) = self.on_tap_dance_timer_expired(event, taps).await {
+                            log::error!("Failed to write tap dance event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ChordWindowExpired(name) => {
+                        if let Err(e) = self.on_chord_window_expired(name.as_str()).await {
+                            log::error!("Failed to write partial chord events: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::EnableEventLog(path) => {
+                        match EventLogWriter::new(path.clone()) {
+                            Ok(event_log) => {
+                                log::info!("Recording events to event log: {path}");
+                                self.event_log = Some(event_log);
+                            }
+                            Err(e) => {
+                                log::error!("Failed to open event log {path}: {e:?}");
+                            }
+                        }
+                    }
+                    CompositeCommand::SetDryRun(enabled) => {
+                        log::info!("Setting dry run mode: {enabled}");
+                        self.dry_run = enabled;
+                    }
+                    CompositeCommand::GetPassthroughMode(sender) => {
+                        if let Err(e) = sender.send(self.passthrough).await {
+                            log::error!("Failed to send passthrough mode: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::LoadWasmScript(module, sender) => {
+                        let result = self.load_wasm_script(module);
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send load transform script result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetPassthroughMode(enabled) => {
+                        log::info!("Setting passthrough mode: {enabled}");
+                        self.passthrough = enabled;
+                    }
+                    CompositeCommand::DisableEventLog => {
+                        if let Some(mut event_log) = self.event_log.take() {
+                            log::info!("Stopped recording to event log: {}", event_log.path());
+                            if let Err(e) = event_log.flush() {
+                                log::error!("Failed to flush event log: {e:?}");
+                            }
+                        }
+                    }
+                    CompositeCommand::StartEventStream(max_hz, sender) => {
+                        let result = match EventStream::new(max_hz) {
+                            Ok((stream, fd)) => {
+                                log::info!("Started event stream at up to {max_hz} Hz");
+                                self.event_stream = Some(stream);
+                                Ok(fd)
+                            }
+                            Err(e) => Err(e.to_string()),
+                        };
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send event stream: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RegisterHook(point, hook, sender) => {
+                        let id = self.next_hook_id;
+                        self.next_hook_id += 1;
+                        log::debug!("Registered {point:?} event hook: {id}");
+                        self.hooks.insert(id, (point, hook));
+                        if let Err(e) = sender.send(id).await {
+                            log::error!("Failed to send hook id: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::UnregisterHook(point, id) => {
+                        let is_registered = self
+                            .hooks
+                            .get(&id)
+                            .is_some_and(|(hook_point, _)| *hook_point == point);
+                        if is_registered {
+                            log::debug!("Unregistered {point:?} event hook: {id}");
+                            self.hooks.remove(&id);
+                        }
+                    }
+                    CompositeCommand::StopEventStream => {
+                        if self.event_stream.take().is_some() {
+                            log::info!("Stopped event stream");
+                        }
+                    }
+                    CompositeCommand::StartReplay(path, sender) => {
+                        self.start_replay(path, sender);
+                    }
+                    CompositeCommand::InjectEvent(event) => {
+                        log::trace!("Injecting event: {:?}", event);
+                        if let Err(e) = self.handle_event(event).await {
+                            log::error!("Failed to inject event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RefreshCapabilities => {
+                        self.refresh_capabilities().await;
+                    }
+                    CompositeCommand::RefreshSourceInfo(device_id) => {
+                        if let Err(e) = self.refresh_source_info(device_id).await {
+                            log::error!("Failed to refresh source device info: {:?}", e);
+                        }
+                    }
                     CompositeCommand::RemoveRecentEvent(cap) => {
                         self.translated_recent_events.remove(&cap);
                     }
-                    CompositeCommand::SetInterceptActivation(activation_caps, target_cap) => {
-                        self.set_intercept_activation(activation_caps, target_cap)
+                    CompositeCommand::SetInterceptActivation(chords) => {
+                        self.set_intercept_activation(chords)
                     }
                     CompositeCommand::Stop => {
                         log::debug!(
@@ -483,6 +1280,12 @@ impl CompositeDevice {
             self.dbus_path.as_ref().unwrap()
         );
 
+        // Stop watching the profile for changes
+        self.profile_watcher = None;
+        if let Some(task) = self.source_health_task.take() {
+            task.abort();
+        }
+
         // Stop all target devices
         log::debug!("Stopping target devices");
         for (path, target) in &self.target_devices {
@@ -498,12 +1301,22 @@ impl CompositeDevice {
             }
         }
 
-        // Unhide all source devices
-        for source_path in self.source_device_paths.clone() {
+        // Unhide all source devices. Source devices that are handed over to
+        // another composite device stay hidden.
+        let source_device_paths = if self.merge_target.is_some() {
+            vec![]
+        } else {
+            self.source_device_paths.clone()
+        };
+        for source_path in source_device_paths {
             if source_path.starts_with("/sys/bus/iio/devices") {
                 log::debug!("Skipping unhiding IIO device: {source_path}");
                 continue;
             }
+            if source_path.starts_with("udp://") {
+                log::debug!("Skipping unhiding network device: {source_path}");
+                continue;
+            }
             log::debug!("Un-hiding device: {}", source_path);
             if let Err(e) = unhide_device(source_path.clone()).await {
                 log::debug!("Unable to unhide device {source_path}: {:?}", e);
@@ -521,7 +1334,15 @@ impl CompositeDevice {
         // Wait on all tasks
         log::debug!("Waiting for source device tasks to finish");
         while let Some(res) = self.source_device_tasks.join_next().await {
-            res?;
+            if let Err(e) = res {
+                log::error!("Failed to join source device task: {e:?}");
+            }
+        }
+
+        // Hand the released source devices over to the composite device this
+        // device was merged into
+        if let Some((target, path)) = self.merge_target.take() {
+            self.hand_over_source_devices(target, path).await;
         }
 
         log::info!(
@@ -538,6 +1359,161 @@ impl CompositeDevice {
         self.tx.clone().into()
     }
 
+    /// Send a copy of the given event to all hooks registered at the given
+    /// hook point. Events are dropped for hooks that fall behind, and hooks
+    /// whose receiver was dropped are unregistered.
+    fn send_to_hooks(&mut self, point: HookPoint, event: &NativeEvent) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        self.hooks.retain(|id, (hook_point, hook)| {
+            if *hook_point != point {
+                return true;
+            }
+            match hook.try_send(event.clone()) {
+                Ok(_) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    log::trace!("Event hook {id} fell behind. Dropping event.");
+                    true
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    log::debug!("Event hook {id} closed. Unregistering hook.");
+                    false
+                }
+            }
+        });
+    }
+
+    /// Replay the events recorded in the event log at the given path. Events
+    /// are injected with the same delays between them as when they were
+    /// recorded, and the given sender is notified when the replay is done.
+    fn start_replay(&self, path: String, sender: mpsc::Sender<()>) {
+        log::info!("Replaying event log: {path}");
+        let client = self.client();
+        tokio::task::spawn(async move {
+            let records = match tokio::fs::read(&path).await {
+                Ok(bytes) => decode_records(bytes.as_slice()),
+                Err(e) => Err(e.into()),
+            };
+            let records = match records {
+                Ok(records) => records,
+                Err(e) => {
+                    log::error!("Failed to read event log {path}: {e:?}");
+                    Vec::new()
+                }
+            };
+
+            let mut last_timestamp = records.first().map(|r| r.timestamp_us);
+            for record in records {
+                if let Some(last) = last_timestamp {
+                    let delay = record.timestamp_us.saturating_sub(last);
+                    if delay > 0 {
+                        tokio::time::sleep(Duration::from_micros(delay)).await;
+                    }
+                }
+                last_timestamp = Some(record.timestamp_us);
+                if let Err(e) = client.inject_event(record.event).await {
+                    log::error!("Failed to inject replayed event: {e:?}");
+                    break;
+                }
+            }
+
+            log::info!("Finished replaying event log: {path}");
+            if let Err(e) = sender.send(()).await {
+                log::error!("Failed to send replay completion: {e:?}");
+            }
+        });
+    }
+
+    /// Watch the given [DeviceProfile] path for changes and automatically reload
+    /// the profile whenever the file is written to. Only one profile can be
+    /// watched at a time, so any previously watched profile will stop being
+    /// watched.
+    fn watch_profile(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        log::debug!("Watching profile for changes: {path}");
+        let (tx, mut rx) = mpsc::channel(8);
+        self.profile_watcher = Some(watcher::watch_file(path.clone(), tx)?);
+
+        // Reload the profile whenever it changes. This task will exit once the
+        // watcher is dropped.
+        let client = self.client();
+        tokio::task::spawn(async move {
+            while rx.recv().await.is_some() {
+                log::info!("Profile changed. Reloading profile: {path}");
+                if let Err(e) = client.load_profile_path(path.clone()).await {
+                    log::error!("Failed to reload profile {path}: {e:?}");
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Returns the union of all capability map and device profile translations
+    /// as a flat JSON array of objects with the source capability, target
+    /// capability, and the name of the capability map or profile that defines
+    /// the translation. Capability map mappings with multiple source events
+    /// list each source capability separated by a '+'.
+    fn effective_mapping_json(&self) -> String {
+        let mut entries = Vec::new();
+
+        // Capability map translations
+        if let Some(map) = self.capability_map.as_ref() {
+            for mapping in map.mapping.iter() {
+                let source_caps: Vec<String> = mapping
+                    .source_events
+                    .iter()
+                    .map(|event| Capability::from(event.clone()).to_capability_string())
+                    .collect();
+                let target_cap = Capability::from(mapping.target_event.clone());
+                entries.push(EffectiveMapping {
+                    source_capability: source_caps.join("+"),
+                    target_capability: target_cap.to_capability_string(),
+                    profile_name: map.name.clone(),
+                });
+            }
+        }
+
+        // Device profile translations
+        let profile_name = self.device_profile.clone().unwrap_or_default();
+        for (source_cap, mappings) in self.device_profile_config_map.iter() {
+            for mapping in mappings.iter() {
+                for target_event in mapping.target_events.iter() {
+                    let target_cap = Capability::from(target_event.clone());
+                    entries.push(EffectiveMapping {
+                        source_capability: source_cap.to_capability_string(),
+                        target_capability: target_cap.to_capability_string(),
+                        profile_name: profile_name.clone(),
+                    });
+                }
+            }
+        }
+
+        // Fallback profile translations for events without a profile mapping
+        let fallback_name = self.fallback_profile.clone().unwrap_or_default();
+        for (source_cap, mappings) in self.fallback_profile_config_map.iter() {
+            if self.device_profile_config_map.contains_key(source_cap) {
+                continue;
+            }
+            for mapping in mappings.iter() {
+                for target_event in mapping.target_events.iter() {
+                    let target_cap = Capability::from(target_event.clone());
+                    entries.push(EffectiveMapping {
+                        source_capability: source_cap.to_capability_string(),
+                        target_capability: target_cap.to_capability_string(),
+                        profile_name: fallback_name.clone(),
+                    });
+                }
+            }
+        }
+        entries.sort();
+
+        serde_json::to_string(&entries).unwrap_or_else(|e| {
+            log::error!("Failed to serialize effective mapping: {e:?}");
+            "[]".to_string()
+        })
+    }
+
     /// Returns an array of all source devices ids being used by this device.
     pub fn get_source_devices_used(&self) -> Vec<String> {
         self.source_devices_used.clone()
@@ -548,6 +1524,15 @@ impl CompositeDevice {
         self.target_dbus_devices = devices;
     }
 
+    /// Load the device profile at the given path that was detected for a
+    /// known controller. The profile the user assigned to this device, if any,
+    /// takes precedence over the detected profile.
+    pub fn load_known_device_profile(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        self.load_device_profile_from_path(path)?;
+        self.load_assigned_profile();
+        Ok(())
+    }
+
     /// Return a list of source device paths (e.g. /dev/hidraw0, /dev/input/event0)
     /// that this composite device is managing
     fn get_source_device_paths(&self) -> Vec<String> {
@@ -567,6 +1552,11 @@ impl CompositeDevice {
                 log::debug!("Skipping hiding IIO device: {source_path}");
                 continue;
             }
+            // Skip hiding network devices
+            if source_path.starts_with("udp://") {
+                log::debug!("Skipping hiding network device: {source_path}");
+                continue;
+            }
             log::debug!("Hiding device: {}", source_path);
             hide_device(source_path).await?;
         }
@@ -584,6 +1574,17 @@ impl CompositeDevice {
 
             let source_tx = source_device.client();
             self.source_devices.insert(device_id.clone(), source_tx);
+
+            // Keep track of the output capabilities of the source device
+            match source_device.get_output_capabilities() {
+                Ok(caps) => {
+                    self.source_devices_output_capabilities
+                        .insert(device_id.clone(), caps.into_iter().collect());
+                }
+                Err(e) => {
+                    log::warn!("Failed to get output capabilities for '{device_id}': {e:?}");
+                }
+            }
             let tx = self.tx.clone();
 
             // Add the IIO IMU Dbus interface. We do this here because it needs the source
@@ -609,6 +1610,16 @@ impl CompositeDevice {
 
     /// Process a single event from a source device. Events are piped through
     /// a translation layer, then dispatched to the appropriate target device(s)
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            device_name = %self.name,
+            profile_name = self.device_profile.as_deref().unwrap_or_default(),
+            source_device_id = %device_id,
+            event_capability,
+        )
+    )]
     async fn process_event(
         &mut self,
         device_id: String,
@@ -627,7 +1638,33 @@ impl CompositeDevice {
             Event::Native(event) => event,
             Event::DBus(_) => todo!(),
         };
+        self.source_health
+            .record_event(&device_id, event.timestamp(), Instant::now());
+
+        // Remap axes that report unsigned values into the signed range
+        let event = self.normalize_axis_range(event);
+
+        // Drop malformed events before they can reach the rest of the pipeline
+        if let Err(e) = event.validate() {
+            log::warn!("Dropping invalid event from {device_id}: {e}");
+            return Ok(());
+        }
+
+        // Send the untranslated event to any registered hooks
+        self.send_to_hooks(HookPoint::Pre, &event);
+
+        // Replace the capability of the event if the user has overridden it
+        // for this source device.
+        let event = match self
+            .capability_overrides
+            .get(&device_id)
+            .and_then(|overrides| overrides.get(&event.as_capability()))
+        {
+            Some(cap) => NativeEvent::new(cap.clone(), event.get_value()),
+            None => event,
+        };
         let cap = event.as_capability();
+        tracing::Span::current().record("event_capability", cap.to_capability_string().as_str());
         log::trace!("Event capability: {:?}", cap);
 
         // Only send valid events to the target device(s)
@@ -639,6 +1676,43 @@ impl CompositeDevice {
             return Ok(());
         }
 
+        // Block the event if a higher priority source device provides or
+        // recently emitted the same capability.
+        if self.is_shadowed_by_priority(&device_id, &cap) {
+            log::trace!("Event '{cap}' from {device_id} shadowed by higher priority device");
+            return Ok(());
+        }
+
+        // Run the event through the source device's filter chain
+        let mut event = event;
+        if let Some(filters) = self.source_devices_filters.get_mut(&device_id) {
+            for filter in filters.iter_mut() {
+                if filter.filter(&device_id, &mut event) == FilterResult::Drop {
+                    log::trace!("Event '{cap}' from {device_id} dropped by filter {filter:?}");
+                    return Ok(());
+                }
+            }
+        }
+        if !self.rate_limit.is_empty()
+            && self.rate_limit.filter(&device_id, &mut event) == FilterResult::Drop
+        {
+            log::trace!("Event '{cap}' from {device_id} held back by rate limit");
+            self.start_rate_limit_timer();
+            return Ok(());
+        }
+
+        self.route_event(&device_id, event).await
+    }
+
+    /// Translate the given filtered event from the given source device with
+    /// the capability map if needed, or handle it directly.
+    async fn route_event(
+        &mut self,
+        device_id: &str,
+        event: NativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+
         // Check if the event needs to be translated based on the
         // capability map. Translated events will be re-enqueued, so this will
         // return early.
@@ -646,9 +1720,12 @@ impl CompositeDevice {
             "Translatable capabilities: {:?}",
             self.translatable_capabilities
         );
-        if self.capability_map.is_some() && self.translatable_capabilities.contains(&cap) {
+        if !self.passthrough
+            && self.capability_map.is_some()
+            && self.translatable_capabilities.contains(&cap)
+        {
             log::trace!("Capability mapping found for event");
-            self.translate_capability(&event).await?;
+            self.translate_capability(device_id, &event).await?;
             return Ok(());
         }
         self.handle_event(event).await?;
@@ -656,6 +1733,130 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Start a timer that sends a [CompositeCommand::RateLimitTimerExpired]
+    /// command once the next event held back by the rate limit is allowed,
+    /// unless such a timer is already running.
+    fn start_rate_limit_timer(&mut self) {
+        if self.rate_limit_timer_running {
+            return;
+        }
+        let now = Instant::now();
+        let Some(ready_at) = self.rate_limit.next_ready_at(now) else {
+            return;
+        };
+        self.rate_limit_timer_running = true;
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            tokio::time::sleep(ready_at.duration_since(now)).await;
+            let command = CompositeCommand::RateLimitTimerExpired;
+            if let Err(e) = tx.send(command).await {
+                log::error!("Failed to send rate limit timer expired command: {:?}", e);
+            }
+        });
+    }
+
+    /// Executed when events held back by the rate limit may be allowed.
+    /// Routes the latest held back value of each capability so the final
+    /// value is never lost.
+    async fn on_rate_limit_timer_expired(&mut self) -> Result<(), Box<dyn Error>> {
+        self.rate_limit_timer_running = false;
+        let events = self.rate_limit.take_ready(Instant::now());
+        for (device_id, event) in events {
+            self.route_event(&device_id, event).await?;
+        }
+        self.start_rate_limit_timer();
+
+        Ok(())
+    }
+
+    /// Returns the rank of the given source device used to decide which source
+    /// device is responsible for a capability. Lower ranks take precedence.
+    /// Source devices are ranked by their configured priority first, then by
+    /// the order of their source device config.
+    fn source_device_rank(&self, device_id: &str) -> (Reverse<u8>, usize) {
+        let priority = self
+            .source_devices_priority
+            .get(device_id)
+            .copied()
+            .unwrap_or_default();
+        let index = self
+            .source_devices_config_index
+            .get(device_id)
+            .copied()
+            .unwrap_or(usize::MAX);
+        (Reverse(priority), index)
+    }
+
+    /// Rebuild the map of capabilities to the highest priority source device
+    /// that provides them. Capabilities are only arbitrated if a priority has
+    /// been configured for at least one source device.
+    fn update_capability_sources(&mut self) {
+        if self.source_devices_priority.is_empty() {
+            self.capability_source.clear();
+            return;
+        }
+
+        let mut source_ids: Vec<&String> = self.source_devices_capabilities.keys().collect();
+        source_ids.sort_by_key(|id| self.source_device_rank(id));
+
+        let mut capability_source = HashMap::new();
+        for id in source_ids {
+            let Some(caps) = self.source_devices_capabilities.get(id) else {
+                continue;
+            };
+            for cap in caps {
+                capability_source
+                    .entry(cap.clone())
+                    .or_insert_with(|| id.clone());
+            }
+        }
+        log::trace!("Updated capability sources: {capability_source:?}");
+        self.capability_source = capability_source;
+    }
+
+    /// Returns true if a source device with a higher priority than the given
+    /// source device provides the given capability, or has emitted it
+    /// recently. Otherwise the given source device is recorded as the last
+    /// source of the capability.
+    fn is_shadowed_by_priority(&mut self, device_id: &str, cap: &Capability) -> bool {
+        // Skip tracking entirely if no priorities have been configured
+        if self.source_devices_priority.is_empty() {
+            return false;
+        }
+
+        // Only the highest ranked source device that provides a capability
+        // may emit it.
+        if let Some(owner_id) = self.capability_source.get(cap) {
+            if owner_id != device_id {
+                return true;
+            }
+        }
+
+        let now = Instant::now();
+        let priority = self
+            .source_devices_priority
+            .get(device_id)
+            .copied()
+            .unwrap_or_default();
+        if let Some((last_id, last_time)) = self.last_event_source.get(cap) {
+            let is_recent = now.duration_since(*last_time) < PRIORITY_SHADOW_DURATION;
+            if last_id != device_id && is_recent {
+                let last_priority = self
+                    .source_devices_priority
+                    .get(last_id)
+                    .copied()
+                    .unwrap_or_default();
+                if last_priority > priority {
+                    return true;
+                }
+            }
+        }
+        self.last_event_source
+            .insert(cap.clone(), (device_id.to_string(), now));
+
+        false
+    }
+
     /// Process a single output event from a target device.
     async fn process_output_event(&mut self, event: OutputEvent) -> Result<(), Box<dyn Error>> {
         //log::trace!("Received output event: {:?}", event);
@@ -673,10 +1874,17 @@ impl CompositeDevice {
                                 continue;
                             };
                             log::debug!("Updating effect {source_effect_id} from {source_id}");
-                            if let Err(e) = source.update_effect(*source_effect_id, *data).await {
+                            let result = source.update_effect(*source_effect_id, *data).await;
+                            self.source_health.record_command(source_id, result.is_ok());
+                            if let Err(e) = result {
                                 log::error!("Error updating effect '{id}' on {source_id}: {e:?}");
                             }
                         }
+                        for (effect_id, effect_data) in self.ff_effects_to_restore.iter_mut() {
+                            if effect_id == id {
+                                *effect_data = *data;
+                            }
+                        }
                         target_dev.send(Some(*id))?;
                         return Ok(());
                     }
@@ -684,8 +1892,13 @@ impl CompositeDevice {
                     // Upload the effect data to the source devices
                     let mut source_effect_ids = HashMap::new();
                     for (source_id, source) in self.source_devices.iter() {
+                        if !self.is_output_routed_to(OutputEventKind::ForceFeedback, source_id) {
+                            continue;
+                        }
                         log::debug!("Uploading effect to {source_id}");
-                        match source.upload_effect(*data).await {
+                        let result = source.upload_effect(*data).await;
+                        self.source_health.record_command(source_id, result.is_ok());
+                        match result {
                             Ok(source_effect_id) => {
                                 // An effect ID of -1 indicates the device does not support
                                 // FF events.
@@ -714,6 +1927,7 @@ impl CompositeDevice {
                         log::debug!("Uploaded effect with effect id {id}");
                         self.ff_effect_ids.remove(&id);
                         self.ff_effect_id_source_map.insert(id, source_effect_ids);
+                        self.ff_effects_to_restore.push((id, *data));
                         target_dev.send(Some(id))?;
                     } else {
                         target_dev.send(None)?;
@@ -728,7 +1942,9 @@ impl CompositeDevice {
                                 continue;
                             };
                             log::debug!("Erasing effect from {source_id}");
-                            if let Err(e) = source.erase_effect(*source_effect_id).await {
+                            let result = source.erase_effect(*source_effect_id).await;
+                            self.source_health.record_command(source_id, result.is_ok());
+                            if let Err(e) = result {
                                 log::warn!("Failed to erase FF effect from {source_id}: {:?}", e);
                             }
                         }
@@ -738,6 +1954,8 @@ impl CompositeDevice {
                     log::debug!("Erased effect with effect id {effect_id}");
                     self.ff_effect_ids.insert(effect_id);
                     self.ff_effect_id_source_map.remove(&effect_id);
+                    self.ff_effects_to_restore
+                        .retain(|(id, _)| *id != effect_id);
                 }
             }
 
@@ -747,8 +1965,168 @@ impl CompositeDevice {
             return Ok(());
         }
 
+        // Only log the event if dry run mode is enabled. Uploaded effects are
+        // still tracked above so they can be played once dry run is disabled.
+        if self.dry_run {
+            log::info!("Dry run: output event: {:?}", event);
+            return Ok(());
+        }
+
+        // Only send LED events to source devices that can handle them
+        if let OutputEvent::LED(led) = event.borrow() {
+            for (source_id, source) in self.source_devices.iter() {
+                let supports_led = self
+                    .source_devices_output_capabilities
+                    .get(source_id)
+                    .is_some_and(|caps| caps.contains(&OutputCapability::LED(LED::Color)));
+                if !supports_led || !self.is_output_routed_to(OutputEventKind::Led, source_id) {
+                    continue;
+                }
+                let result = source.set_led(*led).await;
+                self.source_health.record_command(source_id, result.is_ok());
+                if let Err(e) = result {
+                    log::error!("Failed to send LED event to {}. {:?}", source_id, e)
+                }
+            }
+
+            return Ok(());
+        }
+
+        // Only send trigger feedback events to source devices that can handle them
+        if let OutputEvent::TriggerFeedback {
+            left_intensity,
+            right_intensity,
+        } = event.borrow()
+        {
+            for (source_id, source) in self.source_devices.iter() {
+                let supports_trigger_feedback = self
+                    .source_devices_output_capabilities
+                    .get(source_id)
+                    .is_some_and(|caps| caps.contains(&OutputCapability::TriggerFeedback));
+                if !supports_trigger_feedback
+                    || !self.is_output_routed_to(OutputEventKind::Trigger, source_id)
+                {
+                    continue;
+                }
+                let result = source
+                    .set_trigger_feedback(*left_intensity, *right_intensity)
+                    .await;
+                self.source_health.record_command(source_id, result.is_ok());
+                if let Err(e) = result {
+                    log::error!(
+                        "Failed to send trigger feedback event to {}. {:?}",
+                        source_id,
+                        e
+                    )
+                }
+            }
+
+            return Ok(());
+        }
+
+        // Only send adaptive trigger events to source devices that can handle them
+        if let OutputEvent::AdaptiveTrigger { trigger, effect } = event.borrow() {
+            for (source_id, source) in self.source_devices.iter() {
+                let supports_adaptive_trigger = self
+                    .source_devices_output_capabilities
+                    .get(source_id)
+                    .is_some_and(|caps| caps.contains(&OutputCapability::AdaptiveTrigger));
+                if !supports_adaptive_trigger
+                    || !self.is_output_routed_to(OutputEventKind::Trigger, source_id)
+                {
+                    continue;
+                }
+                let result = source.set_adaptive_trigger(*trigger, *effect).await;
+                self.source_health.record_command(source_id, result.is_ok());
+                if let Err(e) = result {
+                    log::error!(
+                        "Failed to send adaptive trigger event to {}. {:?}",
+                        source_id,
+                        e
+                    )
+                }
+            }
+
+            return Ok(());
+        }
+
+        // Source devices that cannot handle DualSense output reports natively
+        // receive the lightbar and adaptive trigger state as generic events.
+        if let OutputEvent::DualSense(report) = event.borrow() {
+            for translated in OutputEvent::from_dualsense_report(report) {
+                let capability = match translated {
+                    OutputEvent::LED(_) => OutputCapability::LED(LED::Color),
+                    _ => OutputCapability::TriggerFeedback,
+                };
+                for (source_id, source) in self.source_devices.iter() {
+                    let supported = self
+                        .source_devices_output_capabilities
+                        .get(source_id)
+                        .is_some_and(|caps| {
+                            caps.contains(&capability)
+                                && !caps.contains(&OutputCapability::DualSense)
+                        });
+                    if !supported || !self.is_output_routed_to(translated.kind(), source_id) {
+                        continue;
+                    }
+                    let result = match translated {
+                        OutputEvent::LED(led) => source.set_led(led).await,
+                        OutputEvent::TriggerFeedback {
+                            left_intensity,
+                            right_intensity,
+                        } => {
+                            source
+                                .set_trigger_feedback(left_intensity, right_intensity)
+                                .await
+                        }
+                        _ => Ok(()),
+                    };
+                    self.source_health.record_command(source_id, result.is_ok());
+                    if let Err(e) = result {
+                        log::error!("Failed to send Output event to {}. {:?}", source_id, e)
+                    }
+                }
+            }
+        }
+
+        // Raw HID output reports can only be handled by hidraw source devices
+        if let OutputEvent::HIDRaw(report) = event.borrow() {
+            for (source_id, source) in self.source_devices.iter() {
+                if !source_id.starts_with("hidraw://")
+                    || !self.is_output_routed_to(event.kind(), source_id)
+                {
+                    continue;
+                }
+                let result = source
+                    .write_hidraw_report(report.report_id, report.data.clone())
+                    .await;
+                self.source_health.record_command(source_id, result.is_ok());
+                if let Err(e) = result {
+                    log::error!("Failed to send HID output report to {source_id}: {e:?}");
+                }
+            }
+            return Ok(());
+        }
+
+        // Play force feedback events for effects that were never uploaded as
+        // ephemeral rumble effects.
+        if let OutputEvent::Evdev(input_event) = event.borrow() {
+            let is_ff_play = input_event.event_type().0 == evdev::EventType::FORCEFEEDBACK.0
+                && input_event.code() < FFEffectCode::FF_GAIN.0;
+            let effect_id = input_event.code() as i16;
+            if is_ff_play && !self.ff_effect_id_source_map.contains_key(&effect_id) {
+                log::trace!("Playing FF event with unknown id {effect_id} as ephemeral rumble");
+                self.play_ephemeral_rumble(input_event.value()).await;
+                return Ok(());
+            }
+        }
+
         // TODO: Only write the event to devices that are capabile of handling it
+        let kind = event.kind();
         for (source_id, source) in self.source_devices.iter() {
+            if !self.is_output_routed_to(kind, source_id) {
+                continue;
+            }
             // If this is a force feedback event, translate the effect id into
             // the source device's effect id.
             if let OutputEvent::Evdev(input_event) = event {
@@ -777,14 +2155,18 @@ impl CompositeDevice {
                     let output_event = OutputEvent::Evdev(new_event);
 
                     // Write the FF event to the source device
-                    if let Err(e) = source.write_event(output_event).await {
+                    let result = source.write_event(output_event).await;
+                    self.source_health.record_command(source_id, result.is_ok());
+                    if let Err(e) = result {
                         log::error!("Failed to send Output event to {}. {:?}", source_id, e)
                     }
                     continue;
                 }
             }
 
-            if let Err(e) = source.write_event(event.clone()).await {
+            let result = source.write_event(event.clone()).await;
+            self.source_health.record_command(source_id, result.is_ok());
+            if let Err(e) = result {
                 log::error!("Failed to send Output event to {}. {:?}", source_id, e)
             }
         }
@@ -794,137 +2176,545 @@ impl CompositeDevice {
         Ok(())
     }
 
-    /// Translate and write the given event to the appropriate target devices
-    async fn handle_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
-        // Check if we need to reverse the event list.
-        let is_pressed = event.pressed();
-        // Check if this is is a single event or multiple events.
-        let mut is_chord = false;
-        // Track the delay for chord events.
-        let mut sleep_time = 0;
+    /// Returns true if output events of the given kind should be sent to the
+    /// source device with the given id based on the configured output routing.
+    fn is_output_routed_to(&self, kind: OutputEventKind, source_id: &str) -> bool {
+        let Some(prefixes) = self.output_routing.get(&kind) else {
+            return true;
+        };
+        prefixes
+            .iter()
+            .any(|prefix| source_id.starts_with(prefix.as_str()))
+    }
 
-        // Translate the event using the device profile.
-        let mut events = if self.device_profile.is_some() {
-            self.translate_event(&event).await?
-        } else {
-            vec![event]
+    /// Play a rumble effect on all source devices that support force feedback
+    /// without allocating a composite effect id. The strong motor intensity
+    /// is encoded in the upper 16 bits of the given value and the weak motor
+    /// intensity in the lower 16 bits. A value of zero stops the rumble. The
+    /// effect is uploaded to each source device once and updated on every
+    /// subsequent play.
+    async fn play_ephemeral_rumble(&mut self, value: i32) {
+        let effect_data = FFEffectData {
+            direction: 0,
+            trigger: FFTrigger {
+                button: 0,
+                interval: 0,
+            },
+            replay: FFReplay {
+                length: EPHEMERAL_RUMBLE_LENGTH_MS,
+                delay: 0,
+            },
+            kind: FFEffectKind::Rumble {
+                strong_magnitude: (value as u32 >> 16) as u16,
+                weak_magnitude: (value as u32 & 0xFFFF) as u16,
+            },
         };
 
-        // Check if we need to reverse the event list.
-        if events.len() > 1 {
-            //log::trace!("Got chord: {events:?}");
-            is_chord = true;
-            if !is_pressed {
-                events = events.into_iter().rev().collect();
-                // To support on_release events, we need to sleep past the time it takes to emit
-                // the down events.
-                sleep_time = 80 * events.len() as u64;
-                //log::trace!("Chord is an UP event. New chord: {events:?}");
+        for (source_id, source) in self.source_devices.iter() {
+            if !self.is_output_routed_to(OutputEventKind::ForceFeedback, source_id) {
+                continue;
             }
-        }
-
-        let intercept = matches!(self.intercept_mode.clone(), InterceptMode::Pass);
-
-        for event in events {
-            let cap = event.as_capability();
-
-            // Track what is currently active so we can ignore extra events.
-            match cap {
-                Capability::None
-                | Capability::Touchpad(_)
-                | Capability::NotImplemented
-                | Capability::Sync
-                | Capability::DBus(_) => {}
-                Capability::Keyboard(_) => {
-                    if !self.is_new_active_event(&cap, is_pressed) {
-                        continue;
-                    }
-                    if self
-                        .is_intercept_event(&event, is_pressed, intercept)
-                        .await?
-                    {
-                        continue;
-                    }
-                }
-                Capability::Gamepad(ref t) => match t {
-                    Gamepad::Button(_) => {
-                        if !self.is_new_active_event(&cap, is_pressed) {
-                            continue;
-                        }
-                        if self
-                            .is_intercept_event(&event, is_pressed, intercept)
-                            .await?
-                        {
+            let source_effect_id = match self.ff_ephemeral_effect_ids.get(source_id) {
+                Some(source_effect_id) => {
+                    if value != 0 {
+                        if let Err(e) = source.update_effect(*source_effect_id, effect_data).await {
+                            log::error!("Error updating ephemeral effect on {source_id}: {e:?}");
                             continue;
                         }
                     }
-                    Gamepad::Axis(_)
-                    | Gamepad::Trigger(_)
-                    | Gamepad::Accelerometer
-                    | Gamepad::Gyro => {}
-                },
-                Capability::Mouse(ref t) => match t {
-                    Mouse::Motion => {}
-                    Mouse::Button(_) => {
-                        if !self.is_new_active_event(&cap, is_pressed) {
-                            continue;
-                        }
-                        if self
-                            .is_intercept_event(&event, is_pressed, intercept)
-                            .await?
-                        {
-                            continue;
-                        }
+                    *source_effect_id
+                }
+                // Nothing to stop if the effect was never played
+                None if value == 0 => continue,
+                None => match source.upload_effect(effect_data).await {
+                    // An effect ID of -1 indicates the device does not support
+                    // FF events.
+                    Ok(-1) => continue,
+                    Ok(source_effect_id) => {
+                        self.ff_ephemeral_effect_ids
+                            .insert(source_id.clone(), source_effect_id);
+                        source_effect_id
+                    }
+                    Err(e) => {
+                        log::error!("Error uploading ephemeral effect to {source_id}: {e:?}");
+                        continue;
                     }
                 },
-                Capability::Touchscreen(_) => (),
-            }
+            };
 
-            // if this is a chord with no matches to the intercept_active_inputs, add a keypress
-            // delay for event chords. This is required to support steam chords as it will passed
-            // through or miss events if they aren't properly
-            // timed.
-            if is_chord {
-                let tx = self.tx.clone();
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_millis(sleep_time)).await;
-                    if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                        log::error!("Failed to send chord event command: {:?}", e);
-                    }
-                });
-                // Increment the sleep time.
-                sleep_time += 80;
-                continue;
+            // Play or stop the effect on the source device
+            let play = InputEvent::new_now(
+                evdev::EventType::FORCEFEEDBACK.0,
+                source_effect_id as u16,
+                (value != 0) as i32,
+            );
+            let result = source.write_event(OutputEvent::Evdev(play)).await;
+            self.source_health.record_command(source_id, result.is_ok());
+            if let Err(e) = result {
+                log::error!("Failed to send Output event to {}. {:?}", source_id, e)
             }
-
-            // for single events we can emit immediatly without tokio overhead.
-            self.write_event(event).await?;
         }
-        Ok(())
     }
 
-    /// Returns true if this is the first event in intercept_activation_caps, or a follow on event
-    /// if the first event has already been pressed. Otherwise returns false.
-    fn should_hold_intercept_input(&self, cap: &Capability) -> bool {
-        let Some(first_cap) = self.intercept_activation_caps.first() else {
-            log::debug!("No activation capabilities are set. Do not hold input.");
-            return false;
-        };
-        if self.intercept_active_inputs.is_empty() && cap == first_cap {
-            log::debug!("This is the first event in the activation capabilities. Hold input.");
-            return true;
+    /// Translate and write the given event to the appropriate target devices
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            device_name = %self.name,
+            profile_name = self.device_profile.as_deref().unwrap_or_default(),
+            event_capability = %event.as_capability().to_capability_string(),
+        )
+    )]
+    async fn handle_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        // Record the event if event logging is enabled
+        if let Some(event_log) = self.event_log.as_mut() {
+            if let Err(e) = event_log.write(&event) {
+                log::error!("Failed to write to event log. Disabling event log: {e:?}");
+                self.event_log = None;
+            }
         }
-        if !self.intercept_active_inputs.is_empty() {
-            log::debug!("There are other activation capabilities. Hold input.");
-            return true;
+
+        // Send the event to the event stream if one is running
+        if let Some(event_stream) = self.event_stream.as_mut() {
+            if !event_stream.write(&event) {
+                log::info!("Event stream was closed by the reader");
+                self.event_stream = None;
+            }
         }
-        log::debug!("No other buttons are pressed and this is not the first in the list. Do not hold input.");
-        false
-    }
 
-    /// Writes the given event to the appropriate target device.
-    async fn write_event(&self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        // Write the event as-is if passthrough mode is enabled
+        if self.passthrough {
+            return self.write_event(event).await;
+        }
+
+        // Transform the event with the user provided script if one is loaded
+        #[cfg(feature = "wasm-scripts")]
+        let event = self.run_transform_script(event);
+
+        // Translate touchpad motion into mouse motion if touchpad mouse
+        // emulation is enabled. The touchpad motion is not passed on.
+        if self.touchpad_mouse.is_enabled()
+            && matches!(
+                event.as_capability(),
+                Capability::Touchpad(
+                    Touchpad::LeftPad(Touch::Motion)
+                        | Touchpad::RightPad(Touch::Motion)
+                        | Touchpad::CenterPad(Touch::Motion)
+                )
+            )
+        {
+            let motion = self
+                .touchpad_mouse
+                .update(&event.get_value(), Instant::now());
+            if let Some(motion) = motion {
+                self.write_event(motion).await?;
+            }
+            return Ok(());
+        }
+
+        // Translate gyro motion into mouse motion if gyro mouse emulation is
+        // enabled. The gyro motion is not passed on, but the activation
+        // button is.
+        if self.gyro_mouse.is_activation_button(&event.as_capability()) {
+            self.gyro_mouse.set_activated(&event.get_value());
+        }
+        if self.gyro_mouse.is_enabled()
+            && event.as_capability() == Capability::Gamepad(Gamepad::Gyro)
+        {
+            let motion = self.gyro_mouse.update(&event.get_value(), Instant::now());
+            if let Some(motion) = motion {
+                self.write_event(motion).await?;
+            }
+            return Ok(());
+        }
+
+        // Load the profile for the orientation of the device if motion profile
+        // switching is enabled. Accelerometer events are still passed on.
+        if self.motion_profile.is_enabled()
+            && event.as_capability() == Capability::Gamepad(Gamepad::Accelerometer)
+        {
+            let path = self
+                .motion_profile
+                .update(&event.get_value(), Instant::now());
+            if let Some(path) = path {
+                log::info!("Device orientation changed, loading profile {path}");
+                if let Err(e) = self.load_device_profile_from_path(path) {
+                    log::error!("Failed to load motion profile: {e:?}");
+                }
+            }
+        }
+
+        // Hold back clicks of mouse buttons with double click mappings until
+        // it is known whether they are part of a double click
+        if let Capability::Mouse(Mouse::Button(button)) = event.as_capability() {
+            if let InputValue::Bool(pressed) = event.get_value() {
+                if self.has_double_click_mapping(&button) {
+                    let events = self.double_click.update(&button, pressed, Instant::now());
+                    if self.double_click.is_pending() {
+                        self.start_double_click_timer();
+                    }
+                    for event in events {
+                        self.translate_and_write_event(event).await?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        // Discard repeated presses for mappings that suppress key repeat
+        if self.is_key_repeat(&event) {
+            log::trace!("Suppressing key repeat for {:?}", event.as_capability());
+            return Ok(());
+        }
+
+        // Load the next profile if the profile cycle combo was pressed
+        if self.is_profile_cycle_combo_pressed(&event) {
+            log::info!("Profile cycle combo pressed");
+            let tx = self.tx.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = tx.send(CompositeCommand::NextProfile).await {
+                    log::error!("Failed to send next profile command: {e:?}");
+                }
+            });
+        }
+
+        // Release the other button of mutually exclusive pairs before the
+        // newly pressed button is emitted
+        if self.anti_ghosting.is_enabled() {
+            for event in self.anti_ghosting.update(event) {
+                self.translate_and_write_event(event).await?;
+            }
+            return Ok(());
+        }
+
+        self.translate_and_write_event(event).await
+    }
+
+    /// Load the given WebAssembly module as the script used to transform
+    /// events. An empty module unloads the current script.
+    #[cfg(feature = "wasm-scripts")]
+    fn load_wasm_script(&mut self, module: Vec<u8>) -> Result<(), String> {
+        if module.is_empty() {
+            log::info!("Unloading transform script");
+            self.transform_script = None;
+            return Ok(());
+        }
+        match WasmTransform::new(&module) {
+            Ok(script) => {
+                log::info!("Loaded transform script");
+                self.transform_script = Some(script);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Failed to load transform script: {e:?}");
+                Err(format!("Failed to load transform script: {e}"))
+            }
+        }
+    }
+
+    /// Load the given WebAssembly module as the script used to transform
+    /// events. Always fails if support for scripts was not compiled in.
+    #[cfg(not(feature = "wasm-scripts"))]
+    fn load_wasm_script(&mut self, module: Vec<u8>) -> Result<(), String> {
+        log::error!(
+            "Unable to load transform script of {} bytes: support for WASM scripts is not enabled",
+            module.len()
+        );
+        Err("Support for WASM scripts is not enabled".to_string())
+    }
+
+    /// Transform the given event with the loaded transform script. Only events
+    /// with bool or float values are passed to the script, and the transformed
+    /// event keeps the value type of the original event. The event is returned
+    /// unchanged if no script is loaded. Scripts that fail are unloaded.
+    #[cfg(feature = "wasm-scripts")]
+    fn run_transform_script(&mut self, event: NativeEvent) -> NativeEvent {
+        let Some(script) = self.transform_script.as_mut() else {
+            return event;
+        };
+        let capability = event.as_capability();
+        let value = match event.get_value() {
+            InputValue::Bool(pressed) => f32::from(u8::from(pressed)),
+            InputValue::Float(value) => value as f32,
+            _ => return event,
+        };
+        let (id, transformed_value) = match script.transform(&capability, value) {
+            Ok(Some(result)) => result,
+            Ok(None) => return event,
+            Err(e) => {
+                log::error!("Transform script failed. Unloading script: {e:?}");
+                self.transform_script = None;
+                return event;
+            }
+        };
+
+        // Look up the capability of the transformed event
+        let transformed_capability = if id == capability_id(&capability) {
+            capability
+        } else {
+            let found = self
+                .capabilities
+                .iter()
+                .chain(self.target_devices_capabilities.values().flatten())
+                .find(|cap| capability_id(cap) == id)
+                .cloned();
+            let Some(cap) = found else {
+                log::warn!("Transform script returned unknown capability id: {id}");
+                return event;
+            };
+            cap
+        };
+        let transformed_value = match event.get_value() {
+            InputValue::Bool(_) => InputValue::Bool(transformed_value != 0.0),
+            _ => InputValue::Float(transformed_value as f64),
+        };
+
+        let mut transformed = match event.get_source_capability() {
+            Some(source_cap) => {
+                NativeEvent::new_translated(source_cap, transformed_capability, transformed_value)
+            }
+            None => NativeEvent::new(transformed_capability, transformed_value),
+        };
+        transformed.set_timestamp(event.timestamp());
+        transformed
+    }
+
+    /// Returns true if the loaded profile has a mapping with a double click
+    /// of the given mouse button as a source event
+    fn has_double_click_mapping(&self, button: &MouseButton) -> bool {
+        let cap = Capability::Mouse(Mouse::DoubleClick(button.clone()));
+        self.device_profile_config_map.contains_key(&cap)
+            || self.shift_mappings.contains_key(&cap)
+            || self.fallback_profile_config_map.contains_key(&cap)
+    }
+
+    /// Start a timer that sends a [CompositeCommand::DoubleClickWindowExpired]
+    /// command once the double click window has passed
+    fn start_double_click_timer(&self) {
+        let window = self.double_click.window();
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            tokio::time::sleep(window).await;
+            let command = CompositeCommand::DoubleClickWindowExpired;
+            if let Err(e) = tx.send(command).await {
+                log::error!("Failed to send double click expired command: {:?}", e);
+            }
+        });
+    }
+
+    /// Executed when the double click window of a held back click may have
+    /// passed. Writes the held back events of clicks that were not part of a
+    /// double click.
+    async fn on_double_click_window_expired(&mut self) -> Result<(), Box<dyn Error>> {
+        let events = self.double_click.expire(Instant::now());
+        for event in events {
+            self.translate_and_write_event(event).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the given event is a repeated press of a source event
+    /// whose profile mapping has key repeat suppression enabled and that
+    /// arrived within the repeat threshold of the previous press.
+    fn is_key_repeat(&mut self, event: &NativeEvent) -> bool {
+        let InputValue::Bool(pressed) = event.get_value() else {
+            return false;
+        };
         let cap = event.as_capability();
+        let mappings = self
+            .device_profile_config_map
+            .get(&cap)
+            .or_else(|| self.fallback_profile_config_map.get(&cap));
+        let Some(mappings) = mappings else {
+            return false;
+        };
+        let mapping = mappings.iter().find(|mapping| {
+            mapping.suppress_key_repeat.unwrap_or_default()
+                && mapping.required_sources_satisfied(&self.source_devices_used)
+        });
+        let Some(mapping) = mapping else {
+            return false;
+        };
+        if !pressed {
+            self.key_repeat_last_press.remove(&cap);
+            return false;
+        }
+
+        let threshold = mapping
+            .repeat_threshold_ms
+            .map(Duration::from_millis)
+            .unwrap_or(KEY_REPEAT_THRESHOLD);
+        let now = Instant::now();
+        let last_press = self.key_repeat_last_press.insert(cap, now);
+        last_press.is_some_and(|last_press| now.duration_since(last_press) < threshold)
+    }
+
+    /// Translate the given event using the device profile and write the
+    /// translated events to the appropriate target devices.
+    async fn translate_and_write_event(
+        &mut self,
+        event: NativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        // Check if we need to reverse the event list.
+        let is_pressed = event.pressed();
+        // Check if this is is a single event or multiple events.
+        let mut is_chord = false;
+        // Track the delay for chord events.
+        let mut sleep_time = 0;
+
+        // Translate the event using the device profile.
+        let mut events = if self.device_profile.is_some() {
+            self.translate_event(&event).await?
+        } else {
+            vec![event]
+        };
+
+        // Check if we need to reverse the event list. Events that fan out to
+        // different target devices are not a chord and can be written
+        // immediately.
+        if events.len() > 1 && !self.is_fan_out(&events) {
+            //log::trace!("Got chord: {events:?}");
+            is_chord = true;
+            if !is_pressed {
+                events = events.into_iter().rev().collect();
+                // To support on_release events, we need to sleep past the time it takes to emit
+                // the down events.
+                sleep_time = 80 * events.len() as u64;
+                //log::trace!("Chord is an UP event. New chord: {events:?}");
+            }
+        }
+
+        let intercept = matches!(self.intercept_mode.clone(), InterceptMode::Pass);
+
+        for event in events {
+            let cap = event.as_capability();
+
+            // Track what is currently active so we can ignore extra events.
+            match cap {
+                Capability::None
+                | Capability::Touchpad(_)
+                | Capability::NotImplemented
+                | Capability::Sync
+                | Capability::DBus(_) => {}
+                Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                    if !self.is_new_active_event(&cap, is_pressed) {
+                        continue;
+                    }
+                    if self
+                        .is_intercept_event(&event, is_pressed, intercept)
+                        .await?
+                    {
+                        continue;
+                    }
+                }
+                Capability::Gamepad(ref t) => match t {
+                    Gamepad::Button(_) => {
+                        if !self.is_new_active_event(&cap, is_pressed) {
+                            continue;
+                        }
+                        if self
+                            .is_intercept_event(&event, is_pressed, intercept)
+                            .await?
+                        {
+                            continue;
+                        }
+                    }
+                    Gamepad::Axis(_)
+                    | Gamepad::Trigger(_)
+                    | Gamepad::Accelerometer
+                    | Gamepad::Gyro => {}
+                },
+                Capability::Mouse(ref t) => match t {
+                    Mouse::Motion | Mouse::AbsoluteMotion | Mouse::DoubleClick(_) => {}
+                    Mouse::Button(_) => {
+                        if !self.is_new_active_event(&cap, is_pressed) {
+                            continue;
+                        }
+                        if self
+                            .is_intercept_event(&event, is_pressed, intercept)
+                            .await?
+                        {
+                            continue;
+                        }
+                    }
+                },
+                Capability::Touchscreen(_) => (),
+                Capability::Gesture(_) => (),
+            }
+
+            // if this is a chord with no matches to the intercept_active_inputs, add a keypress
+            // delay for event chords. This is required to support steam chords as it will passed
+            // through or miss events if they aren't properly
+            // timed.
+            if is_chord {
+                let tx = self.tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(sleep_time)).await;
+                    if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
+                        log::error!("Failed to send chord event command: {:?}", e);
+                    }
+                });
+                // Increment the sleep time.
+                sleep_time += 80;
+                continue;
+            }
+
+            // for single events we can emit immediatly without tokio overhead.
+            self.write_event(event).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if this is the first event in the activation capabilities of any intercept
+    /// chord, or a follow on event if the first event has already been pressed. Otherwise returns
+    /// false.
+    fn should_hold_intercept_input(&self, cap: &Capability) -> bool {
+        if self.intercept_chords.is_empty() {
+            log::debug!("No activation capabilities are set. Do not hold input.");
+            return false;
+        }
+        let is_first_cap = self
+            .intercept_chords
+            .iter()
+            .any(|chord| chord.activation_caps.first() == Some(cap));
+        if self.intercept_active_inputs.is_empty() && is_first_cap {
+            log::debug!("This is the first event in the activation capabilities. Hold input.");
+            return true;
+        }
+        if !self.intercept_active_inputs.is_empty() {
+            log::debug!("There are other activation capabilities. Hold input.");
+            return true;
+        }
+        log::debug!("No other buttons are pressed and this is not the first in the list. Do not hold input.");
+        false
+    }
+
+    /// Returns true if each of the given events will be written to different
+    /// target devices (e.g. a keyboard key AND a gamepad button from a single
+    /// source event). Such events do not need to be timed like a chord.
+    fn is_fan_out(&self, events: &[NativeEvent]) -> bool {
+        self.target_devices_capabilities.values().all(|caps| {
+            events
+                .iter()
+                .filter(|event| caps.contains(&event.as_capability()))
+                .count()
+                <= 1
+        })
+    }
+
+    /// Writes the given event to the appropriate target device.
+    async fn write_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+
+        // Send the translated event to any registered hooks
+        self.send_to_hooks(HookPoint::Post, &event);
+
+        // Only log the event if dry run mode is enabled
+        if self.dry_run {
+            log::info!("Dry run: event: {:?}", event);
+            return Ok(());
+        }
 
         // If this event implements the DBus capability, send the event to DBus devices
         if matches!(cap, Capability::DBus(_)) {
@@ -947,29 +2737,135 @@ impl CompositeDevice {
             return Ok(());
         }
 
+        // Also write the mapped keyboard key for gamepad buttons if legacy
+        // Steam mode is enabled
+        if self.config.legacy_steam_mode.unwrap_or_default() {
+            if let Capability::Gamepad(Gamepad::Button(button)) = &cap {
+                let key = LEGACY_STEAM_KEYS
+                    .iter()
+                    .find(|(legacy_button, _)| legacy_button == button)
+                    .map(|(_, key)| key.clone());
+                if let Some(key) = key {
+                    let key_event = NativeEvent::new(Capability::Keyboard(key), event.get_value());
+                    self.write_event_to_targets(key_event).await;
+                }
+            }
+        }
+
+        self.write_event_to_targets(event).await;
+        Ok(())
+    }
+
+    /// Writes the given event to all target devices capable of handling it
+    async fn write_event_to_targets(&mut self, event: NativeEvent) {
+        let cap = event.as_capability();
+
         // Find all target devices capable of handling this event
-        let Some(target_paths) = self.target_devices_by_capability.get(&cap) else {
-            log::trace!("No target devices capable of handling this event: {cap}");
-            return Ok(());
-        };
-        let target_devices: Vec<(&str, &TargetDeviceClient)> = target_paths
+        let target_devices: Vec<(&str, &TargetDeviceClient)> = self
+            .target_devices
             .iter()
-            .filter_map(|path| {
-                let device = self.target_devices.get(path);
-                device.map(|client| (path.as_str(), client))
+            .filter(|(path, _)| {
+                self.target_devices_capabilities
+                    .get(*path)
+                    .is_some_and(|caps| caps.contains(&cap))
             })
+            .map(|(path, client)| (path.as_str(), client))
             .collect();
+        if target_devices.is_empty() {
+            log::trace!("No target devices capable of handling this event: {cap}");
+            return;
+        }
 
         // Only write the event to devices that are capabile of handling it
         log::trace!("Emit passed event: {:?}", event);
         for (name, target) in target_devices {
-            if let Err(e) = target.write_event(event.clone()).await {
-                log::error!("Failed to write event to: {name}: {e:?}");
+            // If events are already waiting to be written to this target,
+            // queue this event behind them to preserve event order.
+            let has_overflow = self
+                .target_devices_overflow
+                .get(name)
+                .is_some_and(|queue| !queue.is_empty());
+            let event = if has_overflow {
+                event.clone()
+            } else {
+                match target.write_event(event.clone()).await {
+                    Ok(_) => continue,
+                    Err(TargetClientError::TrySendError(TrySendError::Full(
+                        TargetCommand::WriteEvent(event),
+                    ))) => event,
+                    Err(e) => {
+                        log::error!("Failed to write event to: {name}: {e:?}");
+                        continue;
+                    }
+                }
+            };
+
+            // Buffer the event until the target device channel has capacity
+            let queue = self
+                .target_devices_overflow
+                .entry(name.to_string())
+                .or_default();
+            if queue.len() >= TARGET_OVERFLOW_SIZE {
+                log::warn!(
+                    "Overflow buffer full for target device {name}. Dropping event: {event:?}"
+                );
+                continue;
             }
+            queue.push_back(event);
         }
+    }
+
+    /// Pause or resume the target device with the given DBus path. Paused
+    /// target devices keep running, but drop any input events written to them.
+    async fn pause_target(&self, path: String, paused: bool) -> Result<(), Box<dyn Error>> {
+        let Some(target) = self
+            .target_devices
+            .get(&path)
+            .or_else(|| self.target_dbus_devices.get(&path))
+        else {
+            return Err(format!("No target device found with path: {path}").into());
+        };
+        if paused {
+            target.pause().await?;
+        } else {
+            target.resume().await?;
+        }
+
         Ok(())
     }
 
+    /// Write any events that were buffered because a target device channel was
+    /// full. Events are written in order until the target device channel is
+    /// full again.
+    async fn drain_target_overflow(&mut self) {
+        if self.target_devices_overflow.is_empty() {
+            return;
+        }
+
+        for (path, queue) in self.target_devices_overflow.iter_mut() {
+            let Some(target) = self.target_devices.get(path) else {
+                queue.clear();
+                continue;
+            };
+            while let Some(event) = queue.pop_front() {
+                match target.write_event(event).await {
+                    Ok(_) => (),
+                    Err(TargetClientError::TrySendError(TrySendError::Full(
+                        TargetCommand::WriteEvent(event),
+                    ))) => {
+                        queue.push_front(event);
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to write buffered event to: {path}: {e:?}");
+                    }
+                }
+            }
+        }
+        self.target_devices_overflow
+            .retain(|_, queue| !queue.is_empty());
+    }
+
     /// Handles writing events that come from the dbus send_event interface
     async fn write_send_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
         let cap = event.as_capability();
@@ -1037,11 +2933,48 @@ impl CompositeDevice {
 
         // Loop over each mapping and try to match source events
         for mapping in map.mapping.iter() {
+            let target_cap: Capability = mapping.target_event.clone().into();
             for source_event in mapping.source_events.iter() {
                 let cap = source_event.clone().into();
                 if cap == Capability::NotImplemented {
                     continue;
                 }
+
+                // Translated events are emitted as button presses, so axes and
+                // triggers that are mapped onto themselves are not translated
+                // to keep their analog values.
+                if cap == target_cap
+                    && matches!(
+                        cap,
+                        Capability::Gamepad(Gamepad::Axis(_) | Gamepad::Trigger(_))
+                    )
+                {
+                    continue;
+                }
+
+                // Remap source axes that report unsigned values. Mappings that
+                // only define the range of an axis do not translate it.
+                let axis_range = source_event
+                    .gamepad
+                    .as_ref()
+                    .and_then(|gamepad| gamepad.axis.as_ref())
+                    .and_then(|axis| axis.axis_range);
+                if let Some(range) = axis_range {
+                    self.axis_ranges.insert(cap.clone(), range);
+                    if cap == target_cap {
+                        continue;
+                    }
+                }
+                self.translatable_capabilities.push(cap);
+            }
+            let Some(composite_source) = mapping.composite_source.as_ref() else {
+                continue;
+            };
+            for source_event in composite_source.iter() {
+                let cap = source_event.clone().into();
+                if cap == Capability::NotImplemented {
+                    continue;
+                }
                 self.translatable_capabilities.push(cap);
             }
         }
@@ -1049,22 +2982,128 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Generate a capability map from the HID report descriptor of the given
+    /// hidraw device and use it to translate device capabilities. Used for
+    /// hidraw devices without a native driver when no capability map is
+    /// configured for this composite device.
+    fn load_hid_descriptor_capability_map(&mut self, device: &UdevDevice) {
+        let descriptor = match read_report_descriptor(device) {
+            Ok(descriptor) => descriptor,
+            Err(e) => {
+                log::debug!("Unable to read HID report descriptor: {e:?}");
+                return;
+            }
+        };
+        let map = match CapabilityMap::from_hid_descriptor(&descriptor) {
+            Ok(map) => map,
+            Err(e) => {
+                log::warn!("Unable to generate capability map: {e:?}");
+                return;
+            }
+        };
+        if map.mapping.is_empty() {
+            return;
+        }
+
+        log::info!(
+            "Using capability map generated from HID report descriptor of {}",
+            device.name()
+        );
+        for mapping in map.mapping.iter() {
+            let cap = mapping.target_event.clone().into();
+            if cap == Capability::NotImplemented {
+                continue;
+            }
+            self.capabilities.insert(cap);
+        }
+        self.capability_map = Some(map);
+        if let Err(e) = self.load_capability_map() {
+            log::warn!("Failed to load generated capability map: {e:?}");
+        }
+    }
+
+    /// Remap the value of the given axis event from the raw range defined for
+    /// its capability in the capability map into the signed range between
+    /// -1.0 and 1.0.
+    fn normalize_axis_range(&self, mut event: NativeEvent) -> NativeEvent {
+        let Some(range) = self.axis_ranges.get(&event.as_capability()) else {
+            return event;
+        };
+        if let InputValue::Vector2 { x, y } = event.get_value() {
+            event.set_value(InputValue::Vector2 {
+                x: x.map(|x| range.normalize(x)),
+                y: y.map(|y| range.normalize(y)),
+            });
+        }
+        event
+    }
+
+    /// Returns the translatable active inputs that are pressed on at least one
+    /// source device whose id starts with any of the given prefixes, in the
+    /// order they were pressed.
+    fn group_active_inputs(&self, group: &[String]) -> Vec<Capability> {
+        self.translatable_active_inputs
+            .iter()
+            .filter(|cap| {
+                self.translatable_active_inputs_by_device
+                    .get(cap)
+                    .is_some_and(|devices| {
+                        devices
+                            .iter()
+                            .any(|id| group.iter().any(|prefix| id.starts_with(prefix.as_str())))
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Sets the intercept mode to the given value
-    fn set_intercept_mode(&mut self, mode: InterceptMode) {
+    async fn set_intercept_mode(&mut self, mode: InterceptMode) {
         log::debug!("Setting intercept mode to: {:?}", mode);
+        if !matches!(mode, InterceptMode::Always) {
+            self.intercept_mode_name = None;
+        }
         self.intercept_mode = mode;
+        self.signal_intercept_mode_changed().await;
+    }
+
+    /// Activate intercept mode with the mode name of the given chord
+    async fn activate_intercept_chord(&mut self, chord: &InterceptChord) {
+        log::debug!("Activating intercept mode: {}", chord.mode_name);
+        self.intercept_mode_name = Some(chord.mode_name.clone());
+        self.set_intercept_mode(InterceptMode::Always).await;
     }
 
     /// Translates the given event into a different event based on the given
     /// [CapabilityMap].
-    async fn translate_capability(&mut self, event: &NativeEvent) -> Result<(), Box<dyn Error>> {
+    async fn translate_capability(
+        &mut self,
+        device_id: &str,
+        event: &NativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
         // Get the capability map to translate input events
         let Some(map) = self.capability_map.as_ref() else {
             return Err("Cannot translate device capability without capability map!".into());
         };
 
-        // Add or remove the event from translatable_active_inputs.
+        // Keep track of which source devices are pressing this capability so
+        // composite source mappings can be matched across devices.
         let event_capability = event.as_capability();
+        let devices = self
+            .translatable_active_inputs_by_device
+            .entry(event_capability.clone())
+            .or_default();
+        if event.pressed() {
+            devices.insert(device_id.to_string());
+        } else {
+            devices.remove(device_id);
+            if devices.is_empty() {
+                self.translatable_active_inputs_by_device
+                    .remove(&event_capability);
+            }
+        }
+
+        // Add or remove the event from translatable_active_inputs.
         let capability_idx = self
             .translatable_active_inputs
             .iter()
@@ -1102,26 +3141,78 @@ impl CompositeDevice {
 
         // Loop over each mapping and try to match source events
         for mapping in map.mapping.iter() {
+            // Mappings with a source device group only consider inputs that
+            // are pressed on a source device in the group.
+            let group_inputs = mapping
+                .source_device_group
+                .as_ref()
+                .map(|group| self.group_active_inputs(group));
+            let active_inputs = group_inputs
+                .as_deref()
+                .unwrap_or(&self.translatable_active_inputs);
+
+            // Mappings with a chord window emit their source events
+            // individually if the chord is not completed in time.
+            if let Some(window_ms) = mapping.chord_window_ms {
+                let source_caps = mapping.source_capabilities();
+                if source_caps.contains(&event_capability) {
+                    if !event.pressed() {
+                        // The chord was released before the window passed,
+                        // so emit the partial chord as individual events.
+                        if let Some(timer) = self.chord_timers.remove(&mapping.name) {
+                            timer.abort();
+                            for cap in source_caps.iter() {
+                                let is_pressed =
+                                    cap == &event_capability || active_inputs.contains(cap);
+                                if is_pressed && self.chord_forwarded_inputs.insert(cap.clone()) {
+                                    let event =
+                                        NativeEvent::new(cap.clone(), InputValue::Bool(true));
+                                    emit_queue.push(event);
+                                }
+                            }
+                        }
+                    } else if source_caps
+                        .iter()
+                        .any(|cap| self.chord_forwarded_inputs.contains(cap))
+                    {
+                        // The window of this chord already passed, so keep
+                        // emitting its source events individually.
+                        if self.chord_forwarded_inputs.insert(event_capability.clone()) {
+                            let event =
+                                NativeEvent::new(event_capability.clone(), InputValue::Bool(true));
+                            emit_queue.push(event);
+                        }
+                        continue;
+                    } else if !self.chord_timers.contains_key(&mapping.name)
+                        && !mapping.source_events_active(active_inputs)
+                    {
+                        log::trace!("Starting chord window for mapping: {}", mapping.name);
+                        let tx = self.tx.clone();
+                        let name = mapping.name.clone();
+                        let task = tokio::task::spawn(async move {
+                            tokio::time::sleep(Duration::from_millis(window_ms)).await;
+                            let command = CompositeCommand::ChordWindowExpired(name);
+                            if let Err(e) = tx.send(command).await {
+                                log::error!("Failed to send chord window expired command: {:?}", e);
+                            }
+                        });
+                        self.chord_timers
+                            .insert(mapping.name.clone(), task.abort_handle());
+                    }
+                }
+            }
+
             // If the event was not pressed and it exists in the emitted_mappings array,
             // then we need to check to see if ALL of its events no longer exist in
             // translatable_active_inputs.
             if !event.pressed() && self.emitted_mappings.contains_key(&mapping.name) {
-                let mut has_source_event_pressed = false;
-
-                // Loop through each source capability in the mapping
-                for source_event in mapping.source_events.iter() {
-                    let cap = source_event.clone().into();
-                    if cap == Capability::NotImplemented {
-                        continue;
-                    }
-                    if self.translatable_active_inputs.contains(&cap) {
-                        has_source_event_pressed = true;
-                        break;
-                    }
-                }
+                // If the mapping has composite source events, it should be
+                // released as soon as ANY of them are no longer active.
+                let composite_source_active =
+                    mapping.composite_source_active(&self.translatable_active_inputs_by_device);
 
                 // If no more inputs are being pressed, send a release event.
-                if !has_source_event_pressed {
+                if mapping.is_released(active_inputs, composite_source_active) {
                     let cap = mapping.target_event.clone().into();
                     if cap == Capability::NotImplemented {
                         continue;
@@ -1135,19 +3226,18 @@ impl CompositeDevice {
 
             // If the event is pressed, check for any matches to send a 'press' event
             if event.pressed() {
-                let mut is_missing_source_event = false;
-                for source_event in mapping.source_events.iter() {
-                    let cap = source_event.clone().into();
-                    if cap == Capability::NotImplemented {
-                        continue;
-                    }
-                    if !self.translatable_active_inputs.contains(&cap) {
-                        is_missing_source_event = true;
-                        break;
-                    }
+                let mut is_missing_source_event = !mapping.source_events_active(active_inputs);
+
+                // All composite source events must also be active, each on a
+                // different source device.
+                if !mapping.composite_source_active(&self.translatable_active_inputs_by_device) {
+                    is_missing_source_event = true;
                 }
 
                 if !is_missing_source_event {
+                    if let Some(timer) = self.chord_timers.remove(&mapping.name) {
+                        timer.abort();
+                    }
                     let cap = mapping.target_event.clone().into();
                     if cap == Capability::NotImplemented {
                         continue;
@@ -1161,6 +3251,15 @@ impl CompositeDevice {
             }
         }
 
+        // Source events of an incomplete chord that were emitted individually
+        // are released individually as well.
+        if !event.pressed() && self.chord_forwarded_inputs.remove(&event_capability) {
+            emit_queue.push(NativeEvent::new(
+                event_capability.clone(),
+                InputValue::Bool(false),
+            ));
+        }
+
         // Emit the translated events. If this translated event has been emitted
         // very recently, delay sending subsequent events of the same type.
         let sleep_time = Duration::from_millis(4);
@@ -1202,93 +3301,670 @@ impl CompositeDevice {
 
     /// Translates the given event into a Vec of events based on the currently loaded
     /// [DeviceProfile]
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            device_name = %self.name,
+            profile_name = self.device_profile.as_deref().unwrap_or_default(),
+            event_capability = %event.as_capability().to_capability_string(),
+        )
+    )]
     async fn translate_event(
-        &self,
+        &mut self,
         event: &NativeEvent,
     ) -> Result<Vec<NativeEvent>, Box<dyn Error>> {
         // Lookup the profile mapping associated with this event capability. If
         // none is found, return the original un-translated event.
         let source_cap = event.as_capability();
-        if let Some(mappings) = self.device_profile_config_map.get(&source_cap) {
-            // Find which mapping in the device profile matches this source event
-            let matched_mapping = mappings
-                .iter()
-                .find(|mapping| mapping.source_matches_properties(event));
+        self.update_last_input_value(event);
+
+        // Modifier buttons only select which mappings are used, so they are
+        // tracked as active inputs and not emitted.
+        if self.modifier_caps.contains(&source_cap) {
+            if let InputValue::Bool(pressed) = event.get_value() {
+                self.is_new_active_event(&source_cap, pressed);
+                return Ok(vec![]);
+            }
+        }
+
+        let config_map = if self.is_shift_event(event) {
+            &self.shift_mappings
+        } else {
+            &self.device_profile_config_map
+        };
+        // Source events without a mapping in the profile are translated using
+        // the fallback profile, if one is loaded.
+        let mappings = config_map
+            .get(&source_cap)
+            .or_else(|| self.fallback_profile_config_map.get(&source_cap));
+        if let Some(mappings) = mappings {
+            // Find which mappings in the device profile match this source event
+            // and have all of their required source devices available and
+            // conditions met.
+            let mut matched_mappings = mappings.iter().filter(|mapping| {
+                mapping.required_sources_satisfied(&self.source_devices_used)
+                    && mapping.conditions_satisfied(&self.last_input_values)
+                    && mapping.source_matches_properties(event)
+            });
+
+            // Button events with tap dance mappings are translated depending
+            // on how many times the button is tapped.
+            let is_button = matches!(event.get_value(), InputValue::Bool(_));
+            let has_tap_dance = mappings.iter().any(|mapping| mapping.tap_dance.is_some());
+            if is_button && has_tap_dance {
+                let matched: Vec<ProfileMapping> = matched_mappings
+                    .filter(|mapping| mapping.hold_ms.is_none())
+                    .cloned()
+                    .collect();
+                return Ok(self.translate_tap_dance_event(event, &matched));
+            }
+
+            // Button events with a matching hold mapping are translated
+            // depending on how long the button is held.
+            let hold_mapping = mappings.iter().find(|mapping| {
+                mapping.hold_ms.is_some()
+                    && mapping.required_sources_satisfied(&self.source_devices_used)
+                    && mapping.conditions_satisfied(&self.last_input_values)
+                    && mapping.source_matches_properties(event)
+            });
+            if let (true, Some(hold_mapping)) = (is_button, hold_mapping) {
+                let hold_mapping = hold_mapping.clone();
+                let tap_mapping = matched_mappings
+                    .find(|mapping| mapping.hold_ms.is_none())
+                    .cloned();
+                return Ok(self.translate_hold_event(event, &hold_mapping, tap_mapping.as_ref()));
+            }
 
             // If a mapping was found, translate the event based on the found
             // mapping.
+            let matched_mapping = matched_mappings
+                .find(|mapping| mapping.hold_ms.is_none() && mapping.tap_dance.is_none());
             if let Some(mapping) = matched_mapping {
                 log::trace!(
                     "Found translation for event {:?} in profile mapping: {}",
                     source_cap,
                     mapping.name
                 );
+                if !matches!(event.get_value(), InputValue::Bool(false)) {
+                    *self
+                        .mapping_hit_counts
+                        .entry(mapping.name.clone())
+                        .or_default() += 1;
+                }
+
+                // Combo mappings press and release a sequence of target
+                // events from a single source button press.
+                if let (true, Some(combo)) = (is_button, mapping.combo.as_ref()) {
+                    let mapping = mapping.clone();
+                    let combo = combo.clone();
+                    return Ok(self.translate_combo_event(event, &mapping, &combo));
+                }
+
+                // Wheel button mappings with a scroll speed keep scrolling
+                // while the source button is held.
+                if let (true, Some(scroll_speed)) = (is_button, mapping.scroll_speed()) {
+                    let mapping = mapping.clone();
+                    return Ok(self.translate_scroll_event(event, &mapping, scroll_speed));
+                }
+
+                // Snapping the stick angle requires both axes, so partial stick
+                // events are merged with the last known value of the stick.
+                if mapping.has_angle_snap() {
+                    if let Some(value) = self.last_input_values.get(&source_cap) {
+                        let mut event = event.clone();
+                        event.set_value(value.clone());
+                        return Ok(Self::translate_mapping(&event, mapping));
+                    }
+                }
+
+                return Ok(Self::translate_mapping(event, mapping));
+            }
+        }
+
+        log::trace!("No translation mapping found for event: {:?}", source_cap);
+        Ok(vec![event.clone()])
+    }
+
+    /// Returns true if any of the profile modifiers is currently held
+    fn is_shift_held(&self) -> bool {
+        self.modifier_caps
+            .iter()
+            .any(|cap| self.active_inputs.contains(cap))
+    }
+
+    /// Returns true if the given event should be translated using the shift
+    /// mappings. Buttons pressed while a modifier is held keep using their
+    /// shift mapping until they are released, even if the modifier is
+    /// released first.
+    fn is_shift_event(&mut self, event: &NativeEvent) -> bool {
+        let cap = event.as_capability();
+        if !self.shift_mappings.contains_key(&cap) {
+            return false;
+        }
+        let InputValue::Bool(pressed) = event.get_value() else {
+            return self.is_shift_held();
+        };
+        if !pressed {
+            return self.shift_active_sources.remove(&cap);
+        }
+        if self.shift_active_sources.contains(&cap) || self.is_shift_held() {
+            self.shift_active_sources.insert(cap);
+            return true;
+        }
+        false
+    }
+
+    /// Record the value of the given source event so it can be used to
+    /// evaluate profile mapping conditions. Axes missing from stick events keep
+    /// their previous value.
+    fn update_last_input_value(&mut self, event: &NativeEvent) {
+        let value = match (
+            event.get_value(),
+            self.last_input_values.get(&event.as_capability()),
+        ) {
+            (
+                InputValue::Vector2 { x, y },
+                Some(InputValue::Vector2 {
+                    x: last_x,
+                    y: last_y,
+                }),
+            ) => InputValue::Vector2 {
+                x: x.or(*last_x),
+                y: y.or(*last_y),
+            },
+            (
+                InputValue::Vector3 { x, y, z },
+                Some(InputValue::Vector3 {
+                    x: last_x,
+                    y: last_y,
+                    z: last_z,
+                }),
+            ) => InputValue::Vector3 {
+                x: x.or(*last_x),
+                y: y.or(*last_y),
+                z: z.or(*last_z),
+            },
+            (value, _) => value,
+        };
+        self.last_input_values.insert(event.as_capability(), value);
+    }
+
+    /// Translates the given event into the target events of the given profile
+    /// mapping.
+    fn translate_mapping(event: &NativeEvent, mapping: &ProfileMapping) -> Vec<NativeEvent> {
+        let source_cap = event.as_capability();
+        // Translate the event into the defined target event(s)
+        let mut events = Vec::new();
+        for target_event in mapping.target_events.iter() {
+            // TODO: We can cache this conversion for faster translation
+            let target_cap: Capability = target_event.clone().into();
+            let result = event.get_value().translate(
+                &source_cap,
+                &mapping.source_event,
+                &target_cap,
+                target_event,
+            );
+            let value = match result {
+                Ok(v) => v,
+                Err(err) => match err {
+                    TranslationError::NotImplemented => {
+                        log::warn!(
+                            "Translation not implemented for profile mapping '{}': {:?} -> {:?}",
+                            mapping.name,
+                            source_cap,
+                            target_cap,
+                        );
+                        continue;
+                    }
+                    TranslationError::ImpossibleTranslation(msg) => {
+                        log::warn!(
+                            "Impossible translation for profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                    TranslationError::InvalidSourceConfig(msg) => {
+                        log::warn!(
+                            "Invalid source event config in profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                    TranslationError::InvalidTargetConfig(msg) => {
+                        log::warn!(
+                            "Invalid target event config in profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                },
+            };
+            if matches!(value, InputValue::None) {
+                continue;
+            }
+
+            let mut translated = NativeEvent::new_translated(source_cap.clone(), target_cap, value);
+            translated.set_timestamp(event.timestamp());
+            events.push(translated);
+        }
+
+        events
+    }
+
+    /// Translates the given button event for a profile mapping with a hold
+    /// time. Pressing the button starts a timer that sends a
+    /// [CompositeCommand::HoldTimerExpired] command when the hold time has
+    /// elapsed, which activates the hold mapping. If the button is released
+    /// before then, the tap mapping (or the untranslated event if no tap
+    /// mapping exists) is pressed and released instead.
+    fn translate_hold_event(
+        &mut self,
+        event: &NativeEvent,
+        hold_mapping: &ProfileMapping,
+        tap_mapping: Option<&ProfileMapping>,
+    ) -> Vec<NativeEvent> {
+        let cap = event.as_capability();
+        let translate_tap = |event: &NativeEvent| match tap_mapping {
+            Some(mapping) => Self::translate_mapping(event, mapping),
+            None => vec![event.clone()],
+        };
+
+        if event.pressed() {
+            // The hold timer expired or a tap is being emitted
+            if self.hold_active.contains(&cap) {
+                return Self::translate_mapping(event, hold_mapping);
+            }
+            if self.hold_taps.contains(&cap) {
+                return translate_tap(event);
+            }
+            // Ignore repeated press events while waiting for the timer
+            if self.hold_timers.contains_key(&cap) {
+                return vec![];
+            }
+
+            // Start the hold timer
+            let hold_time = Duration::from_millis(hold_mapping.hold_ms.unwrap_or_default());
+            log::trace!("Starting hold timer for {cap:?}: {hold_time:?}");
+            let tx = self.tx.clone();
+            let press_event = event.clone();
+            let task = tokio::task::spawn(async move {
+                tokio::time::sleep(hold_time).await;
+                let command = CompositeCommand::HoldTimerExpired(press_event);
+                if let Err(e) = tx.send(command).await {
+                    log::error!("Failed to send hold timer expired command: {:?}", e);
+                }
+            });
+            self.hold_timers.insert(cap, task.abort_handle());
+            return vec![];
+        }
+
+        // Release the hold mapping if it was activated
+        if self.hold_active.remove(&cap) {
+            return Self::translate_mapping(event, hold_mapping);
+        }
+        if self.hold_taps.remove(&cap) {
+            return translate_tap(event);
+        }
+
+        // The button was released before the hold time elapsed, so emit a
+        // press and release of the tap mapping instead.
+        let Some(timer) = self.hold_timers.remove(&cap) else {
+            return vec![];
+        };
+        timer.abort();
+        log::trace!("Released {cap:?} before hold time. Emitting tap.");
+        self.hold_taps.insert(cap.clone());
+        let tx = self.tx.clone();
+        let release_event = event.clone();
+        let press_event = NativeEvent::new(cap, InputValue::Bool(true));
+        tokio::task::spawn(async move {
+            for (i, event) in [press_event, release_event].into_iter().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(HOLD_TAP_DURATION).await;
+                }
+                if let Err(e) = tx.send(CompositeCommand::HandleHoldEvent(event)).await {
+                    log::error!("Failed to send hold tap event command: {:?}", e);
+                }
+            }
+        });
+
+        vec![]
+    }
+
+    /// Translates the given button event for the given profile mappings of a
+    /// source button with tap dance mappings. Taps are counted and every
+    /// event of the button is blocked until no additional tap arrives within
+    /// the tap window. Then a [CompositeCommand::TapDanceTimerExpired] command
+    /// presses and releases the mapping for the tap count, or the mapping
+    /// without a tap dance (or the untranslated event) if none exists.
+    fn translate_tap_dance_event(
+        &mut self,
+        event: &NativeEvent,
+        mappings: &[ProfileMapping],
+    ) -> Vec<NativeEvent> {
+        let cap = event.as_capability();
+
+        // The tap count was resolved and its action is being emitted
+        if let Some(taps) = self.tap_dance_active.get(&cap).copied() {
+            if !event.pressed() {
+                self.tap_dance_active.remove(&cap);
+            }
+            let mapping = mappings
+                .iter()
+                .find(|mapping| mapping.tap_dance.as_ref().map(|t| t.taps) == Some(taps))
+                .or_else(|| mappings.iter().find(|mapping| mapping.tap_dance.is_none()));
+            return match mapping {
+                Some(mapping) => Self::translate_mapping(event, mapping),
+                None => vec![event.clone()],
+            };
+        }
+
+        // Pressing the button again stops the tap window until it is released
+        if event.pressed() {
+            if let Some((_, timer)) = self.tap_dance_timers.get(&cap) {
+                timer.abort();
+            }
+            return vec![];
+        }
+
+        // Count the tap and restart the tap window
+        let taps = match self.tap_dance_timers.remove(&cap) {
+            Some((taps, timer)) => {
+                timer.abort();
+                taps + 1
+            }
+            None => 1,
+        };
+        let window_ms = mappings
+            .iter()
+            .filter_map(|mapping| mapping.tap_dance.as_ref())
+            .map(|tap_dance| tap_dance.window_ms)
+            .max()
+            .unwrap_or_default();
+        let window = Duration::from_millis(window_ms);
+        log::trace!("Counted tap {taps} of {cap:?}. Waiting {window:?} for another tap.");
+        let tx = self.tx.clone();
+        let press_event = NativeEvent::new(cap.clone(), InputValue::Bool(true));
+        let task = tokio::task::spawn(async move {
+            tokio::time::sleep(window).await;
+            let command = CompositeCommand::TapDanceTimerExpired(press_event, taps);
+            if let Err(e) = tx.send(command).await {
+                log::error!("Failed to send tap dance timer expired command: {:?}", e);
+            }
+        });
+        self.tap_dance_timers
+            .insert(cap, (taps, task.abort_handle()));
+
+        vec![]
+    }
+
+    /// Translates the given button event for a profile mapping with a scroll
+    /// speed. Pressing the button emits the first scroll wheel click and
+    /// starts a task that keeps emitting clicks at the scroll speed until the
+    /// button is released.
+    fn translate_scroll_event(
+        &mut self,
+        event: &NativeEvent,
+        mapping: &ProfileMapping,
+        scroll_speed: u32,
+    ) -> Vec<NativeEvent> {
+        let events = Self::translate_mapping(event, mapping);
+        if !event.pressed() {
+            if let Some(task) = self.scroll_tasks.remove(&mapping.name) {
+                log::trace!("Stopping scroll for mapping: {}", mapping.name);
+                task.abort();
+            }
+            return events;
+        }
+        // Ignore repeated press events while already scrolling
+        if self.scroll_tasks.contains_key(&mapping.name) {
+            return vec![];
+        }
+
+        // Only the scroll wheel target events are repeated
+        let scroll_events: Vec<NativeEvent> = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event.as_capability(),
+                    Capability::Mouse(Mouse::Button(
+                        MouseButton::WheelUp
+                            | MouseButton::WheelDown
+                            | MouseButton::WheelLeft
+                            | MouseButton::WheelRight
+                    ))
+                )
+            })
+            .cloned()
+            .collect();
+
+        log::trace!(
+            "Starting scroll for mapping {} at {scroll_speed} clicks per second",
+            mapping.name
+        );
+        let period = Duration::from_secs_f64(1.0 / scroll_speed as f64);
+        let tx = self.tx.clone();
+        let task = tokio::task::spawn(async move {
+            let start = tokio::time::Instant::now() + period;
+            let mut interval = tokio::time::interval_at(start, period);
+            loop {
+                interval.tick().await;
+                for event in scroll_events.iter() {
+                    let command = CompositeCommand::WriteEvent(event.clone());
+                    if let Err(e) = tx.send(command).await {
+                        log::error!("Failed to send scroll event command: {:?}", e);
+                        return;
+                    }
+                }
+            }
+        });
+        self.scroll_tasks
+            .insert(mapping.name.clone(), task.abort_handle());
+
+        events
+    }
+
+    /// Translates the given button event for a profile mapping with a combo.
+    /// Pressing the button starts a task that presses the target event of
+    /// each combo step for 'press_ms', releases it and waits 'release_ms'
+    /// before the next step. Releasing the button before the combo has
+    /// finished cancels it and releases the targets of all steps.
+    fn translate_combo_event(
+        &mut self,
+        event: &NativeEvent,
+        mapping: &ProfileMapping,
+        combo: &[ComboStep],
+    ) -> Vec<NativeEvent> {
+        let cap = event.as_capability();
+        if !event.pressed() {
+            let Some(task) = self.active_combo_tasks.remove(&cap) else {
+                return vec![];
+            };
+            if task.is_finished() {
+                return vec![];
+            }
+            log::trace!("Cancelling combo for mapping: {}", mapping.name);
+            task.abort();
+            return combo
+                .iter()
+                .filter_map(|step| Self::translate_combo_step(event, mapping, step))
+                .collect();
+        }
+        // Ignore repeated press events while the combo is running
+        if self.active_combo_tasks.contains_key(&cap) {
+            return vec![];
+        }
+
+        let mut release_event = event.clone();
+        release_event.set_value(InputValue::Bool(false));
+        let steps: Vec<_> = combo
+            .iter()
+            .map(|step| {
+                (
+                    Self::translate_combo_step(event, mapping, step),
+                    Self::translate_combo_step(&release_event, mapping, step),
+                    Duration::from_millis(step.press_ms),
+                    Duration::from_millis(step.release_ms),
+                )
+            })
+            .collect();
+
+        log::trace!(
+            "Starting combo with {} steps for mapping: {}",
+            steps.len(),
+            mapping.name
+        );
+        let tx = self.tx.clone();
+        let task = tokio::task::spawn(async move {
+            for (press, release, press_time, release_time) in steps {
+                for (event, delay) in [(press, press_time), (release, release_time)] {
+                    if let Some(event) = event {
+                        let command = CompositeCommand::WriteEvent(event);
+                        if let Err(e) = tx.send(command).await {
+                            log::error!("Failed to send combo event command: {:?}", e);
+                            return;
+                        }
+                    }
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        });
+        self.active_combo_tasks.insert(cap, task.abort_handle());
+
+        vec![]
+    }
+
+    /// Translates the given source button event into the target event of the
+    /// given combo step
+    fn translate_combo_step(
+        event: &NativeEvent,
+        mapping: &ProfileMapping,
+        step: &ComboStep,
+    ) -> Option<NativeEvent> {
+        let source_cap = event.as_capability();
+        let target_cap: Capability = step.capability.clone().into();
+        let result = event.get_value().translate(
+            &source_cap,
+            &mapping.source_event,
+            &target_cap,
+            &step.capability,
+        );
+        let Ok(value) = result else {
+            log::warn!(
+                "Unable to translate combo step of profile mapping '{}': {:?} -> {:?}",
+                mapping.name,
+                source_cap,
+                target_cap,
+            );
+            return None;
+        };
+        if matches!(value, InputValue::None) {
+            return None;
+        }
+        Some(NativeEvent::new_translated(source_cap, target_cap, value))
+    }
+
+    /// Executed when the hold timer for the given press event expires
+    async fn on_hold_timer_expired(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+        // The timer may have been cancelled after the command was sent
+        if self.hold_timers.remove(&cap).is_none() {
+            return Ok(());
+        }
+        log::trace!("Hold timer expired for {cap:?}");
+        self.hold_active.insert(cap);
+        self.translate_and_write_event(event).await
+    }
+
+    /// Executed when the tap window of the given press event expires with the
+    /// given tap count. Presses and releases the action for the tap count.
+    async fn on_tap_dance_timer_expired(
+        &mut self,
+        event: NativeEvent,
+        taps: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+        // The timer may have been restarted by another tap after the command
+        // was sent
+        if !matches!(self.tap_dance_timers.get(&cap), Some((count, _)) if *count == taps) {
+            return Ok(());
+        }
+        self.tap_dance_timers.remove(&cap);
+        log::trace!("Resolved {taps} taps of {cap:?}");
+        self.tap_dance_active.insert(cap, taps);
+
+        let mut release_event = event.clone();
+        release_event.set_value(InputValue::Bool(false));
+        self.translate_and_write_event(event).await?;
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            tokio::time::sleep(HOLD_TAP_DURATION).await;
+            if let Err(e) = tx
+                .send(CompositeCommand::HandleHoldEvent(release_event))
+                .await
+            {
+                log::error!("Failed to send tap dance release command: {:?}", e);
+            }
+        });
 
-                // Translate the event into the defined target event(s)
-                let mut events = Vec::new();
-                for target_event in mapping.target_events.iter() {
-                    // TODO: We can cache this conversion for faster translation
-                    let target_cap: Capability = target_event.clone().into();
-                    let result = event.get_value().translate(
-                        &source_cap,
-                        &mapping.source_event,
-                        &target_cap,
-                        target_event,
-                    );
-                    let value = match result {
-                        Ok(v) => v,
-                        Err(err) => {
-                            match err {
-                                TranslationError::NotImplemented => {
-                                    log::warn!(
-                                        "Translation not implemented for profile mapping '{}': {:?} -> {:?}",
-                                        mapping.name,
-                                        source_cap,
-                                        target_cap,
-                                    );
-                                    continue;
-                                }
-                                TranslationError::ImpossibleTranslation(msg) => {
-                                    log::warn!(
-                                        "Impossible translation for profile mapping '{}': {msg}",
-                                        mapping.name
-                                    );
-                                    continue;
-                                }
-                                TranslationError::InvalidSourceConfig(msg) => {
-                                    log::warn!("Invalid source event config in profile mapping '{}': {msg}", mapping.name);
-                                    continue;
-                                }
-                                TranslationError::InvalidTargetConfig(msg) => {
-                                    log::warn!("Invalid target event config in profile mapping '{}': {msg}", mapping.name);
-                                    continue;
-                                }
-                            }
-                        }
-                    };
-                    if matches!(value, InputValue::None) {
-                        continue;
-                    }
+        Ok(())
+    }
 
-                    let event = NativeEvent::new_translated(source_cap.clone(), target_cap, value);
-                    events.push(event);
-                }
+    /// Executed when the chord window of the capability mapping with the given
+    /// name passes. Any source events of the chord that are still pressed are
+    /// emitted as individual events.
+    async fn on_chord_window_expired(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        // The timer may have been cancelled after the command was sent
+        if self.chord_timers.remove(name).is_none() {
+            return Ok(());
+        }
+        let Some(mapping) = self
+            .capability_map
+            .as_ref()
+            .and_then(|map| map.mapping.iter().find(|mapping| mapping.name == name))
+        else {
+            return Ok(());
+        };
+        log::trace!("Chord window expired for mapping: {name}");
 
-                return Ok(events);
+        let mut events = Vec::new();
+        for cap in mapping.source_capabilities() {
+            if !self.translatable_active_inputs.contains(&cap) {
+                continue;
+            }
+            if self.chord_forwarded_inputs.insert(cap.clone()) {
+                events.push(NativeEvent::new(cap, InputValue::Bool(true)));
             }
         }
+        for event in events {
+            self.handle_event(event).await?;
+        }
 
-        log::trace!("No translation mapping found for event: {:?}", source_cap);
-        Ok(vec![event.clone()])
+        Ok(())
     }
 
     /// Executed whenever a source device is added to this [CompositeDevice].
     async fn on_source_device_added(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
+        // The device may be added by both the manager and a reconnect task
+        let id = device.get_id();
+        self.source_devices_reconnecting.remove(&id);
+        if self.source_devices_used.contains(&id) {
+            log::debug!("Source device {id} was already added");
+            return Ok(());
+        }
+
         if let Err(e) = self.add_source_device(device) {
             return Err(e.to_string().into());
         }
         self.run_source_devices().await?;
 
+        // The capabilities of a re-added device may have changed
+        if self.source_devices_removed.remove(&id) {
+            log::debug!("Source device {id} was added again. Refreshing capabilities.");
+            self.refresh_capabilities().await;
+        }
+
         // Signal to DBus that source devices have changed
         self.signal_sources_changed().await;
 
@@ -1310,8 +3986,21 @@ impl CompositeDevice {
 
         if let Some(idx) = self.source_devices_used.iter().position(|str| str == &id) {
             self.source_devices_used.remove(idx);
+            self.source_devices_removed.insert(id.clone());
         };
         self.source_devices_blocked.remove(&id);
+        self.source_devices_info.remove(&id);
+        self.source_devices_output_capabilities.remove(&id);
+        self.source_devices_priority.remove(&id);
+        self.last_event_source
+            .retain(|_, (source_id, _)| source_id != &id);
+        self.source_devices_capabilities.remove(&id);
+        self.source_devices_config_index.remove(&id);
+        self.source_devices_filters.remove(&id);
+        self.ff_ephemeral_effect_ids.remove(&id);
+        self.rate_limit.remove_device(&id);
+        self.source_health.remove_device(&id);
+        self.update_capability_sources();
 
         // Signal to DBus that source devices have changed
         self.signal_sources_changed().await;
@@ -1328,6 +4017,373 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Take over all source devices of the composite device with the given
+    /// transmitter. The other composite device stops and releases its source
+    /// devices, then sends a [CompositeCommand::SourceDeviceAdded] command
+    /// for each of them to this device.
+    fn merge_device(&self, other: mpsc::Sender<CompositeCommand>) -> Result<(), Box<dyn Error>> {
+        if other.same_channel(&self.tx) {
+            return Err("Unable to merge a composite device with itself".into());
+        }
+        let Some(path) = self.dbus_path.clone() else {
+            return Err("No DBus path for composite device exists to merge into".into());
+        };
+        log::info!("Merging composite device into {path}");
+
+        // Send the command from a task so this device keeps processing
+        // commands if the other device is waiting on it.
+        let command = CompositeCommand::MergeInto(self.tx.clone(), path);
+        tokio::task::spawn(async move {
+            if let Err(e) = other.send(command).await {
+                log::error!("Failed to send merge command: {e:?}");
+            }
+        });
+        Ok(())
+    }
+
+    /// Hand the source devices of this stopped composite device over to the
+    /// composite device with the given transmitter and DBus path, and let
+    /// the input manager know which composite device now uses them. Source
+    /// devices that cannot be handed over are unhidden.
+    async fn hand_over_source_devices(
+        &mut self,
+        target: mpsc::Sender<CompositeCommand>,
+        path: String,
+    ) {
+        for (id, device) in self.source_devices_info.drain() {
+            log::debug!("Handing source device {id} over to {path}");
+            let subsystem = device.subsystem();
+            let devnode = device.devnode();
+            if let Err(e) = target
+                .send(CompositeCommand::SourceDeviceAdded(device))
+                .await
+            {
+                log::error!("Failed to hand over source device {id}: {e:?}");
+                if matches!(subsystem.as_str(), "input" | "hidraw") {
+                    log::debug!("Un-hiding device: {devnode}");
+                    if let Err(e) = unhide_device(devnode.clone()).await {
+                        log::debug!("Unable to unhide device {devnode}: {:?}", e);
+                    }
+                }
+                continue;
+            }
+            let command = ManagerCommand::SourceDeviceMerged {
+                id,
+                composite_path: path.clone(),
+            };
+            if let Err(e) = self.manager.send(command).await {
+                log::error!("Failed to send source device merged command: {e:?}");
+            }
+        }
+    }
+
+    /// Add a simulated source device with the given id and capabilities that
+    /// does not exist on the system. Returns a channel that events can be
+    /// sent to, which are processed as if they came from the source device.
+    /// When the channel is closed, the simulated source device is removed.
+    async fn add_simulated_source_device(
+        &mut self,
+        id: String,
+        capabilities: HashSet<Capability>,
+    ) -> Result<mpsc::Sender<NativeEvent>, Box<dyn Error>> {
+        if self.source_devices_used.contains(&id) {
+            return Err(format!("Source device already exists: {id}").into());
+        }
+        log::info!("Adding simulated source device {id} with capabilities: {capabilities:?}");
+
+        for cap in capabilities.iter() {
+            if self.translatable_capabilities.contains(cap) {
+                continue;
+            }
+            self.capabilities.insert(cap.clone());
+        }
+        self.source_devices_capabilities
+            .insert(id.clone(), capabilities.into_iter().collect());
+        self.source_devices_simulated.insert(id.clone());
+        self.source_devices_used.push(id.clone());
+        self.update_capability_sources();
+        self.signal_sources_changed().await;
+
+        // Forward events from the channel through the normal event pipeline
+        let (events_tx, mut events_rx) = mpsc::channel(BUFFER_SIZE);
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            while let Some(event) = events_rx.recv().await {
+                let cmd = CompositeCommand::ProcessEvent(id.clone(), Event::Native(event));
+                if tx.send(cmd).await.is_err() {
+                    return;
+                }
+            }
+            if let Err(e) = tx
+                .send(CompositeCommand::SimulatedSourceDeviceStopped(id))
+                .await
+            {
+                log::debug!("Failed to send simulated source device stopped: {e:?}");
+            }
+        });
+
+        Ok(events_tx)
+    }
+
+    /// Remove the simulated source device with the given id
+    async fn remove_simulated_source_device(&mut self, id: String) {
+        if !self.source_devices_simulated.remove(&id) {
+            return;
+        }
+        log::info!("Removing simulated source device: {id}");
+        self.source_devices_used.retain(|used| used != &id);
+        self.last_event_source
+            .retain(|_, (source_id, _)| source_id != &id);
+        self.source_devices_capabilities.remove(&id);
+        self.rate_limit.remove_device(&id);
+        self.source_health.remove_device(&id);
+        self.update_capability_sources();
+        self.signal_sources_changed().await;
+    }
+
+    /// Returns true if the given source device should wait to reconnect instead
+    /// of being removed permanently, based on the reconnect policy in the
+    /// composite device config. Only evdev devices are reconnected.
+    fn should_reconnect_source_device(&self, device: &UdevDevice) -> bool {
+        let Some(policy) = self.config.reconnect.as_ref() else {
+            return false;
+        };
+        policy.reconnect_attempts > 0
+            && device.subsystem() == "input"
+            && self.source_devices_used.contains(&device.get_id())
+    }
+
+    /// Spawn a task that waits for the given stopped source device to reappear
+    /// and re-adds it to the composite device. If the device does not reappear
+    /// after the configured number of attempts, or the device that appears
+    /// does not match the composite device config, a
+    /// [CompositeCommand::SourceDeviceReconnectFailed] is sent instead.
+    fn reconnect_source_device(&mut self, device: UdevDevice) {
+        let Some(policy) = self.config.reconnect.clone() else {
+            return;
+        };
+        let id = device.get_id();
+        if !self.source_devices_reconnecting.insert(id.clone()) {
+            log::debug!("Source device {id} is already waiting to reconnect");
+            return;
+        }
+        log::info!("Waiting for source device {id} to reconnect");
+
+        let tx = self.tx.clone();
+        let config = self.config.clone();
+        tokio::task::spawn(async move {
+            let path = device.devnode();
+            let sysname = device.sysname();
+            let delay = Duration::from_millis(policy.reconnect_delay_ms);
+            for attempt in 1..=policy.reconnect_attempts {
+                log::debug!(
+                    "Waiting for {path} to reappear. Attempt {attempt}/{}",
+                    policy.reconnect_attempts
+                );
+                let wait_path = path.clone();
+                let result =
+                    tokio::task::spawn_blocking(move || watcher::wait_for_file(wait_path, delay))
+                        .await;
+                match result {
+                    Ok(Ok(true)) => (),
+                    Ok(Ok(false)) => continue,
+                    Ok(Err(e)) => {
+                        log::error!("Failed to wait for {path} to reappear: {e:?}");
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to join reconnect task for {path}: {e:?}");
+                        break;
+                    }
+                }
+
+                // Wait until the device has initialized with udev
+                let mut initialized = None;
+                for _ in 0..RECONNECT_INIT_ATTEMPTS {
+                    let Ok(device) = ::udev::Device::from_subsystem_sysname(
+                        "input".to_string(),
+                        sysname.clone(),
+                    ) else {
+                        break;
+                    };
+                    if device.is_initialized() {
+                        initialized = Some(device);
+                        break;
+                    }
+                    tokio::time::sleep(RECONNECT_INIT_INTERVAL).await;
+                }
+                let Some(device) = initialized else {
+                    log::debug!("Unable to create UdevDevice from {path}. Retrying.");
+                    continue;
+                };
+
+                // A different device may have taken the same device node
+                let device: UdevDevice = device.into();
+                if config.get_matching_device(&device).is_none() {
+                    log::warn!(
+                        "Device that appeared at {path} does not match the composite device config. Not reconnecting."
+                    );
+                    break;
+                }
+
+                log::info!("Source device {path} reappeared. Reconnecting.");
+                if let Err(e) = tx.send(CompositeCommand::SourceDeviceAdded(device)).await {
+                    log::error!("Failed to send device added command: {:?}", e);
+                }
+                return;
+            }
+
+            if let Err(e) = tx
+                .send(CompositeCommand::SourceDeviceReconnectFailed(device))
+                .await
+            {
+                log::error!("Failed to send device reconnect failed command: {:?}", e);
+            }
+        });
+    }
+
+    /// Query all running source devices for their capabilities and rebuild the
+    /// capabilities of the composite device from scratch.
+    async fn refresh_capabilities(&mut self) {
+        let mut source_capabilities = HashMap::new();
+        for (id, source) in self.source_devices.iter() {
+            // Blocked source devices do not provide any capabilities
+            if self.source_devices_blocked.contains(id) {
+                continue;
+            }
+            match source.get_capabilities().await {
+                Ok(caps) => {
+                    source_capabilities.insert(id.clone(), caps);
+                }
+                Err(e) => {
+                    log::error!("Failed to get capabilities of source device {id}: {e:?}");
+                    // Keep the last known capabilities of the device
+                    if let Some(caps) = self.source_devices_capabilities.get(id) {
+                        source_capabilities.insert(id.clone(), caps.clone());
+                    }
+                }
+            }
+        }
+
+        // Capabilities translated by the capability map are replaced by the
+        // target capabilities of the map.
+        let mut capabilities: HashSet<Capability> = source_capabilities
+            .values()
+            .flatten()
+            .filter(|cap| !self.translatable_capabilities.contains(cap))
+            .cloned()
+            .collect();
+        if let Some(map) = self.capability_map.as_ref() {
+            for mapping in map.mapping.iter() {
+                let cap = mapping.target_event.clone().into();
+                if cap == Capability::NotImplemented {
+                    continue;
+                }
+                capabilities.insert(cap);
+            }
+        }
+        log::debug!("Refreshed capabilities: {capabilities:?}");
+
+        self.source_devices_capabilities = source_capabilities;
+        self.capabilities = capabilities;
+        self.update_capability_sources();
+        self.signal_capabilities_changed().await;
+    }
+
+    /// Request up-to-date device information from the running source device
+    /// with the given id and update the stored device information.
+    async fn refresh_source_info(&mut self, device_id: String) -> Result<(), Box<dyn Error>> {
+        let Some(source) = self.source_devices.get(&device_id) else {
+            return Err(format!("No running source device found with id: {device_id}").into());
+        };
+        let info = source.get_info().await?;
+        log::debug!("Refreshed source device info for {device_id}: {info:?}");
+        self.source_devices_info.insert(device_id, info);
+
+        Ok(())
+    }
+
+    /// Reload the composite device config from the file it was loaded from.
+    /// Running source devices are blocked or unblocked according to the
+    /// updated config, and the input manager is notified so any new source
+    /// devices matching the updated config get added.
+    async fn reload_config(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = self.config.path.clone() else {
+            return Err("Composite device config was not loaded from a file".into());
+        };
+        log::info!("Reloading composite device config from: {path}");
+        let config = CompositeDeviceConfig::from_yaml_file(path)?;
+        self.output_routing = config.output_routing.clone().unwrap_or_default();
+        let thresholds = config
+            .source_health
+            .as_ref()
+            .map(HealthThresholds::from)
+            .unwrap_or_default();
+        self.source_health.set_thresholds(thresholds);
+        self.config = config;
+
+        // Update the running source devices with their updated source config.
+        // Event devices that were blocked when they were added are grabbed by
+        // a driver that never emits events, so they must be re-added to be
+        // unblocked.
+        for (id, info) in self.source_devices_info.iter() {
+            let source_config = self.config.get_matching_device(info);
+            let blocked = source_config
+                .as_ref()
+                .and_then(|config| config.blocked)
+                .unwrap_or(false);
+            if blocked {
+                if self.source_devices_blocked.insert(id.clone()) {
+                    log::info!("Blocking source device: {id}");
+                }
+            } else if self.source_devices_blocked.remove(id) {
+                log::info!("Unblocking source device: {id}");
+            }
+
+            match source_config.as_ref().and_then(|config| config.priority) {
+                Some(priority) => {
+                    self.source_devices_priority.insert(id.clone(), priority);
+                }
+                None => {
+                    self.source_devices_priority.remove(id);
+                }
+            }
+            match source_config
+                .as_ref()
+                .and_then(|config| config.filters.as_ref())
+            {
+                Some(filters) => {
+                    let filters = filter::filters_from_config(filters);
+                    self.source_devices_filters.insert(id.clone(), filters);
+                }
+                None => {
+                    self.source_devices_filters.remove(id);
+                }
+            }
+            match self.config.get_matching_device_index(info) {
+                Some(index) => {
+                    self.source_devices_config_index.insert(id.clone(), index);
+                }
+                None => {
+                    self.source_devices_config_index.remove(id);
+                }
+            }
+        }
+        self.refresh_capabilities().await;
+
+        // Let the input manager add any new source devices
+        if let Some(path) = self.dbus_path.clone() {
+            self.manager
+                .send(ManagerCommand::CompositeDeviceConfigChanged {
+                    path,
+                    config: self.config.clone(),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Creates and adds a source device using the given [SourceDeviceInfo]
     fn add_source_device(
         &mut self,
@@ -1350,12 +4406,31 @@ impl CompositeDevice {
                 if is_blocked {
                     is_blocked_evdev = true;
                 }
-                let device = EventDevice::new(device, self.client(), is_blocked)?;
+                // Get any defined config for the event device
+                let (config, touchscreen_config) =
+                    if let Some(device_config) = self.config.get_matching_device(&device) {
+                        (device_config.evdev, device_config.touchscreen)
+                    } else {
+                        (None, None)
+                    };
+                let device = EventDevice::new(
+                    device,
+                    self.client(),
+                    config,
+                    touchscreen_config,
+                    is_blocked,
+                )?;
                 SourceDevice::Event(device)
             }
             "hidraw" => {
                 log::debug!("Adding source device: {:?}", device.name());
+                let info = device.clone();
                 let device = HidRawDevice::new(device, self.client())?;
+                // Devices without a native driver use a capability map
+                // generated from their report descriptor if none is configured
+                if device.is_generic() && self.capability_map.is_none() {
+                    self.load_hid_descriptor_capability_map(&info);
+                }
                 SourceDevice::HidRaw(device)
             }
             "iio" => {
@@ -1370,6 +4445,11 @@ impl CompositeDevice {
                 let device = IioDevice::new(device, self.client(), config)?;
                 SourceDevice::Iio(device)
             }
+            "network" => {
+                log::debug!("Adding source device: {:?}", device.name());
+                let device = NetworkDevice::new(device, self.client())?;
+                SourceDevice::Network(device)
+            }
             _ => {
                 return Err(format!(
                     "Unspported subsystem: {subsystem}, unable to add source device {}",
@@ -1382,8 +4462,11 @@ impl CompositeDevice {
         // Get the capabilities of the source device.
         // TODO: When we *remove* a source device, we also need to remove
         // capabilities
+        let id = source_device.get_id();
         if !is_blocked {
             let capabilities = source_device.get_capabilities()?;
+            self.source_devices_capabilities
+                .insert(id.clone(), capabilities.clone());
             for cap in capabilities {
                 if self.translatable_capabilities.contains(&cap) {
                     continue;
@@ -1393,7 +4476,6 @@ impl CompositeDevice {
         }
 
         // Check if this device should be blocked from sending events to target devices.
-        let id = source_device.get_id();
         if let Some(device_config) = self
             .config
             .get_matching_device(source_device.get_device_ref())
@@ -1405,12 +4487,33 @@ impl CompositeDevice {
                     self.source_devices_blocked.insert(id.clone());
                 }
             }
+            if let Some(priority) = device_config.priority {
+                self.source_devices_priority.insert(id.clone(), priority);
+            }
+            if let Some(filters) = device_config.filters.as_ref() {
+                let filters = filter::filters_from_config(filters);
+                self.source_devices_filters.insert(id.clone(), filters);
+            }
         };
+        if let Some(index) = self
+            .config
+            .get_matching_device_index(source_device.get_device_ref())
+        {
+            self.source_devices_config_index.insert(id.clone(), index);
+        }
+        // Blocked source devices never send events to target devices, so they
+        // cannot be responsible for any capabilities.
+        if self.source_devices_blocked.contains(&id) {
+            self.source_devices_capabilities.remove(&id);
+        }
+        self.update_capability_sources();
 
         // TODO: Based on the capability map in the config, translate
         // the capabilities.
         // Keep track of the source device
         let device_path = source_device.get_device_path();
+        self.source_devices_info
+            .insert(id.clone(), source_device.get_device());
         self.source_devices_discovered.push(source_device);
         self.source_device_paths.push(device_path);
         self.source_devices_used.push(id);
@@ -1418,15 +4521,220 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Load the device profile from the given path. If the profile extends a
+    /// base profile, the base profile mappings are loaded first and the
+    /// profile's own mappings are applied on top of them.
+    fn load_device_profile_from_path(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        log::debug!("Loading profile from path: {path}");
+        let profile = Self::load_profile_chain(path.clone())?;
+
+        // Load the fallback profile relative to the directory of the profile
+        let fallback = match profile.fallback_profile.as_ref() {
+            Some(fallback) => {
+                let fallback = match Path::new(&path).parent() {
+                    Some(dir) => dir.join(fallback),
+                    None => PathBuf::from(fallback),
+                };
+                log::debug!("Loading fallback profile from path: {}", fallback.display());
+                Some(Self::load_profile_chain(
+                    fallback.to_string_lossy().to_string(),
+                )?)
+            }
+            None => None,
+        };
+
+        self.load_device_profile(profile)?;
+        self.device_profile_path = Some(path);
+        if let Some(fallback) = fallback {
+            self.load_fallback_profile(fallback);
+        }
+
+        Ok(())
+    }
+
+    /// Load the device profile the user assigned to this composite device in
+    /// the user config. The current profile is kept if no profile is assigned
+    /// or the assigned profile fails to load.
+    fn load_assigned_profile(&mut self) {
+        let path = get_profile_assignments_path();
+        if !path.exists() {
+            return;
+        }
+        let assignments = match ProfileAssignments::from_yaml_file(&path) {
+            Ok(assignments) => assignments,
+            Err(e) => {
+                log::warn!("Failed to load profile assignments from {path:?}: {e}");
+                return;
+            }
+        };
+        let Some(profile_path) = assignments.0.get(&self.config.name) else {
+            return;
+        };
+        log::info!(
+            "Loading assigned profile for {}: {profile_path}",
+            self.config.name
+        );
+        if let Err(e) = self.load_device_profile_from_path(profile_path.clone()) {
+            log::warn!("Failed to load assigned profile {profile_path}: {e}");
+        }
+    }
+
+    /// Save the path of the currently loaded profile as the assigned profile
+    /// of this composite device in the user config.
+    fn save_profile_assignment(&self) -> Result<(), Box<dyn Error>> {
+        let Some(profile_path) = self.device_profile_path.clone() else {
+            return Err("Current profile was not loaded from a file".into());
+        };
+        let path = get_profile_assignments_path();
+        let mut assignments = if path.exists() {
+            ProfileAssignments::from_yaml_file(&path)?
+        } else {
+            ProfileAssignments::default()
+        };
+        assignments.0.insert(self.config.name.clone(), profile_path);
+        assignments.save_yaml_file(&path)?;
+        log::info!(
+            "Saved profile assignment for {} to {path:?}",
+            self.config.name
+        );
+        Ok(())
+    }
+
+    /// Load the device profile at the given path and resolve its chain of
+    /// base profiles.
+    fn load_profile_chain(path: String) -> Result<DeviceProfile, Box<dyn Error>> {
+        let mut profile = DeviceProfile::from_file(&path)?;
+
+        // Resolve the chain of base profiles
+        let mut loaded_paths = vec![PathBuf::from(&path)];
+        let mut profile_path = PathBuf::from(path);
+        let mut base_path = profile.base_profile.clone();
+        while let Some(base) = base_path {
+            let base = match profile_path.parent() {
+                Some(dir) => dir.join(base),
+                None => PathBuf::from(base),
+            };
+            if loaded_paths.contains(&base) {
+                return Err(format!("Profile inheritance loop at {}", base.display()).into());
+            }
+            if loaded_paths.len() > MAX_PROFILE_INHERITANCE_DEPTH {
+                return Err("Too many levels of profile inheritance".into());
+            }
+            log::debug!("Loading base profile from path: {}", base.display());
+            let base_profile = DeviceProfile::from_file(&base.to_string_lossy())?;
+            base_path = base_profile.base_profile.clone();
+            profile.extend(base_profile);
+            loaded_paths.push(base.clone());
+            profile_path = base;
+        }
+
+        Ok(profile)
+    }
+
+    /// Load the mappings of the given profile as the fallback for source
+    /// events that have no mapping in the current device profile. Shift
+    /// mappings of the fallback profile are ignored.
+    fn load_fallback_profile(&mut self, profile: DeviceProfile) {
+        log::debug!("Loading fallback profile {}", profile.name);
+        for mapping in profile.mapping.into_iter() {
+            if mapping.shift.unwrap_or_default() {
+                continue;
+            }
+            let source_event_cap: Capability = mapping.source_event.clone().into();
+            self.fallback_profile_config_map
+                .entry(source_event_cap)
+                .or_default()
+                .push(mapping);
+        }
+        self.fallback_profile = Some(profile.name);
+    }
+
+    /// Load the next or previous profile in the profile cycle list, wrapping
+    /// around at either end of the list.
+    fn cycle_profile(&mut self, forward: bool) -> Result<(), Box<dyn Error>> {
+        let len = self.profile_cycle_list.len();
+        if len == 0 {
+            return Err("No profile cycle list is set".into());
+        }
+        self.profile_cycle_index = if forward {
+            (self.profile_cycle_index + 1) % len
+        } else {
+            (self.profile_cycle_index + len - 1) % len
+        };
+        let path = self.profile_cycle_list[self.profile_cycle_index].clone();
+        log::info!("Cycling to profile {path}");
+        self.load_device_profile_from_path(path)
+    }
+
+    /// Track the buttons of the profile cycle combo and return true when the
+    /// last button of the combo is pressed. The combo will not trigger again
+    /// until one of its buttons is released.
+    fn is_profile_cycle_combo_pressed(&mut self, event: &NativeEvent) -> bool {
+        let cap = event.as_capability();
+        if !self.profile_cycle_combo.contains(&cap) {
+            return false;
+        }
+        if !event.pressed() {
+            self.profile_cycle_pressed.remove(&cap);
+            self.profile_cycle_triggered = false;
+            return false;
+        }
+        self.profile_cycle_pressed.insert(cap);
+
+        let is_complete = self
+            .profile_cycle_combo
+            .iter()
+            .all(|cap| self.profile_cycle_pressed.contains(cap));
+        if !is_complete || self.profile_cycle_triggered {
+            return false;
+        }
+        self.profile_cycle_triggered = true;
+        true
+    }
+
     /// Load the given device profile from the given path
     pub fn load_device_profile(&mut self, profile: DeviceProfile) -> Result<(), Box<dyn Error>> {
         log::debug!("Loading device profile {}", profile.name);
         // Remove all outdated capability mappings.
         log::debug!("Clearing old device profile mappings");
         self.device_profile_config_map.clear();
+        self.fallback_profile = None;
+        self.fallback_profile_config_map.clear();
+        self.shift_mappings.clear();
+        self.shift_active_sources.clear();
+        for (_, timer) in self.hold_timers.drain() {
+            timer.abort();
+        }
+        for (_, task) in self.scroll_tasks.drain() {
+            task.abort();
+        }
+        for (_, task) in self.active_combo_tasks.drain() {
+            task.abort();
+        }
+        for (_, (_, timer)) in self.tap_dance_timers.drain() {
+            timer.abort();
+        }
+        self.hold_active.clear();
+        self.hold_taps.clear();
+        self.tap_dance_active.clear();
 
         // Load and parse the device profile
         self.device_profile = Some(profile.name.clone());
+        self.modifier_caps = profile
+            .modifier_caps
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(Capability::from)
+            .collect();
+        let anti_ghosting = profile
+            .anti_ghosting
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(a, b)| (Capability::from(a), Capability::from(b)))
+            .collect();
+        self.anti_ghosting.set_axes(anti_ghosting);
 
         // Loop through every mapping in the profile, extract the source and target events,
         // and map them into our profile map.
@@ -1445,16 +4753,28 @@ impl CompositeDevice {
                 target_events_caps.push(cap);
             }
 
-            // Insert the translation config for this event
-            let config_map = self
-                .device_profile_config_map
-                .entry(source_event_cap)
-                .or_default();
+            // Insert the translation config for this event. Shift mappings are
+            // kept separately and only used while a modifier is held.
+            let config_map = if mapping.shift.unwrap_or_default() {
+                &mut self.shift_mappings
+            } else {
+                &mut self.device_profile_config_map
+            };
+            let config_map = config_map.entry(source_event_cap).or_default();
             config_map.push(mapping.clone());
         }
 
-        // Set the target devices to use if it is defined in the profile
-        if let Some(target_devices) = profile.target_devices {
+        // Set the target devices to use if it is defined in the profile. Any
+        // user can load a profile, so privileged target devices can only be
+        // set over DBus or in the composite device config.
+        if let Some(mut target_devices) = profile.target_devices {
+            target_devices.retain(|kind| {
+                let privileged = TargetDevice::is_privileged_kind(kind);
+                if privileged {
+                    log::warn!("Ignoring target device '{kind}' set in profile");
+                }
+                !privileged
+            });
             let tx = self.tx.clone();
             tokio::task::spawn(async move {
                 if let Err(e) = tx
@@ -1470,13 +4790,44 @@ impl CompositeDevice {
         Ok(())
     }
 
-    fn set_intercept_activation(
-        &mut self,
-        activation_caps: Vec<Capability>,
-        target_cap: Capability,
-    ) {
-        self.intercept_activation_caps = activation_caps;
-        self.intercept_mode_target_cap = target_cap;
+    fn set_intercept_activation(&mut self, chords: Vec<InterceptChord>) {
+        self.intercept_chords = chords;
+        self.intercept_active_inputs.clear();
+    }
+
+    /// Returns true if the given capability is used to activate any intercept chord
+    fn is_intercept_activation_cap(&self, cap: &Capability) -> bool {
+        self.intercept_chords
+            .iter()
+            .any(|chord| chord.activation_caps.contains(cap))
+    }
+
+    /// Returns the intercept chord that is activated by only the given capability
+    fn find_single_intercept_chord(&self, cap: &Capability) -> Option<InterceptChord> {
+        self.intercept_chords
+            .iter()
+            .find(|chord| chord.activation_caps.as_slice() == [cap.clone()])
+            .cloned()
+    }
+
+    /// Returns the intercept chord whose activation capabilities match the
+    /// currently held intercept inputs, if any. If `partial` is true, chords
+    /// that still need more inputs to be pressed are also matched.
+    fn find_intercept_chord(&self, partial: bool) -> Option<InterceptChord> {
+        let held = &self.intercept_active_inputs;
+        self.intercept_chords
+            .iter()
+            .find(|chord| {
+                let is_length_match = if partial {
+                    chord.activation_caps.len() >= held.len()
+                } else {
+                    chord.activation_caps.len() == held.len()
+                };
+                is_length_match
+                    && chord.activation_caps.first() == held.first()
+                    && held.iter().all(|cap| chord.activation_caps.contains(cap))
+            })
+            .cloned()
     }
 
     /// Adds or removes the given capability to the active inputs and returns true. If an up event is
@@ -1486,6 +4837,7 @@ impl CompositeDevice {
         if is_pressed && !active {
             log::debug!("New active capability: {cap:?}");
             self.active_inputs.push(cap.clone());
+            self.signal_active_inputs_changed();
         }
         // Ignore up events for actions we've already handled.
         if !is_pressed && !active {
@@ -1496,6 +4848,7 @@ impl CompositeDevice {
             log::debug!("Removed inactive capability: {cap:?}");
             let index = self.active_inputs.iter().position(|r| r == cap).unwrap();
             self.active_inputs.remove(index);
+            self.signal_active_inputs_changed();
         }
         true
     }
@@ -1506,7 +4859,14 @@ impl CompositeDevice {
         is_pressed: bool,
         intercept: bool,
     ) -> Result<bool, Box<dyn Error>> {
-        if self.intercept_activation_caps.len() == 1 {
+        // Chords with a single activation capability can activate immediately
+        // if no other chord needs to wait for more inputs with that capability.
+        let cap = event.as_capability();
+        let is_single = self.find_single_intercept_chord(&cap).is_some()
+            && !self.intercept_chords.iter().any(|chord| {
+                chord.activation_caps.len() > 1 && chord.activation_caps.contains(&cap)
+            });
+        if is_single {
             log::debug!("Checking single intercept event.");
             return self
                 .is_intercept_event_single(event, is_pressed, intercept)
@@ -1525,7 +4885,8 @@ impl CompositeDevice {
     ) -> Result<bool, Box<dyn Error>> {
         let cap = event.as_capability();
         // Check if we have met the criteria for InterceptMode:Always
-        if intercept && self.intercept_activation_caps.contains(&cap) && is_pressed {
+        let chord = self.find_single_intercept_chord(&cap);
+        if let (true, Some(chord), true) = (intercept, chord, is_pressed) {
             log::debug!("Found matching intercept event: {:?}", cap);
             log::debug!("It is a DOWN event!");
             // Stop here if this is a repeat event.
@@ -1537,14 +4898,13 @@ impl CompositeDevice {
             self.intercept_active_inputs.push(cap.clone());
             // Send the intercept target.
             log::debug!("Found activation chord!");
-            self.set_intercept_mode(InterceptMode::Always);
-            let target_event =
-                NativeEvent::new(self.intercept_mode_target_cap.clone(), event.get_value());
+            self.activate_intercept_chord(&chord).await;
+            let target_event = NativeEvent::new(chord.target_cap.clone(), event.get_value());
             log::trace!("Release event: {target_event:?}");
             self.write_chord_events(vec![target_event]).await?;
 
             return Ok(true);
-        } else if self.intercept_activation_caps.contains(&cap)
+        } else if self.is_intercept_activation_cap(&cap)
             && self.intercept_active_inputs.contains(&cap)
             && !is_pressed
         {
@@ -1563,6 +4923,7 @@ impl CompositeDevice {
                 log::trace!("Remove from active_inputs: {cap:?}");
                 let index = self.active_inputs.iter().position(|r| r == &cap).unwrap();
                 self.active_inputs.remove(index);
+                self.signal_active_inputs_changed();
             }
 
             let target_event = NativeEvent::new(cap.clone(), event.get_value());
@@ -1584,7 +4945,7 @@ impl CompositeDevice {
         let cap = event.as_capability();
         // Process the event depending on the intercept mode
         // Check if we have met the criteria for InterceptMode:Always
-        if intercept && self.intercept_activation_caps.contains(&cap) {
+        if intercept && self.is_intercept_activation_cap(&cap) {
             log::debug!("Found matching intercept event: {:?}", cap);
             if is_pressed && self.should_hold_intercept_input(&cap) {
                 // Stop here if this is a repeat event.
@@ -1594,38 +4955,64 @@ impl CompositeDevice {
                 };
                 // This is only a partial match, capture the event.
                 self.intercept_active_inputs.push(cap.clone());
-                if self.intercept_active_inputs.len() != self.intercept_activation_caps.len() {
-                    log::debug!("More events needed to activate intercept mode.");
+                let Some(chord) = self.find_intercept_chord(false) else {
+                    if self.find_intercept_chord(true).is_some() {
+                        log::debug!("More events needed to activate intercept mode.");
+                        return Ok(true);
+                    }
+
+                    // No chord can be activated with the held inputs, so send
+                    // all currently held events as a chord.
+                    log::debug!("Held events do not match any activation chord.");
+                    let chord: Vec<NativeEvent> = self
+                        .intercept_active_inputs
+                        .drain(..)
+                        .map(|c| NativeEvent::new(c, InputValue::Bool(true)))
+                        .collect();
+                    log::trace!("Release new chord: {chord:?}");
+                    self.write_chord_events(chord).await?;
                     return Ok(true);
-                }
+                };
 
                 // We must have a match, we are of the correct length and all capabilities matched.
                 log::debug!("Found activation chord!");
-                for c in self.intercept_activation_caps.clone() {
-                    if self.active_inputs.contains(&c) {
+                for c in chord.activation_caps.iter() {
+                    if self.active_inputs.contains(c) {
                         log::trace!("Removed inactive capability: {c:?}");
-                        let index = self.active_inputs.iter().position(|r| r == &c).unwrap();
+                        let index = self.active_inputs.iter().position(|r| r == c).unwrap();
                         self.active_inputs.remove(index);
+                        self.signal_active_inputs_changed();
                     }
                 }
                 self.intercept_active_inputs.clear();
 
-                self.set_intercept_mode(InterceptMode::Always);
+                self.activate_intercept_chord(&chord).await;
                 // Generate a new chord
-                let event = NativeEvent::new(
-                    self.intercept_mode_target_cap.clone(),
-                    InputValue::Bool(true),
-                );
-                let event2 = NativeEvent::new(
-                    self.intercept_mode_target_cap.clone(),
-                    InputValue::Bool(false),
-                );
+                let event = NativeEvent::new(chord.target_cap.clone(), InputValue::Bool(true));
+                let event2 = NativeEvent::new(chord.target_cap.clone(), InputValue::Bool(false));
                 let chord: Vec<NativeEvent> = vec![event, event2];
                 log::trace!("Release new chord: {chord:?}");
                 self.write_chord_events(chord).await?;
                 return Ok(true);
             } else if !is_pressed {
                 log::debug!("It is an UP event!");
+                // If only a capability that activates a chord on its own is
+                // held, activate that chord on release.
+                if self.intercept_active_inputs.as_slice() == [cap.clone()] {
+                    if let Some(chord) = self.find_single_intercept_chord(&cap) {
+                        log::debug!("Found activation chord!");
+                        self.intercept_active_inputs.clear();
+                        self.activate_intercept_chord(&chord).await;
+                        let event =
+                            NativeEvent::new(chord.target_cap.clone(), InputValue::Bool(true));
+                        let event2 = NativeEvent::new(chord.target_cap, InputValue::Bool(false));
+                        let chord: Vec<NativeEvent> = vec![event, event2];
+                        log::trace!("Release new chord: {chord:?}");
+                        self.write_chord_events(chord).await?;
+                        return Ok(true);
+                    }
+                }
+
                 // We only had a partial match and one of those events is released,
                 // release it
                 if self.intercept_active_inputs.contains(&cap) {
@@ -1719,9 +5106,8 @@ impl CompositeDevice {
         for (path, target) in targets_to_stop.clone().into_iter() {
             log::debug!("Stopping old target device: {path}");
             self.target_devices.remove(&path);
-            for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
-                target_devices.remove(&path);
-            }
+            self.target_devices_capabilities.remove(&path);
+            self.target_devices_overflow.remove(&path);
             if let Err(e) = target.stop().await {
                 log::error!("Failed to stop old target device: {e:?}");
             }
@@ -1733,50 +5119,111 @@ impl CompositeDevice {
 
         // Create new target devices using the input manager
         for kind in device_types_to_start {
-            log::debug!("Requesting to create device: {kind}");
-            let (sender, mut receiver) = mpsc::channel(1);
-            self.manager
-                .send(ManagerCommand::CreateTargetDevice { kind, sender })
+            self.create_target_device(kind, composite_path.clone())
                 .await?;
-            let Some(response) = receiver.recv().await else {
-                log::warn!("Channel closed waiting for response from input manager");
-                continue;
-            };
-            let target_path = match response {
-                Ok(path) => path,
-                Err(e) => {
-                    let err = format!("Failed to create target: {e:?}");
-                    log::error!("{err}");
-                    continue;
+        }
+        // Signal change in target devices to DBus
+        // TODO: Check this
+        //self.signal_targets_changed().await;
+
+        Ok(())
+    }
+
+    /// Create a single target device of the given type and attach it to this
+    /// composite device without affecting any other target devices.
+    async fn add_target_device(&mut self, kind: String) -> Result<(), Box<dyn Error>> {
+        log::info!("Adding target device: {kind}");
+        // Requeue the request if target device attachments are pending, like
+        // set_target_devices().
+        if !self.target_devices_queued.is_empty() {
+            log::debug!(
+                "Target devices already waiting for attachment. Re-queueing add target device."
+            );
+            let tx = self.tx.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = tx.send(CompositeCommand::AddTargetDevice(kind)).await {
+                    log::error!("Error sending composite device command! {e:?}");
                 }
-            };
+            });
+            return Ok(());
+        }
 
-            // Attach the target device
-            log::debug!("Requesting to attach target device {target_path} to {composite_path}");
-            let (sender, mut receiver) = mpsc::channel(1);
-            self.manager
-                .send(ManagerCommand::AttachTargetDevice {
-                    target_path: target_path.clone(),
-                    composite_path: composite_path.clone(),
-                    sender,
-                })
-                .await?;
-            let Some(response) = receiver.recv().await else {
-                log::warn!("Channel closed waiting for response from input manager");
-                continue;
-            };
-            if let Err(e) = response {
-                log::error!("Failed to attach target device: {e:?}");
+        if self.target_kind_running(&kind).await? {
+            log::debug!("Target device {kind} already running, nothing to do.");
+            return Ok(());
+        }
+
+        let Some(composite_path) = self.dbus_path.clone() else {
+            return Err("No composite device DBus path found".into());
+        };
+        self.create_target_device(kind, composite_path).await
+    }
+
+    /// Stop the target device with the given DBus path and remove it from this
+    /// composite device without affecting any other target devices.
+    async fn remove_target_device(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        log::info!("Removing target device: {path}");
+        let Some(target) = self.target_devices.remove(&path) else {
+            return Err(format!("No target device found with path: {path}").into());
+        };
+        self.target_devices_capabilities.remove(&path);
+        self.target_devices_overflow.remove(&path);
+        if let Err(e) = target.stop().await {
+            return Err(format!("Failed to stop target device {path}: {e:?}").into());
+        }
+
+        Ok(())
+    }
+
+    /// Request the input manager to create a target device of the given type
+    /// and attach it to the composite device at the given path. The target
+    /// device is queued until the attachment message from the input manager
+    /// arrives.
+    async fn create_target_device(
+        &mut self,
+        kind: String,
+        composite_path: String,
+    ) -> Result<(), Box<dyn Error>> {
+        log::debug!("Requesting to create device: {kind}");
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.manager
+            .send(ManagerCommand::CreateTargetDevice { kind, sender })
+            .await?;
+        let Some(response) = receiver.recv().await else {
+            log::warn!("Channel closed waiting for response from input manager");
+            return Ok(());
+        };
+        let target_path = match response {
+            Ok(path) => path,
+            Err(e) => {
+                let err = format!("Failed to create target: {e:?}");
+                log::error!("{err}");
+                return Ok(());
             }
+        };
 
-            // Enqueue the target device to wait for the attachment message from
-            // the input manager to prevent multiple calls to set_target_devices()
-            // from mangling attachment.
-            self.target_devices_queued.insert(target_path);
+        // Attach the target device
+        log::debug!("Requesting to attach target device {target_path} to {composite_path}");
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.manager
+            .send(ManagerCommand::AttachTargetDevice {
+                target_path: target_path.clone(),
+                composite_path,
+                sender,
+            })
+            .await?;
+        let Some(response) = receiver.recv().await else {
+            log::warn!("Channel closed waiting for response from input manager");
+            return Ok(());
+        };
+        if let Err(e) = response {
+            log::error!("Failed to attach target device: {e:?}");
         }
-        // Signal change in target devices to DBus
-        // TODO: Check this
-        //self.signal_targets_changed().await;
+
+        // Enqueue the target device to wait for the attachment message from
+        // the input manager to prevent multiple calls to set_target_devices()
+        // from mangling attachment.
+        self.target_devices_queued.insert(target_path);
 
         Ok(())
     }
@@ -1805,16 +5252,8 @@ impl CompositeDevice {
     // Get the capabilities of all target devices
     async fn get_target_capabilities(&self) -> Result<HashSet<Capability>, Box<dyn Error>> {
         let mut target_caps = HashSet::new();
-        for target in self.target_devices.values() {
-            let caps = match target.get_capabilities().await {
-                Ok(caps) => caps,
-                Err(e) => {
-                    return Err(format!("Failed to get target capabilities: {e:?}").into());
-                }
-            };
-            for cap in caps {
-                target_caps.insert(cap);
-            }
+        for caps in self.target_devices_capabilities.values() {
+            target_caps.extend(caps.iter().cloned());
         }
         for target in self.target_dbus_devices.values() {
             let caps = match target.get_capabilities().await {
@@ -1831,6 +5270,17 @@ impl CompositeDevice {
         Ok(target_caps)
     }
 
+    /// Register a channel with the given target device that it can use to send
+    /// input events back to the composite device.
+    async fn register_target_event_callback(&mut self, path: &str, target: &TargetDeviceClient) {
+        let (tx, rx) = mpsc::channel(TARGET_EVENT_CALLBACK_BUFFER_SIZE);
+        if let Err(e) = target.register_event_callback(tx).await {
+            log::error!("Failed to register event callback for target device {path}: {e:?}");
+            return;
+        }
+        self.target_event_callbacks.push(rx);
+    }
+
     /// Attach the given target devices to the composite device
     async fn attach_target_devices(
         &mut self,
@@ -1844,6 +5294,7 @@ impl CompositeDevice {
                     format!("Failed to set composite device for target device: {:?}", e).into(),
                 );
             }
+            self.register_target_event_callback(&path, &target).await;
             log::debug!(
                 "Attached device {path} to {:?}",
                 self.dbus_path.as_ref().unwrap_or(&"".to_string())
@@ -1861,26 +5312,58 @@ impl CompositeDevice {
             self.target_devices_queued.remove(&path);
             self.target_devices.insert(path.clone(), target);
 
-            // Track the target device by capabilities it has
-            for cap in caps {
-                self.target_devices_by_capability
-                    .entry(cap)
-                    .and_modify(|devices| {
-                        devices.insert(path.clone());
-                    })
-                    .or_insert_with(|| {
-                        let mut devices = HashSet::new();
-                        devices.insert(path.clone());
-                        devices
-                    });
-            }
+            // Cache the capabilities of the target device
+            self.target_devices_capabilities
+                .insert(path.clone(), caps.into_iter().collect());
         }
         // TODO: check this
         //self.signal_targets_changed().await;
 
+        // Restore any force feedback effects that were uploaded through
+        // previous target devices.
+        self.restore_ff_effects().await;
+
         Ok(())
     }
 
+    /// Re-upload all cached force feedback effects to the source devices that
+    /// are missing them. Effect ids are allocated by the composite device, so
+    /// effects uploaded through target devices that were stopped keep their
+    /// ids and can continue to be played.
+    async fn restore_ff_effects(&mut self) {
+        let routed_sources: Vec<String> = self
+            .source_devices
+            .keys()
+            .filter(|id| self.is_output_routed_to(OutputEventKind::ForceFeedback, id))
+            .cloned()
+            .collect();
+        for (id, data) in self.ff_effects_to_restore.iter() {
+            let source_effect_ids = self.ff_effect_id_source_map.entry(*id).or_default();
+            for (source_id, source) in self.source_devices.iter() {
+                if source_effect_ids.contains_key(source_id) {
+                    continue;
+                }
+                if !routed_sources.contains(source_id) {
+                    continue;
+                }
+                log::debug!("Restoring effect {id} to {source_id}");
+                match source.upload_effect(*data).await {
+                    Ok(source_effect_id) => {
+                        // An effect ID of -1 indicates the device does not support
+                        // FF events.
+                        if source_effect_id == -1 {
+                            continue;
+                        }
+                        source_effect_ids.insert(source_id.clone(), source_effect_id);
+                    }
+                    Err(e) => {
+                        log::error!("Error restoring effect '{id}' to {source_id}: {e:?}");
+                    }
+                }
+            }
+        }
+    }
+
     /// Emit a DBus signal when target devices change
     async fn signal_targets_changed(&self) {
         let Some(dbus_path) = self.dbus_path.clone() else {
@@ -1917,6 +5400,189 @@ impl CompositeDevice {
     }
 
     /// Emit a DBus signal when source devices change
+    /// Emit a DBus signal when the intercept mode or intercept mode name changes
+    async fn signal_intercept_mode_changed(&self) {
+        let Some(dbus_path) = self.dbus_path.clone() else {
+            log::error!("No DBus path for composite device exists to emit signal!");
+            return;
+        };
+        let conn = self.conn.clone();
+        let mode = format!("{:?}", self.intercept_mode);
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the intercept mode changed signals
+            if let Err(e) = CompositeDeviceInterface::intercept_mode_changed_signal(
+                iface_ref.signal_context(),
+                mode,
+            )
+            .await
+            {
+                log::error!("Failed to send InterceptModeChanged signal: {e:?}");
+            }
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .intercept_mode_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send intercept mode changed signal: {e:?}");
+            }
+            if let Err(e) = iface
+                .intercept_mode_name_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send intercept mode name changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when the capabilities of the composite device change
+    async fn signal_capabilities_changed(&self) {
+        let Some(dbus_path) = self.dbus_path.clone() else {
+            log::error!("No DBus path for composite device exists to emit signal!");
+            return;
+        };
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the capabilities changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface.capabilities_changed(iface_ref.signal_context()).await {
+                log::error!("Failed to send capabilities changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when the currently pressed inputs change
+    fn signal_active_inputs_changed(&self) {
+        let Some(dbus_path) = self.dbus_path.clone() else {
+            return;
+        };
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the active inputs changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .active_inputs_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send active inputs changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Start the task that periodically checks the health of the source
+    /// devices
+    fn start_source_health_task(&mut self) {
+        let tx = self.tx.clone();
+        let task = tokio::task::spawn(async move {
+            let mut interval = tokio::time::interval(SOURCE_HEALTH_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if tx.send(CompositeCommand::CheckSourceHealth).await.is_err() {
+                    return;
+                }
+            }
+        });
+        self.source_health_task = Some(task.abort_handle());
+    }
+
+    /// Emit a DBus signal with the health metrics of the given source device
+    fn signal_source_health_changed(&self, device_id: &str) {
+        let Some(dbus_path) = self.dbus_path.clone() else {
+            log::error!("No DBus path for composite device exists to emit signal!");
+            return;
+        };
+        let Some(health) = self.source_health.get(device_id) else {
+            return;
+        };
+        let conn = self.conn.clone();
+        let device_id = device_id.to_string();
+        let status = health.status.to_string();
+        let avg_latency_ms = health.avg_latency_ms as f64;
+        let event_gap_alerts = health.event_gap_alerts;
+        let error_rate = health.error_rate() as f64;
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            if let Err(e) = CompositeDeviceInterface::source_device_health_changed_signal(
+                iface_ref.signal_context(),
+                device_id,
+                status,
+                avg_latency_ms,
+                event_gap_alerts,
+                error_rate,
+            )
+            .await
+            {
+                log::error!("Failed to send SourceDeviceHealthChanged signal: {e:?}");
+            }
+        });
+    }
+
     async fn signal_sources_changed(&self) {
         let Some(dbus_path) = self.dbus_path.clone() else {
             log::error!("No DBus path for composite device exists to emit signal!");
@@ -1952,3 +5618,25 @@ impl CompositeDevice {
         });
     }
 }
+
+/// Receive the next input event sent back by any of the given target device
+/// channels. Closed channels are removed. If there are no channels, this never
+/// completes.
+async fn recv_target_event(
+    receivers: &mut Vec<mpsc::Receiver<NativeEvent>>,
+) -> Option<NativeEvent> {
+    poll_fn(|cx| {
+        let mut i = 0;
+        while i < receivers.len() {
+            match receivers[i].poll_recv(cx) {
+                Poll::Ready(Some(event)) => return Poll::Ready(Some(event)),
+                Poll::Ready(None) => {
+                    receivers.remove(i);
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
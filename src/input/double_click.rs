@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use super::{
+    capability::{Capability, Mouse, MouseButton},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Default amount of time in milliseconds in which both clicks of a double
+/// click must happen
+pub const DEFAULT_DOUBLE_CLICK_WINDOW_MS: u64 = 300;
+
+/// Click state of a single mouse button. Each state that waits for the
+/// double click window holds the time of the first press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickState {
+    /// The button was pressed once and is still held
+    Pressed(Instant),
+    /// The button was clicked once and may be pressed again
+    Released(Instant),
+    /// The button was pressed a second time and is still held
+    SecondPress(Instant),
+    /// The button was held longer than the double click window and its press
+    /// was already emitted
+    Held,
+}
+
+/// Detects double clicks of mouse buttons. Events of the buttons being
+/// tracked are held back until it is known whether they are part of a double
+/// click. If they are, a [Mouse::DoubleClick] press and release is emitted
+/// instead of both single clicks.
+#[derive(Debug)]
+pub struct DoubleClickDetector {
+    window: Duration,
+    clicks: HashMap<MouseButton, ClickState>,
+}
+
+impl Default for DoubleClickDetector {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(DEFAULT_DOUBLE_CLICK_WINDOW_MS),
+            clicks: HashMap::new(),
+        }
+    }
+}
+
+impl DoubleClickDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of time in which both clicks of a double click must
+    /// happen
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Set the amount of time in milliseconds in which both clicks of a
+    /// double click must happen
+    pub fn set_window(&mut self, window_ms: u64) {
+        self.window = Duration::from_millis(window_ms);
+    }
+
+    /// Returns true if clicks of any button are being held back
+    pub fn is_pending(&self) -> bool {
+        self.clicks
+            .values()
+            .any(|state| !matches!(state, ClickState::Held))
+    }
+
+    /// Update the click state of the given button and return the events that
+    /// should be emitted instead of the button event
+    pub fn update(
+        &mut self,
+        button: &MouseButton,
+        pressed: bool,
+        now: Instant,
+    ) -> Vec<NativeEvent> {
+        let state = self.clicks.get(button).copied();
+        let within_window = |since: Instant| now.duration_since(since) < self.window;
+
+        if pressed {
+            return match state {
+                None => {
+                    self.clicks.insert(button.clone(), ClickState::Pressed(now));
+                    vec![]
+                }
+                Some(ClickState::Released(since)) if within_window(since) => {
+                    self.clicks
+                        .insert(button.clone(), ClickState::SecondPress(since));
+                    vec![]
+                }
+                // The window passed before it was expired
+                Some(ClickState::Released(_)) => {
+                    self.clicks.insert(button.clone(), ClickState::Pressed(now));
+                    click(button)
+                }
+                // Ignore repeated presses
+                Some(_) => vec![],
+            };
+        }
+
+        match state {
+            Some(ClickState::Pressed(since)) if within_window(since) => {
+                self.clicks
+                    .insert(button.clone(), ClickState::Released(since));
+                vec![]
+            }
+            Some(ClickState::Pressed(_)) => {
+                self.clicks.remove(button);
+                click(button)
+            }
+            Some(ClickState::SecondPress(since)) => {
+                self.clicks.remove(button);
+                if within_window(since) {
+                    log::trace!("Detected double click of {button}");
+                    let cap = Capability::Mouse(Mouse::DoubleClick(button.clone()));
+                    return vec![
+                        NativeEvent::new(cap.clone(), InputValue::Bool(true)),
+                        NativeEvent::new(cap, InputValue::Bool(false)),
+                    ];
+                }
+                let mut events = click(button);
+                events.extend(click(button));
+                events
+            }
+            Some(ClickState::Held) => {
+                self.clicks.remove(button);
+                vec![button_event(button, false)]
+            }
+            // Releases of buttons that were pressed before tracking started
+            // are passed on
+            Some(ClickState::Released(_)) | None => vec![button_event(button, false)],
+        }
+    }
+
+    /// Emit the held back events of all buttons whose double click window
+    /// has passed at the given time
+    pub fn expire(&mut self, now: Instant) -> Vec<NativeEvent> {
+        let mut events = vec![];
+        let window = self.window;
+        self.clicks.retain(|button, state| {
+            let since = match state {
+                ClickState::Pressed(since)
+                | ClickState::Released(since)
+                | ClickState::SecondPress(since) => *since,
+                ClickState::Held => return true,
+            };
+            if now.duration_since(since) < window {
+                return true;
+            }
+            match state {
+                ClickState::Pressed(_) => {
+                    events.push(button_event(button, true));
+                    *state = ClickState::Held;
+                    true
+                }
+                ClickState::Released(_) => {
+                    events.extend(click(button));
+                    false
+                }
+                ClickState::SecondPress(_) => {
+                    events.extend(click(button));
+                    events.push(button_event(button, true));
+                    *state = ClickState::Held;
+                    true
+                }
+                ClickState::Held => true,
+            }
+        });
+        events
+    }
+}
+
+/// Returns a press or release event of the given mouse button
+fn button_event(button: &MouseButton, pressed: bool) -> NativeEvent {
+    let cap = Capability::Mouse(Mouse::Button(button.clone()));
+    NativeEvent::new(cap, InputValue::Bool(pressed))
+}
+
+/// Returns the press and release events of a single click of the given mouse
+/// button
+fn click(button: &MouseButton) -> Vec<NativeEvent> {
+    vec![button_event(button, true), button_event(button, false)]
+}
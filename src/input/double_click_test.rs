@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Mouse, MouseButton},
+    double_click::DoubleClickDetector,
+    event::native::NativeEvent,
+};
+
+/// Returns the capability and pressed state of each of the given events
+fn summarize(events: Vec<NativeEvent>) -> Vec<(Capability, bool)> {
+    events
+        .iter()
+        .map(|event| (event.as_capability(), event.pressed()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_double_click_detected() {
+    let mut detector = DoubleClickDetector::new();
+    let button = MouseButton::Left;
+    let start = Instant::now();
+
+    assert!(detector.update(&button, true, start).is_empty());
+    let at = start + Duration::from_millis(50);
+    assert!(detector.update(&button, false, at).is_empty());
+    let at = start + Duration::from_millis(100);
+    assert!(detector.update(&button, true, at).is_empty());
+    let at = start + Duration::from_millis(150);
+    let events = summarize(detector.update(&button, false, at));
+
+    let cap = Capability::Mouse(Mouse::DoubleClick(MouseButton::Left));
+    assert_eq!(events, vec![(cap.clone(), true), (cap, false)]);
+    assert!(!detector.is_pending());
+}
+
+#[tokio::test]
+async fn test_double_click_single_click_expires() {
+    let mut detector = DoubleClickDetector::new();
+    detector.set_window(200);
+    let button = MouseButton::Right;
+    let start = Instant::now();
+
+    detector.update(&button, true, start);
+    detector.update(&button, false, start + Duration::from_millis(50));
+    assert!(detector.is_pending());
+    assert!(detector
+        .expire(start + Duration::from_millis(100))
+        .is_empty());
+
+    let events = summarize(detector.expire(start + Duration::from_millis(200)));
+    let cap = Capability::Mouse(Mouse::Button(MouseButton::Right));
+    assert_eq!(events, vec![(cap.clone(), true), (cap, false)]);
+    assert!(!detector.is_pending());
+}
+
+#[tokio::test]
+async fn test_double_click_held_button() {
+    let mut detector = DoubleClickDetector::new();
+    let button = MouseButton::Left;
+    let start = Instant::now();
+    let cap = Capability::Mouse(Mouse::Button(MouseButton::Left));
+
+    // Holding the button past the window emits the press so it can be
+    // used for dragging
+    detector.update(&button, true, start);
+    let events = summarize(detector.expire(start + Duration::from_millis(300)));
+    assert_eq!(events, vec![(cap.clone(), true)]);
+
+    let events = summarize(detector.update(&button, false, start + Duration::from_secs(1)));
+    assert_eq!(events, vec![(cap, false)]);
+}
+
+#[tokio::test]
+async fn test_double_click_second_click_too_late() {
+    let mut detector = DoubleClickDetector::new();
+    let button = MouseButton::Middle;
+    let start = Instant::now();
+    let cap = Capability::Mouse(Mouse::Button(MouseButton::Middle));
+
+    detector.update(&button, true, start);
+    detector.update(&button, false, start + Duration::from_millis(50));
+    detector.update(&button, true, start + Duration::from_millis(250));
+    let events = summarize(detector.update(&button, false, start + Duration::from_millis(350)));
+    assert_eq!(
+        events,
+        vec![
+            (cap.clone(), true),
+            (cap.clone(), false),
+            (cap.clone(), true),
+            (cap, false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_double_click_capability_string() {
+    let cap = Capability::Mouse(Mouse::DoubleClick(MouseButton::Left));
+    assert_eq!(cap.to_capability_string(), "Mouse:DoubleClick:Left");
+    let parsed: Capability = "Mouse:DoubleClick:Left".parse().unwrap();
+    assert_eq!(parsed, cap);
+}
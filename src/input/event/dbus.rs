@@ -402,7 +402,10 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
         Capability::Touchscreen(touch) => match touch {
             Touch::Motion => vec![Action::Touch],
             Touch::Button(_) => vec![Action::None],
+            Touch::Velocity => vec![Action::None],
         },
+        Capability::KeyboardKeysym(_) => vec![Action::None],
+        Capability::Gesture(_) => vec![Action::None],
     }
 }
 
@@ -511,17 +511,31 @@ impl EvdevEvent {
                 None
             };
 
-            // Get the axis direction if this this an ABS event and we need to
-            // translate binary input into axis input. (e.g. DPad buttons)
-            let axis_direction = if event_type == EventType::ABSOLUTE {
-                Some(axis_direction_from_capability(event.as_capability()))
-            } else {
-                None
-            };
+            // Get the axis direction if this this an ABS or REL event and we
+            // need to translate binary input into axis input. (e.g. DPad or
+            // scroll wheel buttons)
+            let axis_direction =
+                if event_type == EventType::ABSOLUTE || event_type == EventType::RELATIVE {
+                    Some(axis_direction_from_capability(event.as_capability()))
+                } else {
+                    None
+                };
 
             // Get the input value from the event and convert it into an evdev
             // input event.
             let value = event.get_value();
+
+            // Absolute mouse positions are normalized between 0.0 and 1.0, so
+            // convert them into the signed range used for absolute axes.
+            let value = match (event.as_capability(), value) {
+                (Capability::Mouse(Mouse::AbsoluteMotion), InputValue::Vector2 { x, y }) => {
+                    InputValue::Vector2 {
+                        x: x.map(|x| x * 2.0 - 1.0),
+                        y: y.map(|y| y * 2.0 - 1.0),
+                    }
+                }
+                (_, value) => value,
+            };
             let event = input_event_from_value(event_type, code, axis_info, axis_direction, value);
             if event.is_none() {
                 continue;
@@ -541,7 +555,15 @@ fn event_type_from_capability(capability: Capability) -> Option<EventType> {
         Capability::Keyboard(_) => Some(EventType::KEY),
         Capability::Mouse(mouse) => match mouse {
             Mouse::Motion => Some(EventType::RELATIVE),
-            Mouse::Button(_) => Some(EventType::KEY),
+            Mouse::AbsoluteMotion => Some(EventType::ABSOLUTE),
+            Mouse::Button(button) => match button {
+                MouseButton::WheelUp
+                | MouseButton::WheelDown
+                | MouseButton::WheelLeft
+                | MouseButton::WheelRight => Some(EventType::RELATIVE),
+                _ => Some(EventType::KEY),
+            },
+            Mouse::DoubleClick(_) => None,
         },
         Capability::Gamepad(gamepad) => match gamepad {
             Gamepad::Button(button) => match button {
@@ -577,6 +599,13 @@ fn axis_direction_from_capability(capability: Capability) -> AxisDirection {
                 AxisDirection::None
             }
         }
+        Capability::Mouse(Mouse::Button(button)) => match button {
+            MouseButton::WheelUp => AxisDirection::Positive,
+            MouseButton::WheelDown => AxisDirection::Negative,
+            MouseButton::WheelLeft => AxisDirection::Negative,
+            MouseButton::WheelRight => AxisDirection::Positive,
+            _ => AxisDirection::None,
+        },
         _ => AxisDirection::None,
     }
 }
@@ -661,17 +690,19 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
         },
         Capability::Mouse(mouse) => match mouse {
             Mouse::Motion => vec![RelativeAxisCode::REL_X.0, RelativeAxisCode::REL_Y.0],
+            Mouse::AbsoluteMotion => vec![AbsoluteAxisCode::ABS_X.0, AbsoluteAxisCode::ABS_Y.0],
             Mouse::Button(button) => match button {
                 MouseButton::Left => vec![KeyCode::BTN_LEFT.0],
                 MouseButton::Right => vec![KeyCode::BTN_RIGHT.0],
                 MouseButton::Middle => vec![KeyCode::BTN_MIDDLE.0],
-                MouseButton::WheelUp => vec![],
-                MouseButton::WheelDown => vec![],
-                MouseButton::WheelLeft => vec![],
-                MouseButton::WheelRight => vec![],
+                MouseButton::WheelUp => vec![RelativeAxisCode::REL_WHEEL.0],
+                MouseButton::WheelDown => vec![RelativeAxisCode::REL_WHEEL.0],
+                MouseButton::WheelLeft => vec![RelativeAxisCode::REL_HWHEEL.0],
+                MouseButton::WheelRight => vec![RelativeAxisCode::REL_HWHEEL.0],
                 MouseButton::Extra => vec![KeyCode::BTN_EXTRA.0],
                 MouseButton::Side => vec![KeyCode::BTN_SIDE.0],
             },
+            Mouse::DoubleClick(_) => vec![],
         },
         Capability::Keyboard(key) => match key {
             Keyboard::Key0 => vec![KeyCode::KEY_0.0],
@@ -847,6 +878,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                     TouchButton::Touch => vec![KeyCode::BTN_TOUCH.0],
                     TouchButton::Press => vec![KeyCode::BTN_LEFT.0],
                 },
+                Touch::Velocity => vec![],
             },
             Touchpad::RightPad(action) => match action {
                 Touch::Motion => vec![
@@ -857,6 +889,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                     TouchButton::Touch => vec![KeyCode::BTN_TOUCH.0],
                     TouchButton::Press => vec![KeyCode::BTN_LEFT.0],
                 },
+                Touch::Velocity => vec![],
             },
             Touchpad::CenterPad(action) => match action {
                 Touch::Motion => vec![
@@ -867,6 +900,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                     TouchButton::Touch => vec![KeyCode::BTN_TOUCH.0],
                     TouchButton::Press => vec![KeyCode::BTN_LEFT.0],
                 },
+                Touch::Velocity => vec![],
             },
         },
         Capability::Touchscreen(touch) => match touch {
@@ -878,7 +912,10 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 TouchButton::Touch => vec![KeyCode::BTN_TOUCH.0],
                 TouchButton::Press => vec![KeyCode::BTN_LEFT.0],
             },
+            Touch::Velocity => vec![],
         },
+        Capability::KeyboardKeysym(_) => vec![],
+        Capability::Gesture(_) => vec![],
     }
 }
 
@@ -900,7 +937,16 @@ fn input_event_from_value(
             // the minimum and maximum values for that axis depending on the
             // axis direction. This is typically done for DPad button input that
             // needs to be translated to an ABS_HAT axis input.
-            if axis_info.is_some() && axis_direction.is_some() {
+            if event_type == EventType::RELATIVE {
+                // Relative button input (e.g. scroll wheel buttons) moves the
+                // axis by one step in the axis direction when pressed.
+                match axis_direction {
+                    _ if value == 0 => None,
+                    Some(AxisDirection::Positive) => Some(1),
+                    Some(AxisDirection::Negative) => Some(-1),
+                    _ => None,
+                }
+            } else if axis_info.is_some() && axis_direction.is_some() {
                 let info = axis_info.unwrap();
                 let direction = axis_direction.unwrap();
                 match direction {
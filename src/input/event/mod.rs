@@ -1,7 +1,14 @@
 pub mod dbus;
 pub mod evdev;
 pub mod native;
+#[cfg(test)]
+mod native_test;
+pub mod network;
+#[cfg(test)]
+mod network_test;
 pub mod value;
+#[cfg(test)]
+mod value_test;
 
 /// Events are events that flow from source devices to target devices
 #[derive(Debug, Clone)]
@@ -1,11 +1,32 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use evdev::AbsoluteAxisCode;
+use thiserror::Error;
 
-use crate::input::capability::{Capability, Gamepad, GamepadButton};
+use crate::input::capability::{Capability, Gamepad, GamepadButton, Mouse, Touch, Touchpad};
 
 use super::{evdev::EvdevEvent, value::InputValue};
 
+/// Possible reasons a [NativeEvent] is malformed
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("value {value:?} is not a valid value type for {capability:?}")]
+    InvalidValueType {
+        capability: Capability,
+        value: InputValue,
+    },
+    #[error("value {value:?} for {capability:?} is not finite")]
+    NotFinite {
+        capability: Capability,
+        value: InputValue,
+    },
+    #[error("value {value:?} for {capability:?} is out of range")]
+    OutOfRange {
+        capability: Capability,
+        value: InputValue,
+    },
+}
+
 /// A native event represents an InputPlumber event
 #[derive(Debug, Clone)]
 pub struct NativeEvent {
@@ -19,6 +40,9 @@ pub struct NativeEvent {
     source_capability: Option<Capability>,
     /// The value of the input event.
     value: InputValue,
+    /// Optional hardware timestamp of when the input event occurred on the
+    /// source device.
+    timestamp: Option<SystemTime>,
 }
 
 impl NativeEvent {
@@ -28,6 +52,7 @@ impl NativeEvent {
             capability,
             value,
             source_capability: None,
+            timestamp: None,
         }
     }
 
@@ -42,6 +67,7 @@ impl NativeEvent {
             capability,
             source_capability: Some(source_capability),
             value,
+            timestamp: None,
         }
     }
 
@@ -55,6 +81,11 @@ impl NativeEvent {
         self.value.clone()
     }
 
+    /// Set the value of this event
+    pub fn set_value(&mut self, value: InputValue) {
+        self.value = value;
+    }
+
     /// Returns true if this event is a translated event and has a source
     /// capability defined.
     pub fn is_translated(&self) -> bool {
@@ -71,16 +102,112 @@ impl NativeEvent {
         self.source_capability.clone()
     }
 
+    /// Returns the hardware timestamp of the event, if it is known
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Set the hardware timestamp of the event
+    pub fn set_timestamp(&mut self, timestamp: Option<SystemTime>) {
+        self.timestamp = timestamp;
+    }
+
     /// Returns whether or not the event is "pressed"
     pub fn pressed(&self) -> bool {
         self.value.pressed()
     }
 
+    /// Check that the value of the event is a valid value for its capability.
+    /// Buttons must have boolean values, axis and trigger values must be
+    /// between -1.0 and 1.0, absolute mouse positions must be between 0.0 and
+    /// 1.0, and all other values must be finite.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let touch = match &self.capability {
+            Capability::Touchpad(
+                Touchpad::LeftPad(touch) | Touchpad::RightPad(touch) | Touchpad::CenterPad(touch),
+            ) => Some(touch),
+            Capability::Touchscreen(touch) => Some(touch),
+            _ => None,
+        };
+
+        // Collect the numeric components of the value along with the range
+        // they are limited to, if any.
+        let (components, range) = match (&self.capability, &self.value, touch) {
+            (
+                Capability::None
+                | Capability::NotImplemented
+                | Capability::Sync
+                | Capability::DBus(_),
+                _,
+                _,
+            ) => return Ok(()),
+            (
+                Capability::Gamepad(Gamepad::Button(_))
+                | Capability::Mouse(Mouse::Button(_))
+                | Capability::Keyboard(_)
+                | Capability::KeyboardKeysym(_)
+                | Capability::Gesture(_),
+                InputValue::Bool(_),
+                _,
+            ) => return Ok(()),
+            (_, InputValue::Bool(_), Some(Touch::Button(_))) => return Ok(()),
+            // Hat axes from evdev devices use boolean values
+            (Capability::Gamepad(Gamepad::Axis(_)), InputValue::Bool(_), _) => return Ok(()),
+            (Capability::Gamepad(Gamepad::Axis(_)), InputValue::Vector2 { x, y }, _) => {
+                (vec![*x, *y], Some(-1.0..=1.0))
+            }
+            (Capability::Gamepad(Gamepad::Trigger(_)), InputValue::Float(value), _) => {
+                (vec![Some(*value)], Some(-1.0..=1.0))
+            }
+            (
+                Capability::Gamepad(Gamepad::Accelerometer | Gamepad::Gyro),
+                InputValue::Vector3 { x, y, z },
+                _,
+            ) => (vec![*x, *y, *z], None),
+            (Capability::Mouse(Mouse::Motion), InputValue::Vector2 { x, y }, _) => {
+                (vec![*x, *y], None)
+            }
+            (Capability::Mouse(Mouse::AbsoluteMotion), InputValue::Vector2 { x, y }, _) => {
+                (vec![*x, *y], Some(0.0..=1.0))
+            }
+            (_, InputValue::Touch { pressure, x, y, .. }, Some(Touch::Motion)) => {
+                (vec![*pressure, *x, *y], None)
+            }
+            (_, InputValue::Vector2 { x, y }, Some(Touch::Velocity)) => (vec![*x, *y], None),
+            _ => {
+                return Err(ValidationError::InvalidValueType {
+                    capability: self.capability.clone(),
+                    value: self.value.clone(),
+                })
+            }
+        };
+
+        for value in components.into_iter().flatten() {
+            if !value.is_finite() {
+                return Err(ValidationError::NotFinite {
+                    capability: self.capability.clone(),
+                    value: self.value.clone(),
+                });
+            }
+            if range.as_ref().is_some_and(|range| !range.contains(&value)) {
+                return Err(ValidationError::OutOfRange {
+                    capability: self.capability.clone(),
+                    value: self.value.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn from_evdev_raw(event: EvdevEvent, hat_state: Option<i32>) -> NativeEvent {
         // If this is a Dpad input, figure out with button this event is for
         let capability = if let Some(old_state) = hat_state {
             let axis = AbsoluteAxisCode(event.as_input_event().code());
-            let value = event.as_input_event().value();
+            // Only the direction of the hat matters, since some devices
+            // report hat values larger than one.
+            let value = event.as_input_event().value().signum();
+            let old_state = old_state.signum();
 
             match axis {
                 AbsoluteAxisCode::ABS_HAT0X => match value {
@@ -111,11 +238,13 @@ impl NativeEvent {
         };
 
         let value = event.get_value();
+        let timestamp = evdev_timestamp(&event);
 
         NativeEvent {
             capability,
             value,
             source_capability: None,
+            timestamp,
         }
     }
 }
@@ -125,14 +254,26 @@ impl From<EvdevEvent> for NativeEvent {
     fn from(item: EvdevEvent) -> Self {
         let capability = item.as_capability();
         let value = item.get_value();
+        let timestamp = evdev_timestamp(&item);
         NativeEvent {
             capability,
             value,
             source_capability: None,
+            timestamp,
         }
     }
 }
 
+/// Returns the timestamp of the given evdev event. Events that were not read
+/// from a device have no timestamp.
+fn evdev_timestamp(event: &EvdevEvent) -> Option<SystemTime> {
+    let timestamp = event.as_input_event().timestamp();
+    if timestamp == UNIX_EPOCH {
+        return None;
+    }
+    Some(timestamp)
+}
+
 impl From<ScheduledNativeEvent> for NativeEvent {
     fn from(value: ScheduledNativeEvent) -> Self {
         value.event
@@ -0,0 +1,115 @@
+use evdev::{AbsoluteAxisCode, EventType, InputEvent};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Mouse},
+    event::{
+        evdev::EvdevEvent,
+        native::{NativeEvent, ValidationError},
+        value::InputValue,
+    },
+};
+
+#[tokio::test]
+async fn test_validate() {
+    let button = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let trigger = Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger));
+    let motion = Capability::Mouse(Mouse::Motion);
+
+    // Valid events
+    let events = vec![
+        NativeEvent::new(button.clone(), InputValue::Bool(true)),
+        NativeEvent::new(
+            stick.clone(),
+            InputValue::Vector2 {
+                x: Some(-1.0),
+                y: None,
+            },
+        ),
+        NativeEvent::new(trigger.clone(), InputValue::Float(0.5)),
+        NativeEvent::new(
+            motion.clone(),
+            InputValue::Vector2 {
+                x: Some(120.0),
+                y: Some(-4.0),
+            },
+        ),
+        NativeEvent::new(Capability::NotImplemented, InputValue::Float(f64::NAN)),
+    ];
+    for event in events {
+        assert!(event.validate().is_ok(), "{event:?}");
+    }
+
+    // Buttons must use boolean values
+    let event = NativeEvent::new(button, InputValue::Float(1.0));
+    assert!(matches!(
+        event.validate(),
+        Err(ValidationError::InvalidValueType { .. })
+    ));
+
+    // Axis values must be within range
+    let event = NativeEvent::new(
+        stick,
+        InputValue::Vector2 {
+            x: Some(1.5),
+            y: Some(0.0),
+        },
+    );
+    assert!(matches!(
+        event.validate(),
+        Err(ValidationError::OutOfRange { .. })
+    ));
+
+    // Absolute mouse positions must be within the screen
+    let event = NativeEvent::new(
+        Capability::Mouse(Mouse::AbsoluteMotion),
+        InputValue::Vector2 {
+            x: Some(0.5),
+            y: Some(-0.5),
+        },
+    );
+    assert!(matches!(
+        event.validate(),
+        Err(ValidationError::OutOfRange { .. })
+    ));
+
+    // Values must be finite
+    let event = NativeEvent::new(
+        motion,
+        InputValue::Vector2 {
+            x: Some(f64::INFINITY),
+            y: None,
+        },
+    );
+    assert!(matches!(
+        event.validate(),
+        Err(ValidationError::NotFinite { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_from_evdev_raw_hat() {
+    let hat_event = |axis: AbsoluteAxisCode, value: i32, old_state: i32| {
+        let event = InputEvent::new(EventType::ABSOLUTE.0, axis.0, value);
+        NativeEvent::from_evdev_raw(EvdevEvent::from(event), Some(old_state))
+    };
+    let left = Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft));
+    let right = Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight));
+    let up = Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp));
+
+    let event = hat_event(AbsoluteAxisCode::ABS_HAT0X, -1, 0);
+    assert_eq!(event.as_capability(), left);
+    assert!(event.pressed());
+
+    // Releasing the hat releases the previous direction
+    let event = hat_event(AbsoluteAxisCode::ABS_HAT0X, 0, -1);
+    assert_eq!(event.as_capability(), left);
+    assert!(!event.pressed());
+
+    // Hat values larger than one are treated as a direction
+    let event = hat_event(AbsoluteAxisCode::ABS_HAT0X, 0, 127);
+    assert_eq!(event.as_capability(), right);
+    let event = hat_event(AbsoluteAxisCode::ABS_HAT0Y, -127, 0);
+    assert_eq!(event.as_capability(), up);
+    assert!(event.pressed());
+}
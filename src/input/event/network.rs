@@ -0,0 +1,373 @@
+//! Compact binary protocol used to stream [NativeEvent]s over the network.
+//!
+//! Every event is sent as one or more 14 byte packets in network byte order:
+//!
+//! | Bytes  | Type | Description                                          |
+//! |--------|------|------------------------------------------------------|
+//! | 0..8   | u64  | Timestamp in microseconds since the UNIX epoch       |
+//! | 8..10  | u16  | Capability discriminant                              |
+//! | 10..14 | f32  | Value of the capability                              |
+//!
+//! The lower 14 bits of the capability discriminant are the index of the
+//! capability in [CAPABILITIES] and the upper 2 bits are the component of the
+//! value (0 for x or scalar values, 1 for y and 2 for z). Multi-axis values
+//! are sent as one packet per axis that changed.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::input::capability::{
+    Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Keyboard, Mouse, MouseButton,
+};
+
+use super::{native::NativeEvent, value::InputValue};
+
+/// Size of a single event packet in bytes
+pub const PACKET_SIZE: usize = 14;
+
+/// Bit offset of the value component in the capability discriminant
+const COMPONENT_SHIFT: u16 = 14;
+/// Mask of the capability index in the capability discriminant
+const INDEX_MASK: u16 = (1 << COMPONENT_SHIFT) - 1;
+
+/// A single input event packet that can be sent over the network
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkEvent {
+    /// Timestamp in microseconds since the UNIX epoch
+    pub timestamp: u64,
+    /// Capability discriminant of the event
+    pub capability: u16,
+    /// Value of the capability component
+    pub value: f32,
+}
+
+impl NetworkEvent {
+    /// Returns the network packets for the given native event. Returns an
+    /// empty list if the capability or value cannot be streamed.
+    pub fn from_native_event(event: &NativeEvent) -> Vec<NetworkEvent> {
+        let cap = event.as_capability();
+        let Some(index) = CAPABILITIES.iter().position(|c| c == &cap) else {
+            return vec![];
+        };
+        let timestamp = event
+            .timestamp()
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let components = match event.get_value() {
+            InputValue::Bool(pressed) => vec![Some(if pressed { 1.0 } else { 0.0 })],
+            InputValue::Float(value) => vec![Some(value)],
+            InputValue::Vector2 { x, y } => vec![x, y],
+            InputValue::Vector3 { x, y, z } => vec![x, y, z],
+            _ => vec![],
+        };
+
+        components
+            .into_iter()
+            .enumerate()
+            .filter_map(|(component, value)| {
+                Some(NetworkEvent {
+                    timestamp,
+                    capability: ((component as u16) << COMPONENT_SHIFT) | index as u16,
+                    value: value? as f32,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the native event for this network packet. Returns None if the
+    /// capability discriminant is unknown.
+    pub fn to_native_event(&self) -> Option<NativeEvent> {
+        let index = (self.capability & INDEX_MASK) as usize;
+        let component = self.capability >> COMPONENT_SHIFT;
+        let cap = CAPABILITIES.get(index)?.clone();
+        let value = self.value as f64;
+
+        let value = match &cap {
+            Capability::Gamepad(Gamepad::Axis(_)) | Capability::Mouse(Mouse::Motion) => {
+                match component {
+                    0 => InputValue::Vector2 {
+                        x: Some(value),
+                        y: None,
+                    },
+                    1 => InputValue::Vector2 {
+                        x: None,
+                        y: Some(value),
+                    },
+                    _ => return None,
+                }
+            }
+            Capability::Gamepad(Gamepad::Accelerometer) | Capability::Gamepad(Gamepad::Gyro) => {
+                match component {
+                    0 => InputValue::Vector3 {
+                        x: Some(value),
+                        y: None,
+                        z: None,
+                    },
+                    1 => InputValue::Vector3 {
+                        x: None,
+                        y: Some(value),
+                        z: None,
+                    },
+                    2 => InputValue::Vector3 {
+                        x: None,
+                        y: None,
+                        z: Some(value),
+                    },
+                    _ => return None,
+                }
+            }
+            Capability::Gamepad(Gamepad::Trigger(_)) => InputValue::Float(value),
+            _ => InputValue::Bool(value > 0.5),
+        };
+
+        let mut event = NativeEvent::new(cap, value);
+        event.set_timestamp(Some(UNIX_EPOCH + Duration::from_micros(self.timestamp)));
+        Some(event)
+    }
+
+    /// Serialize the event into a network packet
+    pub fn to_bytes(&self) -> [u8; PACKET_SIZE] {
+        let mut buf = [0; PACKET_SIZE];
+        buf[0..8].copy_from_slice(&self.timestamp.to_be_bytes());
+        buf[8..10].copy_from_slice(&self.capability.to_be_bytes());
+        buf[10..14].copy_from_slice(&self.value.to_be_bytes());
+        buf
+    }
+
+    /// Deserialize the given network packet. Returns None if the packet has
+    /// an invalid size.
+    pub fn from_bytes(buf: &[u8]) -> Option<NetworkEvent> {
+        if buf.len() != PACKET_SIZE {
+            return None;
+        }
+        Some(NetworkEvent {
+            timestamp: u64::from_be_bytes(buf[0..8].try_into().ok()?),
+            capability: u16::from_be_bytes(buf[8..10].try_into().ok()?),
+            value: f32::from_be_bytes(buf[10..14].try_into().ok()?),
+        })
+    }
+}
+
+/// Capabilities that can be streamed over the network. The index of each
+/// capability is part of the protocol, so new capabilities must only be
+/// appended to the end of this list.
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Keyboard)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Mute)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTop)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle3)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStickTouch)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightTop)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle3)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightStickTouch)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat1)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat2)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat3)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTouchpadForce)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftStickForce)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTouchpadForce)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightStickForce)),
+    Capability::Gamepad(Gamepad::Accelerometer),
+    Capability::Gamepad(Gamepad::Gyro),
+    Capability::Mouse(Mouse::Motion),
+    Capability::Mouse(Mouse::Button(MouseButton::Left)),
+    Capability::Mouse(Mouse::Button(MouseButton::Right)),
+    Capability::Mouse(Mouse::Button(MouseButton::Middle)),
+    Capability::Mouse(Mouse::Button(MouseButton::WheelUp)),
+    Capability::Mouse(Mouse::Button(MouseButton::WheelDown)),
+    Capability::Mouse(Mouse::Button(MouseButton::WheelLeft)),
+    Capability::Mouse(Mouse::Button(MouseButton::WheelRight)),
+    Capability::Mouse(Mouse::Button(MouseButton::Extra)),
+    Capability::Mouse(Mouse::Button(MouseButton::Side)),
+    Capability::Keyboard(Keyboard::KeyEsc),
+    Capability::Keyboard(Keyboard::Key1),
+    Capability::Keyboard(Keyboard::Key2),
+    Capability::Keyboard(Keyboard::Key3),
+    Capability::Keyboard(Keyboard::Key4),
+    Capability::Keyboard(Keyboard::Key5),
+    Capability::Keyboard(Keyboard::Key6),
+    Capability::Keyboard(Keyboard::Key7),
+    Capability::Keyboard(Keyboard::Key8),
+    Capability::Keyboard(Keyboard::Key9),
+    Capability::Keyboard(Keyboard::Key0),
+    Capability::Keyboard(Keyboard::KeyMinus),
+    Capability::Keyboard(Keyboard::KeyEqual),
+    Capability::Keyboard(Keyboard::KeyBackspace),
+    Capability::Keyboard(Keyboard::KeyTab),
+    Capability::Keyboard(Keyboard::KeyQ),
+    Capability::Keyboard(Keyboard::KeyW),
+    Capability::Keyboard(Keyboard::KeyE),
+    Capability::Keyboard(Keyboard::KeyR),
+    Capability::Keyboard(Keyboard::KeyT),
+    Capability::Keyboard(Keyboard::KeyY),
+    Capability::Keyboard(Keyboard::KeyU),
+    Capability::Keyboard(Keyboard::KeyI),
+    Capability::Keyboard(Keyboard::KeyO),
+    Capability::Keyboard(Keyboard::KeyP),
+    Capability::Keyboard(Keyboard::KeyLeftBrace),
+    Capability::Keyboard(Keyboard::KeyRightBrace),
+    Capability::Keyboard(Keyboard::KeyEnter),
+    Capability::Keyboard(Keyboard::KeyLeftCtrl),
+    Capability::Keyboard(Keyboard::KeyA),
+    Capability::Keyboard(Keyboard::KeyS),
+    Capability::Keyboard(Keyboard::KeyD),
+    Capability::Keyboard(Keyboard::KeyF),
+    Capability::Keyboard(Keyboard::KeyG),
+    Capability::Keyboard(Keyboard::KeyH),
+    Capability::Keyboard(Keyboard::KeyJ),
+    Capability::Keyboard(Keyboard::KeyK),
+    Capability::Keyboard(Keyboard::KeyL),
+    Capability::Keyboard(Keyboard::KeySemicolon),
+    Capability::Keyboard(Keyboard::KeyApostrophe),
+    Capability::Keyboard(Keyboard::KeyGrave),
+    Capability::Keyboard(Keyboard::KeyLeftShift),
+    Capability::Keyboard(Keyboard::KeyBackslash),
+    Capability::Keyboard(Keyboard::KeyZ),
+    Capability::Keyboard(Keyboard::KeyX),
+    Capability::Keyboard(Keyboard::KeyC),
+    Capability::Keyboard(Keyboard::KeyV),
+    Capability::Keyboard(Keyboard::KeyB),
+    Capability::Keyboard(Keyboard::KeyN),
+    Capability::Keyboard(Keyboard::KeyM),
+    Capability::Keyboard(Keyboard::KeyComma),
+    Capability::Keyboard(Keyboard::KeyDot),
+    Capability::Keyboard(Keyboard::KeySlash),
+    Capability::Keyboard(Keyboard::KeyRightShift),
+    Capability::Keyboard(Keyboard::KeyKpAsterisk),
+    Capability::Keyboard(Keyboard::KeyLeftAlt),
+    Capability::Keyboard(Keyboard::KeySpace),
+    Capability::Keyboard(Keyboard::KeyCapslock),
+    Capability::Keyboard(Keyboard::KeyF1),
+    Capability::Keyboard(Keyboard::KeyF2),
+    Capability::Keyboard(Keyboard::KeyF3),
+    Capability::Keyboard(Keyboard::KeyF4),
+    Capability::Keyboard(Keyboard::KeyF5),
+    Capability::Keyboard(Keyboard::KeyF6),
+    Capability::Keyboard(Keyboard::KeyF7),
+    Capability::Keyboard(Keyboard::KeyF8),
+    Capability::Keyboard(Keyboard::KeyF9),
+    Capability::Keyboard(Keyboard::KeyF10),
+    Capability::Keyboard(Keyboard::KeyNumlock),
+    Capability::Keyboard(Keyboard::KeyScrollLock),
+    Capability::Keyboard(Keyboard::KeyKp7),
+    Capability::Keyboard(Keyboard::KeyKp8),
+    Capability::Keyboard(Keyboard::KeyKp9),
+    Capability::Keyboard(Keyboard::KeyKpMinus),
+    Capability::Keyboard(Keyboard::KeyKp4),
+    Capability::Keyboard(Keyboard::KeyKp5),
+    Capability::Keyboard(Keyboard::KeyKp6),
+    Capability::Keyboard(Keyboard::KeyKpPlus),
+    Capability::Keyboard(Keyboard::KeyKp1),
+    Capability::Keyboard(Keyboard::KeyKp2),
+    Capability::Keyboard(Keyboard::KeyKp3),
+    Capability::Keyboard(Keyboard::KeyKp0),
+    Capability::Keyboard(Keyboard::KeyKpDot),
+    Capability::Keyboard(Keyboard::KeyZenkakuhankaku),
+    Capability::Keyboard(Keyboard::Key102nd),
+    Capability::Keyboard(Keyboard::KeyF11),
+    Capability::Keyboard(Keyboard::KeyF12),
+    Capability::Keyboard(Keyboard::KeyRo),
+    Capability::Keyboard(Keyboard::KeyKatakana),
+    Capability::Keyboard(Keyboard::KeyHiragana),
+    Capability::Keyboard(Keyboard::KeyHenkan),
+    Capability::Keyboard(Keyboard::KeyKatakanaHiragana),
+    Capability::Keyboard(Keyboard::KeyMuhenkan),
+    Capability::Keyboard(Keyboard::KeyKpJpComma),
+    Capability::Keyboard(Keyboard::KeyKpEnter),
+    Capability::Keyboard(Keyboard::KeyRightCtrl),
+    Capability::Keyboard(Keyboard::KeyKpSlash),
+    Capability::Keyboard(Keyboard::KeySysrq),
+    Capability::Keyboard(Keyboard::KeyRightAlt),
+    Capability::Keyboard(Keyboard::KeyHome),
+    Capability::Keyboard(Keyboard::KeyUp),
+    Capability::Keyboard(Keyboard::KeyPageUp),
+    Capability::Keyboard(Keyboard::KeyLeft),
+    Capability::Keyboard(Keyboard::KeyRight),
+    Capability::Keyboard(Keyboard::KeyEnd),
+    Capability::Keyboard(Keyboard::KeyDown),
+    Capability::Keyboard(Keyboard::KeyPageDown),
+    Capability::Keyboard(Keyboard::KeyInsert),
+    Capability::Keyboard(Keyboard::KeyDelete),
+    Capability::Keyboard(Keyboard::KeyMute),
+    Capability::Keyboard(Keyboard::KeyVolumeDown),
+    Capability::Keyboard(Keyboard::KeyVolumeUp),
+    Capability::Keyboard(Keyboard::KeyPower),
+    Capability::Keyboard(Keyboard::KeyKpEqual),
+    Capability::Keyboard(Keyboard::KeyPause),
+    Capability::Keyboard(Keyboard::KeyKpComma),
+    Capability::Keyboard(Keyboard::KeyHanja),
+    Capability::Keyboard(Keyboard::KeyYen),
+    Capability::Keyboard(Keyboard::KeyLeftMeta),
+    Capability::Keyboard(Keyboard::KeyRightMeta),
+    Capability::Keyboard(Keyboard::KeyCompose),
+    Capability::Keyboard(Keyboard::KeyStop),
+    Capability::Keyboard(Keyboard::KeyAgain),
+    Capability::Keyboard(Keyboard::KeyProps),
+    Capability::Keyboard(Keyboard::KeyUndo),
+    Capability::Keyboard(Keyboard::KeyFront),
+    Capability::Keyboard(Keyboard::KeyCopy),
+    Capability::Keyboard(Keyboard::KeyOpen),
+    Capability::Keyboard(Keyboard::KeyPaste),
+    Capability::Keyboard(Keyboard::KeyFind),
+    Capability::Keyboard(Keyboard::KeyCut),
+    Capability::Keyboard(Keyboard::KeyHelp),
+    Capability::Keyboard(Keyboard::KeyCalc),
+    Capability::Keyboard(Keyboard::KeySleep),
+    Capability::Keyboard(Keyboard::KeyWww),
+    Capability::Keyboard(Keyboard::KeyBack),
+    Capability::Keyboard(Keyboard::KeyForward),
+    Capability::Keyboard(Keyboard::KeyEjectCD),
+    Capability::Keyboard(Keyboard::KeyNextSong),
+    Capability::Keyboard(Keyboard::KeyPlayPause),
+    Capability::Keyboard(Keyboard::KeyPreviousSong),
+    Capability::Keyboard(Keyboard::KeyStopCD),
+    Capability::Keyboard(Keyboard::KeyRefresh),
+    Capability::Keyboard(Keyboard::KeyEdit),
+    Capability::Keyboard(Keyboard::KeyScrollUp),
+    Capability::Keyboard(Keyboard::KeyScrollDown),
+    Capability::Keyboard(Keyboard::KeyKpLeftParen),
+    Capability::Keyboard(Keyboard::KeyKpRightParen),
+    Capability::Keyboard(Keyboard::KeyF13),
+    Capability::Keyboard(Keyboard::KeyF14),
+    Capability::Keyboard(Keyboard::KeyF15),
+    Capability::Keyboard(Keyboard::KeyF16),
+    Capability::Keyboard(Keyboard::KeyF17),
+    Capability::Keyboard(Keyboard::KeyF18),
+    Capability::Keyboard(Keyboard::KeyF19),
+    Capability::Keyboard(Keyboard::KeyF20),
+    Capability::Keyboard(Keyboard::KeyF21),
+    Capability::Keyboard(Keyboard::KeyF22),
+    Capability::Keyboard(Keyboard::KeyF23),
+    Capability::Keyboard(Keyboard::KeyF24),
+    Capability::Keyboard(Keyboard::KeyProg1),
+];
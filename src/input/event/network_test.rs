@@ -0,0 +1,68 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::{
+        native::NativeEvent,
+        network::{NetworkEvent, PACKET_SIZE},
+        value::InputValue,
+    },
+};
+
+#[tokio::test]
+async fn test_network_event_button() {
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let event = NativeEvent::new(cap.clone(), InputValue::Bool(true));
+    let packets = NetworkEvent::from_native_event(&event);
+    assert_eq!(packets.len(), 1);
+
+    let bytes = packets[0].to_bytes();
+    assert_eq!(bytes.len(), PACKET_SIZE);
+    let packet = NetworkEvent::from_bytes(&bytes).unwrap();
+    assert_eq!(packet, packets[0]);
+
+    let event = packet.to_native_event().unwrap();
+    assert_eq!(event.as_capability(), cap);
+    assert!(matches!(event.get_value(), InputValue::Bool(true)));
+    assert!(event.timestamp().is_some());
+}
+
+#[tokio::test]
+async fn test_network_event_axis() {
+    let cap = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let value = InputValue::Vector2 {
+        x: Some(0.5),
+        y: Some(-1.0),
+    };
+    let packets = NetworkEvent::from_native_event(&NativeEvent::new(cap.clone(), value));
+    assert_eq!(packets.len(), 2);
+
+    let x = packets[0].to_native_event().unwrap();
+    assert_eq!(x.as_capability(), cap);
+    assert!(matches!(
+        x.get_value(),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: None
+        } if x == 0.5
+    ));
+    let y = packets[1].to_native_event().unwrap();
+    assert!(matches!(
+        y.get_value(),
+        InputValue::Vector2 {
+            x: None,
+            y: Some(y)
+        } if y == -1.0
+    ));
+}
+
+#[tokio::test]
+async fn test_network_event_invalid() {
+    assert!(NetworkEvent::from_bytes(&[0; PACKET_SIZE - 1]).is_none());
+    let packet = NetworkEvent {
+        timestamp: 0,
+        capability: 0x3FFF,
+        value: 1.0,
+    };
+    assert!(packet.to_native_event().is_none());
+    let event = NativeEvent::new(Capability::Sync, InputValue::Bool(true));
+    assert!(NetworkEvent::from_native_event(&event).is_empty());
+}
@@ -1,5 +1,5 @@
 use crate::{
-    config::CapabilityConfig,
+    config::{AxisCurve, CapabilityConfig},
     input::capability::{Capability, Gamepad, Mouse, Touch, Touchpad},
 };
 
@@ -135,11 +135,16 @@ impl InputValue {
                             Capability::Mouse(mouse) => match mouse {
                                 // Gamepad Button -> Mouse Motion
                                 Mouse::Motion => Err(TranslationError::NotImplemented),
+                                // Gamepad Button -> Mouse Absolute Motion
+                                Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                                 // Gamepad Button -> Mouse Button
                                 Mouse::Button(_) => Ok(self.clone()),
+                                Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                             },
                             // Gamepad Button -> Keyboard
-                            Capability::Keyboard(_) => Ok(self.clone()),
+                            Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                                Ok(self.clone())
+                            }
                             // Gamepad Button -> Touchpad
                             Capability::Touchpad(touch) => match touch {
                                 Touchpad::LeftPad(_) => Err(TranslationError::NotImplemented),
@@ -152,7 +157,10 @@ impl InputValue {
                                 Touch::Motion => Err(TranslationError::NotImplemented),
                                 // Gamepad Button -> Touchscreen Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
+                            // Gamepad Button -> Gesture
+                            Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                         }
                     }
                     // Axis -> ...
@@ -171,7 +179,7 @@ impl InputValue {
                                 // Axis -> Button
                                 Gamepad::Button(_) => self.translate_axis_to_button(source_config),
                                 // Axis -> Axis
-                                Gamepad::Axis(_) => Ok(self.clone()),
+                                Gamepad::Axis(_) => Ok(self.translate_axis_to_axis(target_config)),
                                 // Axis -> Trigger
                                 Gamepad::Trigger(_) => Err(TranslationError::NotImplemented),
                                 // Axis -> Accelerometer
@@ -184,11 +192,16 @@ impl InputValue {
                                 // Axis -> Mouse Motion
                                 Mouse::Motion => self
                                     .translate_axis_to_mouse_motion(source_config, target_config),
+                                // Axis -> Mouse Absolute Motion
+                                Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                                 // Axis -> Mouse Button
                                 Mouse::Button(_) => self.translate_axis_to_button(source_config),
+                                Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                             },
                             // Axis -> Keyboard
-                            Capability::Keyboard(_) => self.translate_axis_to_button(source_config),
+                            Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                                self.translate_axis_to_button(source_config)
+                            }
                             // Axis -> Touchpad
                             Capability::Touchpad(touch) => match touch {
                                 Touchpad::LeftPad(_) => Err(TranslationError::NotImplemented),
@@ -197,6 +210,8 @@ impl InputValue {
                             },
                             // Axis -> Touchscreen
                             Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                            // Axis -> Gesture
+                            Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                         }
                     }
                     // Trigger -> ...
@@ -218,7 +233,9 @@ impl InputValue {
                                 self.translate_trigger_to_axis(source_config, target_config)
                             }
                             // Trigger -> Trigger
-                            Gamepad::Trigger(_) => Ok(self.clone()),
+                            Gamepad::Trigger(_) => {
+                                Ok(self.translate_trigger_to_trigger(target_config))
+                            }
                             // Trigger -> Accelerometer
                             Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                             // Trigger -> Gyro
@@ -228,11 +245,16 @@ impl InputValue {
                         Capability::Mouse(mouse) => match mouse {
                             // Trigger -> Mouse Motion
                             Mouse::Motion => Err(TranslationError::NotImplemented),
+                            // Trigger -> Mouse Absolute Motion
+                            Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                             // Trigger -> Mouse Button
                             Mouse::Button(_) => self.translate_trigger_to_button(source_config),
+                            Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                         },
                         // Trigger -> Keyboard
-                        Capability::Keyboard(_) => self.translate_trigger_to_button(source_config),
+                        Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                            self.translate_trigger_to_button(source_config)
+                        }
                         // Trigger -> Touchpad
                         Capability::Touchpad(touch) => match touch {
                             Touchpad::LeftPad(_) => Err(TranslationError::NotImplemented),
@@ -241,6 +263,8 @@ impl InputValue {
                         },
                         // Trigger -> Touchscreen
                         Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                        // Trigger -> Gesture
+                        Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                     },
                     // Accelerometer -> ...
                     Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
@@ -249,11 +273,23 @@ impl InputValue {
                 }
             }
 
+            // Mouse Double Click -> ...
+            Capability::Mouse(Mouse::DoubleClick(_)) => match target_cap {
+                Capability::None => Ok(InputValue::None),
+                Capability::NotImplemented => Ok(InputValue::None),
+                Capability::Sync => Ok(InputValue::Bool(false)),
+                Capability::DBus(_) => Ok(self.clone()),
+                Capability::Gamepad(Gamepad::Button(_)) => Ok(self.clone()),
+                Capability::Mouse(Mouse::Button(_)) => Ok(self.clone()),
+                Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => Ok(self.clone()),
+                _ => Err(TranslationError::NotImplemented),
+            },
+
             // Mouse -> ...
             Capability::Mouse(_) => Err(TranslationError::NotImplemented),
 
             // Keyboard -> ...
-            Capability::Keyboard(_) => match target_cap {
+            Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => match target_cap {
                 // Keyboard Key -> None
                 Capability::None => Ok(InputValue::None),
                 // Keyboard Key -> NotImplemented
@@ -273,14 +309,18 @@ impl InputValue {
                 // Keyboard Key -> Mouse
                 Capability::Mouse(mouse) => match mouse {
                     Mouse::Motion => Err(TranslationError::NotImplemented),
+                    Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                     Mouse::Button(_) => Ok(self.clone()),
+                    Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                 },
                 // Keyboard Key -> Keyboard
-                Capability::Keyboard(_) => Ok(self.clone()),
+                Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => Ok(self.clone()),
                 // Keyboard Key -> Touchpad
                 Capability::Touchpad(_) => Err(TranslationError::NotImplemented),
                 // Keyboard Key -> Touchscreen
                 Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                // Keyboard Key -> Gesture
+                Capability::Gesture(_) => Err(TranslationError::NotImplemented),
             },
 
             // Touchpad -> ...
@@ -306,9 +346,13 @@ impl InputValue {
                             // TODO:
                             // Touchscreen Motion -> Mouse Motion
                             Mouse::Motion => Err(TranslationError::NotImplemented),
+                            Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                             Mouse::Button(_) => Err(TranslationError::NotImplemented),
+                            Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                         },
-                        Capability::Keyboard(_) => Err(TranslationError::NotImplemented),
+                        Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                            Err(TranslationError::NotImplemented)
+                        }
                         // Touchpad Motion -> Touchpad
                         Capability::Touchpad(touchpad) => match touchpad {
                             Touchpad::LeftPad(target_touch) => match target_touch {
@@ -316,18 +360,21 @@ impl InputValue {
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::RightPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::CenterPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchspad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                         },
                         // Touchpad Motion -> Touchscreen ...
@@ -336,9 +383,13 @@ impl InputValue {
                             Touch::Motion => Ok(self.clone()),
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Gesture
+                        Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Velocity => Err(TranslationError::NotImplemented),
                 },
                 // RightPad -> ...
                 Touchpad::RightPad(touch) => match touch {
@@ -361,9 +412,13 @@ impl InputValue {
                             // TODO:
                             // Touchscreen Motion -> Mouse Motion
                             Mouse::Motion => Err(TranslationError::NotImplemented),
+                            Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                             Mouse::Button(_) => Err(TranslationError::NotImplemented),
+                            Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                         },
-                        Capability::Keyboard(_) => Err(TranslationError::NotImplemented),
+                        Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                            Err(TranslationError::NotImplemented)
+                        }
                         // Touchpad Motion -> Touchpad
                         Capability::Touchpad(touchpad) => match touchpad {
                             Touchpad::LeftPad(target_touch) => match target_touch {
@@ -371,18 +426,21 @@ impl InputValue {
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::RightPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::CenterPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchspad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                         },
                         // Touchpad Motion -> Touchscreen ...
@@ -391,9 +449,13 @@ impl InputValue {
                             Touch::Motion => Ok(self.clone()),
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Gesture
+                        Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Velocity => Err(TranslationError::NotImplemented),
                 },
                 // CenterPad -> ...
                 Touchpad::CenterPad(touch) => match touch {
@@ -416,9 +478,13 @@ impl InputValue {
                             // TODO:
                             // Touchscreen Motion -> Mouse Motion
                             Mouse::Motion => Err(TranslationError::NotImplemented),
+                            Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
                             Mouse::Button(_) => Err(TranslationError::NotImplemented),
+                            Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                         },
-                        Capability::Keyboard(_) => Err(TranslationError::NotImplemented),
+                        Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                            Err(TranslationError::NotImplemented)
+                        }
                         // Touchpad Motion -> Touchpad
                         Capability::Touchpad(touchpad) => match touchpad {
                             Touchpad::LeftPad(target_touch) => match target_touch {
@@ -426,18 +492,21 @@ impl InputValue {
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::RightPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchpad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                             Touchpad::CenterPad(target_touch) => match target_touch {
                                 // Touchpad Motion -> Touchpad Motion
                                 Touch::Motion => Ok(self.clone()),
                                 // Touchspad Motion -> Touchpad Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                                Touch::Velocity => Err(TranslationError::NotImplemented),
                             },
                         },
                         // Touchpad Motion -> Touchscreen ...
@@ -446,9 +515,13 @@ impl InputValue {
                             Touch::Motion => Ok(self.clone()),
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Gesture
+                        Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Velocity => Err(TranslationError::NotImplemented),
                 },
             },
 
@@ -474,11 +547,16 @@ impl InputValue {
                         // TODO:
                         // Touchscreen Motion -> Mouse Motion
                         Mouse::Motion => Err(TranslationError::NotImplemented),
+                        // Touchscreen Motion -> Mouse Absolute Motion
+                        Mouse::AbsoluteMotion => self.translate_touch_to_absolute_motion(),
                         // Touchscreen Motion -> Mouse Button
                         Mouse::Button(_) => Err(TranslationError::NotImplemented),
+                        Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
                     },
                     // Touchscreen Motion -> Keyboard
-                    Capability::Keyboard(_) => Err(TranslationError::NotImplemented),
+                    Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => {
+                        Err(TranslationError::NotImplemented)
+                    }
                     // Touchscreen Motion -> Touchpad
                     Capability::Touchpad(touchpad) => match touchpad {
                         Touchpad::LeftPad(target_touch) => match target_touch {
@@ -486,18 +564,21 @@ impl InputValue {
                             Touch::Motion => Ok(self.clone()),
                             // Touchscreen Motion -> Touchpad Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
                         Touchpad::RightPad(target_touch) => match target_touch {
                             // Touchscreen Motion -> Touchpad Motion
                             Touch::Motion => Ok(self.clone()),
                             // Touchscreen Motion -> Touchpad Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
                         Touchpad::CenterPad(target_touch) => match target_touch {
                             // Touchscreen Motion -> Touchpad Motion
                             Touch::Motion => Ok(self.clone()),
                             // Touchscreen Motion -> Touchpad Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            Touch::Velocity => Err(TranslationError::NotImplemented),
                         },
                     },
                     // Touchscreen Motion -> Touchscreen ...
@@ -506,14 +587,71 @@ impl InputValue {
                         Touch::Motion => Ok(self.clone()),
                         // Touchscreen Motion -> Touchscreen Button
                         Touch::Button(_) => Err(TranslationError::NotImplemented),
+                        Touch::Velocity => Err(TranslationError::NotImplemented),
                     },
+                    // Touchscreen Motion -> Gesture
+                    Capability::Gesture(_) => Err(TranslationError::NotImplemented),
                 },
                 // Touchscreen Button -> ...
                 Touch::Button(_) => Err(TranslationError::NotImplemented),
+                Touch::Velocity => Err(TranslationError::NotImplemented),
+            },
+
+            // Gesture -> ...
+            Capability::Gesture(_) => match target_cap {
+                // Gesture -> None
+                Capability::None => Ok(InputValue::None),
+                // Gesture -> NotImplemented
+                Capability::NotImplemented => Ok(InputValue::None),
+                // Gesture -> Sync
+                Capability::Sync => Ok(InputValue::Bool(false)),
+                // Gesture -> DBus
+                Capability::DBus(_) => Ok(self.clone()),
+                // Gesture -> Gamepad
+                Capability::Gamepad(gamepad) => match gamepad {
+                    // Gesture -> Button
+                    Gamepad::Button(_) => Ok(self.clone()),
+                    // Gesture -> Axis
+                    Gamepad::Axis(_) => self.translate_button_to_axis(target_config),
+                    // Gesture -> Trigger
+                    Gamepad::Trigger(_) => Ok(self.translate_button_to_trigger()),
+                    // Gesture -> Accelerometer
+                    Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
+                    // Gesture -> Gyro
+                    Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                },
+                // Gesture -> Mouse
+                Capability::Mouse(mouse) => match mouse {
+                    // Gesture -> Mouse Motion
+                    Mouse::Motion => Err(TranslationError::NotImplemented),
+                    // Gesture -> Mouse Absolute Motion
+                    Mouse::AbsoluteMotion => Err(TranslationError::NotImplemented),
+                    // Gesture -> Mouse Button
+                    Mouse::Button(_) => Ok(self.clone()),
+                    Mouse::DoubleClick(_) => Err(TranslationError::NotImplemented),
+                },
+                // Gesture -> Keyboard
+                Capability::Keyboard(_) | Capability::KeyboardKeysym(_) => Ok(self.clone()),
+                // Gesture -> Touchpad
+                Capability::Touchpad(_) => Err(TranslationError::NotImplemented),
+                // Gesture -> Touchscreen
+                Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                // Gesture -> Gesture
+                Capability::Gesture(_) => Ok(self.clone()),
             },
         }
     }
 
+    /// Translate the touch value into an absolute mouse position
+    fn translate_touch_to_absolute_motion(&self) -> Result<InputValue, TranslationError> {
+        let InputValue::Touch { x, y, .. } = self else {
+            return Err(TranslationError::ImpossibleTranslation(
+                "Only touch values can be translated to absolute mouse motion".into(),
+            ));
+        };
+        Ok(InputValue::Vector2 { x: *x, y: *y })
+    }
+
     /// Translate the axis value into mouse motion
     fn translate_axis_to_mouse_motion(
         &self,
@@ -851,6 +989,65 @@ impl InputValue {
         }
     }
 
+    /// Translate the axis value into an axis value, applying the range remap,
+    /// angle snap and response curve from the target config if they are
+    /// defined.
+    fn translate_axis_to_axis(&self, target_config: &CapabilityConfig) -> InputValue {
+        let Some(axis) = target_config
+            .gamepad
+            .as_ref()
+            .and_then(|gamepad| gamepad.axis.as_ref())
+        else {
+            return self.clone();
+        };
+        let InputValue::Vector2 { mut x, mut y } = self else {
+            return self.clone();
+        };
+
+        x = x.map(|x| apply_input_range(x, axis.input_min, axis.input_max));
+        y = y.map(|y| apply_input_range(y, axis.input_min, axis.input_max));
+
+        if axis.source_range.is_some() || axis.target_range.is_some() {
+            let source_range = axis.source_range.unwrap_or([-1.0, 1.0]);
+            let target_range = axis.target_range.unwrap_or([-1.0, 1.0]);
+            x = x.map(|x| apply_range_remap(x, source_range, target_range));
+            y = y.map(|y| apply_range_remap(y, source_range, target_range));
+        }
+
+        // The angle can only be snapped if both axes of the stick are known
+        if let (Some(snap_points), Some(x_value), Some(y_value)) = (axis.angle_snap, x, y) {
+            let (snapped_x, snapped_y) = apply_angle_snap(x_value, y_value, snap_points);
+            x = Some(snapped_x);
+            y = Some(snapped_y);
+        }
+
+        if let Some(curve) = axis.curve.as_ref() {
+            x = x.map(|x| apply_curve(x, curve));
+            y = y.map(|y| apply_curve(y, curve));
+        }
+
+        x = x.map(|x| apply_output_range(x, axis.output_min, axis.output_max));
+        y = y.map(|y| apply_output_range(y, axis.output_min, axis.output_max));
+
+        InputValue::Vector2 { x, y }
+    }
+
+    /// Translate the trigger value into a trigger value, applying the input
+    /// and output ranges from the target config if they are defined.
+    fn translate_trigger_to_trigger(&self, target_config: &CapabilityConfig) -> InputValue {
+        let trigger = target_config
+            .gamepad
+            .as_ref()
+            .and_then(|gamepad| gamepad.trigger.as_ref());
+        let (Some(trigger), InputValue::Float(value)) = (trigger, self) else {
+            return self.clone();
+        };
+
+        let value = apply_input_range(*value, trigger.input_min, trigger.input_max);
+        let value = apply_output_range(value, trigger.output_min, trigger.output_max);
+        InputValue::Float(value)
+    }
+
     /// Translate the trigger value into an axis value based on the given config
     fn translate_trigger_to_axis(
         &self,
@@ -899,43 +1096,170 @@ impl InputValue {
             ));
         };
 
-        // Convert the value based on the defined direction
-        match axis_direction.as_str() {
-            "horizontal" => {
-                // Normalize unsigned to signed value (from 0.0 -> 1.0 to -1.0 -> 1.0)
-                let value = (*value * 2.0) - 1.0;
-                Ok(InputValue::Vector2 {
-                    x: Some(value),
-                    y: None,
-                })
+        // Convert the value based on the defined direction. If a source or
+        // target range is defined, the trigger value is linearly remapped
+        // into the target range instead.
+        let is_horizontal = match axis_direction.as_str() {
+            "horizontal" | "left" | "right" => true,
+            "vertical" | "up" | "down" => false,
+            _ => {
+                return Err(TranslationError::InvalidTargetConfig(
+                    "Invalid or unsupported direction".into(),
+                ))
             }
-            "vertical" => {
+        };
+        let value = if axis.source_range.is_some() || axis.target_range.is_some() {
+            let source_range = axis.source_range.unwrap_or([0.0, 1.0]);
+            let target_range = axis.target_range.unwrap_or([-1.0, 1.0]);
+            apply_range_remap(*value, source_range, target_range)
+        } else {
+            match axis_direction.as_str() {
                 // Normalize unsigned to signed value (from 0.0 -> 1.0 to -1.0 -> 1.0)
-                let value = (*value * 2.0) - 1.0;
-                Ok(InputValue::Vector2 {
-                    x: None,
-                    y: Some(value),
-                })
+                "horizontal" | "vertical" => (*value * 2.0) - 1.0,
+                "left" | "up" => -*value,
+                _ => *value,
             }
-            "left" => Ok(InputValue::Vector2 {
-                x: Some(-*value),
-                y: None,
-            }),
-            "right" => Ok(InputValue::Vector2 {
-                x: Some(*value),
+        };
+
+        if is_horizontal {
+            Ok(InputValue::Vector2 {
+                x: Some(value),
                 y: None,
-            }),
-            "up" => Ok(InputValue::Vector2 {
-                x: None,
-                y: Some(-*value),
-            }),
-            "down" => Ok(InputValue::Vector2 {
+            })
+        } else {
+            Ok(InputValue::Vector2 {
                 x: None,
-                y: Some(*value),
-            }),
-            _ => Err(TranslationError::InvalidTargetConfig(
-                "Invalid or unsupported direction".into(),
-            )),
+                y: Some(value),
+            })
+        }
+    }
+}
+
+/// Apply the given response curve to the given axis value between -1.0 and
+/// 1.0. The curve is applied to the magnitude of the value, so the sign of the
+/// value is preserved.
+pub fn apply_curve(x: f64, curve: &AxisCurve) -> f64 {
+    let t = x.abs().min(1.0);
+    let value = match curve {
+        AxisCurve::Linear => t,
+        AxisCurve::Quadratic => t * t,
+        AxisCurve::CubicBezier(x1, y1, x2, y2) => cubic_bezier(t, *x1, *y1, *x2, *y2),
+        AxisCurve::SCurve => t * t * (3.0 - 2.0 * t),
+    };
+    value.copysign(x)
+}
+
+/// Clamp the magnitude of the given value between -1.0 and 1.0 to the given
+/// input range and rescale it to the full range, so values at or below the
+/// minimum become 0.0 and values at or above the maximum become 1.0. The sign
+/// of the value is preserved.
+pub fn apply_input_range(x: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if min.is_none() && max.is_none() {
+        return x;
+    }
+    let min = min.unwrap_or(0.0).clamp(0.0, 1.0);
+    let max = max.unwrap_or(1.0).clamp(0.0, 1.0);
+    if max <= min {
+        return if x.abs() > min {
+            1.0f64.copysign(x)
+        } else {
+            0.0
+        };
+    }
+    let t = ((x.abs() - min) / (max - min)).clamp(0.0, 1.0);
+    t.copysign(x)
+}
+
+/// Clamp the given value to the given source range and linearly remap it
+/// into the given target range, so the first value of the source range
+/// becomes the first value of the target range and the second value of the
+/// source range becomes the second value of the target range. Ranges may be
+/// inverted to invert the value.
+pub fn apply_range_remap(x: f64, source_range: [f64; 2], target_range: [f64; 2]) -> f64 {
+    let [source_start, source_end] = source_range;
+    let [target_start, target_end] = target_range;
+    let t = if source_start == source_end {
+        if x >= source_end {
+            1.0
+        } else {
+            0.0
         }
+    } else {
+        ((x - source_start) / (source_end - source_start)).clamp(0.0, 1.0)
+    };
+    target_start + t * (target_end - target_start)
+}
+
+/// Clamp the given value to the given output range
+pub fn apply_output_range(x: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    let x = min.map_or(x, |min| x.max(min));
+    max.map_or(x, |max| x.min(max))
+}
+
+/// Round the angle of the given stick position to the nearest of the given
+/// number of evenly spaced directions, starting at the positive x axis. The
+/// distance of the stick from the center is preserved.
+pub fn apply_angle_snap(x: f64, y: f64, snap_points: u32) -> (f64, f64) {
+    let magnitude = x.hypot(y);
+    if snap_points == 0 || magnitude == 0.0 {
+        return (x, y);
     }
+    let step = std::f64::consts::TAU / snap_points as f64;
+    let angle = (y.atan2(x) / step).round() * step;
+
+    // Remove floating point error so cardinal directions have an exact zero
+    // on the other axis.
+    let clean = |value: f64| if value.abs() < 1e-9 { 0.0 } else { value };
+    let x = clean(magnitude * angle.cos()).clamp(-1.0, 1.0);
+    let y = clean(magnitude * angle.sin()).clamp(-1.0, 1.0);
+    (x, y)
+}
+
+/// Evaluate a CSS style cubic bezier timing function with the control points
+/// (0, 0), (x1, y1), (x2, y2), and (1, 1) at the given x value between 0.0
+/// and 1.0.
+fn cubic_bezier(x: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    // Like CSS, the x values of the control points are clamped to keep the
+    // curve a function of x.
+    let x1 = x1.clamp(0.0, 1.0);
+    let x2 = x2.clamp(0.0, 1.0);
+
+    // Evaluate one dimension of the curve at the given parameter
+    let bezier = |t: f64, p1: f64, p2: f64| -> f64 {
+        let u = 1.0 - t;
+        3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+    };
+    let bezier_slope = |t: f64, p1: f64, p2: f64| -> f64 {
+        let u = 1.0 - t;
+        3.0 * u * u * p1 + 6.0 * u * t * (p2 - p1) + 3.0 * t * t * (1.0 - p2)
+    };
+
+    // Find the curve parameter for the given x value. Try Newton's method
+    // first, then fall back to bisection if it does not converge.
+    const EPSILON: f64 = 1e-6;
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(t, x1, x2) - x;
+        if error.abs() < EPSILON {
+            return bezier(t, y1, y2);
+        }
+        let slope = bezier_slope(t, x1, x2);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        t -= error / slope;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    while high - low > EPSILON {
+        if bezier(t, x1, x2) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+
+    bezier(t, y1, y2)
 }
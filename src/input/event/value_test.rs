@@ -0,0 +1,86 @@
+use crate::{
+    config::AxisCurve,
+    input::event::value::{
+        apply_angle_snap, apply_curve, apply_input_range, apply_output_range, apply_range_remap,
+    },
+};
+
+#[tokio::test]
+async fn test_apply_curve() {
+    let curves = vec![
+        AxisCurve::Linear,
+        AxisCurve::Quadratic,
+        AxisCurve::CubicBezier(0.42, 0.0, 0.58, 1.0),
+        AxisCurve::SCurve,
+    ];
+
+    // All curves should start at the center, end at the edges, and preserve
+    // the sign of the value.
+    for curve in curves.iter() {
+        assert!(apply_curve(0.0, curve).abs() < 1e-6, "{curve:?}");
+        assert!((apply_curve(1.0, curve) - 1.0).abs() < 1e-6, "{curve:?}");
+        assert!((apply_curve(-1.0, curve) + 1.0).abs() < 1e-6, "{curve:?}");
+        assert_eq!(apply_curve(-0.3, curve), -apply_curve(0.3, curve));
+    }
+
+    assert_eq!(apply_curve(0.5, &AxisCurve::Linear), 0.5);
+    assert_eq!(apply_curve(0.5, &AxisCurve::Quadratic), 0.25);
+    assert_eq!(apply_curve(0.25, &AxisCurve::SCurve), 0.15625);
+
+    // A symmetric "ease-in-out" bezier curve passes through the middle
+    let ease_in_out = AxisCurve::CubicBezier(0.42, 0.0, 0.58, 1.0);
+    assert!((apply_curve(0.5, &ease_in_out) - 0.5).abs() < 1e-4);
+    assert!(apply_curve(0.25, &ease_in_out) < 0.25);
+
+    // A linear bezier curve should match the linear curve
+    let linear_bezier = AxisCurve::CubicBezier(0.0, 0.0, 1.0, 1.0);
+    assert!((apply_curve(0.3, &linear_bezier) - 0.3).abs() < 1e-4);
+}
+
+#[tokio::test]
+async fn test_apply_angle_snap() {
+    // Angles snap to the nearest of 8 directions and keep their magnitude
+    assert_eq!(apply_angle_snap(0.8, 0.1, 8), (0.8f64.hypot(0.1), 0.0));
+    assert_eq!(apply_angle_snap(-0.1, -0.5, 8), (0.0, -0.1f64.hypot(0.5)));
+    let (x, y) = apply_angle_snap(0.6, 0.5, 8);
+    assert!((x - y).abs() < 1e-9);
+    assert!((x.hypot(y) - 0.6f64.hypot(0.5)).abs() < 1e-9);
+
+    // Snapped values stay within the range of the axis
+    let (x, y) = apply_angle_snap(1.0, 0.1, 4);
+    assert_eq!((x, y), (1.0, 0.0));
+
+    // The center and disabled snapping are left untouched
+    assert_eq!(apply_angle_snap(0.0, 0.0, 8), (0.0, 0.0));
+    assert_eq!(apply_angle_snap(0.3, 0.2, 0), (0.3, 0.2));
+}
+
+#[tokio::test]
+async fn test_apply_ranges() {
+    // Values are rescaled from the input range to the full range
+    assert_eq!(apply_input_range(0.1, Some(0.2), Some(0.8)), 0.0);
+    assert_eq!(apply_input_range(0.5, Some(0.25), Some(0.75)), 0.5);
+    assert_eq!(apply_input_range(-0.9, Some(0.2), Some(0.8)), -1.0);
+    assert_eq!(apply_input_range(0.5, None, Some(0.5)), 1.0);
+    assert_eq!(apply_input_range(0.3, None, None), 0.3);
+
+    // Output values are clamped to the output range
+    assert_eq!(apply_output_range(0.7, Some(0.0), Some(0.5)), 0.5);
+    assert_eq!(apply_output_range(-0.7, Some(0.0), None), 0.0);
+    assert_eq!(apply_output_range(0.3, None, None), 0.3);
+}
+
+#[tokio::test]
+async fn test_apply_range_remap() {
+    // Values are linearly remapped from the source range to the target range
+    assert_eq!(apply_range_remap(0.3, [0.3, 1.0], [0.0, 1.0]), 0.0);
+    assert_eq!(apply_range_remap(1.0, [0.3, 1.0], [0.0, 1.0]), 1.0);
+    assert_eq!(apply_range_remap(0.5, [0.0, 1.0], [-1.0, 1.0]), 0.0);
+
+    // Values outside of the source range are clamped
+    assert_eq!(apply_range_remap(0.1, [0.3, 1.0], [0.0, 1.0]), 0.0);
+    assert_eq!(apply_range_remap(-1.0, [0.0, 1.0], [0.0, 0.5]), 0.0);
+
+    // Inverted ranges invert the value
+    assert_eq!(apply_range_remap(0.25, [0.0, 1.0], [1.0, 0.0]), 0.75);
+}
@@ -0,0 +1,312 @@
+//! Binary event log used to record the [NativeEvent]s processed by a composite
+//! device so they can be replayed later for reproducible bug reports.
+//!
+//! An event log is a sequence of records with the following layout, where all
+//! integers are little-endian:
+//!
+//! | Size     | Field                                                     |
+//! |----------|-----------------------------------------------------------|
+//! | 4 bytes  | Magic (`IPEV`)                                            |
+//! | 8 bytes  | Timestamp in microseconds since the UNIX epoch            |
+//! | 2 bytes  | Length of the capability string                           |
+//! | variable | Capability string (e.g. `Gamepad:Button:South`)           |
+//! | 1 byte   | Value type                                                |
+//! | variable | Value payload                                             |
+
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Magic bytes at the start of every event log record
+pub const EVENT_LOG_MAGIC: [u8; 4] = *b"IPEV";
+/// Directory that event logs requested over DBus are written to
+pub const EVENT_LOG_DIR: &str = "/var/log/inputplumber";
+/// Maximum size of an event log file. When this size is exceeded, the log is
+/// moved to "<path>.1" and a new log is started, so at most two logs worth of
+/// the most recent events are kept.
+const EVENT_LOG_MAX_SIZE: u64 = 16 * 1024 * 1024;
+/// File mode of newly created event logs. Event logs can contain keyboard
+/// input, so only the owner may read them.
+const EVENT_LOG_MODE: u32 = 0o600;
+
+// Value types
+const VALUE_NONE: u8 = 0;
+const VALUE_BOOL: u8 = 1;
+const VALUE_FLOAT: u8 = 2;
+const VALUE_VECTOR2: u8 = 3;
+const VALUE_VECTOR3: u8 = 4;
+const VALUE_TOUCH: u8 = 5;
+
+/// A single recorded event
+#[derive(Debug, Clone)]
+pub struct EventLogRecord {
+    /// Time the event was recorded in microseconds since the UNIX epoch
+    pub timestamp_us: u64,
+    /// The recorded event
+    pub event: NativeEvent,
+}
+
+impl EventLogRecord {
+    /// Create a new record for the given event using the current time
+    pub fn new(event: NativeEvent) -> Self {
+        let timestamp_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_micros() as u64)
+            .unwrap_or_default();
+        Self {
+            timestamp_us,
+            event,
+        }
+    }
+
+    /// Serialize the record into its binary representation
+    pub fn encode(&self) -> Vec<u8> {
+        let capability = self.event.as_capability().to_capability_string();
+        let mut bytes = Vec::with_capacity(4 + 8 + 2 + capability.len() + 1 + 8);
+        bytes.extend_from_slice(&EVENT_LOG_MAGIC);
+        bytes.extend_from_slice(&self.timestamp_us.to_le_bytes());
+        bytes.extend_from_slice(&(capability.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(capability.as_bytes());
+
+        match self.event.get_value() {
+            InputValue::None => bytes.push(VALUE_NONE),
+            InputValue::Bool(value) => {
+                bytes.push(VALUE_BOOL);
+                bytes.push(value as u8);
+            }
+            InputValue::Float(value) => {
+                bytes.push(VALUE_FLOAT);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            InputValue::Vector2 { x, y } => {
+                bytes.push(VALUE_VECTOR2);
+                encode_optional(&mut bytes, x);
+                encode_optional(&mut bytes, y);
+            }
+            InputValue::Vector3 { x, y, z } => {
+                bytes.push(VALUE_VECTOR3);
+                encode_optional(&mut bytes, x);
+                encode_optional(&mut bytes, y);
+                encode_optional(&mut bytes, z);
+            }
+            InputValue::Touch {
+                index,
+                is_touching,
+                pressure,
+                x,
+                y,
+            } => {
+                bytes.push(VALUE_TOUCH);
+                bytes.push(index);
+                bytes.push(is_touching as u8);
+                encode_optional(&mut bytes, pressure);
+                encode_optional(&mut bytes, x);
+                encode_optional(&mut bytes, y);
+            }
+        }
+
+        bytes
+    }
+
+    /// Deserialize a record from the start of the given bytes. Returns the
+    /// record and the number of bytes that were read.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), Box<dyn Error + Send + Sync>> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.take(4)? != EVENT_LOG_MAGIC {
+            return Err("Invalid event log record magic".into());
+        }
+        let timestamp_us = u64::from_le_bytes(reader.take(8)?.try_into()?);
+        let capability_len = u16::from_le_bytes(reader.take(2)?.try_into()?);
+        let capability = std::str::from_utf8(reader.take(capability_len as usize)?)?;
+        let Ok(capability) = Capability::from_str(capability) else {
+            return Err(format!("Invalid capability in event log record: {capability}").into());
+        };
+
+        let value = match reader.u8()? {
+            VALUE_NONE => InputValue::None,
+            VALUE_BOOL => InputValue::Bool(reader.u8()? != 0),
+            VALUE_FLOAT => InputValue::Float(reader.f64()?),
+            VALUE_VECTOR2 => InputValue::Vector2 {
+                x: reader.optional()?,
+                y: reader.optional()?,
+            },
+            VALUE_VECTOR3 => InputValue::Vector3 {
+                x: reader.optional()?,
+                y: reader.optional()?,
+                z: reader.optional()?,
+            },
+            VALUE_TOUCH => InputValue::Touch {
+                index: reader.u8()?,
+                is_touching: reader.u8()? != 0,
+                pressure: reader.optional()?,
+                x: reader.optional()?,
+                y: reader.optional()?,
+            },
+            kind => return Err(format!("Invalid value type in event log record: {kind}").into()),
+        };
+
+        let record = Self {
+            timestamp_us,
+            event: NativeEvent::new(capability, value),
+        };
+        Ok((record, reader.offset))
+    }
+}
+
+/// Encode an optional float as a presence byte followed by the value
+fn encode_optional(bytes: &mut Vec<u8>, value: Option<f64>) {
+    match value {
+        Some(value) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+}
+
+/// Helper to read values from a byte slice
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn Error + Send + Sync>> {
+        let Some(bytes) = self.bytes.get(self.offset..self.offset + len) else {
+            return Err("Unexpected end of event log record".into());
+        };
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Box<dyn Error + Send + Sync>> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn f64(&mut self) -> Result<f64, Box<dyn Error + Send + Sync>> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn optional(&mut self) -> Result<Option<f64>, Box<dyn Error + Send + Sync>> {
+        if self.u8()? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.f64()?))
+    }
+}
+
+/// Parse all records from the given event log contents
+pub fn decode_records(bytes: &[u8]) -> Result<Vec<EventLogRecord>, Box<dyn Error + Send + Sync>> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (record, len) = EventLogRecord::decode(&bytes[offset..])?;
+        records.push(record);
+        offset += len;
+    }
+
+    Ok(records)
+}
+
+/// Returns the path of the event log with the given file name in
+/// [EVENT_LOG_DIR]. Names that are not a plain file name, such as absolute
+/// paths or names containing "..", are rejected so event logs can only be
+/// created inside of the event log directory.
+pub fn event_log_path(name: &str) -> Result<PathBuf, io::Error> {
+    let is_file_name = Path::new(name)
+        .file_name()
+        .is_some_and(|file_name| file_name == name);
+    if !is_file_name {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid event log name '{name}'. Expected a file name."),
+        ));
+    }
+
+    Ok(Path::new(EVENT_LOG_DIR).join(name))
+}
+
+/// Appends events to an event log file
+#[derive(Debug)]
+pub struct EventLogWriter {
+    path: String,
+    file: BufWriter<File>,
+    size: u64,
+}
+
+impl EventLogWriter {
+    /// Open the event log at the given path for appending, creating its
+    /// parent directory if it does not exist
+    pub fn new(path: String) -> Result<Self, io::Error> {
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = open_log_file(path.as_str())?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file: BufWriter::new(file),
+            size,
+        })
+    }
+
+    /// Returns the path of the event log
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// Record the given event in the event log
+    pub fn write(&mut self, event: &NativeEvent) -> Result<(), io::Error> {
+        if self.size >= EVENT_LOG_MAX_SIZE {
+            self.rotate()?;
+        }
+        let bytes = EventLogRecord::new(event.clone()).encode();
+        self.file.write_all(&bytes)?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Flush any buffered events to the event log
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.file.flush()
+    }
+
+    /// Move the current event log to "<path>.1" and start a new log
+    fn rotate(&mut self) -> Result<(), io::Error> {
+        self.file.flush()?;
+        fs::rename(&self.path, format!("{}.1", self.path))?;
+        let file = open_log_file(self.path.as_str())?;
+        self.file = BufWriter::new(file);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Open the event log file at the given path for appending, creating it with
+/// [EVENT_LOG_MODE] if it does not exist
+fn open_log_file(path: &str) -> Result<File, io::Error> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(EVENT_LOG_MODE)
+        .open(path)
+}
+
+impl Drop for EventLogWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.flush() {
+            log::error!("Failed to flush event log {}: {e:?}", self.path);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::{native::NativeEvent, value::InputValue},
+    event_log::{decode_records, event_log_path, EventLogRecord, EVENT_LOG_DIR},
+};
+
+#[tokio::test]
+async fn test_event_log_roundtrip() {
+    let events = vec![
+        NativeEvent::new(
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            InputValue::Bool(true),
+        ),
+        NativeEvent::new(
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            InputValue::Vector2 {
+                x: Some(0.5),
+                y: None,
+            },
+        ),
+    ];
+
+    let mut bytes = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        let record = EventLogRecord {
+            timestamp_us: i as u64 * 1000,
+            event: event.clone(),
+        };
+        bytes.append(&mut record.encode());
+    }
+
+    let records = decode_records(bytes.as_slice()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].timestamp_us, 1000);
+    for (record, event) in records.iter().zip(events.iter()) {
+        assert_eq!(record.event.as_capability(), event.as_capability());
+    }
+    let InputValue::Vector2 { x, y } = records[1].event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(0.5));
+    assert_eq!(y, None);
+
+    // Truncated records should fail to decode
+    assert!(decode_records(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[tokio::test]
+async fn test_event_log_path() {
+    let path = event_log_path("events.log").unwrap();
+    assert_eq!(path, std::path::Path::new(EVENT_LOG_DIR).join("events.log"));
+
+    for name in [
+        "",
+        ".",
+        "..",
+        "../events.log",
+        "/etc/passwd",
+        "logs/events.log",
+    ] {
+        assert!(event_log_path(name).is_err(), "{name} should be rejected");
+    }
+}
@@ -0,0 +1,84 @@
+//! Stream of the [NativeEvent]s processed by a composite device that other
+//! processes can read from a Unix domain socket. Events are written to the
+//! socket as [EventLogRecord]s, so the same decoder can be used for event
+//! streams and event logs.
+
+use std::{
+    io,
+    os::{fd::OwnedFd, unix::net::UnixStream},
+    time::{Duration, Instant},
+};
+
+use tokio::{io::AsyncWriteExt, sync::mpsc};
+
+use super::{event::native::NativeEvent, event_log::EventLogRecord};
+
+/// Maximum number of encoded events that can be waiting to be written to the
+/// socket. Events are dropped if the reader falls behind.
+const EVENT_STREAM_BUFFER_SIZE: usize = 1024;
+
+/// Writes processed events to one end of a Unix domain socket pair
+#[derive(Debug)]
+pub struct EventStream {
+    tx: mpsc::Sender<Vec<u8>>,
+    /// Minimum time between two streamed events, if the stream is rate
+    /// limited
+    min_interval: Option<Duration>,
+    last_sent: Option<Instant>,
+}
+
+impl EventStream {
+    /// Create a new event stream that sends at most the given number of
+    /// events per second. A rate of zero does not limit the stream. Returns
+    /// the stream and the file descriptor of the socket to read events from.
+    pub fn new(max_hz: u32) -> Result<(Self, OwnedFd), io::Error> {
+        let (local, remote) = UnixStream::pair()?;
+        local.set_nonblocking(true)?;
+        let mut socket = tokio::net::UnixStream::from_std(local)?;
+
+        // Write events to the socket in a separate task so a slow reader
+        // cannot block input processing. The task stops when the stream is
+        // dropped or the reader closes the socket.
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(EVENT_STREAM_BUFFER_SIZE);
+        tokio::task::spawn(async move {
+            while let Some(bytes) = rx.recv().await {
+                if let Err(e) = socket.write_all(&bytes).await {
+                    log::debug!("Stopping event stream: {e:?}");
+                    break;
+                }
+            }
+        });
+
+        let min_interval = (max_hz > 0).then(|| Duration::from_secs(1) / max_hz);
+        let stream = Self {
+            tx,
+            min_interval,
+            last_sent: None,
+        };
+
+        Ok((stream, remote.into()))
+    }
+
+    /// Send the given event to the stream, unless the rate limit was reached
+    /// or the reader fell behind. Returns false if the reader closed the
+    /// socket.
+    pub fn write(&mut self, event: &NativeEvent) -> bool {
+        let now = Instant::now();
+        if let (Some(min_interval), Some(last_sent)) = (self.min_interval, self.last_sent) {
+            if now.duration_since(last_sent) < min_interval {
+                return true;
+            }
+        }
+        self.last_sent = Some(now);
+
+        let bytes = EventLogRecord::new(event.clone()).encode();
+        match self.tx.try_send(bytes) {
+            Ok(_) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                log::trace!("Event stream reader fell behind. Dropping event.");
+                true
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+}
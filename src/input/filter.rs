@@ -0,0 +1,524 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::config::FilterConfig;
+
+use super::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Result of running a [SourceDeviceFilter] on an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterResult {
+    /// The event was not changed and should continue through the filter chain
+    Pass,
+    /// The event should be dropped and not processed any further
+    Drop,
+    /// The event was modified and should continue through the filter chain
+    Modify,
+}
+
+/// A source device filter can inspect, modify, or drop events from a source
+/// device before they are translated by the capability map or device profile.
+pub trait SourceDeviceFilter: Debug {
+    fn filter(&mut self, device_id: &str, event: &mut NativeEvent) -> FilterResult;
+}
+
+/// Create the filter chain defined by the given filter configs. Filters with
+/// an invalid capability are skipped.
+pub fn filters_from_config(configs: &[FilterConfig]) -> Vec<Box<dyn SourceDeviceFilter + Send>> {
+    let mut filters: Vec<Box<dyn SourceDeviceFilter + Send>> = Vec::with_capacity(configs.len());
+    for config in configs {
+        let filter: Box<dyn SourceDeviceFilter + Send> = match config {
+            FilterConfig::Deadzone {
+                capability,
+                deadzone,
+            } => {
+                let Ok(capability) = parse_capability(capability) else {
+                    continue;
+                };
+                Box::new(DeadzoneFilter::new(capability, *deadzone))
+            }
+            FilterConfig::Threshold {
+                capability,
+                threshold,
+            } => {
+                let Ok(capability) = parse_capability(capability) else {
+                    continue;
+                };
+                Box::new(ThresholdFilter::new(capability, *threshold))
+            }
+            FilterConfig::Invert {
+                capability,
+                x,
+                y,
+                z,
+            } => {
+                let Ok(capability) = parse_capability(capability) else {
+                    continue;
+                };
+                Box::new(InvertFilter::new(
+                    capability,
+                    x.unwrap_or(true),
+                    y.unwrap_or(true),
+                    z.unwrap_or(true),
+                ))
+            }
+        };
+        filters.push(filter);
+    }
+
+    filters
+}
+
+/// Parse the optional capability string of a filter config
+fn parse_capability(capability: &Option<String>) -> Result<Option<Capability>, ()> {
+    let Some(capability) = capability.as_ref() else {
+        return Ok(None);
+    };
+    match Capability::from_str(capability) {
+        Ok(cap) => Ok(Some(cap)),
+        Err(_) => {
+            log::warn!("Invalid capability in source device filter: {capability}");
+            Err(())
+        }
+    }
+}
+
+/// Returns true if the filter with the given capability applies to the given
+/// event. Filters without a capability apply to all events.
+fn applies_to(capability: &Option<Capability>, event: &NativeEvent) -> bool {
+    match capability {
+        Some(capability) => *capability == event.as_capability(),
+        None => true,
+    }
+}
+
+/// Sets values that are within the deadzone to zero and rescales the
+/// remaining range so values still reach 1.0.
+#[derive(Debug)]
+pub struct DeadzoneFilter {
+    capability: Option<Capability>,
+    deadzone: f64,
+}
+
+impl DeadzoneFilter {
+    pub fn new(capability: Option<Capability>, deadzone: f64) -> Self {
+        Self {
+            capability,
+            deadzone: deadzone.clamp(0.0, 0.99),
+        }
+    }
+
+    fn apply(&self, value: f64) -> f64 {
+        if value.abs() < self.deadzone {
+            return 0.0;
+        }
+        (value.abs() - self.deadzone) / (1.0 - self.deadzone) * value.signum()
+    }
+}
+
+impl SourceDeviceFilter for DeadzoneFilter {
+    fn filter(&mut self, _device_id: &str, event: &mut NativeEvent) -> FilterResult {
+        if !applies_to(&self.capability, event) {
+            return FilterResult::Pass;
+        }
+        let value = match event.get_value() {
+            InputValue::Float(value) => InputValue::Float(self.apply(value)),
+            InputValue::Vector2 { x, y } => InputValue::Vector2 {
+                x: x.map(|x| self.apply(x)),
+                y: y.map(|y| self.apply(y)),
+            },
+            _ => return FilterResult::Pass,
+        };
+        event.set_value(value);
+        FilterResult::Modify
+    }
+}
+
+/// Drops analog events that changed less than the threshold since the last
+/// event that was let through. This reduces jitter from noisy axes. Events
+/// that return an axis to zero are never dropped.
+#[derive(Debug)]
+pub struct ThresholdFilter {
+    capability: Option<Capability>,
+    threshold: f64,
+    last_values: HashMap<Capability, [Option<f64>; 3]>,
+}
+
+impl ThresholdFilter {
+    pub fn new(capability: Option<Capability>, threshold: f64) -> Self {
+        Self {
+            capability,
+            threshold,
+            last_values: HashMap::new(),
+        }
+    }
+}
+
+impl SourceDeviceFilter for ThresholdFilter {
+    fn filter(&mut self, _device_id: &str, event: &mut NativeEvent) -> FilterResult {
+        if !applies_to(&self.capability, event) {
+            return FilterResult::Pass;
+        }
+        let values = match event.get_value() {
+            InputValue::Float(value) => [Some(value), None, None],
+            InputValue::Vector2 { x, y } => [x, y, None],
+            InputValue::Vector3 { x, y, z } => [x, y, z],
+            _ => return FilterResult::Pass,
+        };
+
+        let threshold = self.threshold;
+        let has_changed = |value: &Option<f64>, last: &Option<f64>| match (value, last) {
+            (Some(value), Some(last)) => *value == 0.0 || (value - last).abs() >= threshold,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let last_values = self
+            .last_values
+            .entry(event.as_capability())
+            .or_insert([None; 3]);
+        let is_significant = values
+            .iter()
+            .zip(last_values.iter())
+            .any(|(value, last)| has_changed(value, last));
+        if !is_significant {
+            return FilterResult::Drop;
+        }
+
+        for (value, last) in values.iter().zip(last_values.iter_mut()) {
+            if value.is_some() {
+                *last = *value;
+            }
+        }
+        FilterResult::Pass
+    }
+}
+
+/// Inverts the selected axes of analog events
+#[derive(Debug)]
+pub struct InvertFilter {
+    capability: Option<Capability>,
+    x: bool,
+    y: bool,
+    z: bool,
+}
+
+impl InvertFilter {
+    pub fn new(capability: Option<Capability>, x: bool, y: bool, z: bool) -> Self {
+        Self {
+            capability,
+            x,
+            y,
+            z,
+        }
+    }
+}
+
+impl SourceDeviceFilter for InvertFilter {
+    fn filter(&mut self, _device_id: &str, event: &mut NativeEvent) -> FilterResult {
+        if !applies_to(&self.capability, event) {
+            return FilterResult::Pass;
+        }
+        let invert = |value: Option<f64>, enabled: bool| -> Option<f64> {
+            if enabled {
+                value.map(|v| -v)
+            } else {
+                value
+            }
+        };
+        let value = match event.get_value() {
+            InputValue::Float(value) if self.x => InputValue::Float(-value),
+            InputValue::Vector2 { x, y } => InputValue::Vector2 {
+                x: invert(x, self.x),
+                y: invert(y, self.y),
+            },
+            InputValue::Vector3 { x, y, z } => InputValue::Vector3 {
+                x: invert(x, self.x),
+                y: invert(y, self.y),
+                z: invert(z, self.z),
+            },
+            _ => return FilterResult::Pass,
+        };
+        event.set_value(value);
+        FilterResult::Modify
+    }
+}
+
+/// Token bucket that allows up to a maximum number of events per second
+#[derive(Debug)]
+pub struct TokenBucket {
+    /// Time it takes to refill one token
+    interval: Duration,
+    /// Time the bucket was last full, which is used to calculate the number
+    /// of available tokens.
+    full_at: Option<Instant>,
+}
+
+impl TokenBucket {
+    pub fn new(max_hz: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / max_hz.max(1),
+            full_at: None,
+        }
+    }
+
+    /// Take a token from the bucket at the given time. Returns false if no
+    /// token was available.
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        match self.full_at {
+            Some(full_at) if now < full_at => false,
+            _ => {
+                self.full_at = Some(now + self.interval);
+                true
+            }
+        }
+    }
+
+    /// Returns the time the next token will be available. Returns None if a
+    /// token is available now.
+    pub fn next_token_at(&self, now: Instant) -> Option<Instant> {
+        self.full_at.filter(|full_at| now < *full_at)
+    }
+}
+
+/// Returns the given new value with any missing components of vector values
+/// taken from the given old value
+fn merge_values(old: InputValue, new: InputValue) -> InputValue {
+    match (old, new) {
+        (InputValue::Vector2 { x: old_x, y: old_y }, InputValue::Vector2 { x, y }) => {
+            InputValue::Vector2 {
+                x: x.or(old_x),
+                y: y.or(old_y),
+            }
+        }
+        (
+            InputValue::Vector3 {
+                x: old_x,
+                y: old_y,
+                z: old_z,
+            },
+            InputValue::Vector3 { x, y, z },
+        ) => InputValue::Vector3 {
+            x: x.or(old_x),
+            y: y.or(old_y),
+            z: z.or(old_z),
+        },
+        (_, value) => value,
+    }
+}
+
+/// Drops events that exceed the maximum rate for their capability. Each
+/// source device has its own rate limit for each capability. The latest
+/// dropped event is held back and can be taken with [RateLimitFilter::take_ready]
+/// once the rate limit allows it, so the final value of a capability is
+/// never lost. Events with boolean or touch values are never rate limited,
+/// since every press and release must be processed.
+#[derive(Debug, Default)]
+pub struct RateLimitFilter {
+    /// Maximum event rate in Hz for capabilities without their own limit
+    default_max_hz: Option<u32>,
+    /// Maximum event rate in Hz for each capability
+    limits: HashMap<Capability, u32>,
+    buckets: HashMap<(String, Capability), TokenBucket>,
+    /// Latest dropped event of each source device and capability
+    suppressed: HashMap<(String, Capability), NativeEvent>,
+}
+
+impl RateLimitFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new rate limit filter that limits events of all capabilities
+    /// to the given rate.
+    pub fn with_max_hz(max_hz: u32) -> Self {
+        Self {
+            default_max_hz: Some(max_hz),
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if no rate limits are set
+    pub fn is_empty(&self) -> bool {
+        self.default_max_hz.is_none() && self.limits.is_empty()
+    }
+
+    /// Set the maximum event rate for the given capability. A rate of zero
+    /// removes the rate limit.
+    pub fn set_limit(&mut self, capability: Capability, max_hz: u32) {
+        self.buckets.retain(|(_, cap), _| cap != &capability);
+        self.suppressed.retain(|(_, cap), _| cap != &capability);
+        if max_hz == 0 {
+            self.limits.remove(&capability);
+        } else {
+            self.limits.insert(capability, max_hz);
+        }
+    }
+
+    /// Remove the rate limit state of the given source device
+    pub fn remove_device(&mut self, device_id: &str) {
+        self.buckets.retain(|(id, _), _| id != device_id);
+        self.suppressed.retain(|(id, _), _| id != device_id);
+    }
+
+    /// Returns the time the next held back event can be taken with
+    /// [RateLimitFilter::take_ready]. Returns None if no events are held back.
+    pub fn next_ready_at(&self, now: Instant) -> Option<Instant> {
+        self.suppressed
+            .keys()
+            .map(|key| {
+                self.buckets
+                    .get(key)
+                    .and_then(|bucket| bucket.next_token_at(now))
+                    .unwrap_or(now)
+            })
+            .min()
+    }
+
+    /// Returns the held back events that the rate limit allows at the given
+    /// time, along with the id of their source device
+    pub fn take_ready(&mut self, now: Instant) -> Vec<(String, NativeEvent)> {
+        let ready: Vec<(String, Capability)> = self
+            .suppressed
+            .keys()
+            .filter(|key| {
+                self.buckets
+                    .get(*key)
+                    .is_none_or(|bucket| bucket.next_token_at(now).is_none())
+            })
+            .cloned()
+            .collect();
+
+        let mut events = Vec::with_capacity(ready.len());
+        for key in ready {
+            let Some(event) = self.suppressed.remove(&key) else {
+                continue;
+            };
+            if let Some(bucket) = self.buckets.get_mut(&key) {
+                bucket.try_take(now);
+            }
+            events.push((key.0, event));
+        }
+
+        events
+    }
+}
+
+impl SourceDeviceFilter for RateLimitFilter {
+    fn filter(&mut self, device_id: &str, event: &mut NativeEvent) -> FilterResult {
+        let value = event.get_value();
+        if matches!(value, InputValue::Bool(_) | InputValue::Touch { .. }) {
+            return FilterResult::Pass;
+        }
+
+        let capability = event.as_capability();
+        let Some(max_hz) = self
+            .limits
+            .get(&capability)
+            .copied()
+            .or(self.default_max_hz)
+        else {
+            return FilterResult::Pass;
+        };
+        let key = (device_id.to_string(), capability);
+        let bucket = self
+            .buckets
+            .entry(key.clone())
+            .or_insert_with(|| TokenBucket::new(max_hz));
+        let has_token = bucket.try_take(Instant::now());
+
+        // Keep components of vector values from a held back event that were
+        // not updated by this event
+        let suppressed = self.suppressed.remove(&key);
+        let merged = suppressed.map(|suppressed| merge_values(suppressed.get_value(), value));
+
+        if !has_token {
+            let mut event = event.clone();
+            if let Some(value) = merged {
+                event.set_value(value);
+            }
+            self.suppressed.insert(key, event);
+            return FilterResult::Drop;
+        }
+        match merged {
+            Some(value) => {
+                event.set_value(value);
+                FilterResult::Modify
+            }
+            None => FilterResult::Pass,
+        }
+    }
+}
+
+/// Accumulates events from a source device over a time window and only keeps
+/// the latest value of each capability, so bursts of high frequency events
+/// (e.g. from an IMU) are sent to the composite device as a single event.
+/// Events with boolean or touch values are never coalesced, since every
+/// press and release must be processed.
+#[derive(Debug)]
+pub struct EventCoalescer {
+    window: Duration,
+    /// Latest pending event of each capability in the order the capabilities
+    /// were first seen in the current window
+    pending: Vec<NativeEvent>,
+    /// Time the first pending event was added
+    window_start: Option<Instant>,
+}
+
+impl EventCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+            window_start: None,
+        }
+    }
+
+    /// Add the given event to the pending events, replacing the pending event
+    /// with the same capability. Missing components of vector values are
+    /// taken from the replaced event. Returns the event if it cannot be
+    /// coalesced and should be sent immediately.
+    pub fn push(&mut self, event: NativeEvent, now: Instant) -> Option<NativeEvent> {
+        let value = event.get_value();
+        if matches!(value, InputValue::Bool(_) | InputValue::Touch { .. }) {
+            return Some(event);
+        }
+
+        let capability = event.as_capability();
+        let pending = self
+            .pending
+            .iter_mut()
+            .find(|pending| pending.as_capability() == capability);
+        let Some(pending) = pending else {
+            self.window_start.get_or_insert(now);
+            self.pending.push(event);
+            return None;
+        };
+
+        let mut event = event;
+        let value = merge_values(pending.get_value(), value);
+        event.set_value(value);
+        *pending = event;
+
+        None
+    }
+
+    /// Returns all pending events if the coalesce window has elapsed since
+    /// the first pending event was added
+    pub fn flush(&mut self, now: Instant) -> Vec<NativeEvent> {
+        let Some(window_start) = self.window_start else {
+            return Vec::new();
+        };
+        if now.duration_since(window_start) < self.window {
+            return Vec::new();
+        }
+        self.window_start = None;
+        std::mem::take(&mut self.pending)
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    event::{native::NativeEvent, value::InputValue},
+    filter::{
+        DeadzoneFilter, EventCoalescer, FilterResult, InvertFilter, RateLimitFilter,
+        SourceDeviceFilter, ThresholdFilter, TokenBucket,
+    },
+};
+
+const DEVICE_ID: &str = "evdev://event0";
+
+fn stick_event(x: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: None,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_deadzone_filter() {
+    let mut filter = DeadzoneFilter::new(None, 0.2);
+
+    let mut event = stick_event(0.1);
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Modify);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(0.0));
+    assert_eq!(y, None);
+
+    // Values outside of the deadzone are rescaled to still reach 1.0
+    let mut event = stick_event(-1.0);
+    filter.filter(DEVICE_ID, &mut event);
+    let InputValue::Vector2 { x, .. } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(-1.0));
+}
+
+#[tokio::test]
+async fn test_threshold_filter() {
+    let mut filter = ThresholdFilter::new(None, 0.1);
+
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.5)),
+        FilterResult::Pass
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.55)),
+        FilterResult::Drop
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.65)),
+        FilterResult::Pass
+    );
+
+    // Returning to zero is never dropped
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.6)),
+        FilterResult::Drop
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.0)),
+        FilterResult::Pass
+    );
+}
+
+#[tokio::test]
+async fn test_invert_filter() {
+    let trigger = Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger));
+    let mut filter = InvertFilter::new(Some(trigger.clone()), true, true, true);
+
+    // Only events for the configured capability are inverted
+    let mut event = stick_event(0.5);
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Pass);
+
+    let mut event = NativeEvent::new(trigger, InputValue::Float(0.5));
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Modify);
+    let InputValue::Float(value) = event.get_value() else {
+        panic!("Expected Float value");
+    };
+    assert_eq!(value, -0.5);
+}
+
+#[tokio::test]
+async fn test_token_bucket() {
+    let mut bucket = TokenBucket::new(10);
+    let now = Instant::now();
+    assert!(bucket.try_take(now));
+    assert!(!bucket.try_take(now + Duration::from_millis(50)));
+    assert!(bucket.try_take(now + Duration::from_millis(100)));
+}
+
+#[tokio::test]
+async fn test_rate_limit_filter() {
+    let mut filter = RateLimitFilter::new();
+    let trigger = Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger));
+    filter.set_limit(trigger.clone(), 1);
+
+    let mut event = NativeEvent::new(trigger.clone(), InputValue::Float(0.5));
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Pass);
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Drop);
+
+    // Each source device and capability is limited separately
+    assert_eq!(
+        filter.filter("evdev://event1", &mut event),
+        FilterResult::Pass
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.5)),
+        FilterResult::Pass
+    );
+
+    // Removing the limit lets all events through
+    filter.set_limit(trigger, 0);
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Pass);
+    assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Pass);
+}
+
+#[tokio::test]
+async fn test_rate_limit_held_back_events() {
+    let mut filter = RateLimitFilter::with_max_hz(1);
+    let stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+
+    // Button presses and releases are never rate limited
+    let button = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    for pressed in [true, false, true, false] {
+        let mut event = NativeEvent::new(button.clone(), InputValue::Bool(pressed));
+        assert_eq!(filter.filter(DEVICE_ID, &mut event), FilterResult::Pass);
+    }
+
+    // The latest dropped event is held back until the rate limit allows it
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.5)),
+        FilterResult::Pass
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.8)),
+        FilterResult::Drop
+    );
+    assert_eq!(
+        filter.filter(DEVICE_ID, &mut stick_event(0.0)),
+        FilterResult::Drop
+    );
+    let now = Instant::now();
+    assert!(filter.take_ready(now).is_empty());
+    let ready_at = filter.next_ready_at(now).unwrap();
+    assert!(ready_at > now);
+
+    let events = filter.take_ready(ready_at);
+    assert_eq!(events.len(), 1);
+    let (device_id, event) = &events[0];
+    assert_eq!(device_id, DEVICE_ID);
+    assert_eq!(event.as_capability(), stick);
+    let InputValue::Vector2 { x, .. } = event.get_value() else {
+        panic!("Expected a vector value");
+    };
+    assert_eq!(x, Some(0.0));
+    assert!(filter.next_ready_at(ready_at).is_none());
+}
+
+#[tokio::test]
+async fn test_event_coalescer() {
+    let mut coalescer = EventCoalescer::new(Duration::from_millis(4));
+    let start = Instant::now();
+    let gyro = Capability::Gamepad(Gamepad::Gyro);
+    let gyro_event = |x: Option<f64>, y: Option<f64>| {
+        NativeEvent::new(gyro.clone(), InputValue::Vector3 { x, y, z: Some(0.0) })
+    };
+
+    // Events are held until the window elapses
+    assert!(coalescer
+        .push(gyro_event(Some(1.0), Some(1.0)), start)
+        .is_none());
+    assert!(coalescer
+        .push(
+            gyro_event(Some(2.0), None),
+            start + Duration::from_millis(1)
+        )
+        .is_none());
+    assert!(coalescer.flush(start + Duration::from_millis(2)).is_empty());
+
+    // Only the latest value is sent, keeping components that were not updated
+    let events = coalescer.flush(start + Duration::from_millis(4));
+    assert_eq!(events.len(), 1);
+    let InputValue::Vector3 { x, y, .. } = events[0].get_value() else {
+        panic!("Expected a vector value");
+    };
+    assert_eq!((x, y), (Some(2.0), Some(1.0)));
+    assert!(coalescer
+        .flush(start + Duration::from_millis(10))
+        .is_empty());
+
+    // Button events are never coalesced
+    let button = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        InputValue::Bool(true),
+    );
+    assert!(coalescer.push(button, start).is_some());
+}
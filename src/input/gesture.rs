@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use super::{
+    capability::{Capability, Gesture, PinchDirection, SwipeDirection},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Normalized distance all fingers must travel before a swipe is recognized
+const SWIPE_THRESHOLD: f64 = 0.15;
+/// Normalized change in distance between two fingers before a pinch is
+/// recognized
+const PINCH_THRESHOLD: f64 = 0.1;
+
+/// An update to a single multi-touch slot
+#[derive(Debug, Clone, Copy)]
+pub enum SlotUpdate {
+    /// Whether or not a finger is touching in the slot
+    Touch(bool),
+    /// The X position of the touch, normalized between 0.0-1.0, where 0 is
+    /// the left side of the input device and where 1.0 is the right side
+    PositionX(f64),
+    /// The Y position of the touch, normalized between 0.0-1.0, where 0 is
+    /// the top side of the input device and where 1.0 is the bottom side
+    PositionY(f64),
+}
+
+/// Current state of a single multi-touch slot
+#[derive(Debug, Default, Clone, Copy)]
+struct Slot {
+    is_touching: bool,
+    x: f64,
+    y: f64,
+}
+
+/// Recognizes multi-touch gestures from a stream of multi-touch slot updates.
+/// Slot updates are accumulated with [TouchpadGestureRecognizer::update] and
+/// evaluated once per input frame with [TouchpadGestureRecognizer::sync].
+/// Only a single gesture is recognized per touch, so fingers must be lifted
+/// before another gesture can be recognized.
+#[derive(Debug, Default)]
+pub struct TouchpadGestureRecognizer {
+    slots: HashMap<usize, Slot>,
+    /// Position of each touching slot when the current gesture started
+    start_positions: HashMap<usize, (f64, f64)>,
+    /// Whether or not a gesture was already recognized for the current touch
+    recognized: bool,
+}
+
+impl TouchpadGestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the capabilities of all gestures that can be recognized
+    pub fn capabilities() -> Vec<Capability> {
+        Gesture::all()
+            .into_iter()
+            .map(Capability::Gesture)
+            .collect()
+    }
+
+    /// Update the state of the given multi-touch slot
+    pub fn update(&mut self, slot: usize, update: SlotUpdate) {
+        let state = self.slots.entry(slot).or_default();
+        match update {
+            SlotUpdate::Touch(is_touching) => state.is_touching = is_touching,
+            SlotUpdate::PositionX(x) => state.x = x,
+            SlotUpdate::PositionY(y) => state.y = y,
+        }
+    }
+
+    /// Evaluate all slot updates since the last frame and return any gesture
+    /// events that were recognized. Gestures are emitted as a press and
+    /// release of the gesture capability.
+    pub fn sync(&mut self) -> Vec<NativeEvent> {
+        let mut touching: Vec<(usize, Slot)> = self
+            .slots
+            .iter()
+            .filter(|(_, state)| state.is_touching)
+            .map(|(slot, state)| (*slot, *state))
+            .collect();
+        touching.sort_by_key(|(slot, _)| *slot);
+
+        // Restart gesture tracking whenever fingers are added or removed
+        let is_same_touch = touching.len() == self.start_positions.len()
+            && touching
+                .iter()
+                .all(|(slot, _)| self.start_positions.contains_key(slot));
+        if !is_same_touch {
+            self.start_positions = touching
+                .iter()
+                .map(|(slot, state)| (*slot, (state.x, state.y)))
+                .collect();
+            if touching.is_empty() {
+                self.recognized = false;
+            }
+            return vec![];
+        }
+
+        if self.recognized {
+            return vec![];
+        }
+        let Some(gesture) = self.recognize(touching.as_slice()) else {
+            return vec![];
+        };
+        log::debug!("Recognized gesture: {gesture:?}");
+        self.recognized = true;
+
+        let capability = Capability::Gesture(gesture);
+        vec![
+            NativeEvent::new(capability.clone(), InputValue::Bool(true)),
+            NativeEvent::new(capability, InputValue::Bool(false)),
+        ]
+    }
+
+    /// Returns the gesture performed by the given touching slots since the
+    /// gesture started, if any.
+    fn recognize(&self, touching: &[(usize, Slot)]) -> Option<Gesture> {
+        let finger_count = touching.len();
+        if finger_count != 2 && finger_count != 3 {
+            return None;
+        }
+
+        // Calculate the average movement of all fingers
+        let mut delta_x = 0.0;
+        let mut delta_y = 0.0;
+        for (slot, state) in touching {
+            let (start_x, start_y) = self.start_positions.get(slot)?;
+            delta_x += state.x - start_x;
+            delta_y += state.y - start_y;
+        }
+        delta_x /= finger_count as f64;
+        delta_y /= finger_count as f64;
+        let distance_moved = delta_x.hypot(delta_y);
+
+        // Two fingers moving apart or together more than they move in the
+        // same direction is a pinch.
+        if finger_count == 2 {
+            let (slot_a, state_a) = touching[0];
+            let (slot_b, state_b) = touching[1];
+            let (start_ax, start_ay) = self.start_positions.get(&slot_a)?;
+            let (start_bx, start_by) = self.start_positions.get(&slot_b)?;
+            let start_distance = (start_ax - start_bx).hypot(start_ay - start_by);
+            let distance = (state_a.x - state_b.x).hypot(state_a.y - state_b.y);
+            let pinch = distance - start_distance;
+            if pinch.abs() > PINCH_THRESHOLD && pinch.abs() > distance_moved {
+                let direction = if pinch < 0.0 {
+                    PinchDirection::In
+                } else {
+                    PinchDirection::Out
+                };
+                return Some(Gesture::TwoFingerPinch(direction));
+            }
+        }
+
+        if distance_moved < SWIPE_THRESHOLD {
+            return None;
+        }
+        let direction = if delta_x.abs() > delta_y.abs() {
+            if delta_x > 0.0 {
+                SwipeDirection::Right
+            } else {
+                SwipeDirection::Left
+            }
+        } else if delta_y > 0.0 {
+            SwipeDirection::Down
+        } else {
+            SwipeDirection::Up
+        };
+
+        if finger_count == 2 {
+            Some(Gesture::TwoFingerSwipe(direction))
+        } else {
+            Some(Gesture::ThreeFingerSwipe(direction))
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::input::{
+    capability::{Capability, Gesture, PinchDirection, SwipeDirection},
+    gesture::{SlotUpdate, TouchpadGestureRecognizer},
+};
+
+/// Move the given slot to the given position
+fn move_slot(recognizer: &mut TouchpadGestureRecognizer, slot: usize, x: f64, y: f64) {
+    recognizer.update(slot, SlotUpdate::PositionX(x));
+    recognizer.update(slot, SlotUpdate::PositionY(y));
+}
+
+/// Touch the given slots at the given positions and sync the frame
+fn touch(recognizer: &mut TouchpadGestureRecognizer, positions: &[(f64, f64)]) {
+    for (slot, (x, y)) in positions.iter().enumerate() {
+        recognizer.update(slot, SlotUpdate::Touch(true));
+        move_slot(recognizer, slot, *x, *y);
+    }
+    assert!(recognizer.sync().is_empty());
+}
+
+#[tokio::test]
+async fn test_two_finger_swipe() {
+    let mut recognizer = TouchpadGestureRecognizer::new();
+    touch(&mut recognizer, &[(0.2, 0.5), (0.3, 0.5)]);
+
+    // Small movements should not be recognized
+    move_slot(&mut recognizer, 0, 0.25, 0.5);
+    move_slot(&mut recognizer, 1, 0.35, 0.5);
+    assert!(recognizer.sync().is_empty());
+
+    move_slot(&mut recognizer, 0, 0.5, 0.5);
+    move_slot(&mut recognizer, 1, 0.6, 0.5);
+    let events = recognizer.sync();
+    let expected = Capability::Gesture(Gesture::TwoFingerSwipe(SwipeDirection::Right));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].as_capability(), expected);
+    assert!(events[0].pressed());
+    assert!(!events[1].pressed());
+
+    // Only one gesture is recognized until all fingers are lifted
+    move_slot(&mut recognizer, 0, 0.8, 0.5);
+    move_slot(&mut recognizer, 1, 0.9, 0.5);
+    assert!(recognizer.sync().is_empty());
+}
+
+#[tokio::test]
+async fn test_two_finger_pinch() {
+    let mut recognizer = TouchpadGestureRecognizer::new();
+    touch(&mut recognizer, &[(0.4, 0.5), (0.6, 0.5)]);
+
+    move_slot(&mut recognizer, 0, 0.2, 0.5);
+    move_slot(&mut recognizer, 1, 0.8, 0.5);
+    let events = recognizer.sync();
+    let expected = Capability::Gesture(Gesture::TwoFingerPinch(PinchDirection::Out));
+    assert_eq!(events[0].as_capability(), expected);
+}
+
+#[tokio::test]
+async fn test_three_finger_swipe() {
+    let mut recognizer = TouchpadGestureRecognizer::new();
+    touch(&mut recognizer, &[(0.3, 0.8), (0.4, 0.8), (0.5, 0.8)]);
+
+    move_slot(&mut recognizer, 0, 0.3, 0.4);
+    move_slot(&mut recognizer, 1, 0.4, 0.4);
+    move_slot(&mut recognizer, 2, 0.5, 0.4);
+    let events = recognizer.sync();
+    let expected = Capability::Gesture(Gesture::ThreeFingerSwipe(SwipeDirection::Up));
+    assert_eq!(events[0].as_capability(), expected);
+
+    // Lifting all fingers allows another gesture to be recognized
+    for slot in 0..3 {
+        recognizer.update(slot, SlotUpdate::Touch(false));
+    }
+    assert!(recognizer.sync().is_empty());
+    touch(&mut recognizer, &[(0.3, 0.4), (0.4, 0.4), (0.5, 0.4)]);
+    move_slot(&mut recognizer, 0, 0.3, 0.8);
+    move_slot(&mut recognizer, 1, 0.4, 0.8);
+    move_slot(&mut recognizer, 2, 0.5, 0.8);
+    let events = recognizer.sync();
+    let expected = Capability::Gesture(Gesture::ThreeFingerSwipe(SwipeDirection::Down));
+    assert_eq!(events[0].as_capability(), expected);
+}
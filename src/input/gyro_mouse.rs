@@ -0,0 +1,131 @@
+use std::time::Instant;
+
+use super::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Configuration for translating gyro motion into mouse motion
+#[derive(Debug, Clone, PartialEq)]
+pub struct GyroMouseConfig {
+    /// Number of pixels the mouse moves horizontally per degree of yaw
+    pub sensitivity_x: f64,
+    /// Number of pixels the mouse moves vertically per degree of pitch
+    pub sensitivity_y: f64,
+    /// Time constant of the exponential moving average used to smooth out
+    /// gyro noise. A value of 0 disables smoothing.
+    pub smoothing_window_ms: u32,
+    /// Optional button that must be held for gyro motion to move the mouse.
+    /// If not set, gyro motion always moves the mouse.
+    pub activation_button: Option<Capability>,
+}
+
+/// Translates gyro angular velocity into relative mouse motion
+#[derive(Debug, Default)]
+pub struct GyroMouseEmulator {
+    config: Option<GyroMouseConfig>,
+    /// Whether or not the activation button is currently held
+    is_activated: bool,
+    /// Smoothed angular velocity in degrees per second as (pitch, yaw)
+    velocity: (f64, f64),
+    /// Time of the last gyro event
+    last_time: Option<Instant>,
+    /// Fractional pixels that were not yet emitted
+    remainder: (f64, f64),
+}
+
+impl GyroMouseEmulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current configuration, if gyro mouse emulation is enabled
+    pub fn config(&self) -> Option<GyroMouseConfig> {
+        self.config.clone()
+    }
+
+    /// Set the configuration or disable emulation if [None], and reset the
+    /// tracked gyro state
+    pub fn set_config(&mut self, config: Option<GyroMouseConfig>) {
+        self.config = config;
+        self.is_activated = false;
+        self.reset();
+    }
+
+    /// Returns true if gyro motion is being translated into mouse motion
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    /// Returns true if the given capability is the configured activation button
+    pub fn is_activation_button(&self, capability: &Capability) -> bool {
+        let Some(config) = self.config.as_ref() else {
+            return false;
+        };
+        config.activation_button.as_ref() == Some(capability)
+    }
+
+    /// Update the activation state from the given activation button value
+    pub fn set_activated(&mut self, value: &InputValue) {
+        self.is_activated = value.pressed();
+        if !self.is_activated {
+            self.reset();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.velocity = (0.0, 0.0);
+        self.last_time = None;
+        self.remainder = (0.0, 0.0);
+    }
+
+    /// Update the smoothed angular velocity from the given gyro value and
+    /// return a mouse motion event with the relative motion since the last
+    /// gyro event, if the mouse moved. Yaw moves the mouse horizontally and
+    /// pitch moves it vertically.
+    pub fn update(&mut self, value: &InputValue, now: Instant) -> Option<NativeEvent> {
+        let config = self.config.as_ref()?;
+        if config.activation_button.is_some() && !self.is_activated {
+            return None;
+        }
+        let InputValue::Vector3 { x, y, .. } = value else {
+            return None;
+        };
+        let pitch = x.unwrap_or(self.velocity.0);
+        let yaw = y.unwrap_or(self.velocity.1);
+
+        // The first event only sets the starting time
+        let last_time = self.last_time.replace(now);
+        let Some(last_time) = last_time else {
+            self.velocity = (pitch, yaw);
+            return None;
+        };
+        let elapsed = now.duration_since(last_time).as_secs_f64();
+
+        // Smooth the angular velocity with an exponential moving average
+        let window = config.smoothing_window_ms as f64 / 1000.0;
+        let alpha = if window > 0.0 {
+            elapsed / (window + elapsed)
+        } else {
+            1.0
+        };
+        self.velocity.0 += (pitch - self.velocity.0) * alpha;
+        self.velocity.1 += (yaw - self.velocity.1) * alpha;
+
+        // Accumulate fractional pixels so slow motion is not lost. Turning
+        // left or tilting up moves the mouse left or up.
+        let pixels_x = -self.velocity.1 * elapsed * config.sensitivity_x + self.remainder.0;
+        let pixels_y = -self.velocity.0 * elapsed * config.sensitivity_y + self.remainder.1;
+        self.remainder = (pixels_x.fract(), pixels_y.fract());
+        let (pixels_x, pixels_y) = (pixels_x.trunc(), pixels_y.trunc());
+        if pixels_x == 0.0 && pixels_y == 0.0 {
+            return None;
+        }
+
+        let value = InputValue::Vector2 {
+            x: Some(pixels_x),
+            y: Some(pixels_y),
+        };
+        Some(NativeEvent::new(Capability::Mouse(Mouse::Motion), value))
+    }
+}
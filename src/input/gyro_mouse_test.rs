@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    event::value::InputValue,
+    gyro_mouse::{GyroMouseConfig, GyroMouseEmulator},
+};
+
+fn gyro(pitch: f64, yaw: f64) -> InputValue {
+    InputValue::Vector3 {
+        x: Some(pitch),
+        y: Some(yaw),
+        z: Some(0.0),
+    }
+}
+
+fn config(smoothing_window_ms: u32, activation_button: Option<Capability>) -> GyroMouseConfig {
+    GyroMouseConfig {
+        sensitivity_x: 10.0,
+        sensitivity_y: 10.0,
+        smoothing_window_ms,
+        activation_button,
+    }
+}
+
+/// Returns the x and y motion of the given mouse motion event
+fn motion(emulator: &mut GyroMouseEmulator, value: InputValue, now: Instant) -> (f64, f64) {
+    let Some(event) = emulator.update(&value, now) else {
+        return (0.0, 0.0);
+    };
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    (x.unwrap_or_default(), y.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_gyro_mouse() {
+    let mut emulator = GyroMouseEmulator::new();
+    let start = Instant::now();
+    assert!(emulator.update(&gyro(0.0, 10.0), start).is_none());
+
+    emulator.set_config(Some(config(0, None)));
+
+    // The first event only sets the starting time
+    assert!(emulator.update(&gyro(0.0, 10.0), start).is_none());
+
+    // Turning left for one second moves the mouse left
+    let (x, y) = motion(
+        &mut emulator,
+        gyro(0.0, 10.0),
+        start + Duration::from_secs(1),
+    );
+    assert_eq!(x, -100.0);
+    assert_eq!(y, 0.0);
+}
+
+#[tokio::test]
+async fn test_gyro_mouse_smoothing() {
+    let mut emulator = GyroMouseEmulator::new();
+    emulator.set_config(Some(config(1000, None)));
+    let start = Instant::now();
+
+    // A sudden rotation is smoothed out over the smoothing window
+    emulator.update(&gyro(0.0, 0.0), start);
+    let (x, _) = motion(
+        &mut emulator,
+        gyro(0.0, 10.0),
+        start + Duration::from_secs(1),
+    );
+    assert_eq!(x, -50.0);
+}
+
+#[tokio::test]
+async fn test_gyro_mouse_activation_button() {
+    let button = Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper));
+    let mut emulator = GyroMouseEmulator::new();
+    emulator.set_config(Some(config(0, Some(button.clone()))));
+    let start = Instant::now();
+    assert!(emulator.is_activation_button(&button));
+
+    // Gyro motion is ignored until the activation button is held
+    emulator.update(&gyro(0.0, 10.0), start);
+    let next = start + Duration::from_secs(1);
+    assert!(emulator.update(&gyro(0.0, 10.0), next).is_none());
+
+    emulator.set_activated(&InputValue::Bool(true));
+    emulator.update(&gyro(10.0, 0.0), next);
+    let (_, y) = motion(
+        &mut emulator,
+        gyro(10.0, 0.0),
+        next + Duration::from_secs(1),
+    );
+    assert_eq!(y, -100.0);
+}
@@ -0,0 +1,493 @@
+//! Embedded XKB keymap lookup used to translate XKB keysym names (e.g.
+//! "udiaeresis") into the key codes that produce them with the keyboard
+//! layout of the system. Only the printable keys of a small set of common
+//! layouts are embedded, which is enough for on-screen keyboard input.
+use std::{collections::HashMap, fs};
+
+use super::capability::Keyboard;
+
+/// Layout that is used if no layout is configured on the system
+pub const DEFAULT_LAYOUT: &str = "us";
+
+/// Configuration files that can define the XKB layout of the system
+const LAYOUT_CONFIG_PATHS: &[&str] = &["/etc/default/keyboard", "/etc/vconsole.conf"];
+
+/// Key code and modifiers that need to be pressed to emit a keysym
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysymMapping {
+    /// Key that emits the keysym
+    pub key: Keyboard,
+    /// Whether shift needs to be held
+    pub shift: bool,
+    /// Whether AltGr (ISO Level 3 shift) needs to be held
+    pub altgr: bool,
+}
+
+impl KeysymMapping {
+    fn new(key: Keyboard, shift: bool, altgr: bool) -> Self {
+        Self { key, shift, altgr }
+    }
+}
+
+/// Keymap that maps XKB keysym names to key codes
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    layout: String,
+    keysyms: HashMap<String, KeysymMapping>,
+}
+
+impl Keymap {
+    /// Create a keymap for the given XKB layout and optional variant. Unknown
+    /// layouts fall back to [DEFAULT_LAYOUT].
+    pub fn new(layout: &str, variant: Option<&str>) -> Self {
+        let (layout, printable) = match (layout, variant.unwrap_or_default()) {
+            ("us", "dvorak") | ("dvorak", _) => ("us(dvorak)", LAYOUT_US_DVORAK),
+            ("us", _) => ("us", LAYOUT_US),
+            ("de", _) => ("de", LAYOUT_DE),
+            ("jp", _) => ("jp", LAYOUT_JP),
+            (layout, _) => {
+                log::warn!(
+                    "Unsupported keyboard layout '{layout}', falling back to '{DEFAULT_LAYOUT}'"
+                );
+                ("us", LAYOUT_US)
+            }
+        };
+
+        let mut keysyms = HashMap::new();
+        for (keysym, key) in LAYOUT_COMMON {
+            keysyms.insert(
+                keysym.to_string(),
+                KeysymMapping::new(key.clone(), false, false),
+            );
+        }
+
+        // Letters are in the same position on most layouts, so they are
+        // added first and can be overridden by the layout itself.
+        if layout != "us(dvorak)" {
+            for (letter, key) in LETTERS {
+                add_letter(&mut keysyms, *letter, key.clone());
+            }
+        }
+        if layout == "de" {
+            add_letter(&mut keysyms, 'y', Keyboard::KeyZ);
+            add_letter(&mut keysyms, 'z', Keyboard::KeyY);
+        }
+
+        for (key, levels) in printable {
+            let [plain, shifted, altgr] = levels;
+            if let Some(keysym) = plain {
+                keysyms.insert(
+                    keysym.to_string(),
+                    KeysymMapping::new(key.clone(), false, false),
+                );
+            }
+            if let Some(keysym) = shifted {
+                keysyms.insert(
+                    keysym.to_string(),
+                    KeysymMapping::new(key.clone(), true, false),
+                );
+            }
+            if let Some(keysym) = altgr {
+                keysyms.insert(
+                    keysym.to_string(),
+                    KeysymMapping::new(key.clone(), false, true),
+                );
+            }
+        }
+
+        Self {
+            layout: layout.to_string(),
+            keysyms,
+        }
+    }
+
+    /// Create a keymap for the layout that is configured on the system. The
+    /// layout is read from the XKB_DEFAULT_LAYOUT/XKB_DEFAULT_VARIANT
+    /// environment variables, then from the system keyboard configuration.
+    pub fn from_system() -> Self {
+        if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+            let variant = std::env::var("XKB_DEFAULT_VARIANT").ok();
+            return Keymap::new(first_entry(&layout), variant.as_deref().map(first_entry));
+        }
+
+        for path in LAYOUT_CONFIG_PATHS {
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let Some(layout) = read_config_value(&content, "XKBLAYOUT") else {
+                continue;
+            };
+            let variant = read_config_value(&content, "XKBVARIANT");
+            log::debug!("Using keyboard layout '{layout}' from {path}");
+            return Keymap::new(first_entry(&layout), variant.as_deref().map(first_entry));
+        }
+
+        Keymap::new(DEFAULT_LAYOUT, None)
+    }
+
+    /// Returns the name of the layout of this keymap (e.g. "de")
+    pub fn layout(&self) -> &str {
+        self.layout.as_str()
+    }
+
+    /// Returns the key and modifiers that emit the given keysym
+    pub fn lookup(&self, keysym: &str) -> Option<KeysymMapping> {
+        self.keysyms.get(keysym).cloned()
+    }
+
+    /// Returns all keysyms that can be emitted with this keymap
+    pub fn keysyms(&self) -> Vec<String> {
+        let mut keysyms: Vec<String> = self.keysyms.keys().cloned().collect();
+        keysyms.sort();
+        keysyms
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::from_system()
+    }
+}
+
+/// Add the lower and upper case keysyms of the given letter
+fn add_letter(keysyms: &mut HashMap<String, KeysymMapping>, letter: char, key: Keyboard) {
+    keysyms.insert(
+        letter.to_string(),
+        KeysymMapping::new(key.clone(), false, false),
+    );
+    keysyms.insert(
+        letter.to_ascii_uppercase().to_string(),
+        KeysymMapping::new(key, true, false),
+    );
+}
+
+/// Returns the first entry of a comma separated XKB layout list
+fn first_entry(value: &str) -> &str {
+    value.split(',').next().unwrap_or_default().trim()
+}
+
+/// Read the value of the given key from a shell style configuration file
+/// (e.g. 'XKBLAYOUT="de"')
+fn read_config_value(content: &str, key: &str) -> Option<String> {
+    for line in content.lines() {
+        let Some((name, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        if name.trim() != key {
+            continue;
+        }
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        if value.is_empty() {
+            return None;
+        }
+        return Some(value.to_string());
+    }
+    None
+}
+
+/// Keysyms of non-printable keys that are the same on every layout
+const LAYOUT_COMMON: &[(&str, Keyboard)] = &[
+    ("space", Keyboard::KeySpace),
+    ("Return", Keyboard::KeyEnter),
+    ("Escape", Keyboard::KeyEsc),
+    ("BackSpace", Keyboard::KeyBackspace),
+    ("Tab", Keyboard::KeyTab),
+    ("Delete", Keyboard::KeyDelete),
+    ("Insert", Keyboard::KeyInsert),
+    ("Home", Keyboard::KeyHome),
+    ("End", Keyboard::KeyEnd),
+    ("Prior", Keyboard::KeyPageUp),
+    ("Next", Keyboard::KeyPageDown),
+    ("Up", Keyboard::KeyUp),
+    ("Down", Keyboard::KeyDown),
+    ("Left", Keyboard::KeyLeft),
+    ("Right", Keyboard::KeyRight),
+    ("Caps_Lock", Keyboard::KeyCapslock),
+    ("Shift_L", Keyboard::KeyLeftShift),
+    ("Shift_R", Keyboard::KeyRightShift),
+    ("Control_L", Keyboard::KeyLeftCtrl),
+    ("Control_R", Keyboard::KeyRightCtrl),
+    ("Alt_L", Keyboard::KeyLeftAlt),
+    ("Super_L", Keyboard::KeyLeftMeta),
+    ("Super_R", Keyboard::KeyRightMeta),
+    ("Menu", Keyboard::KeyCompose),
+    ("Print", Keyboard::KeySysrq),
+    ("Pause", Keyboard::KeyPause),
+    ("F1", Keyboard::KeyF1),
+    ("F2", Keyboard::KeyF2),
+    ("F3", Keyboard::KeyF3),
+    ("F4", Keyboard::KeyF4),
+    ("F5", Keyboard::KeyF5),
+    ("F6", Keyboard::KeyF6),
+    ("F7", Keyboard::KeyF7),
+    ("F8", Keyboard::KeyF8),
+    ("F9", Keyboard::KeyF9),
+    ("F10", Keyboard::KeyF10),
+    ("F11", Keyboard::KeyF11),
+    ("F12", Keyboard::KeyF12),
+];
+
+/// Letter keys in their QWERTY position
+const LETTERS: &[(char, Keyboard)] = &[
+    ('a', Keyboard::KeyA),
+    ('b', Keyboard::KeyB),
+    ('c', Keyboard::KeyC),
+    ('d', Keyboard::KeyD),
+    ('e', Keyboard::KeyE),
+    ('f', Keyboard::KeyF),
+    ('g', Keyboard::KeyG),
+    ('h', Keyboard::KeyH),
+    ('i', Keyboard::KeyI),
+    ('j', Keyboard::KeyJ),
+    ('k', Keyboard::KeyK),
+    ('l', Keyboard::KeyL),
+    ('m', Keyboard::KeyM),
+    ('n', Keyboard::KeyN),
+    ('o', Keyboard::KeyO),
+    ('p', Keyboard::KeyP),
+    ('q', Keyboard::KeyQ),
+    ('r', Keyboard::KeyR),
+    ('s', Keyboard::KeyS),
+    ('t', Keyboard::KeyT),
+    ('u', Keyboard::KeyU),
+    ('v', Keyboard::KeyV),
+    ('w', Keyboard::KeyW),
+    ('x', Keyboard::KeyX),
+    ('y', Keyboard::KeyY),
+    ('z', Keyboard::KeyZ),
+];
+
+/// Keysyms of a printable key at the plain, shifted and AltGr level
+type Levels = [Option<&'static str>; 3];
+
+/// Printable keys of the US layout
+const LAYOUT_US: &[(Keyboard, Levels)] = &[
+    (
+        Keyboard::KeyGrave,
+        [Some("grave"), Some("asciitilde"), None],
+    ),
+    (Keyboard::Key1, [Some("1"), Some("exclam"), None]),
+    (Keyboard::Key2, [Some("2"), Some("at"), None]),
+    (Keyboard::Key3, [Some("3"), Some("numbersign"), None]),
+    (Keyboard::Key4, [Some("4"), Some("dollar"), None]),
+    (Keyboard::Key5, [Some("5"), Some("percent"), None]),
+    (Keyboard::Key6, [Some("6"), Some("asciicircum"), None]),
+    (Keyboard::Key7, [Some("7"), Some("ampersand"), None]),
+    (Keyboard::Key8, [Some("8"), Some("asterisk"), None]),
+    (Keyboard::Key9, [Some("9"), Some("parenleft"), None]),
+    (Keyboard::Key0, [Some("0"), Some("parenright"), None]),
+    (
+        Keyboard::KeyMinus,
+        [Some("minus"), Some("underscore"), None],
+    ),
+    (Keyboard::KeyEqual, [Some("equal"), Some("plus"), None]),
+    (
+        Keyboard::KeyLeftBrace,
+        [Some("bracketleft"), Some("braceleft"), None],
+    ),
+    (
+        Keyboard::KeyRightBrace,
+        [Some("bracketright"), Some("braceright"), None],
+    ),
+    (
+        Keyboard::KeyBackslash,
+        [Some("backslash"), Some("bar"), None],
+    ),
+    (
+        Keyboard::KeySemicolon,
+        [Some("semicolon"), Some("colon"), None],
+    ),
+    (
+        Keyboard::KeyApostrophe,
+        [Some("apostrophe"), Some("quotedbl"), None],
+    ),
+    (Keyboard::KeyComma, [Some("comma"), Some("less"), None]),
+    (Keyboard::KeyDot, [Some("period"), Some("greater"), None]),
+    (Keyboard::KeySlash, [Some("slash"), Some("question"), None]),
+];
+
+/// Printable keys of the US Dvorak layout
+const LAYOUT_US_DVORAK: &[(Keyboard, Levels)] = &[
+    (
+        Keyboard::KeyGrave,
+        [Some("grave"), Some("asciitilde"), None],
+    ),
+    (Keyboard::Key1, [Some("1"), Some("exclam"), None]),
+    (Keyboard::Key2, [Some("2"), Some("at"), None]),
+    (Keyboard::Key3, [Some("3"), Some("numbersign"), None]),
+    (Keyboard::Key4, [Some("4"), Some("dollar"), None]),
+    (Keyboard::Key5, [Some("5"), Some("percent"), None]),
+    (Keyboard::Key6, [Some("6"), Some("asciicircum"), None]),
+    (Keyboard::Key7, [Some("7"), Some("ampersand"), None]),
+    (Keyboard::Key8, [Some("8"), Some("asterisk"), None]),
+    (Keyboard::Key9, [Some("9"), Some("parenleft"), None]),
+    (Keyboard::Key0, [Some("0"), Some("parenright"), None]),
+    (
+        Keyboard::KeyMinus,
+        [Some("bracketleft"), Some("braceleft"), None],
+    ),
+    (
+        Keyboard::KeyEqual,
+        [Some("bracketright"), Some("braceright"), None],
+    ),
+    (Keyboard::KeyQ, [Some("apostrophe"), Some("quotedbl"), None]),
+    (Keyboard::KeyW, [Some("comma"), Some("less"), None]),
+    (Keyboard::KeyE, [Some("period"), Some("greater"), None]),
+    (Keyboard::KeyR, [Some("p"), Some("P"), None]),
+    (Keyboard::KeyT, [Some("y"), Some("Y"), None]),
+    (Keyboard::KeyY, [Some("f"), Some("F"), None]),
+    (Keyboard::KeyU, [Some("g"), Some("G"), None]),
+    (Keyboard::KeyI, [Some("c"), Some("C"), None]),
+    (Keyboard::KeyO, [Some("r"), Some("R"), None]),
+    (Keyboard::KeyP, [Some("l"), Some("L"), None]),
+    (
+        Keyboard::KeyLeftBrace,
+        [Some("slash"), Some("question"), None],
+    ),
+    (Keyboard::KeyRightBrace, [Some("equal"), Some("plus"), None]),
+    (Keyboard::KeyA, [Some("a"), Some("A"), None]),
+    (Keyboard::KeyS, [Some("o"), Some("O"), None]),
+    (Keyboard::KeyD, [Some("e"), Some("E"), None]),
+    (Keyboard::KeyF, [Some("u"), Some("U"), None]),
+    (Keyboard::KeyG, [Some("i"), Some("I"), None]),
+    (Keyboard::KeyH, [Some("d"), Some("D"), None]),
+    (Keyboard::KeyJ, [Some("h"), Some("H"), None]),
+    (Keyboard::KeyK, [Some("t"), Some("T"), None]),
+    (Keyboard::KeyL, [Some("n"), Some("N"), None]),
+    (Keyboard::KeySemicolon, [Some("s"), Some("S"), None]),
+    (
+        Keyboard::KeyApostrophe,
+        [Some("minus"), Some("underscore"), None],
+    ),
+    (
+        Keyboard::KeyBackslash,
+        [Some("backslash"), Some("bar"), None],
+    ),
+    (Keyboard::KeyZ, [Some("semicolon"), Some("colon"), None]),
+    (Keyboard::KeyX, [Some("q"), Some("Q"), None]),
+    (Keyboard::KeyC, [Some("j"), Some("J"), None]),
+    (Keyboard::KeyV, [Some("k"), Some("K"), None]),
+    (Keyboard::KeyB, [Some("x"), Some("X"), None]),
+    (Keyboard::KeyN, [Some("b"), Some("B"), None]),
+    (Keyboard::KeyM, [Some("m"), Some("M"), None]),
+    (Keyboard::KeyComma, [Some("w"), Some("W"), None]),
+    (Keyboard::KeyDot, [Some("v"), Some("V"), None]),
+    (Keyboard::KeySlash, [Some("z"), Some("Z"), None]),
+];
+
+/// Printable keys of the German layout
+const LAYOUT_DE: &[(Keyboard, Levels)] = &[
+    (
+        Keyboard::KeyGrave,
+        [Some("asciicircum"), Some("degree"), None],
+    ),
+    (Keyboard::Key1, [Some("1"), Some("exclam"), None]),
+    (
+        Keyboard::Key2,
+        [Some("2"), Some("quotedbl"), Some("twosuperior")],
+    ),
+    (
+        Keyboard::Key3,
+        [Some("3"), Some("section"), Some("threesuperior")],
+    ),
+    (Keyboard::Key4, [Some("4"), Some("dollar"), None]),
+    (Keyboard::Key5, [Some("5"), Some("percent"), None]),
+    (Keyboard::Key6, [Some("6"), Some("ampersand"), None]),
+    (
+        Keyboard::Key7,
+        [Some("7"), Some("slash"), Some("braceleft")],
+    ),
+    (
+        Keyboard::Key8,
+        [Some("8"), Some("parenleft"), Some("bracketleft")],
+    ),
+    (
+        Keyboard::Key9,
+        [Some("9"), Some("parenright"), Some("bracketright")],
+    ),
+    (
+        Keyboard::Key0,
+        [Some("0"), Some("equal"), Some("braceright")],
+    ),
+    (
+        Keyboard::KeyMinus,
+        [Some("ssharp"), Some("question"), Some("backslash")],
+    ),
+    (Keyboard::KeyEqual, [Some("acute"), Some("grave"), None]),
+    (Keyboard::KeyQ, [Some("q"), Some("Q"), Some("at")]),
+    (Keyboard::KeyE, [Some("e"), Some("E"), Some("EuroSign")]),
+    (
+        Keyboard::KeyLeftBrace,
+        [Some("udiaeresis"), Some("Udiaeresis"), None],
+    ),
+    (
+        Keyboard::KeyRightBrace,
+        [Some("plus"), Some("asterisk"), Some("asciitilde")],
+    ),
+    (
+        Keyboard::KeySemicolon,
+        [Some("odiaeresis"), Some("Odiaeresis"), None],
+    ),
+    (
+        Keyboard::KeyApostrophe,
+        [Some("adiaeresis"), Some("Adiaeresis"), None],
+    ),
+    (
+        Keyboard::KeyBackslash,
+        [Some("numbersign"), Some("apostrophe"), None],
+    ),
+    (
+        Keyboard::Key102nd,
+        [Some("less"), Some("greater"), Some("bar")],
+    ),
+    (Keyboard::KeyComma, [Some("comma"), Some("semicolon"), None]),
+    (Keyboard::KeyDot, [Some("period"), Some("colon"), None]),
+    (
+        Keyboard::KeySlash,
+        [Some("minus"), Some("underscore"), None],
+    ),
+];
+
+/// Printable keys of the Japanese (JIS) layout
+const LAYOUT_JP: &[(Keyboard, Levels)] = &[
+    (Keyboard::Key1, [Some("1"), Some("exclam"), None]),
+    (Keyboard::Key2, [Some("2"), Some("quotedbl"), None]),
+    (Keyboard::Key3, [Some("3"), Some("numbersign"), None]),
+    (Keyboard::Key4, [Some("4"), Some("dollar"), None]),
+    (Keyboard::Key5, [Some("5"), Some("percent"), None]),
+    (Keyboard::Key6, [Some("6"), Some("ampersand"), None]),
+    (Keyboard::Key7, [Some("7"), Some("apostrophe"), None]),
+    (Keyboard::Key8, [Some("8"), Some("parenleft"), None]),
+    (Keyboard::Key9, [Some("9"), Some("parenright"), None]),
+    (Keyboard::Key0, [Some("0"), None, None]),
+    (Keyboard::KeyMinus, [Some("minus"), Some("equal"), None]),
+    (
+        Keyboard::KeyEqual,
+        [Some("asciicircum"), Some("asciitilde"), None],
+    ),
+    (Keyboard::KeyYen, [Some("backslash"), Some("bar"), None]),
+    (Keyboard::KeyLeftBrace, [Some("at"), Some("grave"), None]),
+    (
+        Keyboard::KeyRightBrace,
+        [Some("bracketleft"), Some("braceleft"), None],
+    ),
+    (
+        Keyboard::KeySemicolon,
+        [Some("semicolon"), Some("plus"), None],
+    ),
+    (
+        Keyboard::KeyApostrophe,
+        [Some("colon"), Some("asterisk"), None],
+    ),
+    (
+        Keyboard::KeyBackslash,
+        [Some("bracketright"), Some("braceright"), None],
+    ),
+    (Keyboard::KeyComma, [Some("comma"), Some("less"), None]),
+    (Keyboard::KeyDot, [Some("period"), Some("greater"), None]),
+    (Keyboard::KeySlash, [Some("slash"), Some("question"), None]),
+    (
+        Keyboard::KeyRo,
+        [Some("backslash"), Some("underscore"), None],
+    ),
+];
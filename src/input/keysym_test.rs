@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use crate::input::{
+    capability::{Capability, Keyboard},
+    keysym::{Keymap, KeysymMapping},
+};
+
+#[tokio::test]
+async fn test_keysym_us_layout() {
+    let keymap = Keymap::new("us", None);
+    assert_eq!(
+        keymap.lookup("a"),
+        Some(KeysymMapping {
+            key: Keyboard::KeyA,
+            shift: false,
+            altgr: false,
+        })
+    );
+    assert_eq!(
+        keymap.lookup("at"),
+        Some(KeysymMapping {
+            key: Keyboard::Key2,
+            shift: true,
+            altgr: false,
+        })
+    );
+    assert_eq!(keymap.lookup("udiaeresis"), None);
+}
+
+#[tokio::test]
+async fn test_keysym_de_layout() {
+    let keymap = Keymap::new("de", None);
+    assert_eq!(keymap.layout(), "de");
+    assert_eq!(keymap.lookup("z").unwrap().key, Keyboard::KeyY);
+    assert_eq!(keymap.lookup("Y").unwrap().key, Keyboard::KeyZ);
+    assert_eq!(
+        keymap.lookup("udiaeresis").unwrap().key,
+        Keyboard::KeyLeftBrace
+    );
+    assert_eq!(
+        keymap.lookup("at"),
+        Some(KeysymMapping {
+            key: Keyboard::KeyQ,
+            shift: false,
+            altgr: true,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_keysym_dvorak_layout() {
+    let keymap = Keymap::new("us", Some("dvorak"));
+    assert_eq!(keymap.layout(), "us(dvorak)");
+    assert_eq!(keymap.lookup("o").unwrap().key, Keyboard::KeyS);
+    assert_eq!(keymap.lookup("Return").unwrap().key, Keyboard::KeyEnter);
+}
+
+#[tokio::test]
+async fn test_keysym_unknown_layout() {
+    let keymap = Keymap::new("xx", None);
+    assert_eq!(keymap.layout(), "us");
+}
+
+#[tokio::test]
+async fn test_keysym_capability_string() {
+    let cap = Capability::KeyboardKeysym("udiaeresis".to_string());
+    assert_eq!(cap.to_capability_string(), "KeyboardKeysym:udiaeresis");
+    assert_eq!(Capability::from_str("KeyboardKeysym:udiaeresis"), Ok(cap));
+    assert!(Capability::from_str("KeyboardKeysym").is_err());
+}
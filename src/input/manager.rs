@@ -2,6 +2,7 @@ use core::panic;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
+use std::net::SocketAddr;
 use std::time::Duration;
 
 use ::procfs::CpuInfo;
@@ -15,8 +16,10 @@ use zbus::zvariant::ObjectPath;
 use zbus::Connection;
 
 use crate::bluetooth::device1::Device1Proxy;
+use crate::config::auto_detect;
 use crate::config::path::get_capability_maps_paths;
 use crate::config::path::get_devices_paths;
+use crate::config::path::get_profiles_path;
 use crate::config::CapabilityMap;
 use crate::config::CompositeDeviceConfig;
 use crate::config::SourceDevice;
@@ -35,6 +38,7 @@ use crate::input::composite_device::CompositeDevice;
 use crate::input::source::evdev;
 use crate::input::source::hidraw;
 use crate::input::source::iio;
+use crate::input::target::web_gamepad;
 use crate::input::target::TargetDevice;
 use crate::input::target::TargetDeviceTypeId;
 use crate::udev;
@@ -56,6 +60,8 @@ pub enum ManagerError {
     CreateTargetDeviceFailed(String),
     #[error("failed to attach target device")]
     AttachTargetDeviceFailed(String),
+    #[error("failed to merge composite devices")]
+    MergeCompositeDevicesFailed(String),
 }
 
 /// Manager commands define all the different ways to interact with [Manager]
@@ -88,6 +94,33 @@ pub enum ManagerCommand {
         path: String,
     },
     CompositeDeviceStopped(String),
+    /// Merge the composite device at 'other_path' into the composite device
+    /// at 'path', which takes over all of its source devices
+    MergeCompositeDevices {
+        path: String,
+        other_path: String,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
+    /// Sent when the source device with the given id was handed over to the
+    /// composite device at the given path after a merge
+    SourceDeviceMerged {
+        id: String,
+        composite_path: String,
+    },
+    CompositeDeviceConfigChanged {
+        path: String,
+        config: CompositeDeviceConfig,
+    },
+    ListProfiles {
+        directory: String,
+        sender: mpsc::Sender<Vec<(String, String)>>,
+    },
+    RegisterKnownDevice {
+        vendor_id: u16,
+        product_id: u16,
+        config: CompositeDeviceConfig,
+        profile_path: String,
+    },
 }
 
 /// Manages input devices
@@ -141,6 +174,11 @@ pub struct Manager {
     /// Mapping of target devices to their respective handles
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <Handle>}
     target_devices: HashMap<String, TargetDeviceClient>,
+    /// Mapping of known controllers registered over DBus to the config and
+    /// path to the device profile to use for them. These take precedence over
+    /// the built-in database of known controllers.
+    /// E.g. {(0x054c, 0x0ce6): (<CompositeDeviceConfig>, "/usr/share/inputplumber/profiles/default.yaml")}
+    known_devices: HashMap<(u16, u16), (CompositeDeviceConfig, String)>,
 }
 
 impl Manager {
@@ -176,6 +214,7 @@ impl Manager {
             used_configs: HashMap::new(),
             composite_device_sources: HashMap::new(),
             composite_device_targets: HashMap::new(),
+            known_devices: HashMap::new(),
         }
     }
 
@@ -202,6 +241,29 @@ impl Manager {
                         log::error!("Error handling stopped composite device: {:?}", e);
                     }
                 }
+                ManagerCommand::MergeCompositeDevices {
+                    path,
+                    other_path,
+                    sender,
+                } => {
+                    log::debug!("Got request to merge composite device {other_path} into {path}");
+                    let result = self.merge_composite_devices(&path, &other_path).await;
+                    if let Err(e) = result.as_ref() {
+                        log::error!("{e}");
+                    }
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::SourceDeviceMerged { id, composite_path } => {
+                    log::debug!("Source device {id} was merged into {composite_path}");
+                    self.source_devices_used.insert(id, composite_path);
+                }
+                ManagerCommand::CompositeDeviceConfigChanged { path, config } => {
+                    if let Err(e) = self.on_composite_device_config_changed(path, config).await {
+                        log::error!("Error handling changed composite device config: {:?}", e);
+                    }
+                }
                 ManagerCommand::CreateTargetDevice { kind, sender } => {
                     // Create the target device
                     log::debug!("Got request to create target device: {kind}");
@@ -290,6 +352,30 @@ impl Manager {
                         log::error!("Error removing device: {e:?}");
                     }
                 }
+                ManagerCommand::ListProfiles { directory, sender } => {
+                    log::debug!("Got request to list profiles in: {directory}");
+                    // Scan the directory in a separate task so other commands
+                    // are not blocked by slow filesystem reads.
+                    task::spawn(async move {
+                        let profiles = Manager::list_profiles(directory.as_str()).await;
+                        if let Err(e) = sender.send(profiles).await {
+                            log::error!("Failed to send response: {e:?}");
+                        }
+                    });
+                }
+                ManagerCommand::RegisterKnownDevice {
+                    vendor_id,
+                    product_id,
+                    config,
+                    profile_path,
+                } => {
+                    log::info!(
+                        "Registering known device {vendor_id:04x}:{product_id:04x} with config {:?} and profile: {profile_path}",
+                        config.name
+                    );
+                    self.known_devices
+                        .insert((vendor_id, product_id), (config, profile_path));
+                }
             }
         }
 
@@ -321,16 +407,25 @@ impl Manager {
             None
         };
 
+        // Lookup the device profile if this is a known controller
+        let known_profile = self.get_known_device_profile(config, &device);
+
         // Create a composite device to manage these devices
         log::info!("Found matching source device for: {:?}", config.name);
         let config = config.clone();
-        let device = CompositeDevice::new(
+        let mut device = CompositeDevice::new(
             self.dbus.clone(),
             self.tx.clone(),
             config,
             device,
             capability_map,
         )?;
+        if let Some(profile_path) = known_profile {
+            log::info!("Loading profile for known device: {profile_path}");
+            if let Err(e) = device.load_known_device_profile(profile_path.clone()) {
+                log::warn!("Failed to load profile for known device {profile_path}: {e:?}");
+            }
+        }
 
         // Check to see if there's already a CompositeDevice for
         // these source devices.
@@ -346,9 +441,54 @@ impl Manager {
         Ok(device)
     }
 
+    /// Returns the path to the device profile to use for the given device if
+    /// it is a known controller that is managed by the given config.
+    fn get_known_device_profile(
+        &self,
+        config: &CompositeDeviceConfig,
+        device: &UdevDevice,
+    ) -> Option<String> {
+        let vendor_id = device.id_vendor();
+        let product_id = device.id_product();
+        if let Some((known_config, profile_path)) = self.known_devices.get(&(vendor_id, product_id))
+        {
+            if known_config.name != config.name {
+                return None;
+            }
+            return Some(profile_path.clone());
+        }
+
+        let (config_name, profile_path) = auto_detect::get_known_device(vendor_id, product_id)?;
+        if config_name != config.name {
+            return None;
+        }
+        let profile_path = get_profiles_path().join(profile_path);
+        Some(profile_path.to_string_lossy().to_string())
+    }
+
     /// Create target input device to emulate based on the given device type.
     async fn create_target_device(&mut self, kind: &str) -> Result<TargetDevice, Box<dyn Error>> {
         log::trace!("Creating target device: {kind}");
+
+        // Network stream targets can include the address to send events to
+        // (e.g. "network:192.168.0.10:27020").
+        if let Some(address) = kind.strip_prefix("network:") {
+            let address = address.parse()?;
+            return TargetDevice::network_stream(address, self.dbus.clone());
+        }
+
+        // Web gamepad targets can include the port to serve the gamepad
+        // webpage on (e.g. "web-gamepad:8080"), which is only reachable from
+        // the local machine. Serving it to other machines requires an
+        // explicit bind address (e.g. "web-gamepad:0.0.0.0:8080").
+        if let Some(address) = kind.strip_prefix("web-gamepad:") {
+            let address = match address.parse::<u16>() {
+                Ok(port) => SocketAddr::new(web_gamepad::DEFAULT_BIND_ADDRESS, port),
+                Err(_) => address.parse()?,
+            };
+            return TargetDevice::web_gamepad(address, self.dbus.clone());
+        }
+
         let Ok(target_id) = TargetDeviceTypeId::try_from(kind) else {
             return Err("Invalid target device ID".to_string().into());
         };
@@ -532,6 +672,34 @@ impl Manager {
         Ok(())
     }
 
+    /// Merge the composite device at the given other path into the composite
+    /// device at the given path
+    async fn merge_composite_devices(
+        &self,
+        path: &str,
+        other_path: &str,
+    ) -> Result<(), ManagerError> {
+        if path == other_path {
+            return Err(ManagerError::MergeCompositeDevicesFailed(
+                "Unable to merge a composite device with itself".into(),
+            ));
+        }
+        let Some(device) = self.composite_devices.get(path) else {
+            return Err(ManagerError::MergeCompositeDevicesFailed(format!(
+                "Failed to find composite device: {path}"
+            )));
+        };
+        let Some(other) = self.composite_devices.get(other_path) else {
+            return Err(ManagerError::MergeCompositeDevicesFailed(format!(
+                "Failed to find composite device: {other_path}"
+            )));
+        };
+        device
+            .merge_device(other)
+            .await
+            .map_err(|e| ManagerError::MergeCompositeDevicesFailed(e.to_string()))
+    }
+
     /// Called when a composite device stops running
     async fn on_composite_device_stopped(&mut self, path: String) -> Result<(), Box<dyn Error>> {
         log::debug!("Removing composite device: {}", path);
@@ -586,6 +754,48 @@ impl Manager {
     /// device configurations to check and see if any configuration matches
     /// the input devices on the system. If a match is found, a [CompositeDevice]
     /// will be created and started.
+    /// Called when the config of a running composite device has been
+    /// reloaded. Any unused source devices that match the updated config are
+    /// added to the composite device.
+    async fn on_composite_device_config_changed(
+        &mut self,
+        path: String,
+        config: CompositeDeviceConfig,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.composite_devices.contains_key(&path) {
+            return Err(format!("No composite device found with path: {path}").into());
+        }
+        log::debug!(
+            "Config for composite device {path} changed: {:?}",
+            config.name
+        );
+        self.used_configs.insert(path.clone(), config.clone());
+
+        for subsystem in ["hidraw", "input", "iio"] {
+            let devices = udev::discover_devices(subsystem)?;
+            for device in devices {
+                let device: UdevDevice = device.into();
+                if device.devnode().is_empty() {
+                    continue;
+                }
+                let id = device.get_id();
+                if self.source_devices_used.contains_key(&id) {
+                    continue;
+                }
+                if config.get_matching_device(&device).is_none() {
+                    continue;
+                }
+                if subsystem == "input" && Manager::is_ignored_virtual_device(&device).await? {
+                    continue;
+                }
+                log::info!("Found new source device {id} matching updated config for {path}");
+                self.on_source_device_added(id, device).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn on_source_device_added(
         &mut self,
         id: String,
@@ -619,6 +829,9 @@ impl Manager {
                             log::trace!("Evdev section is empty");
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         if config.has_matching_evdev(&device, &source_device.clone().evdev.unwrap())
                         {
                             // Check if the device has already been used in this config or not, stop here if the device must be unique.
@@ -685,6 +898,9 @@ impl Manager {
                         if source_device.hidraw.is_none() {
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         if config
                             .has_matching_hidraw(&device, &source_device.clone().hidraw.unwrap())
                         {
@@ -749,6 +965,9 @@ impl Manager {
                         if source_device.iio.is_none() {
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         if config.has_matching_iio(&device, &source_device.clone().iio.unwrap()) {
                             // Check if the device has already been used in this config or not, stop here if the device must be unique.
                             if let Some(sources) =
@@ -816,7 +1035,22 @@ impl Manager {
 
         // Check all CompositeDevice configs to see if this device creates
         // a match that will automatically create a CompositeDevice.
-        let configs = self.load_device_configs().await;
+        let mut configs = self.load_device_configs().await;
+
+        // Check the config of known controllers first so they are not matched
+        // by a generic config.
+        let known_id = (device.id_vendor(), device.id_product());
+        if let Some((config, _)) = self.known_devices.get(&known_id) {
+            log::debug!("Found registered known device config: {:?}", config.name);
+            configs.insert(0, config.clone());
+        } else if let Some((config_name, _)) = auto_detect::get_known_device(known_id.0, known_id.1)
+        {
+            log::debug!("Found known device config: {config_name}");
+            if let Some(idx) = configs.iter().position(|c| c.name == config_name) {
+                let config = configs.remove(idx);
+                configs.insert(0, config);
+            }
+        }
         log::debug!("Checking unused configs");
         for config in configs {
             log::trace!("Checking config {:?} for device", config.name);
@@ -834,6 +1068,9 @@ impl Manager {
                         if source_device.evdev.is_none() {
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         // how to refrence source devices used by this config?
 
                         if config.has_matching_evdev(&device, &source_device.clone().evdev.unwrap())
@@ -872,6 +1109,9 @@ impl Manager {
                         if source_device.hidraw.is_none() {
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         if config
                             .has_matching_hidraw(&device, &source_device.clone().hidraw.unwrap())
                         {
@@ -909,6 +1149,9 @@ impl Manager {
                         if source_device.iio.is_none() {
                             continue;
                         }
+                        if !source_device.matches_usb_topology(&device) {
+                            continue;
+                        }
                         if config.has_matching_iio(&device, &source_device.clone().iio.unwrap()) {
                             if let Some(ignored) = source_device.ignore {
                                 if ignored {
@@ -1032,33 +1275,8 @@ impl Manager {
                 self.source_device_dbus_paths.insert(id.clone(), path);
 
                 // Check to see if the device is virtual
-                if device.is_virtual() {
-                    // Look up the connected device using udev
-                    let device_info = udev::get_device(dev_node.clone()).await?;
-
-                    // Check if the virtual device is using the bluetooth bus
-                    // TODO: Can we get properties from UdevDevice::get_attribute_from_tree?
-                    let id_bus = device_info.properties.get("ID_BUS");
-
-                    log::debug!("Bus ID for {dev_node}: {id_bus:?}");
-                    let is_bluetooth = {
-                        if let Some(bus) = id_bus {
-                            bus == "bluetooth"
-                        } else {
-                            false
-                        }
-                    };
-
-                    if !is_bluetooth {
-                        log::debug!("{} is virtual, skipping consideration.", dev_node);
-                        return Ok(());
-                    }
-                    log::debug!(
-                        "{} is a virtual device node for a bluetooth device. Treating as real.",
-                        dev_node
-                    )
-                } else {
-                    log::trace!("{} is a real device.", dev_node);
+                if Manager::is_ignored_virtual_device(&device).await? {
+                    return Ok(());
                 }
 
                 // Signal that a source device was added
@@ -1439,6 +1657,43 @@ impl Manager {
         Ok(())
     }
 
+    /// Returns true if the given event device is a virtual device that should
+    /// not be considered as a source device. Virtual device nodes for
+    /// bluetooth devices are treated as real devices.
+    async fn is_ignored_virtual_device(device: &UdevDevice) -> Result<bool, Box<dyn Error>> {
+        let dev_node = device.devnode();
+        if !device.is_virtual() {
+            log::trace!("{} is a real device.", dev_node);
+            return Ok(false);
+        }
+
+        // Look up the connected device using udev
+        let device_info = udev::get_device(dev_node.clone()).await?;
+
+        // Check if the virtual device is using the bluetooth bus
+        // TODO: Can we get properties from UdevDevice::get_attribute_from_tree?
+        let id_bus = device_info.properties.get("ID_BUS");
+
+        log::debug!("Bus ID for {dev_node}: {id_bus:?}");
+        let is_bluetooth = {
+            if let Some(bus) = id_bus {
+                bus == "bluetooth"
+            } else {
+                false
+            }
+        };
+
+        if !is_bluetooth {
+            log::debug!("{} is virtual, skipping consideration.", dev_node);
+            return Ok(true);
+        }
+        log::debug!(
+            "{} is a virtual device node for a bluetooth device. Treating as real.",
+            dev_node
+        );
+        Ok(false)
+    }
+
     async fn discover_devices(
         manager_tx: &mpsc::Sender<ManagerCommand>,
         devices: Vec<UdevDevice>,
@@ -1458,6 +1713,17 @@ impl Manager {
         let mut mappings = HashMap::new();
         let paths = get_capability_maps_paths();
 
+        // Built-in capability mappings can be overridden by files on disk
+        #[cfg(feature = "embedded-profiles")]
+        for name in crate::config::embedded::CAPABILITY_MAPS.keys() {
+            match CapabilityMap::from_embedded(name) {
+                Ok(map) => {
+                    mappings.insert(map.id.clone(), map);
+                }
+                Err(e) => log::warn!("Failed to parse embedded capability mapping {name}: {e}"),
+            }
+        }
+
         // Look for capability mappings in all known locations
         for path in paths.iter() {
             let files = fs::read_dir(path);
@@ -1553,6 +1819,66 @@ impl Manager {
         result.unwrap_or_default()
     }
 
+    /// Returns the name and path of all device profiles in the given directory.
+    /// Only the top-level `name` field is read from each profile, so profiles
+    /// are not fully parsed or validated.
+    /// E.g. [("Default", "/usr/share/inputplumber/profiles/default.yaml")]
+    async fn list_profiles(directory: &str) -> Vec<(String, String)> {
+        let mut profiles = Vec::new();
+        let mut entries = match tokio::fs::read_dir(directory).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::debug!("Failed to load directory {directory}: {e}");
+                return profiles;
+            }
+        };
+
+        loop {
+            let entry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    log::debug!("Failed to read entry in {directory}: {e}");
+                    break;
+                }
+            };
+            let path = entry.path();
+
+            // Skip any non-yaml files
+            let is_yaml = path
+                .extension()
+                .map(|ext| ext == "yaml" || ext == "yml")
+                .unwrap_or_default();
+            if !is_yaml {
+                continue;
+            }
+
+            let contents = match tokio::fs::read_to_string(&path).await {
+                Ok(contents) => contents,
+                Err(e) => {
+                    log::debug!("Failed to read profile {}: {e}", path.display());
+                    continue;
+                }
+            };
+
+            // Find the top-level name of the profile
+            let name = contents.lines().find_map(|line| {
+                let name = line.strip_prefix("name:")?.trim();
+                let name = name.trim_matches(|c| c == '"' || c == '\'');
+                Some(name.to_string())
+            });
+            let Some(name) = name else {
+                log::debug!("No profile name found in {}", path.display());
+                continue;
+            };
+
+            profiles.push((name, path.display().to_string()));
+        }
+        profiles.sort_by(|a, b| a.1.cmp(&b.1));
+
+        profiles
+    }
+
     /// Creates a DBus object
     async fn listen_on_dbus(&self) -> Result<(), Box<dyn Error>> {
         let iface = ManagerInterface::new(self.tx.clone());
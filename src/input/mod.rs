@@ -1,9 +1,47 @@
 //pub mod device;
+pub mod anti_ghosting;
+#[cfg(test)]
+mod anti_ghosting_test;
 pub mod capability;
 pub mod composite_device;
+pub mod double_click;
+#[cfg(test)]
+mod double_click_test;
 pub mod event;
+pub mod event_log;
+#[cfg(test)]
+mod event_log_test;
+pub mod event_stream;
+pub mod filter;
+#[cfg(test)]
+mod filter_test;
+pub mod gesture;
+#[cfg(test)]
+mod gesture_test;
+pub mod gyro_mouse;
+#[cfg(test)]
+mod gyro_mouse_test;
+pub mod keysym;
+#[cfg(test)]
+mod keysym_test;
 pub mod manager;
+pub mod motion_profile;
+#[cfg(test)]
+mod motion_profile_test;
 pub mod output_capability;
 pub mod output_event;
 pub mod source;
+pub mod source_health;
+#[cfg(test)]
+mod source_health_test;
 pub mod target;
+pub mod touchpad_mouse;
+#[cfg(test)]
+mod touchpad_mouse_test;
+pub mod touchscreen;
+#[cfg(test)]
+mod touchscreen_test;
+#[cfg(feature = "wasm-scripts")]
+pub mod wasm_transform;
+#[cfg(all(test, feature = "wasm-scripts"))]
+mod wasm_transform_test;
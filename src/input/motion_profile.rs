@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+use super::event::value::InputValue;
+
+/// Orientation of the device, computed from the direction of gravity measured
+/// by the accelerometer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// The device is held roughly flat, e.g. lying on a desk
+    FaceUp,
+    /// The top of the device is tilted back toward the player, e.g. when
+    /// holding it up while lying on a couch
+    TiltedBack,
+    /// The top of the device is tilted forward away from the player
+    TiltedForward,
+}
+
+/// Configuration for loading a different device profile depending on how the
+/// device is held
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionProfileConfig {
+    /// Pitch in degrees at or above which the device is tilted back
+    pub tilted_back_angle: f64,
+    /// Pitch in degrees at or below which the device is tilted forward. This
+    /// is usually a negative angle.
+    pub tilted_forward_angle: f64,
+    /// Time in milliseconds an orientation must be held before its profile
+    /// is loaded, so briefly moving the device does not switch profiles
+    pub hold_ms: u64,
+    /// Path to the profile to load when the device is face up
+    pub face_up_profile: Option<String>,
+    /// Path to the profile to load when the device is tilted back
+    pub tilted_back_profile: Option<String>,
+    /// Path to the profile to load when the device is tilted forward
+    pub tilted_forward_profile: Option<String>,
+}
+
+impl MotionProfileConfig {
+    /// Returns the orientation of the device for the given pitch in degrees
+    pub fn orientation(&self, pitch: f64) -> Orientation {
+        if pitch >= self.tilted_back_angle {
+            Orientation::TiltedBack
+        } else if pitch <= self.tilted_forward_angle {
+            Orientation::TiltedForward
+        } else {
+            Orientation::FaceUp
+        }
+    }
+
+    /// Returns the path to the profile for the given orientation, if any
+    pub fn profile(&self, orientation: Orientation) -> Option<&String> {
+        match orientation {
+            Orientation::FaceUp => self.face_up_profile.as_ref(),
+            Orientation::TiltedBack => self.tilted_back_profile.as_ref(),
+            Orientation::TiltedForward => self.tilted_forward_profile.as_ref(),
+        }
+    }
+}
+
+/// Tracks the orientation of the device from accelerometer events to decide
+/// when a different profile should be loaded
+#[derive(Debug, Default)]
+pub struct MotionProfileSwitcher {
+    config: Option<MotionProfileConfig>,
+    /// Last known acceleration as (x, y, z)
+    acceleration: (f64, f64, f64),
+    /// Orientation whose profile was last selected
+    orientation: Option<Orientation>,
+    /// Orientation the device changed to and when, if it has not yet been
+    /// held long enough to switch profiles
+    pending: Option<(Orientation, Instant)>,
+}
+
+impl MotionProfileSwitcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the configuration or disable motion profile switching if [None],
+    /// and reset the tracked orientation
+    pub fn set_config(&mut self, config: Option<MotionProfileConfig>) {
+        self.config = config;
+        self.orientation = None;
+        self.pending = None;
+    }
+
+    /// Returns true if profiles are switched based on device orientation
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    /// Update the device orientation from the given accelerometer value and
+    /// return the path to the profile that should be loaded, if the device
+    /// was held in a new orientation for long enough. The pitch of the device
+    /// is the rotation of gravity around the x axis, where a positive pitch
+    /// tilts the top of the device back.
+    pub fn update(&mut self, value: &InputValue, now: Instant) -> Option<String> {
+        let config = self.config.as_ref()?;
+        let InputValue::Vector3 { x, y, z } = value else {
+            return None;
+        };
+        self.acceleration = (
+            x.unwrap_or(self.acceleration.0),
+            y.unwrap_or(self.acceleration.1),
+            z.unwrap_or(self.acceleration.2),
+        );
+        let (_, y, z) = self.acceleration;
+        if y == 0.0 && z == 0.0 {
+            return None;
+        }
+        let pitch = y.atan2(z).to_degrees();
+        let orientation = config.orientation(pitch);
+
+        if self.orientation == Some(orientation) {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending {
+            Some((pending, since)) if pending == orientation => since,
+            _ => {
+                self.pending = Some((orientation, now));
+                now
+            }
+        };
+        if now.duration_since(since) < Duration::from_millis(config.hold_ms) {
+            return None;
+        }
+
+        log::debug!("Device orientation changed to {orientation:?} with pitch {pitch:.1}");
+        self.orientation = Some(orientation);
+        self.pending = None;
+        config.profile(orientation).cloned()
+    }
+}
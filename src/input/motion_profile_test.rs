@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    event::value::InputValue,
+    motion_profile::{MotionProfileConfig, MotionProfileSwitcher, Orientation},
+};
+
+/// Returns an accelerometer value for a device with the given pitch in degrees
+fn accel(pitch: f64) -> InputValue {
+    let pitch = pitch.to_radians();
+    InputValue::Vector3 {
+        x: Some(0.0),
+        y: Some(pitch.sin() * 9.81),
+        z: Some(pitch.cos() * 9.81),
+    }
+}
+
+fn config(hold_ms: u64) -> MotionProfileConfig {
+    MotionProfileConfig {
+        tilted_back_angle: 45.0,
+        tilted_forward_angle: -30.0,
+        hold_ms,
+        face_up_profile: Some("desk.yaml".to_string()),
+        tilted_back_profile: Some("couch.yaml".to_string()),
+        tilted_forward_profile: None,
+    }
+}
+
+#[tokio::test]
+async fn test_motion_profile_orientation() {
+    let config = config(0);
+    assert_eq!(config.orientation(0.0), Orientation::FaceUp);
+    assert_eq!(config.orientation(44.0), Orientation::FaceUp);
+    assert_eq!(config.orientation(45.0), Orientation::TiltedBack);
+    assert_eq!(config.orientation(-30.0), Orientation::TiltedForward);
+}
+
+#[tokio::test]
+async fn test_motion_profile_switch() {
+    let mut switcher = MotionProfileSwitcher::new();
+    let start = Instant::now();
+
+    // Nothing is loaded while disabled
+    assert_eq!(switcher.update(&accel(0.0), start), None);
+
+    switcher.set_config(Some(config(500)));
+    assert_eq!(switcher.update(&accel(0.0), start), None);
+    let now = start + Duration::from_millis(500);
+    assert_eq!(
+        switcher.update(&accel(0.0), now),
+        Some("desk.yaml".to_string())
+    );
+
+    // Staying in the same orientation does not load the profile again
+    let now = now + Duration::from_millis(500);
+    assert_eq!(switcher.update(&accel(5.0), now), None);
+
+    // Briefly tilting the device does not switch profiles
+    let now = now + Duration::from_millis(100);
+    assert_eq!(switcher.update(&accel(60.0), now), None);
+    let now = now + Duration::from_millis(100);
+    assert_eq!(switcher.update(&accel(0.0), now), None);
+    let now = now + Duration::from_millis(500);
+    assert_eq!(switcher.update(&accel(60.0), now), None);
+    let now = now + Duration::from_millis(500);
+    assert_eq!(
+        switcher.update(&accel(60.0), now),
+        Some("couch.yaml".to_string())
+    );
+
+    // Orientations without a profile do not load anything
+    let now = now + Duration::from_millis(100);
+    assert_eq!(switcher.update(&accel(-45.0), now), None);
+    let now = now + Duration::from_millis(500);
+    assert_eq!(switcher.update(&accel(-45.0), now), None);
+}
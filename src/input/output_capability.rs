@@ -6,8 +6,15 @@ pub enum OutputCapability {
     ForceFeedback,
     ForceFeedbackUpload,
     ForceFeedbackErase,
+    /// Per-trigger haptic feedback, such as adaptive trigger resistance
+    TriggerFeedback,
+    /// Adaptive trigger effects, such as weapon or vibration effects
+    AdaptiveTrigger,
     #[allow(clippy::upper_case_acronyms)]
     LED(LED),
+    /// Native DualSense output reports, including lightbar and adaptive
+    /// trigger state
+    DualSense,
 }
 
 /// LED capability
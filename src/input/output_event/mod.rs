@@ -1,10 +1,14 @@
 use std::sync::mpsc::Sender;
 
 use ::evdev::{FFEffectData, InputEvent};
+use serde::{Deserialize, Serialize};
 
-use crate::drivers::dualsense::hid_report::SetStatePackedOutputData;
+use crate::drivers::dualsense::{
+    driver::{TRIGGER_EFFECT_CONTINUOUS_RESISTANCE, TRIGGER_EFFECT_OFF},
+    hid_report::{LightBrightness, SetStatePackedOutputData},
+};
 
-use super::output_capability::OutputCapability;
+use super::output_capability::{OutputCapability, LED};
 
 /// Output events are events that flow from target devices back to source devices
 #[derive(Debug, Clone)]
@@ -12,9 +16,88 @@ pub enum OutputEvent {
     Evdev(InputEvent),
     Uinput(UinputOutputEvent),
     DualSense(SetStatePackedOutputData),
+    #[allow(clippy::upper_case_acronyms)]
+    LED(LedEvent),
+    /// Haptic feedback for the left and right triggers, with intensities
+    /// normalized between 0.0 and 1.0.
+    TriggerFeedback {
+        left_intensity: f32,
+        right_intensity: f32,
+    },
+    /// Adaptive trigger effect to apply to the given trigger
+    AdaptiveTrigger {
+        trigger: Trigger,
+        effect: AdaptiveTriggerEffect,
+    },
+    /// Raw HID output report in a device specific format
+    HIDRaw(HIDRawOutputEvent),
+}
+
+/// Kind of an [OutputEvent], used to route output events to specific source
+/// devices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputEventKind {
+    /// LED color events
+    Led,
+    /// Force feedback events, including uploading and erasing effects
+    ForceFeedback,
+    /// Trigger feedback and adaptive trigger events
+    Trigger,
+    /// All other output events
+    Generic,
 }
 
 impl OutputEvent {
+    /// Returns the kind of the output event
+    pub fn kind(&self) -> OutputEventKind {
+        match self {
+            OutputEvent::Evdev(event) => {
+                if event.event_type().0 == evdev::EventType::FORCEFEEDBACK.0 {
+                    OutputEventKind::ForceFeedback
+                } else {
+                    OutputEventKind::Generic
+                }
+            }
+            OutputEvent::Uinput(_) => OutputEventKind::ForceFeedback,
+            OutputEvent::DualSense(_) => OutputEventKind::Generic,
+            OutputEvent::LED(_) => OutputEventKind::Led,
+            OutputEvent::TriggerFeedback { .. } => OutputEventKind::Trigger,
+            OutputEvent::AdaptiveTrigger { .. } => OutputEventKind::Trigger,
+            OutputEvent::HIDRaw(_) => OutputEventKind::Generic,
+        }
+    }
+
+    /// Translates the lightbar and adaptive trigger state of the given DualSense
+    /// output report into generic [OutputEvent::LED] and
+    /// [OutputEvent::TriggerFeedback] events that can be handled by source
+    /// devices which are not DualSense controllers.
+    pub fn from_dualsense_report(report: &SetStatePackedOutputData) -> Vec<OutputEvent> {
+        let mut events = Vec::new();
+        if report.allow_led_color {
+            let brightness = match report.light_brightness {
+                LightBrightness::Bright => u8::MAX,
+                LightBrightness::Mid => 170,
+                LightBrightness::Dim => 85,
+                _ => u8::MAX,
+            };
+            events.push(OutputEvent::LED(LedEvent {
+                r: report.led_red,
+                g: report.led_green,
+                b: report.led_blue,
+                brightness,
+            }));
+        }
+        if report.allow_left_trigger_ffb || report.allow_right_trigger_ffb {
+            events.push(OutputEvent::TriggerFeedback {
+                left_intensity: trigger_effect_intensity(&report.left_trigger_ffb),
+                right_intensity: trigger_effect_intensity(&report.right_trigger_ffb),
+            });
+        }
+
+        events
+    }
+
     /// Returns the capability of the output event
     fn as_capability(&self) -> OutputCapability {
         match self {
@@ -47,10 +130,91 @@ impl OutputEvent {
                     OutputCapability::NotImplemented
                 }
             }
+            OutputEvent::LED(_) => OutputCapability::LED(LED::Color),
+            OutputEvent::TriggerFeedback { .. } => OutputCapability::TriggerFeedback,
+            OutputEvent::AdaptiveTrigger { .. } => OutputCapability::AdaptiveTrigger,
+            OutputEvent::HIDRaw(_) => OutputCapability::NotImplemented,
         }
     }
 }
 
+/// A raw HID output report that is written as-is to hidraw source devices.
+/// This is used to forward output reports with device specific formats that
+/// have no generic [OutputEvent] equivalent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HIDRawOutputEvent {
+    /// Id of the output report
+    pub report_id: u8,
+    /// Contents of the output report without the report id
+    pub data: Vec<u8>,
+}
+
+/// An LED event sets the color and brightness of the LEDs on a source device,
+/// such as the DualSense lightbar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedEvent {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub brightness: u8,
+}
+
+impl LedEvent {
+    /// Returns the RGB color of the LED event with the brightness applied
+    pub fn scaled_color(&self) -> (u8, u8, u8) {
+        let scale = |value: u8| -> u8 {
+            let value = value as f64 * (self.brightness as f64 / u8::MAX as f64);
+            value.round() as u8
+        };
+        (scale(self.r), scale(self.g), scale(self.b))
+    }
+}
+
+/// Trigger an [OutputEvent::AdaptiveTrigger] effect applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// Adaptive trigger effects, modeled after the effects supported by the
+/// DualSense controller. Positions are in zones from the top of the trigger
+/// pull (0) to the bottom (9).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveTriggerEffect {
+    /// Turn off any trigger effect
+    Off,
+    /// Resistance with the given strength (1-8) starting at the given position
+    /// (0-9) until the end of the trigger pull.
+    Feedback { start_position: u8, strength: u8 },
+    /// Resistance with the given strength (1-8) between the start (2-7) and end
+    /// (start+1-8) positions which gives way when pulled past it, like the
+    /// trigger of a gun.
+    Weapon {
+        start_position: u8,
+        end_position: u8,
+        strength: u8,
+    },
+    /// Vibration with the given amplitude (1-8) and frequency in Hz (1-255)
+    /// starting at the given position (0-9) until the end of the trigger pull.
+    Vibration {
+        position: u8,
+        amplitude: u8,
+        frequency: u8,
+    },
+}
+
+/// Returns the intensity of the given DualSense adaptive trigger effect,
+/// normalized between 0.0 and 1.0. Effects other than continuous resistance
+/// are treated as full intensity while they are active.
+fn trigger_effect_intensity(effect: &[u8; 11]) -> f32 {
+    match effect[0] {
+        0 | TRIGGER_EFFECT_OFF => 0.0,
+        TRIGGER_EFFECT_CONTINUOUS_RESISTANCE => effect[2] as f32 / u8::MAX as f32,
+        _ => 1.0,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum UinputOutputEvent {
     /// Effect data to upload to a source device and a channel to send back
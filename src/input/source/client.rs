@@ -1,13 +1,25 @@
-use std::{sync::mpsc::channel, time::Duration};
+use std::time::Duration;
 
 use evdev::FFEffectData;
 use thiserror::Error;
-use tokio::sync::mpsc::{
-    error::{SendError, TrySendError},
-    Sender,
+use tokio::{
+    sync::{
+        mpsc::{
+            error::{SendError, TrySendError},
+            Sender,
+        },
+        oneshot,
+    },
+    time::timeout,
 };
 
-use crate::input::output_event::OutputEvent;
+use crate::{
+    input::{
+        capability::Capability,
+        output_event::{AdaptiveTriggerEffect, LedEvent, OutputEvent, Trigger},
+    },
+    udev::device::UdevDevice,
+};
 
 use super::command::SourceCommand;
 
@@ -22,6 +34,8 @@ pub enum ClientError {
     ServiceError(Box<dyn std::error::Error + Send + Sync>),
     #[error("device no longer exists")]
     ChannelClosed,
+    #[error("timed out waiting for a response from the device")]
+    Timeout,
 }
 
 impl From<SendError<SourceCommand>> for ClientError {
@@ -64,14 +78,15 @@ impl SourceDeviceClient {
     /// Upload the given force feedback effect data to the source device. Returns
     /// a device-specific id of the uploaded effect if it is successful.
     pub async fn upload_effect(&self, effect: FFEffectData) -> Result<i16, ClientError> {
-        let (tx, rx) = channel();
+        let (tx, rx) = oneshot::channel();
         self.tx.try_send(SourceCommand::UploadEffect(effect, tx))?;
-        match rx.recv_timeout(Duration::from_millis(200)) {
-            Ok(result) => match result {
+        match timeout(Duration::from_millis(200), rx).await {
+            Ok(Ok(result)) => match result {
                 Ok(id) => Ok(id),
                 Err(err) => Err(ClientError::ServiceError(err)),
             },
-            Err(_err) => Err(ClientError::ChannelClosed),
+            Ok(Err(_err)) => Err(ClientError::ChannelClosed),
+            Err(_elapsed) => Err(ClientError::Timeout),
         }
     }
 
@@ -89,15 +104,86 @@ impl SourceDeviceClient {
 
     /// Erase the effect with the given id from the source device.
     pub async fn erase_effect(&self, effect_id: i16) -> Result<(), ClientError> {
-        let (tx, rx) = channel();
+        let (tx, rx) = oneshot::channel();
         self.tx
             .try_send(SourceCommand::EraseEffect(effect_id, tx))?;
-        match rx.recv_timeout(Duration::from_secs(1)) {
-            Ok(result) => match result {
+        match timeout(Duration::from_secs(1), rx).await {
+            Ok(Ok(result)) => match result {
                 Ok(_) => Ok(()),
                 Err(err) => Err(ClientError::ServiceError(err)),
             },
-            Err(_err) => Err(ClientError::ChannelClosed),
+            Ok(Err(_err)) => Err(ClientError::ChannelClosed),
+            Err(_elapsed) => Err(ClientError::Timeout),
+        }
+    }
+
+    /// Set the color and brightness of the LEDs on the source device.
+    pub async fn set_led(&self, event: LedEvent) -> Result<(), ClientError> {
+        self.tx.send(SourceCommand::SetLED(event)).await?;
+        Ok(())
+    }
+
+    /// Set the haptic feedback intensity of the left and right triggers,
+    /// normalized between 0.0 and 1.0.
+    pub async fn set_trigger_feedback(
+        &self,
+        left_intensity: f32,
+        right_intensity: f32,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(SourceCommand::SetTriggerFeedback(
+                left_intensity,
+                right_intensity,
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the adaptive trigger effect of the given trigger.
+    pub async fn set_adaptive_trigger(
+        &self,
+        trigger: Trigger,
+        effect: AdaptiveTriggerEffect,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(SourceCommand::SetAdaptiveTrigger(trigger, effect))
+            .await?;
+        Ok(())
+    }
+
+    /// Write the raw HID output report with the given report id and data to
+    /// the source device.
+    pub async fn write_hidraw_report(
+        &self,
+        report_id: u8,
+        data: Vec<u8>,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(SourceCommand::WriteHIDRawReport(report_id, data))
+            .await?;
+        Ok(())
+    }
+
+    /// Returns up-to-date udev device information about the source device.
+    pub async fn get_info(&self) -> Result<UdevDevice, ClientError> {
+        let (tx, rx) = oneshot::channel();
+        self.tx.send(SourceCommand::GetInfo(tx)).await?;
+        match timeout(Duration::from_secs(1), rx).await {
+            Ok(Ok(info)) => Ok(info),
+            Ok(Err(_err)) => Err(ClientError::ChannelClosed),
+            Err(_elapsed) => Err(ClientError::Timeout),
+        }
+    }
+
+    /// Returns the input capabilities the source device is currently capable
+    /// of emitting.
+    pub async fn get_capabilities(&self) -> Result<Vec<Capability>, ClientError> {
+        let (tx, rx) = oneshot::channel();
+        self.tx.send(SourceCommand::GetCapabilities(tx)).await?;
+        match timeout(Duration::from_secs(1), rx).await {
+            Ok(Ok(capabilities)) => Ok(capabilities),
+            Ok(Err(_err)) => Err(ClientError::ChannelClosed),
+            Err(_elapsed) => Err(ClientError::Timeout),
         }
     }
 
@@ -1,12 +1,19 @@
-use std::{error::Error, sync::mpsc::Sender};
+use std::error::Error;
 
 use evdev::FFEffectData;
+use tokio::sync::oneshot::Sender;
 
-use crate::input::output_event::OutputEvent;
+use crate::{
+    input::{
+        capability::Capability,
+        output_event::{AdaptiveTriggerEffect, LedEvent, OutputEvent, Trigger},
+    },
+    udev::device::UdevDevice,
+};
 
 /// A [SourceCommand] is a message that can be sent to a [SourceDevice] over
 /// a channel.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum SourceCommand {
     WriteEvent(OutputEvent),
     UploadEffect(
@@ -15,5 +22,16 @@ pub enum SourceCommand {
     ),
     UpdateEffect(i16, FFEffectData),
     EraseEffect(i16, Sender<Result<(), Box<dyn Error + Send + Sync>>>),
+    GetInfo(Sender<UdevDevice>),
+    /// Get the input capabilities the source device is currently capable of
+    /// emitting
+    GetCapabilities(Sender<Vec<Capability>>),
+    SetLED(LedEvent),
+    /// Set the haptic feedback intensity of the left and right triggers
+    SetTriggerFeedback(f32, f32),
+    /// Set the adaptive trigger effect of the given trigger
+    SetAdaptiveTrigger(Trigger, AdaptiveTriggerEffect),
+    /// Write the raw HID output report with the given report id and data
+    WriteHIDRawReport(u8, Vec<u8>),
     Stop,
 }
@@ -1,14 +1,22 @@
 pub mod blocked;
 pub mod gamepad;
+pub mod touchscreen;
 
-use std::{error::Error, time::Duration};
+use std::{error::Error, str::FromStr, time::Duration};
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
+    config,
+    constants::BUS_SOURCES_PREFIX,
+    input::{
+        capability::GamepadButton, composite_device::client::CompositeDeviceClient,
+        touchscreen::ButtonZone,
+    },
     udev::device::UdevDevice,
 };
 
-use self::{blocked::BlockedEventDevice, gamepad::GamepadEventDevice};
+use self::{
+    blocked::BlockedEventDevice, gamepad::GamepadEventDevice, touchscreen::TouchscreenEventDevice,
+};
 
 use super::{SourceDriver, SourceDriverOptions};
 
@@ -16,6 +24,7 @@ use super::{SourceDriver, SourceDriverOptions};
 enum DriverType {
     Blocked,
     Gamepad,
+    Touchscreen,
 }
 
 /// [EventDevice] represents an input device using the input event subsystem.
@@ -23,21 +32,26 @@ enum DriverType {
 pub enum EventDevice {
     Blocked(SourceDriver<BlockedEventDevice>),
     Gamepad(SourceDriver<GamepadEventDevice>),
+    Touchscreen(SourceDriver<TouchscreenEventDevice>),
 }
 
 impl EventDevice {
     pub fn new(
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
+        config: Option<config::Evdev>,
+        touchscreen_config: Option<config::Touchscreen>,
         is_blocked: bool,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let driver_type = EventDevice::get_driver_type(&device_info, is_blocked);
+        let driver_type =
+            EventDevice::get_driver_type(&device_info, touchscreen_config.is_some(), is_blocked);
 
         match driver_type {
             DriverType::Blocked => {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(200),
                     buffer_size: 4096,
+                    coalesce_window: None,
                 };
                 let device = BlockedEventDevice::new(device_info.clone())?;
                 let source_device =
@@ -45,21 +59,48 @@ impl EventDevice {
                 Ok(Self::Blocked(source_device))
             }
             DriverType::Gamepad => {
-                let device = GamepadEventDevice::new(device_info.clone())?;
+                let mouse_sensitivity = config.and_then(|config| config.mouse_sensitivity);
+                let device = GamepadEventDevice::new(device_info.clone(), mouse_sensitivity)?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
                 Ok(Self::Gamepad(source_device))
             }
+            DriverType::Touchscreen => {
+                let mut zones = Vec::new();
+                let zone_configs = touchscreen_config
+                    .and_then(|config| config.button_zones)
+                    .unwrap_or_default();
+                for zone in zone_configs {
+                    let Ok(button) = GamepadButton::from_str(zone.button.as_str()) else {
+                        return Err(
+                            format!("Invalid touchscreen zone button: {}", zone.button).into()
+                        );
+                    };
+                    zones.push(ButtonZone {
+                        x: zone.x,
+                        y: zone.y,
+                        width: zone.width,
+                        height: zone.height,
+                        button,
+                    });
+                }
+                let device = TouchscreenEventDevice::new(device_info.clone(), zones)?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::Touchscreen(source_device))
+            }
         }
     }
 
     /// Return the driver type for the given vendor and product
-    fn get_driver_type(device: &UdevDevice, is_blocked: bool) -> DriverType {
+    fn get_driver_type(device: &UdevDevice, is_touchscreen: bool, is_blocked: bool) -> DriverType {
         // TODO: add implmentations for other classes of evdev devices (e.g.
         // driving wheels, touch, etc.)
         log::debug!("Finding driver for interface: {:?}", device);
         if is_blocked {
             return DriverType::Blocked;
         }
+        if is_touchscreen {
+            return DriverType::Touchscreen;
+        }
         DriverType::Gamepad
     }
 }
@@ -2,16 +2,18 @@ use std::fmt::Debug;
 use std::{collections::HashMap, error::Error, os::fd::AsRawFd};
 
 use evdev::{
-    AbsInfo, AbsoluteAxisCode, Device, EventType, FFEffect, FFEffectData, FFEffectKind, FFReplay,
-    FFTrigger, InputEvent,
+    AbsInfo, AbsoluteAxisCode, Device, EventType, FFEffect, FFEffectCode, FFEffectData,
+    FFEffectKind, FFReplay, FFTrigger, FFWaveform, InputEvent, SynchronizationCode,
 };
 use nix::fcntl::{FcntlArg, OFlag};
 
 use crate::{
+    config::MouseSensitivity,
     drivers::dualsense::hid_report::SetStatePackedOutputData,
     input::{
         capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
-        event::{evdev::EvdevEvent, native::NativeEvent},
+        event::{evdev::EvdevEvent, native::NativeEvent, value::InputValue},
+        gesture::{SlotUpdate, TouchpadGestureRecognizer},
         output_event::OutputEvent,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
@@ -25,11 +27,17 @@ pub struct GamepadEventDevice {
     ff_effects: HashMap<i16, FFEffect>,
     ff_effects_dualsense: Option<i16>,
     hat_state: HashMap<AbsoluteAxisCode, i32>,
+    gesture_recognizer: Option<TouchpadGestureRecognizer>,
+    mt_slot: usize,
+    mouse_sensitivity: Option<MouseSensitivity>,
 }
 
 impl GamepadEventDevice {
     /// Create a new [Gamepad] source device from the given udev info
-    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new(
+        device_info: UdevDevice,
+        mouse_sensitivity: Option<MouseSensitivity>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let path = device_info.devnode();
         log::debug!("Opening device at: {}", path);
         let mut device = Device::open(path.clone())?;
@@ -49,23 +57,83 @@ impl GamepadEventDevice {
             axes_info.insert(axis, info);
         }
 
+        // Recognize touchpad gestures if the device supports multi-touch
+        let gesture_recognizer = if axes_info.contains_key(&AbsoluteAxisCode::ABS_MT_POSITION_X)
+            && axes_info.contains_key(&AbsoluteAxisCode::ABS_MT_POSITION_Y)
+        {
+            log::debug!("Device supports multi-touch. Enabling gesture recognition.");
+            Some(TouchpadGestureRecognizer::new())
+        } else {
+            None
+        };
+
         Ok(Self {
             device,
             axes_info,
             ff_effects: HashMap::new(),
             ff_effects_dualsense: None,
             hat_state: HashMap::new(),
+            gesture_recognizer,
+            mt_slot: 0,
+            mouse_sensitivity,
         })
     }
 
-    /// Translate the given evdev event into a native event
-    fn translate(&mut self, event: InputEvent) -> Option<NativeEvent> {
+    /// Update the gesture recognizer with the given multi-touch event and
+    /// return any gesture events that were recognized.
+    fn recognize_gestures(&mut self, event: &InputEvent) -> Vec<NativeEvent> {
+        let Some(recognizer) = self.gesture_recognizer.as_mut() else {
+            return vec![];
+        };
+
+        match event.event_type() {
+            EventType::SYNCHRONIZATION => {
+                if event.code() == SynchronizationCode::SYN_REPORT.0 {
+                    return recognizer.sync();
+                }
+            }
+            EventType::ABSOLUTE => {
+                let axis = AbsoluteAxisCode(event.code());
+                let value = event.value();
+                match axis {
+                    AbsoluteAxisCode::ABS_MT_SLOT => self.mt_slot = value.max(0) as usize,
+                    AbsoluteAxisCode::ABS_MT_TRACKING_ID => {
+                        // A tracking id of -1 indicates the finger was lifted
+                        recognizer.update(self.mt_slot, SlotUpdate::Touch(value >= 0));
+                    }
+                    AbsoluteAxisCode::ABS_MT_POSITION_X | AbsoluteAxisCode::ABS_MT_POSITION_Y => {
+                        let Some(info) = self.axes_info.get(&axis) else {
+                            return vec![];
+                        };
+                        let range = (info.maximum() - info.minimum()) as f64;
+                        if range <= 0.0 {
+                            return vec![];
+                        }
+                        let normalized = (value - info.minimum()) as f64 / range;
+                        let update = if axis == AbsoluteAxisCode::ABS_MT_POSITION_X {
+                            SlotUpdate::PositionX(normalized)
+                        } else {
+                            SlotUpdate::PositionY(normalized)
+                        };
+                        recognizer.update(self.mt_slot, update);
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+
+        vec![]
+    }
+
+    /// Translate the given evdev event into native events
+    fn translate(&mut self, event: InputEvent) -> Vec<NativeEvent> {
         log::trace!("Received event: {:?}", event);
 
         // Block Sync events, we create these at the target anyway and they waste processing.
         if event.event_type() == EventType::SYNCHRONIZATION {
             log::trace!("Holding Sync event from propagating through the processing stack.");
-            return None;
+            return vec![];
         }
 
         // If this is an ABS event, get the min/max info for this type of
@@ -96,6 +164,22 @@ impl GamepadEventDevice {
             None
         };
 
+        // If the hat switched directly from one direction to the opposite
+        // direction (e.g. from left to right), release the previous direction
+        // before pressing the new one.
+        let mut events = Vec::with_capacity(2);
+        if let Some(last_value) = state {
+            let value = event.value().signum();
+            if value != 0 && last_value.signum() == -value {
+                let release = InputEvent::new(event.event_type().0, event.code(), 0);
+                let mut evdev_event: EvdevEvent = release.into();
+                if let Some(info) = abs_info {
+                    evdev_event.set_abs_info(*info);
+                }
+                events.push(NativeEvent::from_evdev_raw(evdev_event, state));
+            }
+        }
+
         // Convert the event into an [EvdevEvent] and optionally include
         // the axis information with min/max values
         let mut evdev_event: EvdevEvent = event.into();
@@ -106,7 +190,79 @@ impl GamepadEventDevice {
         // Convert the event into a [NativeEvent]
         let native_event: NativeEvent = NativeEvent::from_evdev_raw(evdev_event, state);
 
-        Some(native_event)
+        // Relative mouse motion carries the movement delta, which is scaled
+        // by the configured sensitivity.
+        if event.event_type() == EventType::RELATIVE {
+            events.push(self.scale_mouse_motion(native_event));
+            return events;
+        }
+
+        events.push(native_event);
+        events
+    }
+
+    /// Scale the relative delta of the given mouse motion event by the
+    /// configured mouse sensitivity
+    fn scale_mouse_motion(&self, event: NativeEvent) -> NativeEvent {
+        let Some(sensitivity) = self.mouse_sensitivity.as_ref() else {
+            return event;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return event;
+        };
+        let value = InputValue::Vector2 {
+            x: x.map(|x| x * sensitivity.x_sensitivity),
+            y: y.map(|y| y * sensitivity.y_sensitivity),
+        };
+        let mut event = event;
+        event.set_value(value);
+        event
+    }
+
+    /// Returns the given effect in a form the device can play. Periodic
+    /// effects (sine, square, triangle, etc.) are uploaded as-is if the device
+    /// supports the waveform. Otherwise they are converted into a rumble
+    /// effect with the same strength so devices that only support rumble can
+    /// still play them.
+    fn as_supported_effect(&self, effect: FFEffectData) -> FFEffectData {
+        let FFEffectKind::Periodic {
+            waveform,
+            magnitude,
+            offset,
+            ..
+        } = effect.kind
+        else {
+            return effect;
+        };
+        let Some(supported) = self.device.supported_ff() else {
+            return effect;
+        };
+        let waveform_code = match waveform {
+            FFWaveform::Square => FFEffectCode::FF_SQUARE,
+            FFWaveform::Triangle => FFEffectCode::FF_TRIANGLE,
+            FFWaveform::Sine => FFEffectCode::FF_SINE,
+            FFWaveform::SawUp => FFEffectCode::FF_SAW_UP,
+            FFWaveform::SawDown => FFEffectCode::FF_SAW_DOWN,
+        };
+        let supports_periodic =
+            supported.contains(FFEffectCode::FF_PERIODIC) && supported.contains(waveform_code);
+        if supports_periodic || !supported.contains(FFEffectCode::FF_RUMBLE) {
+            return effect;
+        }
+
+        // Use the peak of the waveform as the rumble strength, scaled from
+        // i16 to the u16 range used by rumble effects.
+        let peak =
+            (magnitude.unsigned_abs() as u32 + offset.unsigned_abs() as u32).min(i16::MAX as u32);
+        let strength = (peak * 2) as u16;
+        log::debug!("Converting periodic {waveform:?} effect to rumble with strength {strength}");
+        FFEffectData {
+            kind: FFEffectKind::Rumble {
+                strong_magnitude: strength,
+                weak_magnitude: strength,
+            },
+            ..effect
+        }
     }
 
     /// Process dualsense force feedback output reports
@@ -198,10 +354,12 @@ impl SourceInputDevice for GamepadEventDevice {
         };
 
         // Convert the events into native events
-        let native_events = events
-            .into_iter()
-            .filter_map(|e| self.translate(e))
-            .collect();
+        let mut native_events = Vec::with_capacity(events.len());
+        for event in events {
+            let mut gestures = self.recognize_gestures(&event);
+            native_events.append(&mut self.translate(event));
+            native_events.append(&mut gestures);
+        }
 
         Ok(native_events)
     }
@@ -277,6 +435,11 @@ impl SourceInputDevice for GamepadEventDevice {
             }
         }
 
+        // Include any gestures that can be recognized from multi-touch input
+        if self.gesture_recognizer.is_some() {
+            capabilities.extend(TouchpadGestureRecognizer::capabilities());
+        }
+
         Ok(capabilities)
     }
 }
@@ -318,6 +481,10 @@ impl SourceOutputDevice for GamepadEventDevice {
                 Ok(())
             }
             OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::LED(_) => Ok(()),
+            OutputEvent::TriggerFeedback { .. } => Ok(()),
+            OutputEvent::AdaptiveTrigger { .. } => Ok(()),
+            OutputEvent::HIDRaw(_) => Ok(()),
         }
     }
 
@@ -329,6 +496,7 @@ impl SourceOutputDevice for GamepadEventDevice {
             log::debug!("Device does not support FF effects");
             return Ok(-1);
         }
+        let effect = self.as_supported_effect(effect);
         match self.device.upload_ff_effect(effect) {
             Ok(effect) => {
                 let id = effect.id() as i16;
@@ -346,6 +514,7 @@ impl SourceOutputDevice for GamepadEventDevice {
             log::debug!("Device does not support FF effects");
             return Ok(());
         }
+        let effect = self.as_supported_effect(effect);
         let Some(current_effect) = self.ff_effects.get_mut(&effect_id) else {
             log::warn!("Unable to find existing FF effect with id {effect_id}");
             return Ok(());
@@ -377,6 +546,7 @@ impl Debug for GamepadEventDevice {
             .field("ff_effects", &self.ff_effects)
             .field("ff_effects_dualsense", &self.ff_effects_dualsense)
             .field("hat_state", &self.hat_state)
+            .field("gesture_recognizer", &self.gesture_recognizer)
             .finish()
     }
 }
@@ -0,0 +1,150 @@
+use std::fmt::Debug;
+use std::time::Instant;
+use std::{collections::HashMap, error::Error, os::fd::AsRawFd};
+
+use evdev::{AbsInfo, AbsoluteAxisCode, Device, EventType, InputEvent, SynchronizationCode};
+use nix::fcntl::{FcntlArg, OFlag};
+
+use crate::{
+    input::{
+        capability::Capability,
+        event::native::NativeEvent,
+        gesture::SlotUpdate,
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+        touchscreen::{ButtonZone, TouchscreenRecognizer},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Source device implementation for evdev multi-touch touchscreens. Touches
+/// are emitted as touchscreen motion, taps are emitted as mouse clicks, and
+/// touches inside of the configured button zones are emitted as gamepad
+/// buttons.
+pub struct TouchscreenEventDevice {
+    device: Device,
+    axes_info: HashMap<AbsoluteAxisCode, AbsInfo>,
+    recognizer: TouchscreenRecognizer,
+    mt_slot: usize,
+}
+
+impl TouchscreenEventDevice {
+    /// Create a new [TouchscreenEventDevice] source device from the given
+    /// udev info and button zones
+    pub fn new(
+        device_info: UdevDevice,
+        zones: Vec<ButtonZone>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = device_info.devnode();
+        log::debug!("Opening touchscreen at: {}", path);
+        let mut device = Device::open(path.clone())?;
+        device.grab()?;
+
+        // Set the device to do non-blocking reads
+        let raw_fd = device.as_raw_fd();
+        nix::fcntl::fcntl(raw_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+
+        // Query information about the device to get the absolute ranges
+        let mut axes_info = HashMap::new();
+        for (axis, info) in device.get_absinfo()? {
+            axes_info.insert(axis, info);
+        }
+        if !axes_info.contains_key(&AbsoluteAxisCode::ABS_MT_POSITION_X)
+            || !axes_info.contains_key(&AbsoluteAxisCode::ABS_MT_POSITION_Y)
+        {
+            return Err(format!("Device {path} does not support multi-touch").into());
+        }
+
+        Ok(Self {
+            device,
+            axes_info,
+            recognizer: TouchscreenRecognizer::new(zones),
+            mt_slot: 0,
+        })
+    }
+
+    /// Update the recognizer with the given multi-touch event and return any
+    /// events that were recognized.
+    fn process_event(&mut self, event: &InputEvent) -> Vec<NativeEvent> {
+        match event.event_type() {
+            EventType::SYNCHRONIZATION => {
+                if event.code() == SynchronizationCode::SYN_REPORT.0 {
+                    return self.recognizer.sync(Instant::now());
+                }
+            }
+            EventType::ABSOLUTE => {
+                let axis = AbsoluteAxisCode(event.code());
+                let value = event.value();
+                match axis {
+                    AbsoluteAxisCode::ABS_MT_SLOT => self.mt_slot = value.max(0) as usize,
+                    AbsoluteAxisCode::ABS_MT_TRACKING_ID => {
+                        // A tracking id of -1 indicates the finger was lifted
+                        self.recognizer
+                            .update(self.mt_slot, SlotUpdate::Touch(value >= 0));
+                    }
+                    AbsoluteAxisCode::ABS_MT_POSITION_X | AbsoluteAxisCode::ABS_MT_POSITION_Y => {
+                        let Some(info) = self.axes_info.get(&axis) else {
+                            return vec![];
+                        };
+                        let range = (info.maximum() - info.minimum()) as f64;
+                        if range <= 0.0 {
+                            return vec![];
+                        }
+                        let normalized = (value - info.minimum()) as f64 / range;
+                        let update = if axis == AbsoluteAxisCode::ABS_MT_POSITION_X {
+                            SlotUpdate::PositionX(normalized)
+                        } else {
+                            SlotUpdate::PositionY(normalized)
+                        };
+                        self.recognizer.update(self.mt_slot, update);
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+
+        vec![]
+    }
+}
+
+impl SourceInputDevice for TouchscreenEventDevice {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        // Read events from the device
+        let events: Vec<InputEvent> = match self.device.fetch_events() {
+            Ok(events) => events.into_iter().collect(),
+            Err(err) => match err.kind() {
+                // Do nothing if this would block
+                std::io::ErrorKind::WouldBlock => return Ok(vec![]),
+                _ => {
+                    log::trace!("Failed to fetch events: {:?}", err);
+                    let msg = format!("Failed to fetch events: {:?}", err);
+                    return Err(msg.into());
+                }
+            },
+        };
+
+        let mut native_events = Vec::new();
+        for event in events {
+            native_events.append(&mut self.process_event(&event));
+        }
+
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.recognizer.capabilities())
+    }
+}
+
+impl SourceOutputDevice for TouchscreenEventDevice {}
+
+impl Debug for TouchscreenEventDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TouchscreenEventDevice")
+            .field("axes_info", &self.axes_info)
+            .field("recognizer", &self.recognizer)
+            .finish()
+    }
+}
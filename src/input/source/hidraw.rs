@@ -1,9 +1,14 @@
 pub mod dualsense;
 pub mod fts3528;
+pub mod generic;
 pub mod lego;
 pub mod opineo;
 pub mod rog_ally;
 pub mod steam_deck;
+pub mod steam_deck_trackpad;
+#[cfg(test)]
+mod steam_deck_trackpad_test;
+pub mod switch_pro;
 pub mod xpad_uhid;
 
 use std::{error::Error, time::Duration};
@@ -12,13 +17,18 @@ use rog_ally::RogAlly;
 use xpad_uhid::XpadUhid;
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, drivers, input::composite_device::client::CompositeDeviceClient,
+    constants::BUS_SOURCES_PREFIX,
+    drivers::{
+        self, generic_hid::driver::read_report_descriptor, hid_descriptor::ReportDescriptor,
+    },
+    input::composite_device::client::CompositeDeviceClient,
     udev::device::UdevDevice,
 };
 
 use self::{
-    dualsense::DualSenseController, fts3528::Fts3528Touchscreen, lego::LegionController,
-    opineo::OrangePiNeoTouchpad, steam_deck::DeckController,
+    dualsense::DualSenseController, fts3528::Fts3528Touchscreen, generic::GenericHidController,
+    lego::LegionController, opineo::OrangePiNeoTouchpad, steam_deck::DeckController,
+    switch_pro::SwitchProController,
 };
 
 use super::{SourceDriver, SourceDriverOptions};
@@ -26,6 +36,7 @@ use super::{SourceDriver, SourceDriverOptions};
 /// List of available drivers
 enum DriverType {
     Unknown,
+    Generic,
     DualSense,
     SteamDeck,
     LegionGo,
@@ -33,11 +44,13 @@ enum DriverType {
     Fts3528Touchscreen,
     XpadUhid,
     RogAlly,
+    SwitchPro,
 }
 
 /// [HidRawDevice] represents an input device using the hidraw subsystem.
 #[derive(Debug)]
 pub enum HidRawDevice {
+    Generic(SourceDriver<GenericHidController>),
     DualSense(SourceDriver<DualSenseController>),
     SteamDeck(SourceDriver<DeckController>),
     LegionGo(SourceDriver<LegionController>),
@@ -45,6 +58,7 @@ pub enum HidRawDevice {
     Fts3528Touchscreen(SourceDriver<Fts3528Touchscreen>),
     XpadUhid(SourceDriver<XpadUhid>),
     RogAlly(SourceDriver<RogAlly>),
+    SwitchPro(SourceDriver<SwitchProController>),
 }
 
 impl HidRawDevice {
@@ -59,10 +73,16 @@ impl HidRawDevice {
 
         match driver_type {
             DriverType::Unknown => Err("No driver for hidraw interface found".into()),
+            DriverType::Generic => {
+                let device = GenericHidController::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::Generic(source_device))
+            }
             DriverType::DualSense => {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(1),
                     buffer_size: 2048,
+                    coalesce_window: None,
                 };
                 let device = DualSenseController::new(device_info.clone())?;
                 let source_device =
@@ -73,6 +93,7 @@ impl HidRawDevice {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(1),
                     buffer_size: 2048,
+                    coalesce_window: None,
                 };
                 let device = DeckController::new(device_info.clone())?;
                 let source_device =
@@ -104,11 +125,23 @@ impl HidRawDevice {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(500),
                     buffer_size: 1024,
+                    coalesce_window: None,
                 };
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
                 Ok(Self::RogAlly(source_device))
             }
+            DriverType::SwitchPro => {
+                let options = SourceDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                    coalesce_window: None,
+                };
+                let device = SwitchProController::new(device_info.clone())?;
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
+                Ok(Self::SwitchPro(source_device))
+            }
         }
     }
 
@@ -163,10 +196,47 @@ impl HidRawDevice {
             return DriverType::RogAlly;
         }
 
+        // Nintendo Switch Pro Controller
+        if vid == drivers::switch_pro::driver::VID && pid == drivers::switch_pro::driver::PID {
+            log::info!("Detected Nintendo Switch Pro Controller");
+            return DriverType::SwitchPro;
+        }
+
+        // Generic HID gamepad
+        if is_hid_gamepad(device) {
+            log::info!("Detected generic HID gamepad");
+            return DriverType::Generic;
+        }
+
         // Unknown
         log::warn!("No driver for hidraw interface found. VID: {vid}, PID: {pid}");
         DriverType::Unknown
     }
+
+    /// Returns true if the given device uses the generic HID gamepad driver
+    /// because it has no native driver
+    pub fn is_generic(&self) -> bool {
+        matches!(self, Self::Generic(_))
+    }
+}
+
+/// Returns true if the report descriptor of the given hidraw device
+/// describes a joystick or gamepad
+fn is_hid_gamepad(device: &UdevDevice) -> bool {
+    let descriptor = match read_report_descriptor(device) {
+        Ok(descriptor) => descriptor,
+        Err(e) => {
+            log::debug!("Unable to read HID report descriptor: {e:?}");
+            return false;
+        }
+    };
+    match ReportDescriptor::parse(&descriptor) {
+        Ok(descriptor) => descriptor.is_gamepad(),
+        Err(e) => {
+            log::debug!("Unable to parse HID report descriptor: {e:?}");
+            false
+        }
+    }
 }
 
 /// Returns the DBus path for a [HIDRawDevice] from a device path (E.g. /dev/hidraw0)
@@ -3,7 +3,10 @@ use std::{collections::HashMap, error::Error};
 
 use evdev::{FFEffectData, FFEffectKind};
 
-use crate::drivers::dualsense::driver::{DS5_EDGE_PID, DS5_PID, DS5_VID};
+use crate::drivers::dualsense::driver::{
+    DS5_EDGE_PID, DS5_PID, DS5_VID, TRIGGER_EFFECT_FEEDBACK, TRIGGER_EFFECT_OFF,
+    TRIGGER_EFFECT_VIBRATION, TRIGGER_EFFECT_WEAPON,
+};
 use crate::{
     drivers::dualsense::{self, driver::Driver},
     input::{
@@ -12,7 +15,8 @@ use crate::{
             Touchpad,
         },
         event::{native::NativeEvent, value::InputValue},
-        output_event::OutputEvent,
+        output_capability::{OutputCapability, LED},
+        output_event::{AdaptiveTriggerEffect, LedEvent, OutputEvent, Trigger},
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
     udev::device::UdevDevice,
@@ -157,9 +161,68 @@ impl SourceOutputDevice for DualSenseController {
                 Ok(self.driver.write(report)?)
             }
             OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::LED(event) => self.set_led(event),
+            OutputEvent::TriggerFeedback {
+                left_intensity,
+                right_intensity,
+            } => self.set_trigger_feedback(left_intensity, right_intensity),
+            OutputEvent::AdaptiveTrigger { trigger, effect } => {
+                self.set_adaptive_trigger(trigger, effect)
+            }
+            OutputEvent::HIDRaw(report) => self.write_hidraw_report(report.report_id, report.data),
         }
     }
 
+    /// Write the given raw output report to the gamepad
+    fn write_hidraw_report(&mut self, report_id: u8, data: Vec<u8>) -> Result<(), OutputError> {
+        log::debug!("Writing HID output report {report_id} to DualSense");
+        Ok(self.driver.write_report(report_id, data.as_slice())?)
+    }
+
+    /// Set the color and brightness of the lightbar
+    fn set_led(&mut self, event: LedEvent) -> Result<(), OutputError> {
+        let (r, g, b) = event.scaled_color();
+        Ok(self.driver.set_led_color(r, g, b)?)
+    }
+
+    /// Set the resistance of the adaptive triggers
+    fn set_trigger_feedback(
+        &mut self,
+        left_intensity: f32,
+        right_intensity: f32,
+    ) -> Result<(), OutputError> {
+        let scale = |intensity: f32| -> u8 { (intensity.clamp(0.0, 1.0) * u8::MAX as f32) as u8 };
+        Ok(self
+            .driver
+            .set_trigger_resistance(scale(left_intensity), scale(right_intensity))?)
+    }
+
+    /// Set the adaptive trigger effect of the given trigger
+    fn set_adaptive_trigger(
+        &mut self,
+        trigger: Trigger,
+        effect: AdaptiveTriggerEffect,
+    ) -> Result<(), OutputError> {
+        let effect = encode_trigger_effect(effect);
+        let result = match trigger {
+            Trigger::LeftTrigger => self.driver.set_trigger_effect(Some(effect), None),
+            Trigger::RightTrigger => self.driver.set_trigger_effect(None, Some(effect)),
+        };
+        Ok(result?)
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![
+            OutputCapability::ForceFeedback,
+            OutputCapability::LED(LED::Color),
+            OutputCapability::LED(LED::Brightness),
+            OutputCapability::TriggerFeedback,
+            OutputCapability::AdaptiveTrigger,
+            OutputCapability::DualSense,
+        ])
+    }
+
     /// Upload the given force feedback effect data to the source device. Returns
     /// a device-specific id of the uploaded effect if it is successful.
     fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
@@ -196,6 +259,75 @@ impl Debug for DualSenseController {
     }
 }
 
+/// Encode the given adaptive trigger effect into the raw DualSense trigger
+/// effect parameters. Effects with out-of-range parameters turn the trigger
+/// effect off.
+fn encode_trigger_effect(effect: AdaptiveTriggerEffect) -> [u8; 11] {
+    let mut ffb = [0; 11];
+    ffb[0] = TRIGGER_EFFECT_OFF;
+
+    // Bitmask of the active zones and 3-bit force values for each zone from
+    // the given position until the end of the trigger pull.
+    let zones = |position: u8, force: u8| -> (u16, u32) {
+        let force = (force.clamp(1, 8) - 1) as u32;
+        let mut active_zones: u16 = 0;
+        let mut force_zones: u32 = 0;
+        for zone in position..10 {
+            active_zones |= 1 << zone;
+            force_zones |= force << (3 * zone);
+        }
+        (active_zones, force_zones)
+    };
+
+    match effect {
+        AdaptiveTriggerEffect::Off => (),
+        AdaptiveTriggerEffect::Feedback {
+            start_position,
+            strength,
+        } => {
+            if start_position > 9 || strength == 0 {
+                return ffb;
+            }
+            let (active_zones, force_zones) = zones(start_position, strength);
+            ffb[0] = TRIGGER_EFFECT_FEEDBACK;
+            ffb[1..3].copy_from_slice(&active_zones.to_le_bytes());
+            ffb[3..7].copy_from_slice(&force_zones.to_le_bytes());
+        }
+        AdaptiveTriggerEffect::Weapon {
+            start_position,
+            end_position,
+            strength,
+        } => {
+            let is_valid = (2..=7).contains(&start_position)
+                && end_position > start_position
+                && end_position <= 8;
+            if !is_valid || strength == 0 {
+                return ffb;
+            }
+            let start_stop_zones: u16 = (1 << start_position) | (1 << end_position);
+            ffb[0] = TRIGGER_EFFECT_WEAPON;
+            ffb[1..3].copy_from_slice(&start_stop_zones.to_le_bytes());
+            ffb[3] = strength.clamp(1, 8) - 1;
+        }
+        AdaptiveTriggerEffect::Vibration {
+            position,
+            amplitude,
+            frequency,
+        } => {
+            if position > 9 || amplitude == 0 || frequency == 0 {
+                return ffb;
+            }
+            let (active_zones, amplitude_zones) = zones(position, amplitude);
+            ffb[0] = TRIGGER_EFFECT_VIBRATION;
+            ffb[1..3].copy_from_slice(&active_zones.to_le_bytes());
+            ffb[3..7].copy_from_slice(&amplitude_zones.to_le_bytes());
+            ffb[9] = frequency;
+        }
+    }
+
+    ffb
+}
+
 /// Translate the given DualSense events into native events
 fn translate_events(events: Vec<dualsense::event::Event>) -> Vec<NativeEvent> {
     events.into_iter().map(translate_event).collect()
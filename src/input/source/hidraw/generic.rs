@@ -0,0 +1,158 @@
+use std::{collections::HashSet, error::Error, fmt::Debug};
+
+use crate::{
+    config::capability_config_from_hid_usage,
+    drivers::{
+        generic_hid::{
+            driver::Driver,
+            event::{Event, FieldInput},
+        },
+        hid_descriptor::{InputField, Usage},
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+// Generic Desktop usages of the horizontal stick axes
+const USAGE_X: u16 = 0x30;
+const USAGE_RX: u16 = 0x33;
+
+/// Number of directions reported by a hat switch
+const HAT_DIRECTIONS: i32 = 8;
+
+/// Generic HID gamepad source device implementation, used for HID gamepads
+/// without a native driver
+pub struct GenericHidController {
+    driver: Driver,
+    capabilities: Vec<Capability>,
+}
+
+impl GenericHidController {
+    /// Create a new generic HID gamepad source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+
+        // Collect the capabilities of all fields in the report descriptor
+        let mut seen = HashSet::new();
+        let mut capabilities = Vec::new();
+        for field in driver.fields() {
+            let Some(capability) = usage_capability(field.usage) else {
+                continue;
+            };
+            if seen.insert(capability.clone()) {
+                capabilities.push(capability);
+            }
+        }
+
+        Ok(Self {
+            driver,
+            capabilities,
+        })
+    }
+}
+
+impl SourceInputDevice for GenericHidController {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = events.into_iter().filter_map(translate_event).collect();
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.capabilities.clone())
+    }
+}
+
+impl SourceOutputDevice for GenericHidController {}
+
+impl Debug for GenericHidController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenericHidController")
+            .field("capabilities", &self.capabilities)
+            .finish()
+    }
+}
+
+/// Returns the capability of the given HID usage, if it has one
+fn usage_capability(usage: Usage) -> Option<Capability> {
+    let capability: Capability = capability_config_from_hid_usage(usage)?.into();
+    if capability == Capability::NotImplemented {
+        return None;
+    }
+    Some(capability)
+}
+
+/// Returns a value between 0.0 and 1.0 based on the logical range of the
+/// given field
+fn normalize_unsigned_value(field: &InputField, value: i32) -> f64 {
+    let range = field.logical_maximum as f64 - field.logical_minimum as f64;
+    if range <= 0.0 {
+        return 0.0;
+    }
+    let value = (value as f64 - field.logical_minimum as f64) / range;
+    value.clamp(0.0, 1.0)
+}
+
+/// Returns a value between -1.0 and 1.0 based on the logical range of the
+/// given field
+fn normalize_signed_value(field: &InputField, value: i32) -> f64 {
+    normalize_unsigned_value(field, value) * 2.0 - 1.0
+}
+
+/// Returns the horizontal and vertical direction of the given hat switch
+/// value. Hat switches report 8 directions clockwise starting at north, and
+/// any value outside of the logical range when centered.
+fn hat_direction(field: &InputField, value: i32) -> (f64, f64) {
+    let direction = value - field.logical_minimum;
+    if !(0..HAT_DIRECTIONS).contains(&direction) || value > field.logical_maximum {
+        return (0.0, 0.0);
+    }
+    let x = match direction {
+        1..=3 => 1.0,
+        5..=7 => -1.0,
+        _ => 0.0,
+    };
+    let y = match direction {
+        0 | 1 | 7 => -1.0,
+        3..=5 => 1.0,
+        _ => 0.0,
+    };
+    (x, y)
+}
+
+/// Translate the given generic HID event into a native event
+fn translate_event(event: Event) -> Option<NativeEvent> {
+    let Event::Field(FieldInput { field, value }) = event;
+    let capability = usage_capability(field.usage)?;
+    let value = match &capability {
+        Capability::Gamepad(Gamepad::Button(_)) => InputValue::Bool(value != 0),
+        Capability::Gamepad(Gamepad::Trigger(_)) => {
+            InputValue::Float(normalize_unsigned_value(&field, value))
+        }
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0)) => {
+            let (x, y) = hat_direction(&field, value);
+            InputValue::Vector2 {
+                x: Some(x),
+                y: Some(y),
+            }
+        }
+        Capability::Gamepad(Gamepad::Axis(_)) => {
+            let value = Some(normalize_signed_value(&field, value));
+            if matches!(field.usage.id, USAGE_X | USAGE_RX) {
+                InputValue::Vector2 { x: value, y: None }
+            } else {
+                InputValue::Vector2 { x: None, y: value }
+            }
+        }
+        _ => return None,
+    };
+
+    Some(NativeEvent::new(capability, value))
+}
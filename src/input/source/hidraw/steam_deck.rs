@@ -4,7 +4,7 @@ use std::{
     fmt::Debug,
     sync::{Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use evdev::{FFEffectData, FFEffectKind, InputEvent};
@@ -30,10 +30,16 @@ use crate::{
     udev::device::UdevDevice,
 };
 
+use super::steam_deck_trackpad::SteamDeckTrackpadProtocol;
+
 /// Vendor ID
 pub const VID: u16 = 0x28de;
 /// Product ID
 pub const PID: u16 = 0x1205;
+/// Amplitude of the haptic pulse sent when a trackpad is clicked
+const HAPTIC_CLICK_AMPLITUDE: u16 = 0x200;
+/// Period in microseconds of the haptic pulse sent when a trackpad is clicked
+const HAPTIC_CLICK_PERIOD: u16 = 0x100;
 
 pub struct DeckController {
     driver: Driver,
@@ -41,6 +47,7 @@ pub struct DeckController {
     lizard_mode_started: bool,
     lizard_mode_running: Arc<Mutex<bool>>,
     ff_evdev_effects: HashMap<i16, FFEffectData>,
+    trackpads: SteamDeckTrackpadProtocol,
 }
 
 impl DeckController {
@@ -55,6 +62,7 @@ impl DeckController {
             lizard_mode_started: false,
             lizard_mode_running: Arc::new(Mutex::new(false)),
             ff_evdev_effects: HashMap::new(),
+            trackpads: SteamDeckTrackpadProtocol::new(),
         })
     }
 
@@ -183,6 +191,20 @@ impl DeckController {
         Ok(())
     }
 
+    /// Translate the given Steam Deck events into native events. Trackpad
+    /// events are translated by the trackpad protocol.
+    fn translate_events(&mut self, events: Vec<steam_deck::event::Event>) -> Vec<NativeEvent> {
+        let now = Instant::now();
+        let mut native_events = Vec::with_capacity(events.len());
+        for event in events {
+            match self.trackpads.handle_event(&event, now) {
+                Some(trackpad_events) => native_events.extend(trackpad_events),
+                None => native_events.push(translate_event(event)),
+            }
+        }
+        native_events
+    }
+
     /// Process dualsense force feedback output reports
     fn process_dualsense_ff(
         &mut self,
@@ -222,7 +244,21 @@ impl SourceInputDevice for DeckController {
         }
 
         let events = self.driver.poll()?;
-        let native_events = translate_events(events);
+        let native_events = self.translate_events(events);
+
+        // Send a haptic pulse to each trackpad that was clicked
+        for side in self.trackpads.take_haptic_clicks() {
+            let result = self.driver.haptic_pulse(
+                side.pad(),
+                HAPTIC_CLICK_AMPLITUDE,
+                HAPTIC_CLICK_PERIOD,
+                1,
+            );
+            if let Err(e) = result {
+                log::debug!("Failed to send haptic click to {side:?} trackpad: {e:?}");
+            }
+        }
+
         Ok(native_events)
     }
 
@@ -249,6 +285,12 @@ impl SourceOutputDevice for DeckController {
                 }
             }
             OutputEvent::Uinput(_) => (),
+            OutputEvent::LED(_) => (),
+            OutputEvent::TriggerFeedback { .. } => (),
+            OutputEvent::AdaptiveTrigger { .. } => (),
+            OutputEvent::HIDRaw(report) => {
+                self.write_hidraw_report(report.report_id, report.data)?;
+            }
         }
 
         Ok(())
@@ -328,7 +370,7 @@ fn normalize_unsigned_value(raw_value: f64, max: f64) -> f64 {
 
 /// Normalize the value to something between -1.0 and 1.0 based on the Deck's
 /// minimum and maximum axis ranges.
-fn normalize_axis_value(event: steam_deck::event::AxisEvent) -> InputValue {
+pub(super) fn normalize_axis_value(event: steam_deck::event::AxisEvent) -> InputValue {
     match event {
         steam_deck::event::AxisEvent::LPad(value) => {
             let min = steam_deck::hid_report::PAD_X_MIN;
@@ -428,11 +470,6 @@ fn normalize_trigger_value(event: steam_deck::event::TriggerEvent) -> InputValue
     }
 }
 
-/// Translate the given Steam Deck events into native events
-fn translate_events(events: Vec<steam_deck::event::Event>) -> Vec<NativeEvent> {
-    events.into_iter().map(translate_event).collect()
-}
-
 /// Translate the given Steam Deck event into a native event
 fn translate_event(event: steam_deck::event::Event) -> NativeEvent {
     match event {
@@ -646,7 +683,9 @@ pub const CAPABILITIES: &[Capability] = &[
     Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Press))),
     Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Touch))),
     Capability::Touchpad(Touchpad::LeftPad(Touch::Motion)),
+    Capability::Touchpad(Touchpad::LeftPad(Touch::Velocity)),
     Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Press))),
     Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Touch))),
     Capability::Touchpad(Touchpad::RightPad(Touch::Motion)),
+    Capability::Touchpad(Touchpad::RightPad(Touch::Velocity)),
 ];
@@ -0,0 +1,219 @@
+use std::time::Instant;
+
+use crate::{
+    drivers::steam_deck::{
+        event::{AxisEvent, ButtonEvent, Event, TriggerEvent},
+        hid_report::{Pad, PAD_FORCE_MAX},
+    },
+    input::{
+        capability::{Capability, Touch, TouchButton, Touchpad},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::steam_deck::normalize_axis_value;
+
+/// One of the two trackpads of the Steam Deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackpadSide {
+    Left,
+    Right,
+}
+
+impl TrackpadSide {
+    /// Returns the touchpad capability of this trackpad for the given touch
+    /// input
+    fn capability(&self, touch: Touch) -> Capability {
+        match self {
+            TrackpadSide::Left => Capability::Touchpad(Touchpad::LeftPad(touch)),
+            TrackpadSide::Right => Capability::Touchpad(Touchpad::RightPad(touch)),
+        }
+    }
+
+    /// Returns the side used to send haptic pulses to this trackpad
+    pub fn pad(&self) -> Pad {
+        match self {
+            TrackpadSide::Left => Pad::Left,
+            TrackpadSide::Right => Pad::Right,
+        }
+    }
+}
+
+/// Last known state of a single trackpad
+#[derive(Debug, Default, Clone)]
+struct TrackpadState {
+    is_touching: bool,
+    /// Index of the touch reported with the finger position
+    index: u8,
+    /// Last normalized finger position as (x, y)
+    position: Option<(f64, f64)>,
+    /// Time the last finger position was reported
+    position_time: Option<Instant>,
+    /// Normalized force applied to the trackpad between 0.0 and 1.0
+    pressure: f64,
+}
+
+/// Translates the trackpad reports of the Steam Deck controller into touchpad
+/// events. In addition to the finger position, the force applied to each pad
+/// is reported as the touch pressure and the finger velocity is emitted as
+/// [Touch::Velocity] events. Clicks of either pad are recorded so a haptic
+/// pulse can be sent back to the pad that was clicked.
+#[derive(Debug, Default)]
+pub struct SteamDeckTrackpadProtocol {
+    left: TrackpadState,
+    right: TrackpadState,
+    /// Trackpads that were clicked since the last call to
+    /// [SteamDeckTrackpadProtocol::take_haptic_clicks]
+    haptic_clicks: Vec<TrackpadSide>,
+}
+
+impl SteamDeckTrackpadProtocol {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translate the given Steam Deck event if it is a trackpad event. Returns
+    /// [None] for events of any other input.
+    pub fn handle_event(&mut self, event: &Event, now: Instant) -> Option<Vec<NativeEvent>> {
+        let events = match event {
+            Event::Button(ButtonEvent::LPadTouch(value)) => {
+                self.on_touch(TrackpadSide::Left, value.pressed)
+            }
+            Event::Button(ButtonEvent::RPadTouch(value)) => {
+                self.on_touch(TrackpadSide::Right, value.pressed)
+            }
+            Event::Button(ButtonEvent::LPadPress(value)) => {
+                self.on_press(TrackpadSide::Left, value.pressed)
+            }
+            Event::Button(ButtonEvent::RPadPress(value)) => {
+                self.on_press(TrackpadSide::Right, value.pressed)
+            }
+            Event::Axis(axis @ AxisEvent::LPad(_)) => {
+                self.on_motion(TrackpadSide::Left, normalize_axis_value(axis.clone()), now)
+            }
+            Event::Axis(axis @ AxisEvent::RPad(_)) => {
+                self.on_motion(TrackpadSide::Right, normalize_axis_value(axis.clone()), now)
+            }
+            Event::Trigger(TriggerEvent::LPadForce(value)) => {
+                self.on_force(TrackpadSide::Left, value.value)
+            }
+            Event::Trigger(TriggerEvent::RPadForce(value)) => {
+                self.on_force(TrackpadSide::Right, value.value)
+            }
+            _ => return None,
+        };
+        Some(events)
+    }
+
+    /// Returns the trackpads that were clicked since the last call and should
+    /// receive a haptic pulse
+    pub fn take_haptic_clicks(&mut self) -> Vec<TrackpadSide> {
+        std::mem::take(&mut self.haptic_clicks)
+    }
+
+    fn state(&mut self, side: TrackpadSide) -> &mut TrackpadState {
+        match side {
+            TrackpadSide::Left => &mut self.left,
+            TrackpadSide::Right => &mut self.right,
+        }
+    }
+
+    /// Handle the finger touching or leaving the given trackpad. The velocity
+    /// is reset when the finger is lifted.
+    fn on_touch(&mut self, side: TrackpadSide, is_touching: bool) -> Vec<NativeEvent> {
+        let state = self.state(side);
+        state.is_touching = is_touching;
+        let cap = side.capability(Touch::Button(TouchButton::Touch));
+        let mut events = vec![NativeEvent::new(cap, InputValue::Bool(is_touching))];
+        if !is_touching {
+            state.position = None;
+            state.position_time = None;
+            let cap = side.capability(Touch::Velocity);
+            let value = InputValue::Vector2 {
+                x: Some(0.0),
+                y: Some(0.0),
+            };
+            events.push(NativeEvent::new(cap, value));
+        }
+        events
+    }
+
+    /// Handle a click of the given trackpad
+    fn on_press(&mut self, side: TrackpadSide, pressed: bool) -> Vec<NativeEvent> {
+        if pressed {
+            self.haptic_clicks.push(side);
+        }
+        let cap = side.capability(Touch::Button(TouchButton::Press));
+        vec![NativeEvent::new(cap, InputValue::Bool(pressed))]
+    }
+
+    /// Handle a new finger position on the given trackpad. The velocity is
+    /// computed from the distance to the last position while touching.
+    fn on_motion(
+        &mut self,
+        side: TrackpadSide,
+        value: InputValue,
+        now: Instant,
+    ) -> Vec<NativeEvent> {
+        let InputValue::Touch {
+            index,
+            is_touching,
+            x,
+            y,
+            ..
+        } = value
+        else {
+            return vec![];
+        };
+        let state = self.state(side);
+        let position = (x.unwrap_or_default(), y.unwrap_or_default());
+        let last_position = state.position.zip(state.position_time);
+        state.index = index;
+        state.position = Some(position);
+        state.position_time = Some(now);
+
+        let value = InputValue::Touch {
+            index,
+            is_touching,
+            pressure: Some(state.pressure),
+            x,
+            y,
+        };
+        let mut events = vec![NativeEvent::new(side.capability(Touch::Motion), value)];
+
+        let Some(((last_x, last_y), last_time)) = last_position else {
+            return events;
+        };
+        let elapsed = now.duration_since(last_time).as_secs_f64();
+        if !is_touching || elapsed <= 0.0 {
+            return events;
+        }
+        let value = InputValue::Vector2 {
+            x: Some((position.0 - last_x) / elapsed),
+            y: Some((position.1 - last_y) / elapsed),
+        };
+        events.push(NativeEvent::new(side.capability(Touch::Velocity), value));
+        events
+    }
+
+    /// Handle a change of the force applied to the given trackpad. The new
+    /// pressure is emitted with the last finger position while touching.
+    fn on_force(&mut self, side: TrackpadSide, force: u16) -> Vec<NativeEvent> {
+        let state = self.state(side);
+        state.pressure = (force as f64 / PAD_FORCE_MAX).clamp(0.0, 1.0);
+        let Some((x, y)) = state.position else {
+            return vec![];
+        };
+        if !state.is_touching {
+            return vec![];
+        }
+        let value = InputValue::Touch {
+            index: state.index,
+            is_touching: true,
+            pressure: Some(state.pressure),
+            x: Some(x),
+            y: Some(y),
+        };
+        vec![NativeEvent::new(side.capability(Touch::Motion), value)]
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    drivers::steam_deck::event::{
+        AxisEvent, BinaryInput, ButtonEvent, Event, TouchAxisInput, TriggerEvent, TriggerInput,
+    },
+    input::{
+        capability::{Capability, Touch, TouchButton, Touchpad},
+        event::value::InputValue,
+        source::hidraw::steam_deck_trackpad::{SteamDeckTrackpadProtocol, TrackpadSide},
+    },
+};
+
+fn left_pad_motion(x: i16, y: i16) -> Event {
+    Event::Axis(AxisEvent::LPad(TouchAxisInput {
+        index: 0,
+        is_touching: true,
+        x,
+        y,
+    }))
+}
+
+#[tokio::test]
+async fn test_trackpad_ignores_other_events() {
+    let mut protocol = SteamDeckTrackpadProtocol::new();
+    let event = Event::Button(ButtonEvent::A(BinaryInput { pressed: true }));
+    assert!(protocol.handle_event(&event, Instant::now()).is_none());
+}
+
+#[tokio::test]
+async fn test_trackpad_pressure_and_velocity() {
+    let mut protocol = SteamDeckTrackpadProtocol::new();
+    let start = Instant::now();
+
+    let touch = Event::Button(ButtonEvent::LPadTouch(BinaryInput { pressed: true }));
+    protocol.handle_event(&touch, start).unwrap();
+    let force = Event::Trigger(TriggerEvent::LPadForce(TriggerInput { value: 0 }));
+    protocol.handle_event(&force, start).unwrap();
+
+    // The first position has no velocity
+    let events = protocol
+        .handle_event(&left_pad_motion(0, 0), start)
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Touchpad(Touchpad::LeftPad(Touch::Motion))
+    );
+
+    // Applying force emits the pressure with the last position
+    let force = Event::Trigger(TriggerEvent::LPadForce(TriggerInput { value: 32767 }));
+    let events = protocol.handle_event(&force, start).unwrap();
+    let InputValue::Touch { pressure, .. } = events[0].get_value() else {
+        panic!("Expected touch value");
+    };
+    assert_eq!(pressure, Some(1.0));
+
+    // Moving the finger emits its velocity
+    let at = start + Duration::from_millis(500);
+    let events = protocol
+        .handle_event(&left_pad_motion(32767, 0), at)
+        .unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[1].as_capability(),
+        Capability::Touchpad(Touchpad::LeftPad(Touch::Velocity))
+    );
+    let InputValue::Vector2 { x: Some(x), .. } = events[1].get_value() else {
+        panic!("Expected vector value");
+    };
+    assert!((x - 2.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_trackpad_haptic_click() {
+    let mut protocol = SteamDeckTrackpadProtocol::new();
+    let press = Event::Button(ButtonEvent::RPadPress(BinaryInput { pressed: true }));
+    let events = protocol.handle_event(&press, Instant::now()).unwrap();
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Press)))
+    );
+    assert_eq!(protocol.take_haptic_clicks(), vec![TrackpadSide::Right]);
+    assert!(protocol.take_haptic_clicks().is_empty());
+}
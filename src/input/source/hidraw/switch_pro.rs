@@ -0,0 +1,223 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::switch_pro::{
+        driver::{Driver, JOY_AXIS_MAX, JOY_AXIS_MIN},
+        event,
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Nintendo Switch Pro Controller source device implementation
+pub struct SwitchProController {
+    driver: Driver,
+}
+
+impl SwitchProController {
+    /// Create a new source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self { driver })
+    }
+}
+
+impl SourceInputDevice for SwitchProController {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for SwitchProController {}
+
+impl Debug for SwitchProController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SwitchProController").finish()
+    }
+}
+
+/// Returns a value between -1.0 and 1.0 based on the given value with its
+/// minimum and maximum values.
+fn normalize_signed_value(raw_value: f64, min: f64, max: f64) -> f64 {
+    let mid = (max + min) / 2.0;
+    let event_value = raw_value.clamp(min, max) - mid;
+
+    // Normalize the value
+    if event_value >= 0.0 {
+        let maximum = max - mid;
+        event_value / maximum
+    } else {
+        let minimum = min - mid;
+        let value = event_value / minimum;
+        -value
+    }
+}
+
+/// Normalize the value to something between -1.0 and 1.0 based on the
+/// minimum and maximum axis ranges. The Y axis of the controller points up,
+/// so it is inverted.
+fn normalize_axis_value(value: event::JoyAxisInput) -> InputValue {
+    let min = JOY_AXIS_MIN;
+    let max = JOY_AXIS_MAX;
+    let x = normalize_signed_value(value.x as f64, min, max);
+    let y = -normalize_signed_value(value.y as f64, min, max);
+    InputValue::Vector2 {
+        x: Some(x),
+        y: Some(y),
+    }
+}
+
+/// Translate the given events into native events
+fn translate_events(events: Vec<event::Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given event into a native event
+fn translate_event(event: event::Event) -> NativeEvent {
+    match event {
+        event::Event::Button(button) => match button {
+            // Face buttons are mapped by their position on the controller
+            event::ButtonEvent::A(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::B(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::X(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Y(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::L(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::R(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            // The triggers are digital, so they are either fully pulled or
+            // released.
+            event::ButtonEvent::ZL(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+                InputValue::Float(if value.pressed { 1.0 } else { 0.0 }),
+            ),
+            event::ButtonEvent::ZR(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+                InputValue::Float(if value.pressed { 1.0 } else { 0.0 }),
+            ),
+            event::ButtonEvent::Plus(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Minus(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Home(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Capture(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::LStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::RStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadUp(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadRight(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadDown(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadLeft(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+        event::Event::Axis(axis) => match axis {
+            event::AxisEvent::LStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+                normalize_axis_value(value),
+            ),
+            event::AxisEvent::RStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+                normalize_axis_value(value),
+            ),
+        },
+        event::Event::Inertia(inertia) => match inertia {
+            event::InertialEvent::Accelerometer(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Accelerometer),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64),
+                    y: Some(value.y as f64),
+                    z: Some(value.z as f64),
+                },
+            ),
+            event::InertialEvent::Gyro(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Gyro),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64),
+                    y: Some(value.y as f64),
+                    z: Some(value.z as f64),
+                },
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the Switch Pro Controller driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Accelerometer),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+    Capability::Gamepad(Gamepad::Gyro),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+];
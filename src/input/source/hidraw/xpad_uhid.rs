@@ -146,6 +146,10 @@ impl SourceOutputDevice for XpadUhid {
             OutputEvent::Evdev(input_event) => Ok(self.process_evdev_ff(input_event)?),
             OutputEvent::DualSense(_) => Ok(()),
             OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::LED(_) => Ok(()),
+            OutputEvent::TriggerFeedback { .. } => Ok(()),
+            OutputEvent::AdaptiveTrigger { .. } => Ok(()),
+            OutputEvent::HIDRaw(report) => self.write_hidraw_report(report.report_id, report.data),
         }
     }
 
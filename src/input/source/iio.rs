@@ -3,7 +3,7 @@ pub mod accel_gyro_3d_new;
 pub mod bmi_imu;
 pub mod bmi_imu_new;
 
-use std::error::Error;
+use std::{error::Error, time::Duration};
 
 use glob_match::glob_match;
 
@@ -14,7 +14,7 @@ use crate::{
 
 use self::{accel_gyro_3d_new::AccelGyro3dImu, bmi_imu_new::BmiImu};
 
-use super::SourceDriver;
+use super::{SourceDriver, SourceDriverOptions};
 
 /// List of available drivers
 enum DriverType {
@@ -40,17 +40,26 @@ impl IioDevice {
         config: Option<config::IIO>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = IioDevice::get_driver_type(&device_info);
+        let options = SourceDriverOptions {
+            coalesce_window: config
+                .as_ref()
+                .and_then(|config| config.coalesce_window_us)
+                .map(Duration::from_micros),
+            ..Default::default()
+        };
 
         match driver_type {
             DriverType::Unknown => Err("No driver for iio interface found".into()),
             DriverType::BmiImu => {
                 let device = BmiImu::new(device_info.clone(), config)?;
-                let source_device = SourceDriver::new(composite_device, device, device_info);
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
                 Ok(Self::BmiImu(source_device))
             }
             DriverType::AccelGryo3D => {
                 let device = AccelGyro3dImu::new(device_info.clone(), config)?;
-                let source_device = SourceDriver::new(composite_device, device, device_info);
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
                 Ok(Self::AccelGryo3D(source_device))
             }
         }
@@ -1,4 +1,4 @@
-use std::{error::Error, f64::consts::PI, fmt::Debug};
+use std::{error::Error, f64::consts::PI, fmt::Debug, time::Instant};
 
 use crate::{
     config,
@@ -6,6 +6,7 @@ use crate::{
     input::{
         capability::{Capability, Gamepad},
         event::{native::NativeEvent, value::InputValue},
+        filter::{FilterResult, RateLimitFilter, SourceDeviceFilter},
         source::{InputError, SourceInputDevice, SourceOutputDevice},
     },
     udev::device::UdevDevice,
@@ -13,6 +14,8 @@ use crate::{
 
 pub struct AccelGyro3dImu {
     driver: Driver,
+    device_id: String,
+    rate_limit: Option<RateLimitFilter>,
 }
 
 impl AccelGyro3dImu {
@@ -38,11 +41,21 @@ impl AccelGyro3dImu {
             None
         };
 
+        let rate_limit = config
+            .as_ref()
+            .and_then(|config| config.max_hz)
+            .map(RateLimitFilter::with_max_hz);
+
         let id = device_info.sysname();
         let name = device_info.name();
+        let device_id = id.clone();
         let driver = Driver::new(id, name, mount_matrix)?;
 
-        Ok(Self { driver })
+        Ok(Self {
+            driver,
+            device_id,
+            rate_limit,
+        })
     }
 }
 
@@ -50,7 +63,18 @@ impl SourceInputDevice for AccelGyro3dImu {
     /// Poll the given input device for input events
     fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
         let events = self.driver.poll()?;
-        let native_events = translate_events(events);
+        let mut native_events = translate_events(events);
+
+        // Hold back events that exceed the configured maximum rate, and send
+        // the latest held back events once the rate allows them.
+        if let Some(rate_limit) = self.rate_limit.as_mut() {
+            native_events.retain_mut(|event| {
+                rate_limit.filter(&self.device_id, event) != FilterResult::Drop
+            });
+            let ready = rate_limit.take_ready(Instant::now());
+            native_events.extend(ready.into_iter().map(|(_, event)| event));
+        }
+
         Ok(native_events)
     }
 
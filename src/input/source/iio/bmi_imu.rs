@@ -51,6 +51,7 @@ impl IMU {
         let device_id = self.device_id.clone();
         let composite_device = self.composite_device.clone();
         let mut rx = self.rx.take().unwrap();
+        let mut device_info = self.device.clone();
 
         // Override the mount matrix if one is defined in the config
         let mount_matrix = if let Some(config) = self.config.as_ref() {
@@ -74,7 +75,7 @@ impl IMU {
             tokio::task::spawn_blocking(move || -> Result<(), Box<dyn Error + Send + Sync>> {
                 let driver = Driver::new(id, name, mount_matrix)?;
                 loop {
-                    receive_commands(&mut rx)?;
+                    receive_commands(&mut rx, &mut device_info)?;
                     let events = driver.poll()?;
                     let native_events = translate_events(events);
                     for event in native_events {
@@ -144,6 +145,7 @@ fn translate_event(event: iio_imu::event::Event) -> NativeEvent {
 /// empty.
 fn receive_commands(
     rx: &mut mpsc::Receiver<SourceCommand>,
+    device_info: &mut UdevDevice,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     const MAX_COMMANDS: u8 = 64;
     let mut commands_processed = 0;
@@ -154,6 +156,24 @@ fn receive_commands(
                 SourceCommand::UploadEffect(_, _) => (),
                 SourceCommand::UpdateEffect(_, _) => (),
                 SourceCommand::EraseEffect(_, _) => (),
+                SourceCommand::GetInfo(composite_dev) => {
+                    match device_info.refresh() {
+                        Ok(info) => *device_info = info,
+                        Err(e) => log::warn!("Failed to refresh device info: {e:?}"),
+                    }
+                    if let Err(e) = composite_dev.send(device_info.clone()) {
+                        log::error!("Failed to send device info: {:?}", e);
+                    }
+                }
+                SourceCommand::GetCapabilities(composite_dev) => {
+                    let capabilities = super::bmi_imu_new::CAPABILITIES.to_vec();
+                    if let Err(e) = composite_dev.send(capabilities) {
+                        log::error!("Failed to send device capabilities: {:?}", e);
+                    }
+                }
+                SourceCommand::SetLED(_) => (),
+                SourceCommand::SetTriggerFeedback(_, _) => (),
+                SourceCommand::SetAdaptiveTrigger(_, _) => (),
                 SourceCommand::Stop => return Err("Device stopped".into()),
             },
             Err(e) => match e {
@@ -2,7 +2,7 @@ use std::{
     error::Error,
     sync::{Arc, Mutex, MutexGuard},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use ::evdev::FFEffectData;
@@ -13,14 +13,16 @@ use crate::udev::device::UdevDevice;
 
 use self::{
     client::SourceDeviceClient, command::SourceCommand, evdev::EventDevice, hidraw::HidRawDevice,
-    iio::IioDevice,
+    iio::IioDevice, network::NetworkDevice,
 };
 
 use super::{
     capability::Capability,
-    composite_device::client::CompositeDeviceClient,
+    composite_device::client::{ClientError, CompositeDeviceClient},
     event::{native::NativeEvent, Event},
-    output_event::OutputEvent,
+    filter::EventCoalescer,
+    output_capability::OutputCapability,
+    output_event::{AdaptiveTriggerEffect, LedEvent, OutputEvent, Trigger},
 };
 
 pub mod client;
@@ -28,11 +30,19 @@ pub mod command;
 pub mod evdev;
 pub mod hidraw;
 pub mod iio;
+pub mod network;
 
 /// Size of the [SourceCommand] buffer for receiving output events
 const BUFFER_SIZE: usize = 2048;
 /// Default poll rate (2.5ms/400Hz)
 const POLL_RATE: Duration = Duration::from_micros(2500);
+/// Interval to check the load of the composite device command channel
+const CHANNEL_LOAD_POLL_RATE: Duration = Duration::from_millis(100);
+/// Composite device channel load above which source devices slow down
+const CHANNEL_LOAD_THRESHOLD: f32 = 0.8;
+/// Delay before sending each event per unit of channel load above the
+/// threshold
+const CHANNEL_LOAD_DELAY: Duration = Duration::from_millis(100);
 
 /// Possible errors for a source device client
 #[derive(Error, Debug)]
@@ -144,6 +154,49 @@ pub trait SourceOutputDevice {
         Ok(())
     }
 
+    /// Set the color and brightness of the LEDs on the source device.
+    fn set_led(&mut self, event: LedEvent) -> Result<(), OutputError> {
+        //log::trace!("Received set LED: {event:?}");
+        let _ = event;
+        Ok(())
+    }
+
+    /// Set the haptic feedback intensity of the left and right triggers,
+    /// normalized between 0.0 and 1.0.
+    fn set_trigger_feedback(
+        &mut self,
+        left_intensity: f32,
+        right_intensity: f32,
+    ) -> Result<(), OutputError> {
+        //log::trace!("Received set trigger feedback: {left_intensity}, {right_intensity}");
+        let _ = (left_intensity, right_intensity);
+        Ok(())
+    }
+
+    /// Set the adaptive trigger effect of the given trigger.
+    fn set_adaptive_trigger(
+        &mut self,
+        trigger: Trigger,
+        effect: AdaptiveTriggerEffect,
+    ) -> Result<(), OutputError> {
+        //log::trace!("Received set adaptive trigger: {trigger:?} {effect:?}");
+        let _ = (trigger, effect);
+        Ok(())
+    }
+
+    /// Write the given raw HID output report to the source device. The data
+    /// does not include the report id.
+    fn write_hidraw_report(&mut self, report_id: u8, data: Vec<u8>) -> Result<(), OutputError> {
+        //log::trace!("Received HID output report: {report_id} {data:?}");
+        let _ = (report_id, data);
+        Ok(())
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![])
+    }
+
     /// Stop the source device.
     fn stop(&mut self) -> Result<(), OutputError> {
         Ok(())
@@ -155,6 +208,9 @@ pub trait SourceOutputDevice {
 pub struct SourceDriverOptions {
     pub poll_rate: Duration,
     pub buffer_size: usize,
+    /// Optional time window in which events with the same capability are
+    /// coalesced into a single event with the latest value
+    pub coalesce_window: Option<Duration>,
 }
 
 impl Default for SourceDriverOptions {
@@ -162,10 +218,66 @@ impl Default for SourceDriverOptions {
         Self {
             poll_rate: POLL_RATE,
             buffer_size: BUFFER_SIZE,
+            coalesce_window: None,
         }
     }
 }
 
+/// Applies back-pressure to a source device when the command channel of its
+/// composite device is close to full, so events are delayed instead of
+/// overflowing the channel.
+#[derive(Debug)]
+struct ChannelThrottle {
+    /// Last known channel load as a fraction of the channel capacity
+    load: f32,
+    /// When the channel load was last requested
+    last_request: Instant,
+    /// Receiver for the response to a pending channel load request
+    pending: Option<mpsc::Receiver<f32>>,
+}
+
+impl ChannelThrottle {
+    fn new() -> Self {
+        Self {
+            load: 0.0,
+            last_request: Instant::now(),
+            pending: None,
+        }
+    }
+
+    /// Update the channel load from the composite device without blocking.
+    /// The load is requested at most once every [CHANNEL_LOAD_POLL_RATE].
+    fn update(&mut self, composite_device: &CompositeDeviceClient) {
+        if let Some(rx) = self.pending.as_mut() {
+            match rx.try_recv() {
+                Ok(load) => self.load = load,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => (),
+            }
+            self.pending = None;
+        }
+        if self.last_request.elapsed() < CHANNEL_LOAD_POLL_RATE {
+            return;
+        }
+        self.last_request = Instant::now();
+
+        match composite_device.try_get_channel_load() {
+            Ok(rx) => self.pending = Some(rx),
+            // The request itself could not be queued
+            Err(ClientError::ChannelFull) => self.load = 1.0,
+            Err(e) => log::trace!("Failed to request channel load: {e:?}"),
+        }
+    }
+
+    /// Returns how long to wait before sending the next event
+    fn delay(&self) -> Duration {
+        if self.load <= CHANNEL_LOAD_THRESHOLD {
+            return Duration::ZERO;
+        }
+        CHANNEL_LOAD_DELAY.mul_f32(self.load - CHANNEL_LOAD_THRESHOLD)
+    }
+}
+
 /// A [SourceDriver] is any physical input device that emits input events
 #[derive(Debug)]
 pub struct SourceDriver<T: SourceInputDevice + SourceOutputDevice> {
@@ -224,6 +336,14 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
         self.implementation.lock().unwrap().get_capabilities()
     }
 
+    /// Returns the possible output events this device is capable of handling
+    pub fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        self.implementation
+            .lock()
+            .unwrap()
+            .get_output_capabilities()
+    }
+
     /// Returns the path to the device (e.g. "/dev/input/event0")
     pub fn get_device_path(&self) -> String {
         self.device_info.devnode()
@@ -252,11 +372,34 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
         let task =
             tokio::task::spawn_blocking(move || -> Result<(), Box<dyn Error + Send + Sync>> {
                 let mut rx = self.rx;
+                let mut device_info = self.device_info.clone();
                 let mut implementation = self.implementation.lock().unwrap();
+                let mut coalescer = self.options.coalesce_window.map(EventCoalescer::new);
+                let mut throttle = ChannelThrottle::new();
                 loop {
                     // Poll the implementation for events
                     let events = implementation.poll()?;
+
+                    // Coalesce bursts of events if a coalesce window is set
+                    let events = match coalescer.as_mut() {
+                        Some(coalescer) => {
+                            let now = Instant::now();
+                            let mut events: Vec<NativeEvent> = events
+                                .into_iter()
+                                .filter_map(|event| coalescer.push(event, now))
+                                .collect();
+                            events.append(&mut coalescer.flush(now));
+                            events
+                        }
+                        None => events,
+                    };
+                    // Slow down if the composite device is falling behind
+                    throttle.update(&self.composite_device);
                     for event in events.into_iter() {
+                        let delay = throttle.delay();
+                        if !delay.is_zero() {
+                            thread::sleep(delay);
+                        }
                         let event = Event::Native(event);
                         let result = self
                             .composite_device
@@ -267,7 +410,11 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
                     }
 
                     // Receive commands/output events
-                    if let Err(e) = SourceDriver::receive_commands(&mut rx, &mut implementation) {
+                    if let Err(e) = SourceDriver::receive_commands(
+                        &mut rx,
+                        &mut implementation,
+                        &mut device_info,
+                    ) {
                         log::debug!("Error receiving commands: {:?}", e);
                         break;
                     }
@@ -292,6 +439,7 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
     fn receive_commands(
         rx: &mut mpsc::Receiver<SourceCommand>,
         implementation: &mut MutexGuard<'_, T>,
+        device_info: &mut UdevDevice,
     ) -> Result<(), Box<dyn Error>> {
         const MAX_COMMANDS: u8 = 64;
         let mut commands_processed = 0;
@@ -315,7 +463,7 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
                     }
                     SourceCommand::EraseEffect(id, composite_dev) => {
                         let res = match implementation.erase_effect(id) {
-                            Ok(_) => Ok(()),
+                            Ok(_) => composite_dev.send(Ok(())),
                             Err(e) => {
                                 let err = format!("Failed to erase effect: {e:?}");
                                 composite_dev.send(Err(err.into()))
@@ -325,6 +473,39 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
                             log::error!("Failed to send erase result: {:?}", err);
                         }
                     }
+                    SourceCommand::GetInfo(composite_dev) => {
+                        match device_info.refresh() {
+                            Ok(info) => *device_info = info,
+                            Err(e) => log::warn!("Failed to refresh device info: {e:?}"),
+                        }
+                        if let Err(e) = composite_dev.send(device_info.clone()) {
+                            log::error!("Failed to send device info: {:?}", e);
+                        }
+                    }
+                    SourceCommand::GetCapabilities(composite_dev) => {
+                        let capabilities = match implementation.get_capabilities() {
+                            Ok(capabilities) => capabilities,
+                            Err(e) => {
+                                log::warn!("Failed to get source device capabilities: {e:?}");
+                                Vec::new()
+                            }
+                        };
+                        if let Err(e) = composite_dev.send(capabilities) {
+                            log::error!("Failed to send device capabilities: {:?}", e);
+                        }
+                    }
+                    SourceCommand::SetLED(event) => {
+                        implementation.set_led(event)?;
+                    }
+                    SourceCommand::SetTriggerFeedback(left_intensity, right_intensity) => {
+                        implementation.set_trigger_feedback(left_intensity, right_intensity)?;
+                    }
+                    SourceCommand::SetAdaptiveTrigger(trigger, effect) => {
+                        implementation.set_adaptive_trigger(trigger, effect)?;
+                    }
+                    SourceCommand::WriteHIDRawReport(report_id, data) => {
+                        implementation.write_hidraw_report(report_id, data)?;
+                    }
                     SourceCommand::WriteEvent(event) => {
                         log::trace!("Received output event: {:?}", event);
                         implementation.write_event(event)?;
@@ -358,6 +539,7 @@ pub enum SourceDevice {
     Event(EventDevice),
     HidRaw(HidRawDevice),
     Iio(IioDevice),
+    Network(NetworkDevice),
 }
 
 impl SourceDevice {
@@ -367,8 +549,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.info(),
                 EventDevice::Blocked(device) => device.info(),
+                EventDevice::Touchscreen(device) => device.info(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.info(),
                 HidRawDevice::DualSense(device) => device.info(),
                 HidRawDevice::SteamDeck(device) => device.info(),
                 HidRawDevice::LegionGo(device) => device.info(),
@@ -376,11 +560,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.info(),
                 HidRawDevice::XpadUhid(device) => device.info(),
                 HidRawDevice::RogAlly(device) => device.info(),
+                HidRawDevice::SwitchPro(device) => device.info(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.info(),
                 IioDevice::AccelGryo3D(device) => device.info(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.info(),
+            },
         }
     }
 
@@ -390,8 +578,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.info_ref(),
                 EventDevice::Blocked(device) => device.info_ref(),
+                EventDevice::Touchscreen(device) => device.info_ref(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.info_ref(),
                 HidRawDevice::DualSense(device) => device.info_ref(),
                 HidRawDevice::SteamDeck(device) => device.info_ref(),
                 HidRawDevice::LegionGo(device) => device.info_ref(),
@@ -399,11 +589,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.info_ref(),
                 HidRawDevice::XpadUhid(device) => device.info_ref(),
                 HidRawDevice::RogAlly(device) => device.info_ref(),
+                HidRawDevice::SwitchPro(device) => device.info_ref(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.info_ref(),
                 IioDevice::AccelGryo3D(device) => device.info_ref(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.info_ref(),
+            },
         }
     }
 
@@ -413,8 +607,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.get_id(),
                 EventDevice::Blocked(device) => device.get_id(),
+                EventDevice::Touchscreen(device) => device.get_id(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.get_id(),
                 HidRawDevice::DualSense(device) => device.get_id(),
                 HidRawDevice::SteamDeck(device) => device.get_id(),
                 HidRawDevice::LegionGo(device) => device.get_id(),
@@ -422,11 +618,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.get_id(),
                 HidRawDevice::XpadUhid(device) => device.get_id(),
                 HidRawDevice::RogAlly(device) => device.get_id(),
+                HidRawDevice::SwitchPro(device) => device.get_id(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.get_id(),
                 IioDevice::AccelGryo3D(device) => device.get_id(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.get_id(),
+            },
         }
     }
 
@@ -436,8 +636,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.client(),
                 EventDevice::Blocked(device) => device.client(),
+                EventDevice::Touchscreen(device) => device.client(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.client(),
                 HidRawDevice::DualSense(device) => device.client(),
                 HidRawDevice::SteamDeck(device) => device.client(),
                 HidRawDevice::LegionGo(device) => device.client(),
@@ -445,11 +647,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.client(),
                 HidRawDevice::XpadUhid(device) => device.client(),
                 HidRawDevice::RogAlly(device) => device.client(),
+                HidRawDevice::SwitchPro(device) => device.client(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.client(),
                 IioDevice::AccelGryo3D(device) => device.client(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.client(),
+            },
         }
     }
 
@@ -459,8 +665,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.run().await,
                 EventDevice::Blocked(device) => device.run().await,
+                EventDevice::Touchscreen(device) => device.run().await,
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.run().await,
                 HidRawDevice::DualSense(device) => device.run().await,
                 HidRawDevice::SteamDeck(device) => device.run().await,
                 HidRawDevice::LegionGo(device) => device.run().await,
@@ -468,11 +676,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.run().await,
                 HidRawDevice::XpadUhid(device) => device.run().await,
                 HidRawDevice::RogAlly(device) => device.run().await,
+                HidRawDevice::SwitchPro(device) => device.run().await,
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.run().await,
                 IioDevice::AccelGryo3D(device) => device.run().await,
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.run().await,
+            },
         }
     }
 
@@ -482,8 +694,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.get_capabilities(),
                 EventDevice::Blocked(device) => device.get_capabilities(),
+                EventDevice::Touchscreen(device) => device.get_capabilities(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.get_capabilities(),
                 HidRawDevice::DualSense(device) => device.get_capabilities(),
                 HidRawDevice::SteamDeck(device) => device.get_capabilities(),
                 HidRawDevice::LegionGo(device) => device.get_capabilities(),
@@ -491,11 +705,44 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.get_capabilities(),
                 HidRawDevice::XpadUhid(device) => device.get_capabilities(),
                 HidRawDevice::RogAlly(device) => device.get_capabilities(),
+                HidRawDevice::SwitchPro(device) => device.get_capabilities(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.get_capabilities(),
                 IioDevice::AccelGryo3D(device) => device.get_capabilities(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.get_capabilities(),
+            },
+        }
+    }
+
+    /// Returns the output capabilities that this source device can fulfill.
+    pub fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        match self {
+            SourceDevice::Event(device) => match device {
+                EventDevice::Gamepad(device) => device.get_output_capabilities(),
+                EventDevice::Blocked(device) => device.get_output_capabilities(),
+                EventDevice::Touchscreen(device) => device.get_output_capabilities(),
+            },
+            SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.get_output_capabilities(),
+                HidRawDevice::DualSense(device) => device.get_output_capabilities(),
+                HidRawDevice::SteamDeck(device) => device.get_output_capabilities(),
+                HidRawDevice::LegionGo(device) => device.get_output_capabilities(),
+                HidRawDevice::OrangePiNeo(device) => device.get_output_capabilities(),
+                HidRawDevice::Fts3528Touchscreen(device) => device.get_output_capabilities(),
+                HidRawDevice::XpadUhid(device) => device.get_output_capabilities(),
+                HidRawDevice::RogAlly(device) => device.get_output_capabilities(),
+                HidRawDevice::SwitchPro(device) => device.get_output_capabilities(),
+            },
+            SourceDevice::Iio(device) => match device {
+                IioDevice::BmiImu(device) => device.get_output_capabilities(),
+                IioDevice::AccelGryo3D(device) => device.get_output_capabilities(),
+            },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.get_output_capabilities(),
+            },
         }
     }
 
@@ -505,8 +752,10 @@ impl SourceDevice {
             SourceDevice::Event(device) => match device {
                 EventDevice::Gamepad(device) => device.get_device_path(),
                 EventDevice::Blocked(device) => device.get_device_path(),
+                EventDevice::Touchscreen(device) => device.get_device_path(),
             },
             SourceDevice::HidRaw(device) => match device {
+                HidRawDevice::Generic(device) => device.get_device_path(),
                 HidRawDevice::DualSense(device) => device.get_device_path(),
                 HidRawDevice::SteamDeck(device) => device.get_device_path(),
                 HidRawDevice::LegionGo(device) => device.get_device_path(),
@@ -514,11 +763,15 @@ impl SourceDevice {
                 HidRawDevice::Fts3528Touchscreen(device) => device.get_device_path(),
                 HidRawDevice::XpadUhid(device) => device.get_device_path(),
                 HidRawDevice::RogAlly(device) => device.get_device_path(),
+                HidRawDevice::SwitchPro(device) => device.get_device_path(),
             },
             SourceDevice::Iio(device) => match device {
                 IioDevice::BmiImu(device) => device.get_device_path(),
                 IioDevice::AccelGryo3D(device) => device.get_device_path(),
             },
+            SourceDevice::Network(device) => match device {
+                NetworkDevice::Stream(device) => device.get_device_path(),
+            },
         }
     }
 }
@@ -0,0 +1,36 @@
+pub mod stream;
+
+use std::{error::Error, time::Duration};
+
+use crate::{input::composite_device::client::CompositeDeviceClient, udev::device::UdevDevice};
+
+use self::stream::NetworkStream;
+
+use super::{SourceDriver, SourceDriverOptions};
+
+/// [NetworkDevice] represents an input device that receives input events
+/// over the network.
+#[derive(Debug)]
+pub enum NetworkDevice {
+    Stream(SourceDriver<NetworkStream>),
+}
+
+impl NetworkDevice {
+    /// Create a new [NetworkDevice] that listens on the address of the given
+    /// device, created with [UdevDevice::from_network_address].
+    pub fn new(
+        device_info: UdevDevice,
+        composite_device: CompositeDeviceClient,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let address = device_info.sysname().parse()?;
+        let device = NetworkStream::new(address)?;
+        let options = SourceDriverOptions {
+            poll_rate: Duration::from_millis(1),
+            buffer_size: 2048,
+            coalesce_window: None,
+        };
+        let source_device =
+            SourceDriver::new_with_options(composite_device, device, device_info, options);
+        Ok(Self::Stream(source_device))
+    }
+}
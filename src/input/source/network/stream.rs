@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    fmt::Debug,
+    io,
+    net::{SocketAddr, UdpSocket},
+};
+
+use crate::input::{
+    capability::Capability,
+    event::{
+        native::NativeEvent,
+        network::{self, NetworkEvent, PACKET_SIZE},
+    },
+    source::{InputError, SourceInputDevice, SourceOutputDevice},
+};
+
+/// Source device that receives input events over UDP from a remote host
+/// using the protocol defined in [network].
+pub struct NetworkStream {
+    socket: UdpSocket,
+}
+
+impl NetworkStream {
+    /// Create a new network stream that listens for events on the given
+    /// address
+    pub fn new(address: SocketAddr) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        log::info!("Listening for input events on {address}");
+        Ok(Self { socket })
+    }
+}
+
+impl SourceInputDevice for NetworkStream {
+    /// Read all packets that were received since the last poll
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let mut events = Vec::new();
+        // Use a larger buffer than a packet so oversized packets are detected
+        let mut buf = [0; PACKET_SIZE + 1];
+        loop {
+            let (size, sender) = match self.socket.recv_from(&mut buf) {
+                Ok(result) => result,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string().into()),
+            };
+            let Some(packet) = NetworkEvent::from_bytes(&buf[..size]) else {
+                log::trace!("Dropping invalid packet of {size} bytes from {sender}");
+                continue;
+            };
+            let Some(event) = packet.to_native_event() else {
+                log::trace!("Dropping packet with unknown capability from {sender}: {packet:?}");
+                continue;
+            };
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(network::CAPABILITIES.to_vec())
+    }
+}
+
+impl SourceOutputDevice for NetworkStream {}
+
+impl Debug for NetworkStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetworkStream")
+            .field("address", &self.socket.local_addr().ok())
+            .finish()
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::config::SourceHealthConfig;
+
+/// Default time in milliseconds without events from a continuously reporting
+/// source device before an event gap is reported
+pub const DEFAULT_EVENT_GAP_MS: u64 = 1000;
+/// Default average event latency in milliseconds above which a source device
+/// is degraded
+pub const DEFAULT_MAX_LATENCY_MS: f32 = 50.0;
+/// Default ratio of failed source commands above which a source device is
+/// degraded
+pub const DEFAULT_MAX_ERROR_RATE: f32 = 0.25;
+
+/// Weight of each new sample in the moving averages of event latency and
+/// interval
+const SMOOTHING: f32 = 0.1;
+/// Average interval between events in milliseconds at or below which a source
+/// device is considered to report continuously (e.g. motion sensors), so
+/// silence from it is unexpected.
+const STREAMING_INTERVAL_MS: f32 = 50.0;
+/// Minimum number of source commands before the error rate is considered
+const MIN_COMMANDS: u32 = 4;
+
+/// Health status of a source device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    /// The source device is still connected, but has an event gap, high
+    /// latency or fails commands
+    Degraded,
+}
+
+impl Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthStatus::Healthy => write!(f, "Healthy"),
+            HealthStatus::Degraded => write!(f, "Degraded"),
+        }
+    }
+}
+
+/// Thresholds at which a source device is reported as degraded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthThresholds {
+    /// Time in milliseconds without events from a continuously reporting
+    /// source device before an event gap is reported
+    pub event_gap_ms: u64,
+    /// Average event latency in milliseconds above which a source device is
+    /// degraded
+    pub max_latency_ms: f32,
+    /// Ratio of failed source commands above which a source device is
+    /// degraded
+    pub max_error_rate: f32,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            event_gap_ms: DEFAULT_EVENT_GAP_MS,
+            max_latency_ms: DEFAULT_MAX_LATENCY_MS,
+            max_error_rate: DEFAULT_MAX_ERROR_RATE,
+        }
+    }
+}
+
+impl From<&SourceHealthConfig> for HealthThresholds {
+    fn from(config: &SourceHealthConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            event_gap_ms: config.event_gap_ms.unwrap_or(defaults.event_gap_ms),
+            max_latency_ms: config.max_latency_ms.unwrap_or(defaults.max_latency_ms),
+            max_error_rate: config.max_error_rate.unwrap_or(defaults.max_error_rate),
+        }
+    }
+}
+
+/// Health metrics of a single source device
+#[derive(Debug, Clone)]
+pub struct SourceDeviceHealth {
+    /// Time the last event from the source device arrived
+    pub last_event_ms: Instant,
+    /// Number of events received from the source device
+    pub events_received: u64,
+    /// Number of times the source device stopped reporting unexpectedly
+    pub event_gap_alerts: u32,
+    /// Average time in milliseconds from the hardware event to its arrival
+    pub avg_latency_ms: f32,
+    /// Number of commands sent to the source device
+    pub commands_sent: u32,
+    /// Number of commands sent to the source device that failed
+    pub command_errors: u32,
+    /// Health status last reported for the source device
+    pub status: HealthStatus,
+    /// Whether any event with a hardware timestamp was received
+    has_latency: bool,
+    /// Average interval between events in milliseconds
+    avg_interval_ms: Option<f32>,
+    /// Whether the source device is currently in an event gap
+    in_gap: bool,
+}
+
+impl SourceDeviceHealth {
+    fn new(now: Instant) -> Self {
+        Self {
+            last_event_ms: now,
+            events_received: 0,
+            event_gap_alerts: 0,
+            avg_latency_ms: 0.0,
+            commands_sent: 0,
+            command_errors: 0,
+            status: HealthStatus::Healthy,
+            has_latency: false,
+            avg_interval_ms: None,
+            in_gap: false,
+        }
+    }
+
+    /// Returns the ratio of commands sent to the source device that failed
+    pub fn error_rate(&self) -> f32 {
+        if self.commands_sent == 0 {
+            return 0.0;
+        }
+        self.command_errors as f32 / self.commands_sent as f32
+    }
+
+    /// Returns true if the source device is reporting events continuously
+    fn is_streaming(&self) -> bool {
+        self.avg_interval_ms
+            .is_some_and(|interval| interval <= STREAMING_INTERVAL_MS)
+    }
+
+    /// Returns the health status of the source device for the given
+    /// thresholds
+    fn evaluate(&self, thresholds: &HealthThresholds) -> HealthStatus {
+        let high_error_rate =
+            self.commands_sent >= MIN_COMMANDS && self.error_rate() > thresholds.max_error_rate;
+        if self.in_gap || self.avg_latency_ms > thresholds.max_latency_ms || high_error_rate {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+}
+
+/// Tracks the health of the source devices of a composite device. Event
+/// latency and intervals are measured as events arrive, and event gaps are
+/// detected by periodically calling [SourceHealthMonitor::check], which
+/// returns the source devices whose health status changed.
+#[derive(Debug, Default)]
+pub struct SourceHealthMonitor {
+    thresholds: HealthThresholds,
+    devices: HashMap<String, SourceDeviceHealth>,
+}
+
+impl SourceHealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the thresholds at which source devices are reported as degraded
+    pub fn set_thresholds(&mut self, thresholds: HealthThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Returns the health metrics of the given source device, if any events
+    /// or commands were recorded for it
+    pub fn get(&self, device_id: &str) -> Option<&SourceDeviceHealth> {
+        self.devices.get(device_id)
+    }
+
+    /// Record an event from the given source device that arrived at the given
+    /// time. The latency is measured from the hardware timestamp of the
+    /// event, if it has one.
+    pub fn record_event(&mut self, device_id: &str, timestamp: Option<SystemTime>, now: Instant) {
+        let health = self.device(device_id, now);
+        if health.events_received > 0 {
+            let interval = now.duration_since(health.last_event_ms).as_secs_f32() * 1000.0;
+            health.avg_interval_ms = Some(match health.avg_interval_ms {
+                Some(avg) => avg + (interval - avg) * SMOOTHING,
+                None => interval,
+            });
+        }
+        health.events_received = health.events_received.saturating_add(1);
+        health.last_event_ms = now;
+        health.in_gap = false;
+
+        let latency = timestamp.and_then(|timestamp| timestamp.elapsed().ok());
+        if let Some(latency) = latency {
+            let latency = latency.as_secs_f32() * 1000.0;
+            if health.has_latency {
+                health.avg_latency_ms += (latency - health.avg_latency_ms) * SMOOTHING;
+            } else {
+                health.avg_latency_ms = latency;
+                health.has_latency = true;
+            }
+        }
+    }
+
+    /// Record the result of a command sent to the given source device
+    pub fn record_command(&mut self, device_id: &str, success: bool) {
+        let health = self.device(device_id, Instant::now());
+        health.commands_sent = health.commands_sent.saturating_add(1);
+        if !success {
+            health.command_errors = health.command_errors.saturating_add(1);
+        }
+    }
+
+    /// Stop tracking the health of the given source device
+    pub fn remove_device(&mut self, device_id: &str) {
+        self.devices.remove(device_id);
+    }
+
+    /// Detect event gaps at the given time and update the health status of
+    /// all source devices. Returns the ids of the source devices whose
+    /// health status changed.
+    pub fn check(&mut self, now: Instant) -> Vec<String> {
+        let gap = Duration::from_millis(self.thresholds.event_gap_ms);
+        let mut changed = Vec::new();
+        for (device_id, health) in self.devices.iter_mut() {
+            let is_silent = now.duration_since(health.last_event_ms) > gap;
+            if is_silent && health.is_streaming() && !health.in_gap {
+                log::warn!(
+                    "No events from source device {device_id} for over {}ms",
+                    self.thresholds.event_gap_ms
+                );
+                health.in_gap = true;
+                health.event_gap_alerts = health.event_gap_alerts.saturating_add(1);
+            }
+
+            let status = health.evaluate(&self.thresholds);
+            if status == health.status {
+                continue;
+            }
+            log::info!("Source device {device_id} health changed to {status}");
+            health.status = status;
+            changed.push(device_id.clone());
+        }
+        changed
+    }
+
+    /// Returns the health of the given source device, tracking it if it is
+    /// not tracked yet
+    fn device(&mut self, device_id: &str, now: Instant) -> &mut SourceDeviceHealth {
+        self.devices
+            .entry(device_id.to_string())
+            .or_insert_with(|| SourceDeviceHealth::new(now))
+    }
+}
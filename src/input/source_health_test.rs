@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::input::source_health::{HealthStatus, HealthThresholds, SourceHealthMonitor};
+
+const DEVICE_ID: &str = "hidraw://hidraw0";
+
+#[tokio::test]
+async fn test_source_health_event_gap() {
+    let mut monitor = SourceHealthMonitor::new();
+    let start = Instant::now();
+
+    // Report events continuously at 100Hz
+    for i in 0..20 {
+        monitor.record_event(DEVICE_ID, None, start + Duration::from_millis(i * 10));
+    }
+    let last_event = start + Duration::from_millis(190);
+    assert!(monitor.check(last_event).is_empty());
+
+    // Silence from a continuously reporting device is an event gap
+    let changed = monitor.check(last_event + Duration::from_millis(1500));
+    assert_eq!(changed, vec![DEVICE_ID.to_string()]);
+    let health = monitor.get(DEVICE_ID).unwrap();
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert_eq!(health.event_gap_alerts, 1);
+
+    // The gap is only reported once
+    assert!(monitor
+        .check(last_event + Duration::from_millis(3000))
+        .is_empty());
+    assert_eq!(monitor.get(DEVICE_ID).unwrap().event_gap_alerts, 1);
+
+    // The device recovers once it reports events again
+    let at = last_event + Duration::from_millis(3500);
+    monitor.record_event(DEVICE_ID, None, at);
+    assert_eq!(monitor.check(at), vec![DEVICE_ID.to_string()]);
+    assert_eq!(
+        monitor.get(DEVICE_ID).unwrap().status,
+        HealthStatus::Healthy
+    );
+}
+
+#[tokio::test]
+async fn test_source_health_idle_device() {
+    let mut monitor = SourceHealthMonitor::new();
+    let start = Instant::now();
+
+    // Devices that only report occasionally are not expected to be active
+    monitor.record_event(DEVICE_ID, None, start);
+    monitor.record_event(DEVICE_ID, None, start + Duration::from_millis(500));
+    assert!(monitor.check(start + Duration::from_secs(10)).is_empty());
+    assert_eq!(monitor.get(DEVICE_ID).unwrap().event_gap_alerts, 0);
+}
+
+#[tokio::test]
+async fn test_source_health_latency() {
+    let mut monitor = SourceHealthMonitor::new();
+    monitor.set_thresholds(HealthThresholds {
+        max_latency_ms: 10.0,
+        ..Default::default()
+    });
+
+    let now = Instant::now();
+    let timestamp = SystemTime::now() - Duration::from_millis(200);
+    monitor.record_event(DEVICE_ID, Some(timestamp), now);
+    assert_eq!(monitor.check(now), vec![DEVICE_ID.to_string()]);
+    let health = monitor.get(DEVICE_ID).unwrap();
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert!(health.avg_latency_ms > 10.0);
+}
+
+#[tokio::test]
+async fn test_source_health_error_rate() {
+    let mut monitor = SourceHealthMonitor::new();
+    let now = Instant::now();
+
+    monitor.record_command(DEVICE_ID, true);
+    monitor.record_command(DEVICE_ID, true);
+    monitor.record_command(DEVICE_ID, false);
+    assert!(monitor.check(now).is_empty());
+
+    monitor.record_command(DEVICE_ID, false);
+    assert_eq!(monitor.check(now), vec![DEVICE_ID.to_string()]);
+    let health = monitor.get(DEVICE_ID).unwrap();
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert_eq!(health.error_rate(), 0.5);
+
+    monitor.remove_device(DEVICE_ID);
+    assert!(monitor.get(DEVICE_ID).is_none());
+}
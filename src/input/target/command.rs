@@ -0,0 +1,25 @@
+use tokio::sync::mpsc::Sender;
+
+use crate::input::{
+    capability::Capability, composite_device::client::CompositeDeviceClient,
+    event::native::NativeEvent,
+};
+
+/// A [TargetCommand] is a message that can be sent to a [TargetDevice] over
+/// a channel.
+#[derive(Debug, Clone)]
+pub enum TargetCommand {
+    WriteEvent(NativeEvent),
+    SetCompositeDevice(CompositeDeviceClient),
+    GetCapabilities(Sender<Vec<Capability>>),
+    GetType(Sender<String>),
+    /// Stop processing input events without stopping the target device.
+    /// Input events written while paused are dropped.
+    Pause,
+    /// Resume processing input events after a [TargetCommand::Pause]
+    Resume,
+    /// Register a channel that input events emitted by the target device are
+    /// sent to, so they can be handled by the composite device.
+    RegisterEventCallback(Sender<NativeEvent>),
+    Stop,
+}
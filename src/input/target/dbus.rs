@@ -0,0 +1,224 @@
+use std::{error::Error, fmt::Debug};
+
+use tokio::runtime::Handle;
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::dbus::TargetDBusInterface,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+        event::{
+            dbus::{Action, DBusEvent},
+            native::NativeEvent,
+            value::InputValue,
+        },
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Axis values above this threshold are considered a direction press
+const AXIS_THRESHOLD: f64 = 0.60;
+
+/// The internal emulated device state for tracking analog input
+#[derive(Debug, Clone, Default)]
+struct State {
+    pressed_left: bool,
+    pressed_right: bool,
+    pressed_up: bool,
+    pressed_down: bool,
+}
+
+/// The [DBusDevice] is a virtual input device that can emit input events as
+/// DBus signals.
+pub struct DBusDevice {
+    state: State,
+    conn: Connection,
+    dbus_path: Option<String>,
+}
+
+impl DBusDevice {
+    /// Create a new [DBusDevice] that emits signals on the given connection
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            state: State::default(),
+            conn,
+            dbus_path: None,
+        }
+    }
+
+    /// Translate the given native event into one or more DBus events
+    fn translate_event(&mut self, event: NativeEvent) -> Vec<DBusEvent> {
+        let mut translated = vec![];
+        let events = DBusEvent::from_native_event(event);
+        for mut event in events {
+            // Axis input is a special case, where we need to keep track of the
+            // current state of the axis, and only emit events whenever the axis
+            // passes or falls below the defined threshold.
+            let pressed = match event.action {
+                Action::Left => &mut self.state.pressed_left,
+                Action::Right => &mut self.state.pressed_right,
+                Action::Up => &mut self.state.pressed_up,
+                Action::Down => &mut self.state.pressed_down,
+                _ => {
+                    translated.push(event);
+                    continue;
+                }
+            };
+            let value = event.as_f64();
+            if *pressed && value < AXIS_THRESHOLD {
+                event.value = InputValue::Float(0.0);
+                *pressed = false;
+            } else if !*pressed && value >= AXIS_THRESHOLD {
+                event.value = InputValue::Float(1.0);
+                *pressed = true;
+            } else {
+                continue;
+            }
+            translated.push(event);
+        }
+
+        translated
+    }
+
+    /// Emit the given DBus event as a signal
+    fn write_dbus_event(&self, event: DBusEvent) -> Result<(), Box<dyn Error>> {
+        // Only send valid events
+        if event.action == Action::None {
+            return Ok(());
+        }
+
+        // DBus events can only be written if there is a DBus path reference.
+        let Some(path) = self.dbus_path.clone() else {
+            return Err("No dbus path exists to send events to".into());
+        };
+
+        // Touch input cannot be expressed as a single value
+        let value = match event.value {
+            InputValue::Bool(_) | InputValue::Float(_) => event.as_f64(),
+            _ => {
+                log::trace!("Unable to send DBus event with value: {:?}", event.value);
+                return Ok(());
+            }
+        };
+
+        // The target device runs in a blocking task, so block on the runtime
+        // handle to send the signal in order.
+        let result: zbus::Result<()> = Handle::current().block_on(async {
+            let iface_ref = self
+                .conn
+                .object_server()
+                .interface::<_, TargetDBusInterface>(path.as_str())
+                .await?;
+            TargetDBusInterface::input_event(
+                iface_ref.signal_context(),
+                event.action.as_string(),
+                value,
+            )
+            .await
+        });
+        result?;
+
+        Ok(())
+    }
+}
+
+impl TargetInputDevice for DBusDevice {
+    fn start_dbus_interface(
+        &mut self,
+        dbus: Connection,
+        path: String,
+        _client: TargetDeviceClient,
+    ) {
+        log::debug!("Starting dbus interface: {path}");
+        self.dbus_path = Some(path.clone());
+        tokio::task::spawn(async move {
+            let iface = TargetDBusInterface::new();
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let dbus_events = self.translate_event(event);
+        for dbus_event in dbus_events {
+            log::trace!("Writing DBus event: {dbus_event:?}");
+            self.write_dbus_event(dbus_event)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::DBus(Action::Guide),
+            Capability::DBus(Action::Quick),
+            Capability::DBus(Action::Quick2),
+            Capability::DBus(Action::Context),
+            Capability::DBus(Action::Option),
+            Capability::DBus(Action::Select),
+            Capability::DBus(Action::Accept),
+            Capability::DBus(Action::Back),
+            Capability::DBus(Action::ActOn),
+            Capability::DBus(Action::Left),
+            Capability::DBus(Action::Right),
+            Capability::DBus(Action::Up),
+            Capability::DBus(Action::Down),
+            Capability::DBus(Action::L1),
+            Capability::DBus(Action::L2),
+            Capability::DBus(Action::L3),
+            Capability::DBus(Action::R1),
+            Capability::DBus(Action::R2),
+            Capability::DBus(Action::R3),
+            Capability::DBus(Action::VolumeUp),
+            Capability::DBus(Action::VolumeDown),
+            Capability::DBus(Action::VolumeMute),
+            Capability::DBus(Action::Keyboard),
+            Capability::DBus(Action::Screenshot),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+        ])
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetDBusInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for DBusDevice {}
+
+impl Debug for DBusDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DBusDevice")
+            .field("state", &self.state)
+            .field("dbus_path", &self.dbus_path)
+            .finish()
+    }
+}
@@ -0,0 +1,181 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode,
+};
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::keyboard::TargetKeyboardInterface,
+    input::{
+        capability::{Capability, Keyboard},
+        event::{evdev::EvdevEvent, native::NativeEvent, value::InputValue},
+        keysym::Keymap,
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// The [KeyboardDevice] is a virtual keyboard that can emit every key that
+/// has a keyboard capability. Keysym events are translated into the key and
+/// modifiers that produce them with the keyboard layout of the system.
+pub struct KeyboardDevice {
+    device: VirtualDevice,
+    capabilities: Vec<Capability>,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+    keymap: Keymap,
+}
+
+impl KeyboardDevice {
+    /// Create a new virtual keyboard
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let keymap = Keymap::from_system();
+        log::debug!("Using keyboard layout: {}", keymap.layout());
+        let keys = KeyboardDevice::supported_keys();
+        let mut capabilities = keys
+            .iter()
+            .map(|key| key_capability(*key))
+            .collect::<Vec<Capability>>();
+        capabilities.extend(keymap.keysyms().into_iter().map(Capability::KeyboardKeysym));
+        let device = KeyboardDevice::create_virtual_device(keys)?;
+        Ok(Self {
+            device,
+            capabilities,
+            axis_map: HashMap::new(),
+            keymap,
+        })
+    }
+
+    /// Returns all keyboard key codes that can be translated into a keyboard
+    /// capability.
+    fn supported_keys() -> Vec<KeyCode> {
+        (0..KeyCode::BTN_0.0)
+            .map(KeyCode::new)
+            .filter(|key| matches!(key_capability(*key), Capability::Keyboard(_)))
+            .collect()
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        if let Capability::KeyboardKeysym(keysym) = event.as_capability() {
+            return self.translate_keysym(keysym.as_str(), event.pressed());
+        }
+        self.translate_key(event)
+    }
+
+    /// Translate the given keysym into the key events that emit it. Modifiers
+    /// are pressed before the key and released after it.
+    fn translate_keysym(&self, keysym: &str, pressed: bool) -> Vec<InputEvent> {
+        let Some(mapping) = self.keymap.lookup(keysym) else {
+            log::warn!(
+                "Keysym '{keysym}' is not available in keyboard layout '{}'",
+                self.keymap.layout()
+            );
+            return vec![];
+        };
+
+        let mut keys = Vec::with_capacity(3);
+        if mapping.shift {
+            keys.push(Keyboard::KeyLeftShift);
+        }
+        if mapping.altgr {
+            keys.push(Keyboard::KeyRightAlt);
+        }
+        keys.push(mapping.key);
+        if !pressed {
+            keys.reverse();
+        }
+
+        keys.into_iter()
+            .flat_map(|key| {
+                let event = NativeEvent::new(Capability::Keyboard(key), InputValue::Bool(pressed));
+                self.translate_key(event)
+            })
+            .collect()
+    }
+
+    /// Translate the given keyboard key event into evdev events
+    fn translate_key(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(keys: Vec<KeyCode>) -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut key_set = AttributeSet::<KeyCode>::new();
+        for key in keys {
+            key_set.insert(key);
+        }
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Keyboard")
+            .with_keys(&key_set)?
+            .build()?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for KeyboardDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        tokio::task::spawn(async move {
+            let iface = TargetKeyboardInterface::new(client);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.capabilities.clone())
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetKeyboardInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for KeyboardDevice {}
+
+impl Debug for KeyboardDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyboardDevice")
+            .field("capabilities", &self.capabilities)
+            .field("keymap", &self.keymap.layout())
+            .finish()
+    }
+}
+
+/// Returns the capability of the given key code
+fn key_capability(key: KeyCode) -> Capability {
+    let event = InputEvent::new(EventType::KEY.0, key.0, 0);
+    EvdevEvent::from(event).as_capability()
+}
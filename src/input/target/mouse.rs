@@ -0,0 +1,146 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, InputEvent, KeyCode, PropType, RelativeAxisCode,
+    UinputAbsSetup,
+};
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::mouse::TargetMouseInterface,
+    input::{
+        capability::{Capability, Mouse, MouseButton},
+        event::{evdev::EvdevEvent, native::NativeEvent},
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Maximum value of the absolute position axes
+const ABS_POSITION_MAX: i32 = 65535;
+
+/// The [MouseDevice] is a virtual mouse with relative motion, buttons and a
+/// scroll wheel. Absolute mouse motion (e.g. from a touchscreen) is emitted
+/// on the absolute X/Y axes of the device.
+pub struct MouseDevice {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+}
+
+impl MouseDevice {
+    /// Create a new virtual mouse
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let device = MouseDevice::create_virtual_device()?;
+        let position = AbsInfo::new(0, 0, ABS_POSITION_MAX, 0, 0, 0);
+        let axis_map = HashMap::from([
+            (AbsoluteAxisCode::ABS_X, position),
+            (AbsoluteAxisCode::ABS_Y, position),
+        ]);
+        Ok(Self { device, axis_map })
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device() -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut buttons = AttributeSet::<KeyCode>::new();
+        buttons.insert(KeyCode::BTN_LEFT);
+        buttons.insert(KeyCode::BTN_RIGHT);
+        buttons.insert(KeyCode::BTN_MIDDLE);
+        buttons.insert(KeyCode::BTN_SIDE);
+        buttons.insert(KeyCode::BTN_EXTRA);
+
+        let mut axes = AttributeSet::<RelativeAxisCode>::new();
+        axes.insert(RelativeAxisCode::REL_X);
+        axes.insert(RelativeAxisCode::REL_Y);
+        axes.insert(RelativeAxisCode::REL_WHEEL);
+        axes.insert(RelativeAxisCode::REL_HWHEEL);
+
+        let mut properties = AttributeSet::<PropType>::new();
+        properties.insert(PropType::POINTER);
+
+        let position = AbsInfo::new(0, 0, ABS_POSITION_MAX, 0, 0, 0);
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Mouse")
+            .with_keys(&buttons)?
+            .with_relative_axes(&axes)?
+            .with_properties(&properties)?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_X, position))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_Y, position))?
+            .build()?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for MouseDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, _: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        tokio::task::spawn(async move {
+            let iface = TargetMouseInterface::new();
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Mouse(Mouse::Button(MouseButton::Left)),
+            Capability::Mouse(Mouse::Button(MouseButton::Right)),
+            Capability::Mouse(Mouse::Button(MouseButton::Middle)),
+            Capability::Mouse(Mouse::Button(MouseButton::Side)),
+            Capability::Mouse(Mouse::Button(MouseButton::Extra)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelUp)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelDown)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelLeft)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelRight)),
+            Capability::Mouse(Mouse::Motion),
+            Capability::Mouse(Mouse::AbsoluteMotion),
+        ])
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetMouseInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for MouseDevice {}
+
+impl Debug for MouseDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MouseDevice").finish()
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    error::Error,
+    net::{SocketAddr, UdpSocket},
+};
+
+use crate::input::{
+    capability::Capability,
+    event::{
+        native::NativeEvent,
+        network::{self, NetworkEvent},
+    },
+};
+
+use super::{InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Address that network stream target devices send events to if no address
+/// is specified.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:27020";
+
+/// Target device that streams input events over UDP to a remote host using
+/// the protocol defined in [network].
+#[derive(Debug)]
+pub struct NetworkStreamDevice {
+    socket: UdpSocket,
+    address: SocketAddr,
+}
+
+impl NetworkStreamDevice {
+    /// Create a new network stream that sends events to the given address
+    pub fn new(address: SocketAddr) -> Result<Self, Box<dyn Error>> {
+        let bind_address = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_address)?;
+        log::info!("Streaming input events to {address}");
+        Ok(Self { socket, address })
+    }
+}
+
+impl TargetInputDevice for NetworkStreamDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        for packet in NetworkEvent::from_native_event(&event) {
+            self.socket.send_to(&packet.to_bytes(), self.address)?;
+        }
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(network::CAPABILITIES.to_vec())
+    }
+}
+
+impl TargetOutputDevice for NetworkStreamDevice {}
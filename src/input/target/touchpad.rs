@@ -0,0 +1,209 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode, PropType,
+    UinputAbsSetup,
+};
+
+use crate::input::{
+    capability::{Capability, Touch, TouchButton, Touchpad},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::{InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Resolution of the virtual touchpad
+const WIDTH: i32 = 1024;
+const HEIGHT: i32 = 1024;
+/// Maximum value of the touch pressure axis
+const MAX_PRESSURE: i32 = 255;
+/// Number of touches that can be tracked at the same time
+const MAX_SLOTS: i32 = 5;
+
+/// The [TouchpadDevice] is a virtual multi-touch clickpad. Input from any
+/// touchpad (e.g. the left and right pads of a Steam Deck) is emitted on the
+/// same touch surface.
+pub struct TouchpadDevice {
+    device: VirtualDevice,
+    /// Tracking ids of the active touches by touch index
+    touches: HashMap<u8, i32>,
+    next_tracking_id: i32,
+}
+
+impl TouchpadDevice {
+    /// Create a new virtual touchpad
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let device = TouchpadDevice::create_virtual_device()?;
+        Ok(Self {
+            device,
+            touches: HashMap::new(),
+            next_tracking_id: 0,
+        })
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&mut self, event: NativeEvent) -> Vec<InputEvent> {
+        let touch = match event.as_capability() {
+            Capability::Touchpad(
+                Touchpad::LeftPad(touch) | Touchpad::RightPad(touch) | Touchpad::CenterPad(touch),
+            ) => touch,
+            _ => return vec![],
+        };
+
+        match touch {
+            Touch::Motion => self.translate_motion(event.get_value()),
+            Touch::Button(TouchButton::Press) => {
+                let value = if event.pressed() { 1 } else { 0 };
+                vec![key_event(KeyCode::BTN_LEFT, value)]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Translate the given touch value into multi-touch evdev events
+    fn translate_motion(&mut self, value: InputValue) -> Vec<InputEvent> {
+        let InputValue::Touch {
+            index,
+            is_touching,
+            pressure,
+            x,
+            y,
+        } = value
+        else {
+            return vec![];
+        };
+        if index as i32 >= MAX_SLOTS {
+            log::trace!("Ignoring touch with index {index}");
+            return vec![];
+        }
+
+        let mut events = vec![abs_event(AbsoluteAxisCode::ABS_MT_SLOT, index as i32)];
+        let previous_count = self.touches.len();
+
+        // Start or end the touch in this slot
+        if is_touching && !self.touches.contains_key(&index) {
+            let tracking_id = self.next_tracking_id;
+            self.next_tracking_id = (self.next_tracking_id + 1) % u16::MAX as i32;
+            self.touches.insert(index, tracking_id);
+            events.push(abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, tracking_id));
+        } else if !is_touching && self.touches.remove(&index).is_some() {
+            events.push(abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1));
+        }
+
+        if is_touching {
+            if let Some(x) = x {
+                let x = denormalize(x, WIDTH);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, x));
+                events.push(abs_event(AbsoluteAxisCode::ABS_X, x));
+            }
+            if let Some(y) = y {
+                let y = denormalize(y, HEIGHT);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_POSITION_Y, y));
+                events.push(abs_event(AbsoluteAxisCode::ABS_Y, y));
+            }
+            if let Some(pressure) = pressure {
+                let pressure = denormalize(pressure, MAX_PRESSURE);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_PRESSURE, pressure));
+            }
+        }
+
+        // Report the number of fingers on the touchpad
+        let count = self.touches.len();
+        if count != previous_count {
+            events.push(key_event(KeyCode::BTN_TOUCH, (count > 0) as i32));
+            events.push(key_event(KeyCode::BTN_TOOL_FINGER, (count == 1) as i32));
+            events.push(key_event(KeyCode::BTN_TOOL_DOUBLETAP, (count == 2) as i32));
+            events.push(key_event(KeyCode::BTN_TOOL_TRIPLETAP, (count >= 3) as i32));
+        }
+
+        events
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device() -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::BTN_LEFT);
+        keys.insert(KeyCode::BTN_TOUCH);
+        keys.insert(KeyCode::BTN_TOOL_FINGER);
+        keys.insert(KeyCode::BTN_TOOL_DOUBLETAP);
+        keys.insert(KeyCode::BTN_TOOL_TRIPLETAP);
+
+        let mut properties = AttributeSet::<PropType>::new();
+        properties.insert(PropType::POINTER);
+        properties.insert(PropType::BUTTONPAD);
+
+        let slot = AbsInfo::new(0, 0, MAX_SLOTS - 1, 0, 0, 0);
+        let tracking_id = AbsInfo::new(0, 0, u16::MAX as i32, 0, 0, 0);
+        let x = AbsInfo::new(0, 0, WIDTH, 0, 0, 0);
+        let y = AbsInfo::new(0, 0, HEIGHT, 0, 0, 0);
+        let pressure = AbsInfo::new(0, 0, MAX_PRESSURE, 0, 0, 0);
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Touchpad")
+            .with_keys(&keys)?
+            .with_properties(&properties)?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_X, x))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_Y, y))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_SLOT, slot))?
+            .with_absolute_axis(&UinputAbsSetup::new(
+                AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+                tracking_id,
+            ))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_POSITION_X, x))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_POSITION_Y, y))?
+            .with_absolute_axis(&UinputAbsSetup::new(
+                AbsoluteAxisCode::ABS_MT_PRESSURE,
+                pressure,
+            ))?
+            .build()?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for TouchpadDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Touchpad(Touchpad::CenterPad(Touch::Button(TouchButton::Press))),
+            Capability::Touchpad(Touchpad::CenterPad(Touch::Motion)),
+        ])
+    }
+}
+
+impl TargetOutputDevice for TouchpadDevice {}
+
+impl Debug for TouchpadDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TouchpadDevice")
+            .field("touches", &self.touches)
+            .finish()
+    }
+}
+
+/// Returns an absolute axis event with the given code and value
+fn abs_event(code: AbsoluteAxisCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::ABSOLUTE.0, code.0, value)
+}
+
+/// Returns a key event with the given code and value
+fn key_event(code: KeyCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::KEY.0, code.0, value)
+}
+
+/// De-normalizes the given value from 0.0 - 1.0 into a value between 0 and
+/// the given maximum.
+fn denormalize(normal_value: f64, max: i32) -> i32 {
+    (normal_value.clamp(0.0, 1.0) * max as f64).round() as i32
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode, PropType,
+    UinputAbsSetup,
+};
+
+use crate::input::{
+    capability::{Capability, Touch},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::{InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Resolution of the virtual touchscreen
+const WIDTH: i32 = 1280;
+const HEIGHT: i32 = 800;
+/// Maximum value of the touch pressure axis
+const MAX_PRESSURE: i32 = 255;
+/// Number of touches that can be tracked at the same time
+const MAX_SLOTS: i32 = 10;
+
+/// The [TouchscreenDevice] is a virtual multi-touch touchscreen
+pub struct TouchscreenDevice {
+    device: VirtualDevice,
+    /// Tracking ids of the active touches by touch index
+    touches: HashMap<u8, i32>,
+    next_tracking_id: i32,
+}
+
+impl TouchscreenDevice {
+    /// Create a new virtual touchscreen
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let device = TouchscreenDevice::create_virtual_device()?;
+        Ok(Self {
+            device,
+            touches: HashMap::new(),
+            next_tracking_id: 0,
+        })
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&mut self, event: NativeEvent) -> Vec<InputEvent> {
+        let Capability::Touchscreen(Touch::Motion) = event.as_capability() else {
+            return vec![];
+        };
+        let InputValue::Touch {
+            index,
+            is_touching,
+            pressure,
+            x,
+            y,
+        } = event.get_value()
+        else {
+            return vec![];
+        };
+        if index as i32 >= MAX_SLOTS {
+            log::trace!("Ignoring touch with index {index}");
+            return vec![];
+        }
+
+        let mut events = vec![abs_event(AbsoluteAxisCode::ABS_MT_SLOT, index as i32)];
+        let was_touching = !self.touches.is_empty();
+
+        // Start or end the touch in this slot
+        if is_touching && !self.touches.contains_key(&index) {
+            let tracking_id = self.next_tracking_id;
+            self.next_tracking_id = (self.next_tracking_id + 1) % u16::MAX as i32;
+            self.touches.insert(index, tracking_id);
+            events.push(abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, tracking_id));
+        } else if !is_touching && self.touches.remove(&index).is_some() {
+            events.push(abs_event(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1));
+        }
+
+        if is_touching {
+            if let Some(x) = x {
+                let x = denormalize(x, WIDTH);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_POSITION_X, x));
+                events.push(abs_event(AbsoluteAxisCode::ABS_X, x));
+            }
+            if let Some(y) = y {
+                let y = denormalize(y, HEIGHT);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_POSITION_Y, y));
+                events.push(abs_event(AbsoluteAxisCode::ABS_Y, y));
+            }
+            if let Some(pressure) = pressure {
+                let pressure = denormalize(pressure, MAX_PRESSURE);
+                events.push(abs_event(AbsoluteAxisCode::ABS_MT_PRESSURE, pressure));
+            }
+        }
+
+        // Report whether or not anything is touching the screen
+        let touching = !self.touches.is_empty();
+        if touching != was_touching {
+            let value = if touching { 1 } else { 0 };
+            events.push(InputEvent::new(
+                EventType::KEY.0,
+                KeyCode::BTN_TOUCH.0,
+                value,
+            ));
+        }
+
+        events
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device() -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::BTN_TOUCH);
+
+        let mut properties = AttributeSet::<PropType>::new();
+        properties.insert(PropType::DIRECT);
+
+        let slot = AbsInfo::new(0, 0, MAX_SLOTS - 1, 0, 0, 0);
+        let tracking_id = AbsInfo::new(0, 0, u16::MAX as i32, 0, 0, 0);
+        let x = AbsInfo::new(0, 0, WIDTH, 0, 0, 0);
+        let y = AbsInfo::new(0, 0, HEIGHT, 0, 0, 0);
+        let pressure = AbsInfo::new(0, 0, MAX_PRESSURE, 0, 0, 0);
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Touchscreen")
+            .with_keys(&keys)?
+            .with_properties(&properties)?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_X, x))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_Y, y))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_SLOT, slot))?
+            .with_absolute_axis(&UinputAbsSetup::new(
+                AbsoluteAxisCode::ABS_MT_TRACKING_ID,
+                tracking_id,
+            ))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_POSITION_X, x))?
+            .with_absolute_axis(&UinputAbsSetup::new(AbsoluteAxisCode::ABS_MT_POSITION_Y, y))?
+            .with_absolute_axis(&UinputAbsSetup::new(
+                AbsoluteAxisCode::ABS_MT_PRESSURE,
+                pressure,
+            ))?
+            .build()?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for TouchscreenDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![Capability::Touchscreen(Touch::Motion)])
+    }
+}
+
+impl TargetOutputDevice for TouchscreenDevice {}
+
+impl Debug for TouchscreenDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TouchscreenDevice")
+            .field("touches", &self.touches)
+            .finish()
+    }
+}
+
+/// Returns an absolute axis event with the given code and value
+fn abs_event(code: AbsoluteAxisCode, value: i32) -> InputEvent {
+    InputEvent::new(EventType::ABSOLUTE.0, code.0, value)
+}
+
+/// De-normalizes the given value from 0.0 - 1.0 into a value between 0 and
+/// the given maximum.
+fn denormalize(normal_value: f64, max: i32) -> i32 {
+    (normal_value.clamp(0.0, 1.0) * max as f64).round() as i32
+}
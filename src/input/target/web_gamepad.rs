@@ -0,0 +1,450 @@
+//! Target device that serves a webpage over HTTP which exposes gamepad input
+//! to web games through the W3C Gamepad API.
+//!
+//! Pages can include the "/gamepad.js" script served by the device, which
+//! connects to the "/ws" WebSocket endpoint and replaces
+//! `navigator.getGamepads()` with a virtual gamepad using the "standard"
+//! W3C mapping. The gamepad state is sent over the WebSocket as JSON in the
+//! form `{"buttons":[...],"axes":[...]}` whenever it changes.
+//!
+//! The server only listens on the loopback interface by default, since it
+//! serves the live gamepad state without any authentication. Listening on
+//! other interfaces must be explicitly configured with a bind address.
+use std::{
+    error::Error,
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::header,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    oneshot,
+};
+use zbus::{fdo, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::interface::target::gamepad::TargetGamepadInterface,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Address the HTTP server binds to if no address is specified
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+/// Port the HTTP server listens on if no port is specified
+pub const DEFAULT_PORT: u16 = 27021;
+
+/// Number of buttons in the W3C standard gamepad mapping
+const BUTTON_COUNT: usize = 17;
+/// Number of axes in the W3C standard gamepad mapping
+const AXIS_COUNT: usize = 4;
+/// Maximum number of state updates buffered for each WebSocket client
+const BROADCAST_BUFFER_SIZE: usize = 64;
+
+/// Webpage served at the root of the HTTP server
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>InputPlumber Web Gamepad</title>
+    <script src="/gamepad.js"></script>
+  </head>
+  <body>
+    <h1>InputPlumber Web Gamepad</h1>
+    <p>Include <code>&lt;script src="http://HOST:PORT/gamepad.js"&gt;&lt;/script&gt;</code>
+      in a web game to use this gamepad through the Gamepad API.</p>
+    <pre id="state">Waiting for gamepad...</pre>
+    <script>
+      const update = () => {
+        const pad = navigator.getGamepads()[0];
+        if (pad && pad.connected) {
+          const buttons = pad.buttons.map((b) => b.value.toFixed(2)).join(" ");
+          const axes = pad.axes.map((a) => a.toFixed(2)).join(" ");
+          document.getElementById("state").textContent =
+            `${pad.id}\nButtons: ${buttons}\nAxes: ${axes}`;
+        }
+        window.requestAnimationFrame(update);
+      };
+      window.requestAnimationFrame(update);
+    </script>
+  </body>
+</html>
+"#;
+
+/// Script that replaces the Gamepad API of the page it is included in with a
+/// virtual gamepad driven by the WebSocket endpoint.
+const GAMEPAD_JS: &str = r#"(() => {
+  const script = document.currentScript;
+  const url = new URL("/ws", script ? script.src : window.location.href);
+  url.protocol = url.protocol === "https:" ? "wss:" : "ws:";
+
+  const pad = {
+    id: "InputPlumber Web Gamepad (STANDARD GAMEPAD)",
+    index: 0,
+    connected: false,
+    mapping: "standard",
+    timestamp: 0,
+    axes: [0, 0, 0, 0],
+    buttons: Array.from({ length: 17 }, () => ({ pressed: false, touched: false, value: 0 })),
+    vibrationActuator: null,
+  };
+
+  const dispatch = (type) => {
+    const event = new Event(type);
+    event.gamepad = pad;
+    window.dispatchEvent(event);
+  };
+
+  // Bypass the gamepad support of the browser while connected
+  const getGamepads = navigator.getGamepads ? navigator.getGamepads.bind(navigator) : () => [];
+  navigator.getGamepads = () => (pad.connected ? [pad, null, null, null] : getGamepads());
+
+  const connect = () => {
+    const socket = new WebSocket(url);
+    socket.onmessage = (message) => {
+      const state = JSON.parse(message.data);
+      state.buttons.forEach((value, i) => {
+        pad.buttons[i].value = value;
+        pad.buttons[i].pressed = value > 0.1;
+        pad.buttons[i].touched = value > 0;
+      });
+      pad.axes = state.axes;
+      pad.timestamp = performance.now();
+      if (!pad.connected) {
+        pad.connected = true;
+        dispatch("gamepadconnected");
+      }
+    };
+    socket.onclose = () => {
+      if (pad.connected) {
+        pad.connected = false;
+        dispatch("gamepaddisconnected");
+      }
+      setTimeout(connect, 1000);
+    };
+  };
+  connect();
+})();
+"#;
+
+/// State of the virtual gamepad using the W3C standard gamepad mapping
+#[derive(Debug, Default, Clone)]
+struct GamepadState {
+    buttons: [f64; BUTTON_COUNT],
+    axes: [f64; AXIS_COUNT],
+}
+
+impl GamepadState {
+    /// Update the state from the given event. Returns true if the event
+    /// changed the state.
+    fn update(&mut self, event: &NativeEvent) -> bool {
+        let previous = self.clone();
+        let cap = event.as_capability();
+        let value = event.get_value();
+        match (cap, value) {
+            (Capability::Gamepad(Gamepad::Button(button)), value) => {
+                let Some(index) = button_index(&button) else {
+                    return false;
+                };
+                self.buttons[index] = match value {
+                    InputValue::Bool(pressed) => pressed as u8 as f64,
+                    InputValue::Float(value) => value,
+                    _ => return false,
+                };
+            }
+            (Capability::Gamepad(Gamepad::Trigger(trigger)), InputValue::Float(value)) => {
+                let index = match trigger {
+                    GamepadTrigger::LeftTrigger => 6,
+                    GamepadTrigger::RightTrigger => 7,
+                    _ => return false,
+                };
+                self.buttons[index] = value;
+            }
+            (Capability::Gamepad(Gamepad::Axis(axis)), InputValue::Vector2 { x, y }) => {
+                let offset = match axis {
+                    GamepadAxis::LeftStick => 0,
+                    GamepadAxis::RightStick => 2,
+                    _ => return false,
+                };
+                if let Some(x) = x {
+                    self.axes[offset] = x;
+                }
+                if let Some(y) = y {
+                    self.axes[offset + 1] = y;
+                }
+            }
+            _ => return false,
+        }
+
+        self.buttons != previous.buttons || self.axes != previous.axes
+    }
+
+    /// Serialize the state to the JSON message sent to WebSocket clients
+    fn to_json(&self) -> String {
+        let join = |values: &[f64]| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        format!(
+            "{{\"buttons\":[{}],\"axes\":[{}]}}",
+            join(&self.buttons),
+            join(&self.axes)
+        )
+    }
+}
+
+/// Returns the index of the given button in the W3C standard gamepad mapping
+fn button_index(button: &GamepadButton) -> Option<usize> {
+    let index = match button {
+        GamepadButton::South => 0,
+        GamepadButton::East => 1,
+        GamepadButton::North => 2,
+        GamepadButton::West => 3,
+        GamepadButton::LeftBumper => 4,
+        GamepadButton::RightBumper => 5,
+        GamepadButton::Select => 8,
+        GamepadButton::Start => 9,
+        GamepadButton::LeftStick => 10,
+        GamepadButton::RightStick => 11,
+        GamepadButton::DPadUp => 12,
+        GamepadButton::DPadDown => 13,
+        GamepadButton::DPadLeft => 14,
+        GamepadButton::DPadRight => 15,
+        GamepadButton::Guide => 16,
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// Shared state of the HTTP server
+#[derive(Debug, Clone)]
+struct ServerState {
+    state: Arc<Mutex<GamepadState>>,
+    updates: broadcast::Sender<String>,
+}
+
+/// Target device that serves the gamepad state to web games over HTTP
+#[derive(Debug)]
+pub struct WebGamepadDevice {
+    address: SocketAddr,
+    state: Arc<Mutex<GamepadState>>,
+    updates: broadcast::Sender<String>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl WebGamepadDevice {
+    /// Create a new web gamepad that serves the gamepad webpage on the given
+    /// address. This must be called from within a tokio runtime.
+    pub fn new(address: SocketAddr) -> Result<Self, Box<dyn Error>> {
+        if !address.ip().is_loopback() {
+            log::warn!("Web gamepad state will be served without authentication on {address}");
+        }
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+
+        let state = Arc::new(Mutex::new(GamepadState::default()));
+        let (updates, _) = broadcast::channel(BROADCAST_BUFFER_SIZE);
+        let server_state = ServerState {
+            state: state.clone(),
+            updates: updates.clone(),
+        };
+        let app = Router::new()
+            .route("/", get(|| async { Html(INDEX_HTML) }))
+            .route("/gamepad.js", get(serve_script))
+            .route("/ws", get(upgrade_websocket))
+            .with_state(server_state);
+
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        tokio::task::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                log::error!("Web gamepad server stopped with error: {e:?}");
+            }
+            log::debug!("Web gamepad server on {address} stopped");
+        });
+        log::info!("Serving web gamepad on {address}");
+
+        Ok(Self {
+            address,
+            state,
+            updates,
+            shutdown: Some(shutdown),
+        })
+    }
+}
+
+/// Serve the script that provides the virtual gamepad to web pages
+async fn serve_script() -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/javascript"),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        ],
+        GAMEPAD_JS,
+    )
+        .into_response()
+}
+
+/// Upgrade the connection to a WebSocket that streams the gamepad state
+async fn upgrade_websocket(ws: WebSocketUpgrade, State(server): State<ServerState>) -> Response {
+    ws.on_upgrade(move |socket| stream_state(socket, server))
+}
+
+/// Send the current gamepad state, followed by every state update, to the
+/// given WebSocket client until it disconnects.
+async fn stream_state(mut socket: WebSocket, server: ServerState) {
+    let mut updates = server.updates.subscribe();
+    let state = server.state.lock().unwrap().to_json();
+    if socket.send(Message::Text(state)).await.is_err() {
+        return;
+    }
+    loop {
+        let state = match updates.recv().await {
+            Ok(state) => state,
+            // Skip to the latest state if the client falls behind
+            Err(RecvError::Lagged(_)) => server.state.lock().unwrap().to_json(),
+            Err(RecvError::Closed) => break,
+        };
+        if socket.send(Message::Text(state)).await.is_err() {
+            break;
+        }
+    }
+    log::debug!("Web gamepad client disconnected");
+}
+
+impl TargetInputDevice for WebGamepadDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        log::trace!("Using device client: {client:?}");
+        let address = self.address;
+        tokio::task::spawn(async move {
+            let iface = TargetGamepadInterface::new("Web Gamepad".to_string());
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+                return;
+            }
+            let iface = TargetWebGamepadInterface::new(address);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let state = {
+            let mut state = self.state.lock().unwrap();
+            if !state.update(&event) {
+                return Ok(());
+            }
+            state.to_json()
+        };
+        // Sending only fails if no WebSocket clients are connected
+        let _ = self.updates.send(state);
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+        ])
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let object_server = dbus.object_server();
+            let result = object_server
+                .remove::<TargetWebGamepadInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            }
+            let result = object_server
+                .remove::<TargetGamepadInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+
+    fn stop(&mut self) -> Result<(), InputError> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        Ok(())
+    }
+}
+
+impl TargetOutputDevice for WebGamepadDevice {}
+
+/// DBus interface exposing information about a web gamepad target device
+pub struct TargetWebGamepadInterface {
+    address: SocketAddr,
+}
+
+impl TargetWebGamepadInterface {
+    pub fn new(address: SocketAddr) -> TargetWebGamepadInterface {
+        TargetWebGamepadInterface { address }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.WebGamepad")]
+impl TargetWebGamepadInterface {
+    /// Address the HTTP server serving the web gamepad is bound to
+    #[zbus(property)]
+    async fn address(&self) -> fdo::Result<String> {
+        Ok(self.address.ip().to_string())
+    }
+
+    /// Port the HTTP server serving the web gamepad listens on
+    #[zbus(property)]
+    async fn port(&self) -> fdo::Result<u16> {
+        Ok(self.address.port())
+    }
+}
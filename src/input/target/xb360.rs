@@ -0,0 +1,292 @@
+use std::{
+    collections::HashMap, error::Error, fmt::Debug, os::fd::AsRawFd, sync::mpsc::channel,
+    time::Duration,
+};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, EventSummary, FFEffectCode, InputEvent,
+    InputId, KeyCode, UInputCode, UinputAbsSetup,
+};
+use nix::fcntl::{FcntlArg, OFlag};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    composite_device::client::CompositeDeviceClient,
+    event::{
+        evdev::EvdevEvent,
+        native::{NativeEvent, ScheduledNativeEvent},
+    },
+    output_capability::OutputCapability,
+    output_event::{OutputEvent, UinputOutputEvent},
+};
+
+use super::{InputError, OutputError, TargetInputDevice, TargetOutputDevice};
+
+/// Maximum number of force feedback effects that can be uploaded
+const FF_EFFECTS_MAX: u32 = 16;
+/// Time to wait for the composite device to upload a force feedback effect
+const FF_UPLOAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The [XBox360Controller] is a virtual gamepad that emulates a wired
+/// Microsoft X-Box 360 pad.
+pub struct XBox360Controller {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+    queued_events: Vec<ScheduledNativeEvent>,
+}
+
+impl XBox360Controller {
+    /// Create a new emulated X-Box 360 pad
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let axis_map = XBox360Controller::create_axes_map();
+        let device = XBox360Controller::create_virtual_device(&axis_map)?;
+        Ok(Self {
+            device,
+            axis_map,
+            queued_events: vec![],
+        })
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Return an [AbsInfo] map of the axes of the device
+    fn create_axes_map() -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        let joystick = AbsInfo::new(0, -32768, 32767, 16, 128, 0);
+        let trigger = AbsInfo::new(0, 0, 255, 0, 0, 0);
+        let hat = AbsInfo::new(0, -1, 1, 0, 0, 0);
+        HashMap::from([
+            (AbsoluteAxisCode::ABS_X, joystick),
+            (AbsoluteAxisCode::ABS_Y, joystick),
+            (AbsoluteAxisCode::ABS_RX, joystick),
+            (AbsoluteAxisCode::ABS_RY, joystick),
+            (AbsoluteAxisCode::ABS_Z, trigger),
+            (AbsoluteAxisCode::ABS_RZ, trigger),
+            (AbsoluteAxisCode::ABS_HAT0X, hat),
+            (AbsoluteAxisCode::ABS_HAT0Y, hat),
+        ])
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(
+        axis_map: &HashMap<AbsoluteAxisCode, AbsInfo>,
+    ) -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut buttons = AttributeSet::<KeyCode>::new();
+        buttons.insert(KeyCode::BTN_SOUTH);
+        buttons.insert(KeyCode::BTN_EAST);
+        buttons.insert(KeyCode::BTN_NORTH);
+        buttons.insert(KeyCode::BTN_WEST);
+        buttons.insert(KeyCode::BTN_TL);
+        buttons.insert(KeyCode::BTN_TR);
+        buttons.insert(KeyCode::BTN_SELECT);
+        buttons.insert(KeyCode::BTN_START);
+        buttons.insert(KeyCode::BTN_MODE);
+        buttons.insert(KeyCode::BTN_THUMBL);
+        buttons.insert(KeyCode::BTN_THUMBR);
+
+        let mut builder = VirtualDeviceBuilder::new()?
+            .name("Microsoft X-Box 360 pad")
+            .input_id(InputId::new(BusType::BUS_USB, 0x045e, 0x028e, 0x0110))
+            .with_keys(&buttons)?;
+        for (axis, info) in axis_map.iter() {
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(*axis, *info))?;
+        }
+        let device = builder
+            .with_ff(&ff_effect_codes())?
+            .with_ff_effects_max(FF_EFFECTS_MAX)
+            .build()?;
+
+        // Force feedback requests are read in the poll loop, so reads must
+        // not block.
+        nix::fcntl::fcntl(device.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for XBox360Controller {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        // Check for QuickAccess, create chord for event.
+        let cap = event.as_capability();
+        if cap == Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)) {
+            self.queued_events.append(&mut quick_access_chord(event));
+            return Ok(());
+        }
+
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        ])
+    }
+
+    /// Returns any events in the queue up to the [TargetDriver]
+    fn scheduled_events(&mut self) -> Option<Vec<ScheduledNativeEvent>> {
+        if self.queued_events.is_empty() {
+            return None;
+        }
+        Some(self.queued_events.drain(..).collect())
+    }
+}
+
+impl TargetOutputDevice for XBox360Controller {
+    /// Read force feedback requests from the virtual device
+    fn poll(
+        &mut self,
+        composite_device: &Option<CompositeDeviceClient>,
+    ) -> Result<Vec<OutputEvent>, OutputError> {
+        poll_ff_events(&mut self.device, composite_device)
+    }
+
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![
+            OutputCapability::ForceFeedback,
+            OutputCapability::ForceFeedbackUpload,
+            OutputCapability::ForceFeedbackErase,
+        ])
+    }
+}
+
+impl Debug for XBox360Controller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XBox360Controller")
+            .field("axis_map", &self.axis_map)
+            .finish()
+    }
+}
+
+/// Returns the force feedback effects supported by emulated Xbox controllers
+pub(super) fn ff_effect_codes() -> AttributeSet<FFEffectCode> {
+    let mut codes = AttributeSet::<FFEffectCode>::new();
+    codes.insert(FFEffectCode::FF_RUMBLE);
+    codes.insert(FFEffectCode::FF_PERIODIC);
+    codes.insert(FFEffectCode::FF_SQUARE);
+    codes.insert(FFEffectCode::FF_TRIANGLE);
+    codes.insert(FFEffectCode::FF_SINE);
+    codes.insert(FFEffectCode::FF_GAIN);
+    codes
+}
+
+/// Returns the scheduled Guide + South chord that emulates the given
+/// QuickAccess button event, which opens the Steam quick access menu.
+pub(super) fn quick_access_chord(event: NativeEvent) -> Vec<ScheduledNativeEvent> {
+    let pressed = event.pressed();
+    let guide = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+        event.get_value(),
+    );
+    let south = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        event.get_value(),
+    );
+
+    let (guide, south) = if pressed {
+        let guide = ScheduledNativeEvent::new(guide, Duration::from_millis(0));
+        let south = ScheduledNativeEvent::new(south, Duration::from_millis(160));
+        (guide, south)
+    } else {
+        let guide = ScheduledNativeEvent::new(guide, Duration::from_millis(240));
+        let south = ScheduledNativeEvent::new(south, Duration::from_millis(160));
+        (guide, south)
+    };
+
+    vec![guide, south]
+}
+
+/// Read force feedback events written to the given virtual device. Effect
+/// uploads are handed to the composite device, which allocates the effect
+/// id, so they can be completed before the writer is unblocked.
+pub(super) fn poll_ff_events(
+    device: &mut VirtualDevice,
+    composite_device: &Option<CompositeDeviceClient>,
+) -> Result<Vec<OutputEvent>, OutputError> {
+    let events: Vec<InputEvent> = match device.fetch_events() {
+        Ok(events) => events.collect(),
+        Err(err) => match err.kind() {
+            // Do nothing if this would block
+            std::io::ErrorKind::WouldBlock => return Ok(vec![]),
+            _ => return Err(err.into()),
+        },
+    };
+
+    let mut output_events = vec![];
+    for event in events {
+        match event.destructure() {
+            EventSummary::ForceFeedback(..) => {
+                output_events.push(OutputEvent::Evdev(event));
+            }
+            EventSummary::UInput(event, UInputCode::UI_FF_UPLOAD, ..) => {
+                let mut event = device.process_ff_upload(event)?;
+                let Some(composite_device) = composite_device else {
+                    log::debug!("No composite device to upload effect to");
+                    event.set_retval(-1);
+                    continue;
+                };
+
+                // Upload the effect through the composite device and wait for
+                // the effect id it allocated.
+                let (tx, rx) = channel();
+                let upload = UinputOutputEvent::FFUpload(event.effect_id(), event.effect(), tx);
+                let upload = OutputEvent::Uinput(upload);
+                if let Err(e) = composite_device.blocking_process_output_event(upload) {
+                    log::error!("Failed to send FF upload to composite device: {e:?}");
+                    event.set_retval(-1);
+                    continue;
+                }
+                match rx.recv_timeout(FF_UPLOAD_TIMEOUT) {
+                    Ok(Some(id)) => {
+                        event.set_effect_id(id);
+                        event.set_retval(0);
+                    }
+                    Ok(None) => event.set_retval(-1),
+                    Err(e) => {
+                        log::error!("Failed to receive FF effect id: {e:?}");
+                        event.set_retval(-1);
+                    }
+                }
+            }
+            EventSummary::UInput(event, UInputCode::UI_FF_ERASE, ..) => {
+                let event = device.process_ff_erase(event)?;
+                let erase = UinputOutputEvent::FFErase(event.effect_id());
+                output_events.push(OutputEvent::Uinput(erase));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(output_events)
+}
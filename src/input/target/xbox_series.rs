@@ -0,0 +1,189 @@
+use std::{collections::HashMap, error::Error, fmt::Debug, os::fd::AsRawFd};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, InputEvent, InputId, KeyCode, UinputAbsSetup,
+};
+use nix::fcntl::{FcntlArg, OFlag};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    composite_device::client::CompositeDeviceClient,
+    event::{
+        evdev::EvdevEvent,
+        native::{NativeEvent, ScheduledNativeEvent},
+    },
+    output_capability::OutputCapability,
+    output_event::OutputEvent,
+};
+
+use super::{
+    xb360::{ff_effect_codes, poll_ff_events, quick_access_chord},
+    InputError, OutputError, TargetInputDevice, TargetOutputDevice,
+};
+
+/// Maximum number of force feedback effects that can be uploaded
+const FF_EFFECTS_MAX: u32 = 16;
+
+/// The [XboxSeriesController] is a virtual gamepad that emulates a wired
+/// Microsoft Xbox Series S|X controller with a share button.
+pub struct XboxSeriesController {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+    queued_events: Vec<ScheduledNativeEvent>,
+}
+
+impl XboxSeriesController {
+    /// Create a new emulated Microsoft Xbox Series S|X Controller
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let axis_map = XboxSeriesController::create_axes_map();
+        let device = XboxSeriesController::create_virtual_device(&axis_map)?;
+        Ok(Self {
+            device,
+            axis_map,
+            queued_events: vec![],
+        })
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Return an [AbsInfo] map of the axes of the device
+    fn create_axes_map() -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        let joystick = AbsInfo::new(0, -32768, 32767, 16, 128, 0);
+        let trigger = AbsInfo::new(0, 0, 1023, 0, 0, 0);
+        let hat = AbsInfo::new(0, -1, 1, 0, 0, 0);
+        HashMap::from([
+            (AbsoluteAxisCode::ABS_X, joystick),
+            (AbsoluteAxisCode::ABS_Y, joystick),
+            (AbsoluteAxisCode::ABS_RX, joystick),
+            (AbsoluteAxisCode::ABS_RY, joystick),
+            (AbsoluteAxisCode::ABS_Z, trigger),
+            (AbsoluteAxisCode::ABS_RZ, trigger),
+            (AbsoluteAxisCode::ABS_HAT0X, hat),
+            (AbsoluteAxisCode::ABS_HAT0Y, hat),
+        ])
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(
+        axis_map: &HashMap<AbsoluteAxisCode, AbsInfo>,
+    ) -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut buttons = AttributeSet::<KeyCode>::new();
+        buttons.insert(KeyCode::BTN_SOUTH);
+        buttons.insert(KeyCode::BTN_EAST);
+        buttons.insert(KeyCode::BTN_NORTH);
+        buttons.insert(KeyCode::BTN_WEST);
+        buttons.insert(KeyCode::BTN_TL);
+        buttons.insert(KeyCode::BTN_TR);
+        buttons.insert(KeyCode::BTN_SELECT);
+        buttons.insert(KeyCode::BTN_START);
+        buttons.insert(KeyCode::BTN_MODE);
+        buttons.insert(KeyCode::BTN_THUMBL);
+        buttons.insert(KeyCode::BTN_THUMBR);
+        buttons.insert(KeyCode::KEY_RECORD);
+
+        let mut builder = VirtualDeviceBuilder::new()?
+            .name("Microsoft Xbox Series S|X Controller")
+            .input_id(InputId::new(BusType::BUS_USB, 0x045e, 0x0b12, 0x0001))
+            .with_keys(&buttons)?;
+        for (axis, info) in axis_map.iter() {
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(*axis, *info))?;
+        }
+        let device = builder
+            .with_ff(&ff_effect_codes())?
+            .with_ff_effects_max(FF_EFFECTS_MAX)
+            .build()?;
+
+        // Force feedback requests are read in the poll loop, so reads must
+        // not block.
+        nix::fcntl::fcntl(device.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for XboxSeriesController {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        // Check for QuickAccess, create chord for event.
+        let cap = event.as_capability();
+        if cap == Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)) {
+            self.queued_events.append(&mut quick_access_chord(event));
+            return Ok(());
+        }
+
+        let evdev_events = self.translate_event(event);
+        if evdev_events.is_empty() {
+            return Ok(());
+        }
+        self.device.emit(evdev_events.as_slice())?;
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        ])
+    }
+
+    /// Returns any events in the queue up to the [TargetDriver]
+    fn scheduled_events(&mut self) -> Option<Vec<ScheduledNativeEvent>> {
+        if self.queued_events.is_empty() {
+            return None;
+        }
+        Some(self.queued_events.drain(..).collect())
+    }
+}
+
+impl TargetOutputDevice for XboxSeriesController {
+    /// Read force feedback requests from the virtual device
+    fn poll(
+        &mut self,
+        composite_device: &Option<CompositeDeviceClient>,
+    ) -> Result<Vec<OutputEvent>, OutputError> {
+        poll_ff_events(&mut self.device, composite_device)
+    }
+
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![
+            OutputCapability::ForceFeedback,
+            OutputCapability::ForceFeedbackUpload,
+            OutputCapability::ForceFeedbackErase,
+        ])
+    }
+}
+
+impl Debug for XboxSeriesController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XboxSeriesController")
+            .field("axis_map", &self.axis_map)
+            .finish()
+    }
+}
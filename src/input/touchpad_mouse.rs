@@ -0,0 +1,152 @@
+use std::time::Instant;
+
+use super::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Number of pixels the mouse moves when a finger is moved across the full
+/// width of the touchpad without acceleration.
+const TOUCHPAD_MOUSE_SPEED: f64 = 1200.0;
+/// Finger speed in touchpad widths per second where adaptive acceleration
+/// starts to increase the pointer speed.
+const ADAPTIVE_THRESHOLD: f64 = 0.4;
+/// Minimum acceleration factor applied to slow finger motion to allow
+/// precise pointing
+const ADAPTIVE_MIN_FACTOR: f64 = 0.3;
+/// Maximum acceleration factor applied to fast finger motion
+const ADAPTIVE_MAX_FACTOR: f64 = 2.5;
+/// Increase of the acceleration factor per touchpad width per second above
+/// the threshold.
+const ADAPTIVE_INCLINE: f64 = 1.2;
+
+/// Pointer acceleration model used when emulating a mouse with a touchpad
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TouchpadMouseEmulation {
+    /// Touchpad motion is not translated into mouse motion
+    #[default]
+    Disabled,
+    /// Finger motion is scaled linearly into mouse motion
+    Flat,
+    /// Finger motion is scaled based on the speed of the finger, similar to
+    /// the default pointer acceleration in libinput. Slow motion moves the
+    /// pointer precisely while fast motion covers more distance.
+    Adaptive,
+}
+
+/// Translates single-finger touchpad motion into relative mouse motion
+#[derive(Debug, Default)]
+pub struct TouchpadMouseEmulator {
+    mode: TouchpadMouseEmulation,
+    /// Last position of the finger driving the pointer
+    last_position: Option<(f64, f64)>,
+    /// Time of the last finger motion
+    last_time: Option<Instant>,
+    /// Fractional pixels that were not yet emitted
+    remainder: (f64, f64),
+}
+
+impl TouchpadMouseEmulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current emulation mode
+    pub fn mode(&self) -> TouchpadMouseEmulation {
+        self.mode
+    }
+
+    /// Set the emulation mode and reset the tracked finger position
+    pub fn set_mode(&mut self, mode: TouchpadMouseEmulation) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// Returns true if touchpad motion is being translated into mouse motion
+    pub fn is_enabled(&self) -> bool {
+        self.mode != TouchpadMouseEmulation::Disabled
+    }
+
+    fn reset(&mut self) {
+        self.last_position = None;
+        self.last_time = None;
+        self.remainder = (0.0, 0.0);
+    }
+
+    /// Update the finger position from the given touch value and return a
+    /// mouse motion event with the relative motion of the finger, if it moved.
+    /// Only the first finger moves the pointer.
+    pub fn update(&mut self, value: &InputValue, now: Instant) -> Option<NativeEvent> {
+        let InputValue::Touch {
+            index,
+            is_touching,
+            x,
+            y,
+            ..
+        } = value
+        else {
+            return None;
+        };
+        if *index != 0 {
+            return None;
+        }
+        if !is_touching {
+            self.reset();
+            return None;
+        }
+
+        let (last_x, last_y) = self.last_position.unwrap_or((0.0, 0.0));
+        let position = (x.unwrap_or(last_x), y.unwrap_or(last_y));
+        let last_position = self.last_position.replace(position);
+        let last_time = self.last_time.replace(now);
+
+        // The first touch only sets the starting position
+        let (Some((last_x, last_y)), Some(last_time)) = (last_position, last_time) else {
+            return None;
+        };
+        let delta = (position.0 - last_x, position.1 - last_y);
+        if delta == (0.0, 0.0) {
+            return None;
+        }
+
+        let factor = match self.mode {
+            TouchpadMouseEmulation::Disabled => return None,
+            TouchpadMouseEmulation::Flat => 1.0,
+            TouchpadMouseEmulation::Adaptive => {
+                let elapsed = now.duration_since(last_time).as_secs_f64();
+                let speed = if elapsed > 0.0 {
+                    delta.0.hypot(delta.1) / elapsed
+                } else {
+                    0.0
+                };
+                adaptive_factor(speed)
+            }
+        };
+
+        // Accumulate fractional pixels so slow motion is not lost
+        let pixels_x = delta.0 * TOUCHPAD_MOUSE_SPEED * factor + self.remainder.0;
+        let pixels_y = delta.1 * TOUCHPAD_MOUSE_SPEED * factor + self.remainder.1;
+        self.remainder = (pixels_x.fract(), pixels_y.fract());
+        let (pixels_x, pixels_y) = (pixels_x.trunc(), pixels_y.trunc());
+        if pixels_x == 0.0 && pixels_y == 0.0 {
+            return None;
+        }
+
+        let value = InputValue::Vector2 {
+            x: Some(pixels_x),
+            y: Some(pixels_y),
+        };
+        Some(NativeEvent::new(Capability::Mouse(Mouse::Motion), value))
+    }
+}
+
+/// Returns the acceleration factor for the given finger speed in touchpad
+/// widths per second. Slow motion is decelerated up to the threshold, after
+/// which the factor increases linearly up to the maximum.
+fn adaptive_factor(speed: f64) -> f64 {
+    if speed < ADAPTIVE_THRESHOLD {
+        let t = speed / ADAPTIVE_THRESHOLD;
+        return ADAPTIVE_MIN_FACTOR + (1.0 - ADAPTIVE_MIN_FACTOR) * t;
+    }
+    (1.0 + (speed - ADAPTIVE_THRESHOLD) * ADAPTIVE_INCLINE).min(ADAPTIVE_MAX_FACTOR)
+}
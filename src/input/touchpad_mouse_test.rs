@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    event::value::InputValue,
+    touchpad_mouse::{TouchpadMouseEmulation, TouchpadMouseEmulator},
+};
+
+fn touch(index: u8, is_touching: bool, x: f64, y: f64) -> InputValue {
+    InputValue::Touch {
+        index,
+        is_touching,
+        pressure: None,
+        x: Some(x),
+        y: Some(y),
+    }
+}
+
+/// Returns the x and y motion of the given mouse motion event
+fn motion(emulator: &mut TouchpadMouseEmulator, value: InputValue, now: Instant) -> (f64, f64) {
+    let Some(event) = emulator.update(&value, now) else {
+        return (0.0, 0.0);
+    };
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    (x.unwrap_or_default(), y.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_flat_emulation() {
+    let mut emulator = TouchpadMouseEmulator::new();
+    emulator.set_mode(TouchpadMouseEmulation::Flat);
+    let now = Instant::now();
+
+    // The first touch only sets the starting position
+    assert!(emulator.update(&touch(0, true, 0.5, 0.5), now).is_none());
+    let (x, y) = motion(&mut emulator, touch(0, true, 0.6, 0.5), now);
+    assert!(x > 0.0);
+    assert_eq!(y, 0.0);
+
+    // Only the first finger moves the pointer
+    assert!(emulator.update(&touch(1, true, 0.1, 0.1), now).is_none());
+
+    // Lifting the finger resets the position
+    assert!(emulator.update(&touch(0, false, 0.6, 0.5), now).is_none());
+    assert!(emulator.update(&touch(0, true, 0.1, 0.1), now).is_none());
+}
+
+#[tokio::test]
+async fn test_adaptive_emulation() {
+    let mut emulator = TouchpadMouseEmulator::new();
+    emulator.set_mode(TouchpadMouseEmulation::Adaptive);
+    let start = Instant::now();
+
+    // Moving the same distance faster moves the pointer further
+    emulator.update(&touch(0, true, 0.2, 0.5), start);
+    let slow_time = start + Duration::from_millis(500);
+    let (slow, _) = motion(&mut emulator, touch(0, true, 0.3, 0.5), slow_time);
+    let fast_time = slow_time + Duration::from_millis(10);
+    let (fast, _) = motion(&mut emulator, touch(0, true, 0.4, 0.5), fast_time);
+    assert!(fast > slow);
+}
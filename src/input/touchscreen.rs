@@ -0,0 +1,230 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use super::{
+    capability::{Capability, Gamepad, GamepadButton, Mouse, MouseButton, Touch},
+    event::{native::NativeEvent, value::InputValue},
+    gesture::SlotUpdate,
+};
+
+/// Maximum time fingers can touch the screen for the touch to be a tap
+const TAP_TIMEOUT: Duration = Duration::from_millis(250);
+/// Maximum normalized distance a finger can move for the touch to be a tap
+const TAP_MOVE_THRESHOLD: f64 = 0.02;
+
+/// Rectangular region of the touchscreen that emits a gamepad button while
+/// it is touched. Coordinates are normalized between 0.0 and 1.0, where
+/// (0, 0) is the top-left corner of the touchscreen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButtonZone {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub button: GamepadButton,
+}
+
+impl ButtonZone {
+    /// Returns true if the given position is inside the zone
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+}
+
+/// Current state of a single multi-touch slot
+#[derive(Debug, Default, Clone, Copy)]
+struct Slot {
+    is_touching: bool,
+    x: f64,
+    y: f64,
+    /// Whether or not the slot changed since the last frame
+    changed: bool,
+}
+
+/// Translates a stream of multi-touch slot updates from a touchscreen into
+/// touchscreen motion events, mouse clicks for taps, and gamepad buttons for
+/// touches inside of button zones. A single finger tap is a left click and
+/// a two finger tap is a right click, so a double tap is a double click.
+/// Slot updates are accumulated with [TouchscreenRecognizer::update] and
+/// evaluated once per input frame with [TouchscreenRecognizer::sync].
+#[derive(Debug, Default)]
+pub struct TouchscreenRecognizer {
+    zones: Vec<ButtonZone>,
+    slots: HashMap<usize, Slot>,
+    /// Zone index of each slot that started touching inside a button zone
+    zone_slots: HashMap<usize, usize>,
+    /// Time the current touch started, if any finger is touching
+    touch_start: Option<Instant>,
+    /// Position of each slot when it started touching
+    start_positions: HashMap<usize, (f64, f64)>,
+    /// Maximum number of fingers touching at once during the current touch
+    max_fingers: usize,
+    /// Whether or not any finger moved too far for the touch to be a tap
+    moved: bool,
+}
+
+impl TouchscreenRecognizer {
+    /// Create a new recognizer with the given button zones
+    pub fn new(zones: Vec<ButtonZone>) -> Self {
+        Self {
+            zones,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the capabilities of all events that can be emitted
+    pub fn capabilities(&self) -> Vec<Capability> {
+        let mut capabilities = vec![
+            Capability::Touchscreen(Touch::Motion),
+            Capability::Mouse(Mouse::Button(MouseButton::Left)),
+            Capability::Mouse(Mouse::Button(MouseButton::Right)),
+        ];
+        for zone in self.zones.iter() {
+            let cap = Capability::Gamepad(Gamepad::Button(zone.button.clone()));
+            if !capabilities.contains(&cap) {
+                capabilities.push(cap);
+            }
+        }
+        capabilities
+    }
+
+    /// Update the state of the given multi-touch slot
+    pub fn update(&mut self, slot: usize, update: SlotUpdate) {
+        let state = self.slots.entry(slot).or_default();
+        match update {
+            SlotUpdate::Touch(is_touching) => state.is_touching = is_touching,
+            SlotUpdate::PositionX(x) => state.x = x,
+            SlotUpdate::PositionY(y) => state.y = y,
+        }
+        state.changed = true;
+    }
+
+    /// Evaluate all slot updates since the last frame and return the
+    /// resulting events.
+    pub fn sync(&mut self, now: Instant) -> Vec<NativeEvent> {
+        let mut events = Vec::new();
+        let mut changed: Vec<usize> = self
+            .slots
+            .iter()
+            .filter(|(_, state)| state.changed)
+            .map(|(slot, _)| *slot)
+            .collect();
+        changed.sort();
+
+        for slot in changed {
+            let Some(state) = self.slots.get_mut(&slot) else {
+                continue;
+            };
+            state.changed = false;
+            let state = *state;
+            let was_zone_touch = self.zone_slots.contains_key(&slot);
+
+            if state.is_touching && !self.start_positions.contains_key(&slot) {
+                self.on_touch_down(slot, &state, now, &mut events);
+            } else if !state.is_touching && self.start_positions.contains_key(&slot) {
+                self.on_touch_up(slot, &mut events);
+            } else if let (false, Some((start_x, start_y))) =
+                (was_zone_touch, self.start_positions.get(&slot))
+            {
+                let distance = (state.x - start_x).hypot(state.y - start_y);
+                if distance > TAP_MOVE_THRESHOLD {
+                    self.moved = true;
+                }
+            }
+
+            // Touches inside of button zones are not emitted as touch motion
+            if was_zone_touch || self.zone_slots.contains_key(&slot) {
+                continue;
+            }
+            let value = InputValue::Touch {
+                index: slot as u8,
+                is_touching: state.is_touching,
+                pressure: None,
+                x: Some(state.x),
+                y: Some(state.y),
+            };
+            events.push(NativeEvent::new(
+                Capability::Touchscreen(Touch::Motion),
+                value,
+            ));
+        }
+
+        // Recognize taps once all fingers outside of button zones are lifted
+        if self.start_positions.len() == self.zone_slots.len() {
+            if let Some(start) = self.touch_start.take() {
+                let is_tap = !self.moved && now.duration_since(start) <= TAP_TIMEOUT;
+                let button = match self.max_fingers {
+                    1 => Some(MouseButton::Left),
+                    2 => Some(MouseButton::Right),
+                    _ => None,
+                };
+                if let (true, Some(button)) = (is_tap, button) {
+                    log::trace!("Recognized {} finger tap", self.max_fingers);
+                    let cap = Capability::Mouse(Mouse::Button(button));
+                    events.push(NativeEvent::new(cap.clone(), InputValue::Bool(true)));
+                    events.push(NativeEvent::new(cap, InputValue::Bool(false)));
+                }
+                self.max_fingers = 0;
+                self.moved = false;
+            }
+        }
+
+        events
+    }
+
+    /// Start tracking the given slot when a finger touches the screen
+    fn on_touch_down(
+        &mut self,
+        slot: usize,
+        state: &Slot,
+        now: Instant,
+        events: &mut Vec<NativeEvent>,
+    ) {
+        self.start_positions.insert(slot, (state.x, state.y));
+
+        // Touches that start inside of a button zone only press the button
+        let zone = self
+            .zones
+            .iter()
+            .position(|zone| zone.contains(state.x, state.y));
+        if let Some(index) = zone {
+            let button = self.zones[index].button.clone();
+            let is_pressed = self
+                .zone_slots
+                .values()
+                .any(|i| self.zones[*i].button == button);
+            self.zone_slots.insert(slot, index);
+            if !is_pressed {
+                let cap = Capability::Gamepad(Gamepad::Button(button));
+                events.push(NativeEvent::new(cap, InputValue::Bool(true)));
+            }
+            return;
+        }
+
+        let fingers = self.start_positions.len() - self.zone_slots.len();
+        if self.touch_start.is_none() {
+            self.touch_start = Some(now);
+        }
+        self.max_fingers = self.max_fingers.max(fingers);
+    }
+
+    /// Stop tracking the given slot when a finger is lifted
+    fn on_touch_up(&mut self, slot: usize, events: &mut Vec<NativeEvent>) {
+        self.start_positions.remove(&slot);
+        let Some(index) = self.zone_slots.remove(&slot) else {
+            return;
+        };
+        let button = self.zones[index].button.clone();
+        let pressed: HashSet<&GamepadButton> = self
+            .zone_slots
+            .values()
+            .map(|i| &self.zones[*i].button)
+            .collect();
+        if !pressed.contains(&button) {
+            let cap = Capability::Gamepad(Gamepad::Button(button));
+            events.push(NativeEvent::new(cap, InputValue::Bool(false)));
+        }
+    }
+}
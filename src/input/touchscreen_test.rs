@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, Mouse, MouseButton, Touch},
+    gesture::SlotUpdate,
+    touchscreen::{ButtonZone, TouchscreenRecognizer},
+};
+
+/// Touch or lift the given slot at the given position
+fn update_slot(
+    recognizer: &mut TouchscreenRecognizer,
+    slot: usize,
+    touching: bool,
+    x: f64,
+    y: f64,
+) {
+    recognizer.update(slot, SlotUpdate::Touch(touching));
+    recognizer.update(slot, SlotUpdate::PositionX(x));
+    recognizer.update(slot, SlotUpdate::PositionY(y));
+}
+
+#[tokio::test]
+async fn test_touchscreen_tap() {
+    let mut recognizer = TouchscreenRecognizer::new(vec![]);
+    let start = Instant::now();
+
+    update_slot(&mut recognizer, 0, true, 0.5, 0.5);
+    let events = recognizer.sync(start);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Touchscreen(Touch::Motion)
+    );
+
+    update_slot(&mut recognizer, 0, false, 0.5, 0.5);
+    let events = recognizer.sync(start + Duration::from_millis(100));
+    let left = Capability::Mouse(Mouse::Button(MouseButton::Left));
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[1].as_capability(), left);
+    assert!(events[1].pressed());
+    assert!(!events[2].pressed());
+
+    // Long touches are not taps
+    update_slot(&mut recognizer, 0, true, 0.5, 0.5);
+    recognizer.sync(start);
+    update_slot(&mut recognizer, 0, false, 0.5, 0.5);
+    let events = recognizer.sync(start + Duration::from_secs(1));
+    assert_eq!(events.len(), 1);
+}
+
+#[tokio::test]
+async fn test_touchscreen_two_finger_tap() {
+    let mut recognizer = TouchscreenRecognizer::new(vec![]);
+    let start = Instant::now();
+
+    update_slot(&mut recognizer, 0, true, 0.4, 0.5);
+    update_slot(&mut recognizer, 1, true, 0.6, 0.5);
+    recognizer.sync(start);
+    update_slot(&mut recognizer, 0, false, 0.4, 0.5);
+    update_slot(&mut recognizer, 1, false, 0.6, 0.5);
+    let events = recognizer.sync(start + Duration::from_millis(100));
+    let right = Capability::Mouse(Mouse::Button(MouseButton::Right));
+    assert!(events.iter().any(|event| event.as_capability() == right));
+}
+
+#[tokio::test]
+async fn test_touchscreen_button_zone() {
+    let zone = ButtonZone {
+        x: 0.0,
+        y: 0.0,
+        width: 0.2,
+        height: 0.2,
+        button: GamepadButton::South,
+    };
+    let mut recognizer = TouchscreenRecognizer::new(vec![zone]);
+    let start = Instant::now();
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    assert!(recognizer.capabilities().contains(&south));
+
+    // Touches inside the zone only press and release the button
+    update_slot(&mut recognizer, 0, true, 0.1, 0.1);
+    let events = recognizer.sync(start);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), south);
+    assert!(events[0].pressed());
+
+    update_slot(&mut recognizer, 0, false, 0.1, 0.1);
+    let events = recognizer.sync(start + Duration::from_millis(50));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), south);
+    assert!(!events[0].pressed());
+}
@@ -0,0 +1,102 @@
+//! User defined event transformation with WebAssembly modules.
+//!
+//! A transform module must export its linear `memory`, an
+//! `alloc(size: u32) -> u32` function that returns a pointer to `size` bytes
+//! of memory the host can write to, and a
+//! `transform(capability: u32, value_ptr: u32) -> u32` function. The value of
+//! each event is written as a little-endian f32 to `value_ptr` before
+//! `transform` is called. Bool values are written as 0.0 or 1.0.
+//!
+//! The transform function returns 0 if the event should be left unchanged.
+//! Otherwise it returns the capability id of the transformed event, and the
+//! transformed value is read back from `value_ptr`. Capability ids are the
+//! 32-bit FNV-1a hash of the capability string (e.g. "Gamepad:Button:South").
+use std::{error::Error, fmt};
+
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, TypedFunc};
+
+use super::capability::Capability;
+
+/// Amount of fuel a transform function can consume for a single event before
+/// it is aborted. This prevents a faulty script from blocking all input.
+const FUEL_PER_EVENT: u64 = 1_000_000;
+
+/// Returns the id of the given capability used by transform modules, which is
+/// the 32-bit FNV-1a hash of its capability string.
+pub fn capability_id(capability: &Capability) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in capability.to_capability_string().bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+/// An instantiated WebAssembly transform module
+pub struct WasmTransform {
+    store: Store<()>,
+    memory: Memory,
+    transform: TypedFunc<(u32, u32), u32>,
+    value_ptr: u32,
+}
+
+impl fmt::Debug for WasmTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmTransform")
+            .field("value_ptr", &self.value_ptr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmTransform {
+    /// Compile and instantiate the given WebAssembly module
+    pub fn new(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, bytes)?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(FUEL_PER_EVENT)?;
+
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Transform module does not export 'memory'")?;
+        let alloc = instance.get_typed_func::<u32, u32>(&mut store, "alloc")?;
+        let transform = instance.get_typed_func::<(u32, u32), u32>(&mut store, "transform")?;
+
+        // Reserve memory in the module to pass values to the transform function
+        let value_ptr = alloc.call(&mut store, std::mem::size_of::<f32>() as u32)?;
+
+        Ok(Self {
+            store,
+            memory,
+            transform,
+            value_ptr,
+        })
+    }
+
+    /// Run the transform function of the module for the given capability and
+    /// value. Returns the capability id and value of the transformed event, or
+    /// None if the module left the event unchanged.
+    pub fn transform(
+        &mut self,
+        capability: &Capability,
+        value: f32,
+    ) -> Result<Option<(u32, f32)>, Box<dyn Error>> {
+        let ptr = self.value_ptr as usize;
+        self.memory
+            .write(&mut self.store, ptr, &value.to_le_bytes())?;
+        self.store.set_fuel(FUEL_PER_EVENT)?;
+        let result = self
+            .transform
+            .call(&mut self.store, (capability_id(capability), self.value_ptr))?;
+        if result == 0 {
+            return Ok(None);
+        }
+
+        let mut bytes = [0; 4];
+        self.memory.read(&self.store, ptr, &mut bytes)?;
+        Ok(Some((result, f32::from_le_bytes(bytes))))
+    }
+}
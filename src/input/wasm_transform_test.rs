@@ -0,0 +1,14 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    wasm_transform::capability_id,
+};
+
+#[test]
+fn test_capability_id() {
+    // FNV-1a hash of "Gamepad:Button:South"
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    assert_eq!(capability_id(&south), 0xcfaa49e5);
+
+    let east = Capability::Gamepad(Gamepad::Button(GamepadButton::East));
+    assert_ne!(capability_id(&south), capability_id(&east));
+}
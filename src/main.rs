@@ -2,6 +2,9 @@ use std::env;
 use std::error::Error;
 use std::future::pending;
 use std::process;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 use zbus::fdo::ObjectManager;
 use zbus::Connection;
 
@@ -27,8 +30,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Ok(value) => value,
         Err(_) => "info".to_string(),
     };
-    env::set_var("RUST_LOG", log_level);
-    env_logger::init();
+    let filter = EnvFilter::try_new(&log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+
+    // Log records from the 'log' crate are forwarded to the subscriber, so
+    // they are printed with the fields of the span they were logged in.
+    let fmt_layer = tracing_subscriber::fmt::layer().with_filter(filter);
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+    #[cfg(feature = "dev")]
+    let registry = registry.with(console_subscriber::spawn());
+    registry.init();
     const VERSION: &str = env!("CARGO_PKG_VERSION");
     log::info!("Starting InputPlumber v{}", VERSION);
 
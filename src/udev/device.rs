@@ -297,6 +297,45 @@ impl UdevDevice {
         }
     }
 
+    /// Probes udev for the device at the given device path and returns a
+    /// fully populated UdevDevice.
+    /// e.g. UdevDevice::from_path("/dev/input/event3");
+    pub fn from_path(path: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let device_path = Path::new(path);
+        let base_path = device_path.parent().and_then(|base| base.to_str());
+        let name = device_path.file_name().and_then(|name| name.to_str());
+        let (Some(base_path), Some(name)) = (base_path, name) else {
+            return Err(format!("Invalid device path: {path}").into());
+        };
+
+        let device = Self::from_devnode(base_path, name);
+        if device.subsystem.is_empty() {
+            return Err(format!("No supported subsystem detected for {path}").into());
+        }
+        let device = ::udev::Device::from_subsystem_sysname(device.subsystem, device.sysname)?;
+        if !device.is_initialized() {
+            return Err(format!("Device {path} is not yet initialized by udev").into());
+        }
+
+        Ok(device.into())
+    }
+
+    /// Returns a UdevDevice object for a network stream source device that
+    /// listens on the given address. Network devices do not exist in udev.
+    /// e.g. UdevDevice::from_network_address("0.0.0.0:27020");
+    pub fn from_network_address(address: &str) -> Self {
+        Self {
+            devnode: format!("udp://{address}"),
+            subsystem: "network".to_string(),
+            syspath: "".to_string(),
+            sysname: address.to_string(),
+            name: Some("InputPlumber Network Stream".to_string()),
+            vendor_id: None,
+            product_id: None,
+            bus_type: None,
+        }
+    }
+
     /// Returns a udev::Device from the stored syspath.
     pub fn get_device(&self) -> Result<::udev::Device, Box<dyn Error + Send + Sync>> {
         match ::udev::Device::from_syspath(Path::new(self.syspath.as_str())) {
@@ -305,6 +344,14 @@ impl UdevDevice {
         }
     }
 
+    /// Returns a new [UdevDevice] with its properties re-read from udev. This
+    /// can be used to pick up device information that changed after the
+    /// device was first discovered (e.g. after a firmware update).
+    pub fn refresh(&self) -> Result<UdevDevice, Box<dyn Error + Send + Sync>> {
+        let device = self.get_device()?;
+        Ok(device.into())
+    }
+
     /// Returns true if this device is virtual
     pub fn is_virtual(&self) -> bool {
         self.syspath().contains("/devices/virtual") || self.syspath().contains("vhci_hcd")
@@ -429,6 +476,12 @@ impl UdevDevice {
         self.syspath.clone()
     }
 
+    /// Returns the USB bus number and port path of the device if it is
+    /// connected over USB. E.g. ("1", "2.3")
+    pub fn usb_topology(&self) -> Option<(String, String)> {
+        parse_usb_topology(self.syspath.as_str())
+    }
+
     /// Returns the uniq property of the device
     pub fn uniq(&self) -> String {
         let Ok(device) = self.get_device() else {
@@ -450,6 +503,9 @@ impl UdevDevice {
             "iio" => {
                 format!("iio://{}", self.sysname)
             }
+            "network" => {
+                format!("network://{}", self.sysname)
+            }
             _ => "".to_string(),
         }
     }
@@ -483,6 +539,25 @@ impl From<::udev::Device> for UdevDevice {
     }
 }
 
+/// Returns the USB bus number and port path of the USB device in the given
+/// sysfs path. The deepest USB device in the path is used, so devices behind a
+/// hub include the hub port in their port path.
+/// E.g. "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2.3/1-2.3:1.0/..." => ("1", "2.3")
+pub fn parse_usb_topology(syspath: &str) -> Option<(String, String)> {
+    let is_usb_device = |name: &&str| {
+        let Some((bus, port)) = name.split_once('-') else {
+            return false;
+        };
+        !bus.is_empty()
+            && !port.is_empty()
+            && bus.chars().all(|c| c.is_ascii_digit())
+            && port.chars().all(|c| c.is_ascii_digit() || c == '.')
+    };
+    let name = syspath.split('/').rev().find(is_usb_device)?;
+    let (bus, port) = name.split_once('-')?;
+    Some((bus.to_string(), port.to_string()))
+}
+
 /// Container for system devices
 /// This contains parsed data from a single device entry from 'udevadm info'
 #[derive(Debug, Clone, Default)]
@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use crate::udev::get_device;
+use crate::udev::{device::parse_usb_topology, get_device};
 
 #[tokio::test]
 async fn test_get_device() -> Result<(), Box<dyn Error>> {
@@ -15,3 +15,21 @@ async fn test_get_device() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_usb_topology() {
+    let path = "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2.3/1-2.3:1.0/0003:0F0D:0092.0005/input/input12/event12";
+    assert_eq!(
+        parse_usb_topology(path),
+        Some(("1".to_string(), "2.3".to_string()))
+    );
+
+    let path = "/sys/devices/pci0000:00/0000:00:08.1/0000:04:00.3/usb3/3-1/3-1:1.0/hidraw/hidraw2";
+    assert_eq!(
+        parse_usb_topology(path),
+        Some(("3".to_string(), "1".to_string()))
+    );
+
+    let path = "/sys/devices/platform/i8042/serio0/input/input3/event3";
+    assert_eq!(parse_usb_topology(path), None);
+}
@@ -1,6 +1,15 @@
-use inotify::{EventMask, Inotify, WatchMask};
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask, Watches};
 use tokio::sync::mpsc::Sender;
 
+/// Interval to poll for inotify events while waiting for a file to appear
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 #[derive(Debug, Clone)]
 pub enum WatchEvent {
     Create { name: String, base_path: String },
@@ -74,3 +83,123 @@ pub fn watch(path: String, tx: Sender<WatchEvent>) {
         }
     }
 }
+
+/// Handle to a file being watched with [watch_file]. The watch is removed and
+/// its thread is stopped when this handle is dropped.
+#[derive(Debug)]
+pub struct FileWatcher {
+    watches: Watches,
+    descriptor: WatchDescriptor,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        // Removing the watch will wake up the watching thread with an
+        // IN_IGNORED event, causing it to exit.
+        if let Err(e) = self.watches.remove(self.descriptor.clone()) {
+            log::debug!("Failed to remove inotify watch: {e:?}");
+        }
+    }
+}
+
+/// Watch the given file for changes, sending a [WatchEvent::Modify] to the
+/// given channel whenever the file is written to. The parent directory is
+/// watched instead of the file itself so editors that replace the file on
+/// save are also detected.
+pub fn watch_file(path: String, tx: Sender<WatchEvent>) -> Result<FileWatcher, io::Error> {
+    let file_path = Path::new(&path);
+    let (Some(dir), Some(file_name)) = (file_path.parent(), file_path.file_name()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid file path to watch: {path}"),
+        ));
+    };
+    let base_path = dir.to_string_lossy().to_string();
+    let name = file_name.to_string_lossy().to_string();
+
+    let mut inotify = Inotify::init()?;
+    let mut watches = inotify.watches();
+    let descriptor = watches.add(dir, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)?;
+
+    tokio::task::spawn_blocking(move || {
+        log::debug!("Started file watcher for: {base_path}/{name}");
+        let mut buffer = [0u8; 4096];
+        'outer: loop {
+            let events = match inotify.read_events_blocking(&mut buffer) {
+                Ok(events) => events,
+                Err(e) => {
+                    log::error!("Failed to read inotify events: {e:?}");
+                    break;
+                }
+            };
+
+            for event in events {
+                // The watch was removed, so stop watching
+                if event.mask.contains(EventMask::IGNORED) {
+                    break 'outer;
+                }
+                let Some(event_name) = event.name else {
+                    continue;
+                };
+                if event_name.to_string_lossy() != name {
+                    continue;
+                }
+
+                let value = WatchEvent::Modify {
+                    name: name.clone(),
+                    base_path: base_path.clone(),
+                };
+                if let Err(e) = tx.blocking_send(value) {
+                    log::debug!("Error sending event: {}", e);
+                    break 'outer;
+                }
+            }
+        }
+        log::debug!("Stopped file watcher for: {base_path}/{name}");
+    });
+
+    Ok(FileWatcher {
+        watches,
+        descriptor,
+    })
+}
+
+/// Wait up to the given timeout for the given file to be created. Returns true
+/// if the file exists before the timeout expires. This blocks the current
+/// thread, so it should be called with [tokio::task::spawn_blocking].
+pub fn wait_for_file(path: String, timeout: Duration) -> Result<bool, io::Error> {
+    let file_path = Path::new(&path);
+    let (Some(dir), Some(file_name)) = (file_path.parent(), file_path.file_name()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid file path to wait for: {path}"),
+        ));
+    };
+
+    let mut inotify = Inotify::init()?;
+    inotify.watches().add(dir, WatchMask::CREATE)?;
+
+    // The file may have been created before the watch was added
+    if file_path.exists() {
+        return Ok(true);
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0u8; 4096];
+    while Instant::now() < deadline {
+        match inotify.read_events(&mut buffer) {
+            Ok(events) => {
+                for event in events {
+                    if event.name == Some(file_name) {
+                        return Ok(true);
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+            Err(e) => return Err(e),
+        }
+        std::thread::sleep(WAIT_POLL_INTERVAL);
+    }
+
+    Ok(file_path.exists())
+}